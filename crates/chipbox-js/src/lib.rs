@@ -8,4 +8,9 @@ mod runtime;
 
 #[cfg(debug_assertions)]
 pub use self::hmr::{HmrClient, HmrRecv};
-pub use self::{display::DisplayJsValue, repl::repl, runtime::Runtime};
+pub use self::{
+    display::DisplayJsValue,
+    modules::scene::{collect_garbage, with_scene_tree, with_shortcuts},
+    repl::repl,
+    runtime::Runtime,
+};
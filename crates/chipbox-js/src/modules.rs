@@ -6,6 +6,7 @@
 
 use rquickjs::loader::{BuiltinResolver, ModuleLoader};
 
+pub mod scene;
 pub mod tracing;
 
 macro_rules! modules {
@@ -33,4 +34,4 @@ macro_rules! modules {
     };
 }
 
-modules!(tracing);
+modules!(scene, tracing);
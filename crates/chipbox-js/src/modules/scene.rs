@@ -0,0 +1,215 @@
+//! Retained scene graph mutated by the Solid universal renderer.
+//!
+//! Nodes live in a Rust-side [`SceneTree`] and are exposed to JavaScript as numeric handles.
+//! The tree is owned by the thread running the `QuickJS` runtime,
+//! along with the global keyboard shortcuts registered by scripts.
+
+use std::cell::RefCell;
+
+use chipbox_scene::{SceneTree, ShortcutMap, TreeError};
+
+pub type JsModule = js_scene_mod;
+
+thread_local! {
+    static SCENE: RefCell<SceneTree> = RefCell::new(SceneTree::new());
+    static SHORTCUTS: RefCell<ShortcutMap> = RefCell::new(ShortcutMap::new());
+}
+
+/// Call the closure with the scene tree of the current thread.
+pub fn with_scene_tree<R>(f: impl FnOnce(&SceneTree) -> R) -> R {
+    SCENE.with_borrow(f)
+}
+
+/// Call the closure with the shortcuts registered on the current thread,
+/// e.g. to pass them to [`chipbox_scene::FocusManager::dispatch`] with the key input of a frame.
+pub fn with_shortcuts<R>(f: impl FnOnce(&ShortcutMap) -> R) -> R {
+    SHORTCUTS.with_borrow(f)
+}
+
+/// Drop the nodes of the current thread that scripts removed from the scene.
+///
+/// Removed nodes are only detached, since Solid may insert them again.
+/// Call this once scripts have settled, e.g. between frames,
+/// after which handles of removed nodes are no longer valid.
+pub fn collect_garbage() {
+    let count = SCENE.with_borrow_mut(SceneTree::collect_garbage);
+    if count > 0 {
+        tracing::debug!(count, "dropped removed scene nodes");
+    }
+}
+
+/// Drop all nodes of the scene tree and all shortcuts of the current thread.
+pub(crate) fn reset() {
+    SCENE.with_borrow_mut(|tree| *tree = SceneTree::new());
+    SHORTCUTS.with_borrow_mut(|shortcuts| *shortcuts = ShortcutMap::new());
+}
+
+/// Run a fallible operation on the scene tree, throwing tree errors as JS exceptions.
+fn try_with_tree<'js, R>(
+    ctx: &rquickjs::Ctx<'js>,
+    f: impl FnOnce(&mut SceneTree) -> Result<R, TreeError>,
+) -> rquickjs::Result<R> {
+    SCENE
+        .with_borrow_mut(f)
+        .map_err(|e| rquickjs::Exception::throw_message(ctx, &e.to_string()))
+}
+
+#[rquickjs::module]
+#[allow(clippy::needless_pass_by_value, reason = "required by FromJsFunc")]
+pub mod scene_mod {
+    use chipbox_scene::{ElementKind, NodeData, NodeId, SceneTree, Shortcut, TreeError};
+    use rquickjs::{Ctx, Exception, Value};
+
+    use super::{SCENE, SHORTCUTS, try_with_tree};
+
+    /// Handle of the root node.
+    #[qjs(rename = "root")]
+    pub const ROOT: u64 = SceneTree::ROOT.0;
+
+    #[rquickjs::function]
+    #[qjs(rename = "createElement")]
+    pub fn create_element(ctx: Ctx<'_>, tag: String) -> rquickjs::Result<u64> {
+        let kind = ElementKind::from_name(&tag)
+            .ok_or_else(|| Exception::throw_type(&ctx, &format!("unknown element `{tag}`")))?;
+        Ok(SCENE.with_borrow_mut(|tree| tree.create_element(kind)).0)
+    }
+
+    #[rquickjs::function]
+    #[qjs(rename = "createTextNode")]
+    pub fn create_text_node(value: String) -> u64 {
+        SCENE.with_borrow_mut(|tree| tree.create_text(value)).0
+    }
+
+    #[rquickjs::function]
+    #[qjs(rename = "replaceText")]
+    pub fn replace_text(ctx: Ctx<'_>, node: u64, value: String) -> rquickjs::Result<()> {
+        try_with_tree(&ctx, |tree| tree.replace_text(NodeId(node), value))
+    }
+
+    #[rquickjs::function]
+    #[qjs(rename = "isTextNode")]
+    pub fn is_text_node(ctx: Ctx<'_>, node: u64) -> rquickjs::Result<bool> {
+        try_with_tree(&ctx, |tree| {
+            Ok(matches!(tree.data(NodeId(node))?, NodeData::Text(_)))
+        })
+    }
+
+    /// Set a property from any JSON-serializable value.
+    /// `undefined` and `null` reset the property to its default.
+    #[rquickjs::function]
+    #[qjs(rename = "setProperty")]
+    pub fn set_property<'js>(
+        ctx: Ctx<'js>,
+        node: u64,
+        name: String,
+        value: Value<'js>,
+    ) -> rquickjs::Result<()> {
+        let value = match ctx.json_stringify(value)? {
+            Some(json) => serde_json::from_str(&json.to_string()?).map_err(|e| {
+                Exception::throw_type(&ctx, &format!("property `{name}` is not JSON: {e}"))
+            })?,
+            None => serde_json::Value::Null,
+        };
+        try_with_tree(&ctx, |tree| tree.set_property(NodeId(node), name, value))
+    }
+
+    #[rquickjs::function]
+    #[qjs(rename = "insertNode")]
+    pub fn insert_node(
+        ctx: Ctx<'_>,
+        parent: u64,
+        node: u64,
+        anchor: Option<u64>,
+    ) -> rquickjs::Result<()> {
+        try_with_tree(&ctx, |tree| {
+            tree.insert(NodeId(parent), NodeId(node), anchor.map(NodeId))
+        })
+    }
+
+    /// Detach a node from its parent.
+    /// It is dropped by [`super::collect_garbage`] unless it is inserted again.
+    #[rquickjs::function]
+    #[qjs(rename = "removeNode")]
+    pub fn remove_node(ctx: Ctx<'_>, parent: u64, node: u64) -> rquickjs::Result<()> {
+        try_with_tree(&ctx, |tree| {
+            let (parent, node) = (NodeId(parent), NodeId(node));
+            if tree.parent(node)? != Some(parent) {
+                return Err(TreeError::NotAChild { parent, node });
+            }
+            tree.detach(node)
+        })
+    }
+
+    #[rquickjs::function]
+    #[qjs(rename = "getParentNode")]
+    pub fn get_parent_node(ctx: Ctx<'_>, node: u64) -> rquickjs::Result<Option<u64>> {
+        try_with_tree(&ctx, |tree| Ok(tree.parent(NodeId(node))?.map(|id| id.0)))
+    }
+
+    #[rquickjs::function]
+    #[qjs(rename = "getFirstChild")]
+    pub fn get_first_child(ctx: Ctx<'_>, node: u64) -> rquickjs::Result<Option<u64>> {
+        try_with_tree(&ctx, |tree| {
+            Ok(tree.first_child(NodeId(node))?.map(|id| id.0))
+        })
+    }
+
+    #[rquickjs::function]
+    #[qjs(rename = "getNextSibling")]
+    pub fn get_next_sibling(ctx: Ctx<'_>, node: u64) -> rquickjs::Result<Option<u64>> {
+        try_with_tree(&ctx, |tree| {
+            Ok(tree.next_sibling(NodeId(node))?.map(|id| id.0))
+        })
+    }
+
+    /// Bind a global shortcut such as `Ctrl+S` to a name.
+    /// Returns the name the shortcut was previously bound to.
+    #[rquickjs::function]
+    #[qjs(rename = "registerShortcut")]
+    pub fn register_shortcut(
+        ctx: Ctx<'_>,
+        shortcut: String,
+        name: String,
+    ) -> rquickjs::Result<Option<String>> {
+        let shortcut = shortcut
+            .parse::<Shortcut>()
+            .map_err(|e| Exception::throw_type(&ctx, &e.to_string()))?;
+        Ok(SHORTCUTS
+            .with_borrow_mut(|shortcuts| shortcuts.register(shortcut, name))
+            .map(|previous| previous.to_string()))
+    }
+
+    /// Remove every shortcut bound to the name.
+    #[rquickjs::function]
+    #[qjs(rename = "unregisterShortcut")]
+    pub fn unregister_shortcut(name: String) -> bool {
+        SHORTCUTS.with_borrow_mut(|shortcuts| shortcuts.unregister(&name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chipbox_scene::ElementKind;
+
+    use super::*;
+
+    #[test]
+    fn test_collect_garbage() {
+        let (kept, removed) = SCENE.with_borrow_mut(|tree| {
+            let kept = tree.create_element(ElementKind::Box);
+            let removed = tree.create_element(ElementKind::Box);
+            tree.insert(SceneTree::ROOT, kept, None)
+                .expect("kept is inserted");
+            tree.insert(kept, removed, None)
+                .expect("removed is inserted");
+            // Like `removeNode`, which only detaches the node.
+            tree.detach(removed).expect("removed is detached");
+            (kept, removed)
+        });
+        collect_garbage();
+        with_scene_tree(|tree| {
+            assert!(tree.data(kept).is_ok());
+            assert!(matches!(tree.data(removed), Err(TreeError::UnknownNode(_))));
+        });
+    }
+}
@@ -162,6 +162,8 @@ impl Runtime {
             .await
             .with_no_ctx()
             .map_err(RuntimeError::InitQjsContext)?;
+        // Nodes created by the previous context are no longer reachable.
+        crate::modules::scene::reset();
         // Re-initialize vite globals in the new context
         #[cfg(debug_assertions)]
         rquickjs::async_with!(ctx => |ctx| {
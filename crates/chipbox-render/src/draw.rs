@@ -0,0 +1,195 @@
+//! Painting of a laid out scene, element by element.
+//!
+//! Elements are painted in the order of [`LayoutNode::stacked_children`],
+//! texture elements push offscreen layers for the rest of their parent,
+//! and scroll elements clip their descendants.
+
+mod content;
+mod text;
+
+use chipbox_scene::{
+    Affine, Axis, BoxElement, Compositor, ElementNode, FillRule, Layer, LayoutElement, LayoutNode,
+    LayoutTree, LengthContext, LinearRgba, Pixmap, Point, Rect, Scene, ScrollElement, Size,
+};
+
+use crate::{
+    Resources,
+    raster::{self, Mask, Outline},
+};
+
+/// An element of the scene.
+/// Items of arrays, grids and flex elements are not [`ElementNode`]s,
+/// but are laid out like any other element.
+#[derive(Clone, Copy, Debug)]
+enum Element<'a> {
+    Node(&'a ElementNode),
+    Box(&'a BoxElement),
+    /// A grid or flex item, which only contributes its children.
+    Item(&'a [ElementNode]),
+}
+
+impl<'a> Element<'a> {
+    /// The child at an index, matching the indices of [`LayoutNode::indexed_children`].
+    fn child(self, index: usize) -> Option<Self> {
+        let layout = match self {
+            Self::Node(ElementNode::Layout(e)) => e,
+            Self::Node(_) => return None,
+            Self::Box(e) => return e.children.get(index).map(Self::Node),
+            Self::Item(children) => return children.get(index).map(Self::Node),
+        };
+        match layout {
+            LayoutElement::Array(e) => e.children.get(index).map(Self::Box),
+            LayoutElement::Grid(e) => e.children.get(index).map(|e| Self::Item(&e.children)),
+            LayoutElement::Flex(e) => e.children.get(index).map(|e| Self::Item(&e.children)),
+            _ => self.nodes().get(index).map(Self::Node),
+        }
+    }
+
+    /// The children which are element nodes, and so may be behavior elements.
+    fn nodes(self) -> &'a [ElementNode] {
+        match self {
+            Self::Node(ElementNode::Layout(e)) => match e {
+                LayoutElement::Box(e) => &e.children,
+                LayoutElement::Margin(e) => &e.children,
+                LayoutElement::ContentBox(e) => &e.children,
+                LayoutElement::Align(e) => &e.children,
+                LayoutElement::Scroll(e) => &e.children,
+                LayoutElement::Array(_) | LayoutElement::Grid(_) | LayoutElement::Flex(_) => &[],
+            },
+            Self::Node(_) => &[],
+            Self::Box(e) => &e.children,
+            Self::Item(children) => children,
+        }
+    }
+}
+
+/// Paints elements onto the layers of a compositor.
+pub struct Painter<'a> {
+    ctx: LengthContext<'a>,
+    /// Area of the scene in device pixels.
+    bounds: Rect,
+    resources: Resources<'a>,
+    compositor: Compositor,
+    /// Coverage of the innermost clip, already intersected with the ones outside it.
+    clips: Vec<Mask>,
+}
+
+impl<'a> Painter<'a> {
+    #[must_use]
+    pub fn new(scene: &Scene, resources: Resources<'a>) -> Self {
+        Self {
+            ctx: LengthContext::for_scene(scene, resources.text).with_images(resources.images),
+            bounds: Rect::from_size(0.0, 0.0, scene.px_size()),
+            resources,
+            compositor: Compositor::new(scene.px_width, scene.px_height),
+            clips: Vec::new(),
+        }
+    }
+
+    /// Paint every element of the scene, which was laid out as `layout`.
+    #[must_use]
+    pub fn paint(mut self, scene: &Scene, layout: &LayoutTree) -> Pixmap {
+        for (index, node) in layout.stacked_children() {
+            if let Some(element) = scene.children.get(index) {
+                self.node(Element::Node(element), self.bounds, node);
+            }
+        }
+        self.compositor.finish()
+    }
+
+    /// Paint an element laid out inside `bounds`, and its descendants.
+    fn node(&mut self, element: Element, bounds: Rect, node: &LayoutNode) {
+        if node.kind.is_behavior() {
+            return;
+        }
+        if let Element::Node(ElementNode::Content(content)) = element {
+            self.content(content, bounds, node);
+            return;
+        }
+        let layers = Layer::of_children(element.nodes(), node.bounds(), &self.ctx);
+        let depth = layers.len();
+        for layer in layers {
+            self.compositor.push(layer);
+        }
+        let clip = node.clip();
+        if let Some(clip) = clip {
+            let outline = Outline::of(&raster::rect(clip), raster::tolerance(node.transform));
+            self.push_clip(&outline.transformed(node.transform));
+        }
+        let inner = self.inner_bounds(element, bounds, node);
+        for (index, child) in node.stacked_children() {
+            if let Some(element) = element.child(index) {
+                self.node(element, inner, child);
+            }
+        }
+        if clip.is_some() {
+            self.clips.pop();
+        }
+        for _ in 0..depth {
+            self.compositor.pop();
+        }
+    }
+
+    /// The bounds the children of an element were laid out inside.
+    /// Text wraps to the width of these bounds, so they must match the layout pass.
+    fn inner_bounds(&self, element: Element, bounds: Rect, node: &LayoutNode) -> Rect {
+        match element {
+            // Content boxes size to their children, which are laid out in the parent bounds.
+            Element::Node(ElementNode::Layout(LayoutElement::ContentBox(_))) => bounds,
+            Element::Node(ElementNode::Layout(LayoutElement::Scroll(e))) => {
+                self.row_bounds(e, node)
+            }
+            _ => node.rect,
+        }
+    }
+
+    /// The bounds of a row of a virtualized scroll element, or its own bounds otherwise.
+    fn row_bounds(&self, element: &ScrollElement, node: &LayoutNode) -> Rect {
+        let Some(item) = &element.item_extent else {
+            return node.rect;
+        };
+        let axis = element.axis.unwrap_or(Axis::Vertical);
+        let item = item
+            .resolve(&self.ctx.with_parent(node.rect.size()))
+            .max(0.0);
+        Rect::from_axes(
+            axis,
+            node.rect.start(axis),
+            node.rect.cross_start(axis),
+            Size::from_axes(axis, item, node.rect.size().cross(axis)),
+        )
+    }
+
+    /// Clip everything painted until the clip is popped to an outline in device pixels.
+    fn push_clip(&mut self, outline: &Outline) {
+        let outer = self.clips.last();
+        let area = outer.map_or(self.bounds, Mask::bounds);
+        let mask = Mask::fill(outline, FillRule::NonZero, area);
+        let mask = match outer {
+            Some(outer) => mask.intersect(outer),
+            None => mask,
+        };
+        self.clips.push(mask);
+    }
+
+    /// Fill an outline in the space of an element mapped to device pixels by `transform`,
+    /// with a paint sampled in the same space.
+    fn fill(
+        &mut self,
+        outline: Outline,
+        rule: FillRule,
+        transform: Affine,
+        paint: impl Fn(Point) -> LinearRgba,
+    ) {
+        // Collapsed elements cover no pixels.
+        let Some(inverse) = transform.invert() else {
+            return;
+        };
+        let outline = outline.transformed(transform);
+        let target = self.compositor.target();
+        let clip = self.clips.last();
+        let area = clip.map_or(self.bounds, Mask::bounds);
+        let mask = Mask::fill(&outline, rule, area.intersection(target.bounds()));
+        raster::paint(target, &mask, clip, |point| paint(inverse.apply(point)));
+    }
+}
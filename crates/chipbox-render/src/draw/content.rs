@@ -0,0 +1,273 @@
+use chipbox_scene::{
+    Affine, ContentElement, DecodedImage, DropShadow, Effect, Fill, FillRule, ImageQuad,
+    LayoutNode, LinearRgba, PaintedImage, PaintedPath, PaintedRect, PaintedWaveform, PathVerb,
+    Pixmap, Point, Rect,
+};
+
+use super::Painter;
+use crate::raster::{self, Mask, Outline};
+
+impl Painter<'_> {
+    /// Paint a content element laid out inside `bounds`.
+    pub(super) fn content(&mut self, element: &ContentElement, bounds: Rect, node: &LayoutNode) {
+        let transform = node.transform;
+        match element {
+            ContentElement::Rect(e) => {
+                self.rect(&PaintedRect::of(e, node.rect, &self.ctx), transform);
+            }
+            ContentElement::Path(e) => {
+                self.path(&PaintedPath::of(e, node.rect, &self.ctx), transform);
+            }
+            ContentElement::Text(e) => {
+                if let Some(text) = self.resources.text {
+                    self.text(e, text, bounds, node);
+                }
+            }
+            ContentElement::Image(e) => {
+                let Some(images) = self.resources.images else {
+                    return;
+                };
+                match PaintedImage::of(e, node.rect, &self.ctx, images) {
+                    Ok(image) => self.image(&image, transform),
+                    Err(err) => tracing::warn!(%err, "skipped image"),
+                }
+            }
+            ContentElement::Waveform(e) => {
+                let Some(buffers) = self.resources.buffers else {
+                    return;
+                };
+                if let Some(waveform) = PaintedWaveform::of(e, node.rect, &self.ctx, buffers) {
+                    self.waveform(&waveform, transform);
+                } else {
+                    tracing::warn!(buffer = %e.buffer, "skipped waveform without samples");
+                }
+            }
+        }
+    }
+
+    fn rect(&mut self, rect: &PaintedRect, transform: Affine) {
+        let tolerance = raster::tolerance(transform);
+        // The first shadow is on top.
+        for shadow in rect.shadows.iter().rev() {
+            self.shadow(rect, shadow, transform);
+        }
+        if let Some(fill) = &rect.fill {
+            let outline = Outline::of(&raster::rounded_rect(rect.rect, rect.radii), tolerance);
+            self.fill(outline, FillRule::NonZero, transform, |point| {
+                fill.color_at(point)
+            });
+        }
+        if let Some(border) = &rect.border {
+            // Borders are drawn inside the rect, centered on an outline inset by half their width.
+            let half = border.width / 2.0;
+            let inner = rect.rect.inset(half, half, half, half);
+            let radii = rect.radii.map(|radius| (radius - half).max(0.0));
+            let outline = Outline::of(&raster::rounded_rect(inner, radii), tolerance);
+            let outline = raster::stroke(&outline, border, tolerance);
+            self.fill(outline, FillRule::NonZero, transform, |point| {
+                border.fill.color_at(point)
+            });
+        }
+    }
+
+    /// Paint a shadow of a rect, grown by its spread, moved by its offset and blurred.
+    fn shadow(&mut self, rect: &PaintedRect, shadow: &DropShadow, transform: Affine) {
+        let spread = shadow.spread;
+        let shape = rect.rect.inset(-spread, -spread, -spread, -spread);
+        let shape = Rect::new(
+            shape.x + shadow.offset.x,
+            shape.y + shadow.offset.y,
+            shape.width,
+            shape.height,
+        );
+        // Square corners stay square, as in CSS.
+        let radii = rect
+            .radii
+            .map(|radius| if radius > 0.0 { radius + spread } else { 0.0 });
+        let outline = Outline::of(
+            &raster::rounded_rect(shape, radii),
+            raster::tolerance(transform),
+        );
+        if shadow.blur <= 0.0 {
+            self.fill(outline, FillRule::NonZero, transform, |_| shadow.color);
+            return;
+        }
+        let outline = outline.transformed(transform);
+        let Some(bounds) = outline.bounds() else {
+            return;
+        };
+        let effect = Effect::Blur { sigma: shadow.blur };
+        let outset = effect.outset();
+        let mut layer = Pixmap::covering(bounds.inset(-outset, -outset, -outset, -outset));
+        let mask = Mask::fill(&outline, FillRule::NonZero, layer.bounds());
+        raster::paint(&mut layer, &mask, None, |_| shadow.color);
+        effect.apply(&mut layer);
+        raster::composite(self.compositor.target(), &layer, self.clips.last());
+    }
+
+    fn path(&mut self, path: &PaintedPath, transform: Affine) {
+        let tolerance = raster::tolerance(transform);
+        let outline = Outline::of(&path.verbs, tolerance);
+        if let Some(stroke) = &path.stroke {
+            let stroked = raster::stroke(&outline, stroke, tolerance);
+            if let Some(fill) = &path.fill {
+                self.fill(outline, path.fill_rule, transform, |point| {
+                    fill.color_at(point)
+                });
+            }
+            self.fill(stroked, FillRule::NonZero, transform, |point| {
+                stroke.fill.color_at(point)
+            });
+        } else if let Some(fill) = &path.fill {
+            self.fill(outline, path.fill_rule, transform, |point| {
+                fill.color_at(point)
+            });
+        }
+    }
+
+    fn image(&mut self, image: &PaintedImage, transform: Affine) {
+        let tolerance = raster::tolerance(transform);
+        for quad in &image.quads {
+            let outline = Outline::of(&raster::rect(quad.target), tolerance);
+            self.fill(outline, FillRule::NonZero, transform, |point| {
+                sample(&image.image, quad, point)
+            });
+        }
+    }
+
+    /// Paint the peaks of every column, with the RMS on top of them.
+    fn waveform(&mut self, waveform: &PaintedWaveform, transform: Affine) {
+        let mut peaks = Vec::new();
+        let mut rms = Vec::new();
+        for lane in &waveform.lanes {
+            let (lane_top, lane_bottom) = (lane.rect.y, lane.rect.bottom());
+            for (column, peak) in lane.peaks.iter().enumerate() {
+                let Some(peak) = peak else {
+                    continue;
+                };
+                #[allow(
+                    clippy::cast_precision_loss,
+                    reason = "column counts are far below 2^24"
+                )]
+                let left = lane.rect.x + column as f32;
+                let right = lane.rect.right().min(left + 1.0);
+                // Silence still draws a line a pixel high.
+                let (top, bottom) = (lane.y(peak.max), lane.y(peak.min));
+                let middle = f32::midpoint(top, bottom);
+                let (top, bottom) = (top.min(middle - 0.5), bottom.max(middle + 0.5));
+                let (top, bottom) = (top.max(lane_top), bottom.min(lane_bottom));
+                peaks.extend(raster::rect(Rect::new(
+                    left,
+                    top,
+                    right - left,
+                    bottom - top,
+                )));
+                let rms_top = lane.y(peak.rms).max(top);
+                let rms_bottom = lane.y(-peak.rms).min(bottom);
+                if rms_bottom > rms_top {
+                    let rect = Rect::new(left, rms_top, right - left, rms_bottom - rms_top);
+                    rms.extend(raster::rect(rect));
+                }
+            }
+        }
+        let tolerance = raster::tolerance(transform);
+        let mut fill = |verbs: &[PathVerb], paint: &Fill| {
+            let outline = Outline::of(verbs, tolerance);
+            self.fill(outline, FillRule::NonZero, transform, |point| {
+                paint.color_at(point)
+            });
+        };
+        fill(&peaks, &waveform.fill);
+        if let Some(rms_fill) = &waveform.rms_fill {
+            fill(&rms, rms_fill);
+        }
+    }
+}
+
+/// Bilinear sample of an image quad at a point of its target, in premultiplied linear light.
+/// Samples never reach outside the source of the quad, so neighboring quads do not bleed.
+fn sample(image: &DecodedImage, quad: &ImageQuad, point: Point) -> LinearRgba {
+    let (source, target) = (quad.source, quad.target);
+    let u = (point.x - target.x).mul_add(source.width / target.width, source.x) - 0.5;
+    let v = (point.y - target.y).mul_add(source.height / target.height, source.y) - 0.5;
+    #[allow(
+        clippy::cast_possible_truncation,
+        reason = "image dimensions are far below 2^31"
+    )]
+    let limits = |start: f32, end: f32, size: u32| {
+        let last = i64::from(size) - 1;
+        (
+            (start.floor() as i64).clamp(0, last),
+            ((end.ceil() as i64) - 1).clamp(0, last),
+        )
+    };
+    let (min_x, max_x) = limits(source.x, source.right(), image.width);
+    let (min_y, max_y) = limits(source.y, source.bottom(), image.height);
+    let texel = |x: i64, y: i64| {
+        let (x, y) = (x.clamp(min_x, max_x), y.clamp(min_y, max_y));
+        let index = usize::try_from((y * i64::from(image.width) + x) * 4).unwrap_or_default();
+        image
+            .pixels
+            .get(index..index + 4)
+            .and_then(|bytes| <[u8; 4]>::try_from(bytes).ok())
+            .map_or_else(LinearRgba::default, LinearRgba::from_srgba8)
+    };
+    let (x, y) = (u.floor(), v.floor());
+    let (fx, fy) = (u - x, v - y);
+    #[allow(
+        clippy::cast_possible_truncation,
+        reason = "sample positions are clamped to the image right after"
+    )]
+    let (x, y) = (x as i64, y as i64);
+    let top = mix(texel(x, y), texel(x + 1, y), fx);
+    let bottom = mix(texel(x, y + 1), texel(x + 1, y + 1), fx);
+    mix(top, bottom, fy)
+}
+
+fn mix(from: LinearRgba, to: LinearRgba, t: f32) -> LinearRgba {
+    let mix = |a: f32, b: f32| (b - a).mul_add(t, a);
+    LinearRgba {
+        r: mix(from.r, to.r),
+        g: mix(from.g, to.g),
+        b: mix(from.b, to.b),
+        a: mix(from.a, to.a),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    /// Two by one pixels, opaque black on the left and white on the right.
+    fn image() -> DecodedImage {
+        DecodedImage {
+            width: 2,
+            height: 1,
+            pixels: Arc::from([0, 0, 0, 255, 255, 255, 255, 255].as_slice()),
+        }
+    }
+
+    #[test]
+    fn test_sample_image() {
+        let quad = ImageQuad {
+            source: Rect::new(0.0, 0.0, 2.0, 1.0),
+            target: Rect::new(0.0, 0.0, 4.0, 2.0),
+        };
+        let red = |point| sample(&image(), &quad, point).r;
+        // Texel centers keep their color, points between them blend in linear light.
+        assert!(red(Point::new(1.0, 1.0)).abs() < 1e-6);
+        assert!((red(Point::new(3.0, 1.0)) - 1.0).abs() < 1e-6);
+        assert!((red(Point::new(2.0, 1.0)) - 0.5).abs() < 1e-6);
+        // Edges clamp instead of wrapping around.
+        assert!(red(Point::new(0.0, 0.0)).abs() < 1e-6);
+
+        // A quad of the left half never picks up the right half.
+        let left = ImageQuad {
+            source: Rect::new(0.0, 0.0, 1.0, 1.0),
+            target: Rect::new(0.0, 0.0, 4.0, 4.0),
+        };
+        assert!(sample(&image(), &left, Point::new(3.9, 2.0)).r.abs() < 1e-6);
+    }
+}
@@ -0,0 +1,134 @@
+use chipbox_scene::{
+    FillRule, GlyphRun, LayoutNode, PathVerb, Point, Rect, TextElement, TextRun, TextStyle,
+    TextSystem,
+};
+use skrifa::{
+    FontRef, GlyphId, MetadataProvider,
+    instance::{LocationRef, Size},
+    metrics::Decoration,
+    outline::{DrawSettings, OutlinePen},
+};
+
+use super::Painter;
+use crate::raster::{self, Outline};
+
+impl Painter<'_> {
+    /// Paint the glyphs of a text element laid out inside `bounds`.
+    pub(super) fn text(
+        &mut self,
+        element: &TextElement,
+        text: &TextSystem,
+        bounds: Rect,
+        node: &LayoutNode,
+    ) {
+        let runs = TextRun::of(element, &self.ctx);
+        let style = TextStyle::of(element, &self.ctx);
+        // Same arguments as in the layout pass, so the cached layout is reused.
+        let layout = text.layout(&runs, &style, Some(bounds.width));
+        let tolerance = raster::tolerance(node.transform);
+        for line in &layout.lines {
+            let baseline = node.rect.y + line.baseline;
+            for glyphs in &line.runs {
+                let Some(run) = runs.get(glyphs.span) else {
+                    continue;
+                };
+                let Some(data) = text.font_data(glyphs.font) else {
+                    continue;
+                };
+                let font = match FontRef::from_index(data.data(), data.index()) {
+                    Ok(font) => font,
+                    Err(err) => {
+                        tracing::warn!(%err, "skipped glyphs of unreadable font");
+                        continue;
+                    }
+                };
+                let mut verbs = outlines(&font, glyphs, Point::new(node.rect.x, baseline));
+                if run.style.underline {
+                    verbs.extend(underline(&font, glyphs, Point::new(node.rect.x, baseline)));
+                }
+                let color = run.style.color.to_premultiplied();
+                let outline = Outline::of(&verbs, tolerance);
+                self.fill(outline, FillRule::NonZero, node.transform, |_| color);
+            }
+        }
+    }
+}
+
+/// Outlines of every glyph of a run, relative to the start of the baseline at `origin`.
+fn outlines(font: &FontRef, run: &GlyphRun, origin: Point) -> Vec<PathVerb> {
+    let glyphs = font.outline_glyphs();
+    let settings = || DrawSettings::unhinted(Size::new(run.size), LocationRef::default());
+    let mut pen = GlyphPen::default();
+    for glyph in &run.glyphs {
+        let Some(outline) = glyphs.get(GlyphId::new(u32::from(glyph.id))) else {
+            continue;
+        };
+        pen.origin = Point::new(origin.x + glyph.x, origin.y + glyph.y);
+        if let Err(err) = outline.draw(settings(), &mut pen) {
+            tracing::warn!(%err, glyph = glyph.id, "skipped glyph");
+        }
+    }
+    pen.verbs
+}
+
+/// A line under the glyphs of a run, placed as the font suggests.
+fn underline(font: &FontRef, run: &GlyphRun, origin: Point) -> Vec<PathVerb> {
+    let (Some(first), Some(last)) = (run.glyphs.first(), run.glyphs.last()) else {
+        return Vec::new();
+    };
+    // Glyphs are in visual order, so the run spans from the first to the end of the last.
+    let left = first.x.min(last.x);
+    let right = (last.x + last.advance).max(first.x + first.advance);
+    let Decoration { offset, thickness } = font
+        .metrics(Size::new(run.size), LocationRef::default())
+        .underline
+        .unwrap_or(Decoration {
+            offset: -run.size / 10.0,
+            thickness: run.size / 14.0,
+        });
+    // The offset is the top of the line above the baseline.
+    let top = origin.y + first.y - offset;
+    raster::rect(Rect::new(
+        origin.x + left,
+        top,
+        right - left,
+        thickness.max(1.0),
+    ))
+}
+
+/// Collects glyph outlines as path verbs, flipped to point down and moved to the glyph origin.
+#[derive(Default)]
+struct GlyphPen {
+    origin: Point,
+    verbs: Vec<PathVerb>,
+}
+
+impl GlyphPen {
+    fn point(&self, x: f32, y: f32) -> Point {
+        Point::new(self.origin.x + x, self.origin.y - y)
+    }
+}
+
+impl OutlinePen for GlyphPen {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.verbs.push(PathVerb::MoveTo(self.point(x, y)));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.verbs.push(PathVerb::LineTo(self.point(x, y)));
+    }
+
+    fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+        let verb = PathVerb::QuadTo(self.point(cx0, cy0), self.point(x, y));
+        self.verbs.push(verb);
+    }
+
+    fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+        let verb = PathVerb::CubicTo(self.point(cx0, cy0), self.point(cx1, cy1), self.point(x, y));
+        self.verbs.push(verb);
+    }
+
+    fn close(&mut self) {
+        self.verbs.push(PathVerb::Close);
+    }
+}
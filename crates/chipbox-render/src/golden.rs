@@ -0,0 +1,494 @@
+//! Golden-image tests, comparing rendered scenes with reference images.
+//!
+//! References are stored as `golden/<name>.png` in the crate directory.
+//! A missing reference fails the test. Setting `CHIPBOX_BLESS=1` writes every
+//! reference instead of comparing with it, to add a case or accept an intended change.
+//! When an image differs, the rendered image and a map of the differing pixels
+//! are written to `target/golden` for inspection.
+//!
+//! Images are compared perceptually: pixels differ when their distance in the YIQ
+//! color space exceeds a threshold, and a test fails when too many pixels differ.
+//! This tolerates the tiny changes of anti-aliased edges between platforms.
+
+use std::{
+    io::Cursor,
+    path::{Path, PathBuf},
+};
+
+use chipbox_scene::{BufferId, ImageCache, SampleBuffer, SampleBuffers, Scene, TextSystem};
+use image::{ImageFormat, Rgba, RgbaImage};
+use serde_json::{Value, json};
+
+use crate::{Resources, RgbaBuffer, render};
+
+/// Largest possible squared YIQ distance of two colors.
+const MAX_DELTA: f32 = 35215.0;
+
+/// How different a rendered image may be from its reference.
+#[derive(Clone, Copy, Debug)]
+struct Tolerance {
+    /// Largest perceptual distance of matching pixels, from `0` for identical colors
+    /// to `1` for the most distant ones.
+    threshold: f32,
+    /// Largest share of pixels that may differ.
+    max_ratio: f32,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            threshold: 0.1,
+            max_ratio: 0.001,
+        }
+    }
+}
+
+/// The perceptual distance of two pixels from `0` to `1`, after blending both onto white.
+fn delta(first: [u8; 4], second: [u8; 4]) -> f32 {
+    let yiq = |[r, g, b, a]: [u8; 4]| {
+        let alpha = f32::from(a) / 255.0;
+        let [r, g, b] = [r, g, b].map(|c| (f32::from(c) - 255.0).mul_add(alpha, 255.0));
+        [
+            r.mul_add(0.298_895_3, g.mul_add(0.586_622_5, b * 0.114_482_2)),
+            r.mul_add(0.595_978, g.mul_add(-0.274_176_1, b * -0.321_801_9)),
+            r.mul_add(0.211_470_2, g.mul_add(-0.522_617_2, b * 0.311_147_1)),
+        ]
+    };
+    let ([y1, i1, q1], [y2, i2, q2]) = (yiq(first), yiq(second));
+    let (dy, di, dq) = (y1 - y2, i1 - i2, q1 - q2);
+    (dy * dy).mul_add(0.5053, (di * di).mul_add(0.299, dq * dq * 0.1957)) / MAX_DELTA
+}
+
+/// Whether each pixel of two images of the same size differs beyond the threshold.
+fn differing(expected: &RgbaBuffer, actual: &RgbaBuffer, threshold: f32) -> Vec<bool> {
+    expected
+        .pixels
+        .chunks_exact(4)
+        .zip(actual.pixels.chunks_exact(4))
+        .map(|(a, b)| {
+            let pixel = |bytes: &[u8]| <[u8; 4]>::try_from(bytes).unwrap_or_default();
+            delta(pixel(a), pixel(b)) > threshold * threshold
+        })
+        .collect()
+}
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("golden")
+}
+
+fn output_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../../target/golden")
+}
+
+fn env_flag(name: &str) -> bool {
+    std::env::var_os(name).is_some_and(|value| !value.is_empty() && value != "0")
+}
+
+fn save(path: &Path, buffer: &RgbaBuffer) {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).expect("create image directory");
+    }
+    RgbaImage::from_raw(buffer.width, buffer.height, buffer.pixels.clone())
+        .expect("buffer matching its size")
+        .save(path)
+        .unwrap_or_else(|err| panic!("write {}: {err}", path.display()));
+}
+
+fn load(path: &Path) -> Option<RgbaBuffer> {
+    let image = image::open(path).ok()?.into_rgba8();
+    Some(RgbaBuffer {
+        width: image.width(),
+        height: image.height(),
+        pixels: image.into_raw(),
+    })
+}
+
+/// Faded reference with the differing pixels in red.
+fn diff_image(expected: &RgbaBuffer, differing: &[bool]) -> RgbaBuffer {
+    let pixels = expected
+        .pixels
+        .chunks_exact(4)
+        .zip(differing)
+        .flat_map(|(pixel, differs)| {
+            if *differs {
+                return [255, 0, 0, 255];
+            }
+            let pixel = <[u8; 4]>::try_from(pixel).unwrap_or_default();
+            let gray = (delta(pixel, [0, 0, 0, 0]).sqrt() * 255.0).round();
+            #[allow(
+                clippy::cast_possible_truncation,
+                clippy::cast_sign_loss,
+                reason = "the gray level is in 0..=255"
+            )]
+            let gray = (255.0 - gray / 4.0) as u8;
+            [gray, gray, gray, 255]
+        })
+        .collect();
+    RgbaBuffer {
+        width: expected.width,
+        height: expected.height,
+        pixels,
+    }
+}
+
+/// Compare a rendered image with its reference, or write the reference when blessing.
+/// ## Panics
+/// - If the images differ beyond the tolerance, or the reference is missing.
+#[track_caller]
+fn check(name: &str, actual: &RgbaBuffer, tolerance: Tolerance) {
+    let path = golden_dir().join(format!("{name}.png"));
+    if env_flag("CHIPBOX_BLESS") {
+        save(&path, actual);
+        return;
+    }
+    let Some(expected) = load(&path) else {
+        panic!(
+            "missing golden image {}, run the tests with CHIPBOX_BLESS=1 to create it",
+            path.display()
+        );
+    };
+    let output = output_dir().join(format!("{name}.png"));
+    if (expected.width, expected.height) != (actual.width, actual.height) {
+        save(&output, actual);
+        panic!(
+            "{name}: rendered {}x{}, expected {}x{}, see {}",
+            actual.width,
+            actual.height,
+            expected.width,
+            expected.height,
+            output.display()
+        );
+    }
+    let differing = differing(&expected, actual, tolerance.threshold);
+    let count = differing.iter().filter(|differs| **differs).count();
+    #[allow(
+        clippy::cast_precision_loss,
+        reason = "test images are far smaller than 2^24 pixels"
+    )]
+    let ratio = count as f32 / differing.len().max(1) as f32;
+    if ratio > tolerance.max_ratio {
+        save(&output, actual);
+        let diff = output_dir().join(format!("{name}.diff.png"));
+        save(&diff, &diff_image(&expected, &differing));
+        panic!(
+            "{name}: {count} pixels differ from {}, see {} and {}",
+            path.display(),
+            output.display(),
+            diff.display()
+        );
+    }
+}
+
+fn scene(width: u32, height: u32, children: Value) -> Scene {
+    let mut scene = json!({ "pxWidth": width, "pxHeight": height });
+    scene["children"] = children;
+    serde_json::from_value(scene).expect("valid scene")
+}
+
+/// Elements in a box of `width` by `height` pixels with its top left corner at `x`, `y`.
+fn place(x: u32, y: u32, width: u32, height: u32, children: Value) -> Value {
+    let mut sized = json!({
+        "type": "box",
+        "width": format!("{width}px"),
+        "height": format!("{height}px"),
+    });
+    sized["children"] = children;
+    json!({
+        "type": "margin",
+        "left": format!("{x}px"),
+        "top": format!("{y}px"),
+        "children": [sized],
+    })
+}
+
+#[track_caller]
+fn check_scene(name: &str, scene: &Scene, resources: Resources) {
+    let layout = scene.layout_with(resources).expect("layout");
+    check(
+        name,
+        &render(scene, &layout, resources),
+        Tolerance::default(),
+    );
+}
+
+#[test]
+fn test_delta() {
+    let (black, white) = ([0, 0, 0, 255], [255, 255, 255, 255]);
+    assert!(delta(black, black).abs() < 1e-6);
+    assert!(delta(black, white) > 0.9);
+    // Transparent pixels look like the white background.
+    assert!(delta([0, 0, 0, 0], white).abs() < 1e-6);
+    let (red, blue) = ([255, 0, 0, 255], [0, 0, 255, 255]);
+    assert!(delta(red, blue) > delta(red, [250, 0, 0, 255]));
+}
+
+#[test]
+fn test_differing() {
+    let buffer = |pixels: Vec<u8>| RgbaBuffer {
+        width: 3,
+        height: 1,
+        pixels,
+    };
+    let expected = buffer(vec![0, 0, 0, 255, 128, 128, 128, 255, 0, 0, 0, 0]);
+    let actual = buffer(vec![4, 4, 4, 255, 0, 0, 0, 255, 255, 255, 255, 255]);
+    assert_eq!(
+        differing(&expected, &actual, Tolerance::default().threshold),
+        [false, true, false]
+    );
+    assert_eq!(differing(&expected, &actual, 1.0), [false; 3]);
+}
+
+#[test]
+fn test_golden_rect() {
+    let solid = json!([{
+        "type": "rect",
+        "fill": "#3a7bd5",
+        "radius": "8px",
+    }]);
+    let gradient = json!([{
+        "type": "rect",
+        "fill": { "type": "linear", "angle": 90, "stops": [
+            { "color": "#ff8000" },
+            { "offset": 1, "color": "#8000ff" },
+        ] },
+        "radius": { "topLeft": "12px", "bottomRight": "4px" },
+        "border": { "width": "2px", "paint": "#202020" },
+    }]);
+    let shadowed = json!([{
+        "type": "rect",
+        "fill": "#fff",
+        "radius": "4px",
+        "shadows": [
+            { "y": "2px", "blur": "3px", "color": "#00000080" },
+            { "x": "-2px", "spread": "1px", "color": "#ff000060" },
+        ],
+    }]);
+    let dashed = json!([{
+        "type": "rect",
+        "fill": { "type": "radial", "stops": [
+            { "color": "#fff" },
+            { "offset": 1, "color": "#0a0" },
+        ] },
+        "border": { "width": "2px", "paint": "#000", "dash": ["4px", "2px"] },
+    }]);
+    let scene = scene(
+        96,
+        64,
+        json!([
+            place(4, 4, 40, 24, solid),
+            place(52, 4, 40, 24, gradient),
+            place(8, 36, 32, 20, shadowed),
+            place(52, 36, 40, 20, dashed),
+        ]),
+    );
+    check_scene("rect", &scene, Resources::default());
+}
+
+#[test]
+fn test_golden_path() {
+    let star = json!([{
+        "type": "path",
+        "data": "M12 1 L15 9 L23 9 L17 14 L19 22 L12 17 L5 22 L7 14 L1 9 L9 9 Z",
+        "viewBox": { "width": 24, "height": 24 },
+        "fill": "#ffcc00",
+        "fillRule": "evenOdd",
+        "stroke": { "width": "2px", "paint": "#804000", "join": "round" },
+    }]);
+    let dashed = json!([{
+        "type": "path",
+        "data": "M2 20 L8 4 L14 20 Q20 2 22 12 C23 18 18 22 12 22",
+        "viewBox": { "width": 24, "height": 24 },
+        "stroke": {
+            "width": "3px",
+            "paint": "#0060c0",
+            "cap": "round",
+            "join": "miter",
+            "dash": ["6px", "3px"],
+        },
+    }]);
+    let scene = scene(
+        96,
+        48,
+        json!([place(4, 4, 40, 40, star), place(52, 4, 40, 40, dashed)]),
+    );
+    check_scene("path", &scene, Resources::default());
+}
+
+#[test]
+fn test_golden_text() {
+    let text = TextSystem::bundled();
+    let paragraph = json!([{
+        "type": "text",
+        "children": [
+            "Golden ",
+            { "type": "span", "children": ["images"], "color": "#c03000", "underline": true },
+            " of wrapped text",
+            { "type": "span", "children": ["2"], "baselineShift": 5 },
+        ],
+        "font": "Inter",
+        "weight": 400,
+        "size": 16,
+        "color": "#202020",
+    }]);
+    let scene = scene(160, 64, json!([place(4, 4, 152, 56, paragraph)]));
+    let resources = Resources {
+        text: Some(&text),
+        ..Resources::default()
+    };
+    check_scene("text", &scene, resources);
+}
+
+#[test]
+fn test_golden_image() {
+    let checker = RgbaImage::from_fn(4, 4, |x, y| {
+        if (x + y) % 2 == 0 {
+            Rgba([255, 64, 0, 255])
+        } else {
+            Rgba([0, 64, 255, 128])
+        }
+    });
+    let mut png = Cursor::new(Vec::new());
+    checker
+        .write_to(&mut png, ImageFormat::Png)
+        .expect("encode test image");
+    let mut images = ImageCache::default();
+    images.register("checker", png.into_inner());
+    let source = json!({ "type": "asset", "id": "checker" });
+    let filled = json!([{
+        "type": "image",
+        "source": source,
+        "fit": "fill",
+        "width": "40px",
+        "height": "40px",
+    }]);
+    let covered = json!([{
+        "type": "image",
+        "source": source,
+        "fit": "cover",
+        "width": "40px",
+        "height": "20px",
+    }]);
+    let scene = scene(
+        96,
+        48,
+        json!([place(4, 4, 40, 40, filled), place(52, 4, 40, 20, covered)]),
+    );
+    let resources = Resources {
+        images: Some(&images),
+        ..Resources::default()
+    };
+    check_scene("image", &scene, resources);
+}
+
+#[test]
+fn test_golden_waveform() {
+    #[allow(
+        clippy::cast_precision_loss,
+        reason = "the buffer is far shorter than 2^24 frames"
+    )]
+    let channel = |frequency: f32| -> Vec<f32> {
+        (0..4800)
+            .map(|frame| {
+                let t = frame as f32 / 4800.0;
+                (t * frequency * std::f32::consts::TAU).sin() * (1.0 - t)
+            })
+            .collect()
+    };
+    let mut buffers = SampleBuffers::default();
+    buffers.insert(
+        BufferId("tone".into()),
+        SampleBuffer::new(vec![channel(9.0), channel(23.5)]),
+    );
+    let waveform = json!([{
+        "type": "waveform",
+        "buffer": "tone",
+        "fill": "#40a0ff",
+        "rmsFill": "#1060c0",
+    }]);
+    let scene = scene(128, 64, json!([place(0, 0, 128, 64, waveform)]));
+    let resources = Resources {
+        buffers: Some(&buffers),
+        ..Resources::default()
+    };
+    check_scene("waveform", &scene, resources);
+}
+
+#[test]
+fn test_golden_texture() {
+    let squares = |texture: Value| {
+        let red = json!([{ "type": "rect", "fill": "#e04040" }]);
+        let blue = json!([{ "type": "rect", "fill": "#4040e0" }]);
+        json!([
+            place(0, 0, 24, 24, red),
+            place(12, 12, 24, 24, blue),
+            texture
+        ])
+    };
+    let faded = squares(json!({ "type": "texture", "opacity": 0.5 }));
+    let blurred =
+        squares(json!({ "type": "texture", "shader": { "type": "blur", "radius": "2px" } }));
+    let scene = scene(
+        96,
+        48,
+        json!([place(4, 4, 36, 36, faded), place(52, 4, 36, 36, blurred)]),
+    );
+    check_scene("texture", &scene, Resources::default());
+}
+
+#[test]
+fn test_golden_transform() {
+    let rotated = json!([
+        { "type": "transform", "rotate": 30, "scaleX": 1.25 },
+        { "type": "rect", "fill": "#20a060", "border": { "width": "2px", "paint": "#000" } },
+    ]);
+    let scene = scene(64, 64, json!([place(16, 16, 32, 32, rotated)]));
+    check_scene("transform", &scene, Resources::default());
+}
+
+#[test]
+fn test_golden_scroll() {
+    let rows: Vec<Value> = (0..6)
+        .map(|row| {
+            let color = if row % 2 == 0 { "#d0d0d0" } else { "#606060" };
+            place(
+                0,
+                row * 12,
+                48,
+                12,
+                json!([{ "type": "rect", "fill": color }]),
+            )
+        })
+        .collect();
+    let scroll = json!([{
+        "type": "scroll",
+        "axis": "y",
+        "offsetY": "6px",
+        "children": rows,
+    }]);
+    let scene = scene(64, 48, json!([place(8, 8, 48, 32, scroll)]));
+    check_scene("scroll", &scene, Resources::default());
+}
+
+#[test]
+fn test_golden_stacking() {
+    // Stacking elements order their parent, the margin, among its siblings.
+    let square = |x: u32, color: &str, z_index: i32| {
+        let rect = json!({ "type": "rect", "fill": color, "radius": "4px" });
+        let mut square = place(x, 8, 24, 24, json!([rect]));
+        square["children"]
+            .as_array_mut()
+            .expect("array of elements")
+            .push(json!({ "type": "stacking", "zIndex": z_index }));
+        square
+    };
+    let scene = scene(
+        64,
+        40,
+        json!([
+            square(4, "#e04040", 2),
+            square(16, "#40a040", 1),
+            square(28, "#4040e0", 0),
+        ]),
+    );
+    check_scene("stacking", &scene, Resources::default());
+}
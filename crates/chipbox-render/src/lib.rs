@@ -1 +1,123 @@
+//! Rendering of laid out scenes.
+//!
+//! [`render`] rasterizes a scene on the CPU without any graphics device,
+//! which makes it the reference for the output of every scene feature
+//! and is what the golden-image tests compare against.
 
+mod draw;
+#[cfg(test)]
+mod golden;
+mod raster;
+
+pub use chipbox_scene::Resources;
+use chipbox_scene::{LayoutTree, Pixmap, Scene};
+
+/// Pixels of a rendered scene.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RgbaBuffer {
+    pub width: u32,
+    pub height: u32,
+    /// Rows of straight (not premultiplied) sRGB RGBA bytes, from the top.
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaBuffer {
+    /// The bytes of the pixel at `x`, `y`, or transparent black outside the buffer.
+    #[must_use]
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        if x >= self.width || y >= self.height {
+            return [0; 4];
+        }
+        let index = usize::try_from((u64::from(y) * u64::from(self.width) + u64::from(x)) * 4)
+            .unwrap_or(usize::MAX);
+        self.pixels
+            .get(index..index + 4)
+            .and_then(|bytes| bytes.try_into().ok())
+            .unwrap_or_default()
+    }
+}
+
+impl From<&Pixmap> for RgbaBuffer {
+    fn from(pixmap: &Pixmap) -> Self {
+        Self {
+            width: pixmap.width(),
+            height: pixmap.height(),
+            pixels: pixmap
+                .pixels()
+                .iter()
+                .flat_map(|pixel| pixel.to_srgba8())
+                .collect(),
+        }
+    }
+}
+
+/// Rasterize a scene laid out as `layout` to 8-bit sRGB.
+#[must_use]
+pub fn render(scene: &Scene, layout: &LayoutTree, resources: Resources) -> RgbaBuffer {
+    RgbaBuffer::from(&render_pixmap(scene, layout, resources))
+}
+
+/// Rasterize a scene laid out as `layout` to premultiplied linear light,
+/// which keeps the full precision for further compositing.
+#[must_use]
+pub fn render_pixmap(scene: &Scene, layout: &LayoutTree, resources: Resources) -> Pixmap {
+    draw::Painter::new(scene, resources).paint(scene, layout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_rect() {
+        let scene: Scene = serde_json::from_str(
+            r##"{
+                "pxWidth": 8,
+                "pxHeight": 6,
+                "children": [{
+                    "type": "margin",
+                    "left": "2px",
+                    "top": "1px",
+                    "children": [{
+                        "type": "box",
+                        "width": "4.5px",
+                        "height": "3px",
+                        "children": [{ "type": "rect", "fill": "#ff800080" }]
+                    }]
+                }]
+            }"##,
+        )
+        .expect("valid scene");
+        let layout = scene.layout_with(Resources::default()).expect("layout");
+        let buffer = render(&scene, &layout, Resources::default());
+        assert_eq!((buffer.width, buffer.height), (8, 6));
+        assert_eq!(buffer.pixels.len(), 8 * 6 * 4);
+        assert_eq!(buffer.pixel(0, 0), [0; 4]);
+        assert_eq!(buffer.pixel(2, 1), [255, 128, 0, 128]);
+        assert_eq!(buffer.pixel(5, 3), [255, 128, 0, 128]);
+        // Half covered pixels keep their color at half the alpha.
+        assert_eq!(buffer.pixel(6, 2), [255, 128, 0, 64]);
+        assert_eq!(buffer.pixel(2, 4), [0; 4]);
+        assert_eq!(buffer.pixel(8, 0), [0; 4]);
+    }
+
+    #[test]
+    fn test_render_empty_layer() {
+        let scene: Scene = serde_json::from_str(
+            r#"{
+                "pxWidth": 4,
+                "pxHeight": 4,
+                "children": [{
+                    "type": "box",
+                    "width": 0,
+                    "height": "4px",
+                    "children": [{ "type": "texture", "opacity": 0.5 }]
+                }]
+            }"#,
+        )
+        .expect("valid scene");
+        let layout = scene.layout_with(Resources::default()).expect("layout");
+        let buffer = render(&scene, &layout, Resources::default());
+        assert!(buffer.pixels.iter().all(|byte| *byte == 0));
+    }
+}
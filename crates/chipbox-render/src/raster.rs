@@ -0,0 +1,587 @@
+//! Coverage rasterization of outlines on the CPU.
+//!
+//! Curves are flattened to polylines, and every pixel row is sampled along
+//! [`SUBSAMPLES`] scanlines with exact horizontal coverage along each of them.
+
+mod stroke;
+
+use chipbox_scene::{Affine, FillRule, LinearRgba, PathVerb, Pixmap, Point, Rect};
+
+pub use self::stroke::stroke;
+
+/// Maximum distance of flattened curves from the true outline, in device pixels.
+const TOLERANCE: f32 = 0.1;
+/// Scanlines sampled per pixel row.
+const SUBSAMPLES: usize = 16;
+/// Distance of cubic control points approximating a quarter circle of unit radius.
+const KAPPA: f32 = 0.552_284_8;
+
+/// Flattening tolerance in the space mapped to device pixels by `transform`.
+#[must_use]
+pub fn tolerance(transform: Affine) -> f32 {
+    let scale = transform
+        .a
+        .hypot(transform.b)
+        .max(transform.c.hypot(transform.d));
+    if scale > 0.0 && scale.is_finite() {
+        TOLERANCE / scale
+    } else {
+        TOLERANCE
+    }
+}
+
+/// A connected run of line segments.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Polyline {
+    pub points: Vec<Point>,
+    /// Whether the last point connects back to the first when stroked.
+    /// Filling always treats polylines as closed.
+    pub closed: bool,
+}
+
+/// Flattened subpaths of a path.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Outline {
+    pub polylines: Vec<Polyline>,
+}
+
+impl Outline {
+    /// Flatten path verbs, keeping curves within `tolerance` of the true outline.
+    #[must_use]
+    pub fn of(verbs: &[PathVerb], tolerance: f32) -> Self {
+        let mut outline = Self::default();
+        let mut current = Polyline::default();
+        let mut last = Point::default();
+        for verb in verbs {
+            // Drawing after a close continues from the start of the closed subpath.
+            if current.points.is_empty() && !matches!(verb, PathVerb::MoveTo(_) | PathVerb::Close) {
+                current.points.push(last);
+            }
+            match *verb {
+                PathVerb::MoveTo(point) => {
+                    outline.push(std::mem::take(&mut current));
+                    current.points.push(point);
+                }
+                PathVerb::LineTo(point) => current.points.push(point),
+                PathVerb::QuadTo(control, end) => {
+                    let deviation = second_difference(last, control, end);
+                    let count = segments(deviation / 4.0, tolerance);
+                    current.points.extend((1..=count).map(|i| {
+                        let t = step(i, count);
+                        let u = 1.0 - t;
+                        bezier([(last, u * u), (control, 2.0 * u * t), (end, t * t)])
+                    }));
+                }
+                PathVerb::CubicTo(c1, c2, end) => {
+                    let deviation =
+                        second_difference(last, c1, c2).max(second_difference(c1, c2, end));
+                    let count = segments(deviation * 0.75, tolerance);
+                    current.points.extend((1..=count).map(|i| {
+                        let t = step(i, count);
+                        let u = 1.0 - t;
+                        bezier([
+                            (last, u * u * u),
+                            (c1, 3.0 * u * u * t),
+                            (c2, 3.0 * u * t * t),
+                            (end, t * t * t),
+                        ])
+                    }));
+                }
+                PathVerb::Close => {
+                    current.closed = true;
+                    last = current.points.first().copied().unwrap_or(last);
+                    outline.push(std::mem::take(&mut current));
+                }
+            }
+            if let Some(point) = current.points.last() {
+                last = *point;
+            }
+        }
+        outline.push(current);
+        outline
+    }
+
+    /// Keep subpaths with at least one segment.
+    /// Segments of zero length still get caps when stroked.
+    fn push(&mut self, polyline: Polyline) {
+        if polyline.points.len() > 1 {
+            self.polylines.push(polyline);
+        }
+    }
+
+    /// The outline with every point mapped by `transform`.
+    #[must_use]
+    pub fn transformed(mut self, transform: Affine) -> Self {
+        if transform != Affine::IDENTITY {
+            for point in self.polylines.iter_mut().flat_map(|p| &mut p.points) {
+                *point = transform.apply(*point);
+            }
+        }
+        self
+    }
+
+    /// The smallest rectangle containing every point, if there are any.
+    #[must_use]
+    pub fn bounds(&self) -> Option<Rect> {
+        let mut points = self.polylines.iter().flat_map(|p| &p.points);
+        let first = *points.next()?;
+        let (min, max) = points.fold((first, first), |(min, max), point| {
+            (
+                Point::new(min.x.min(point.x), min.y.min(point.y)),
+                Point::new(max.x.max(point.x), max.y.max(point.y)),
+            )
+        });
+        Some(Rect::from_points(min.x, min.y, max.x, max.y))
+    }
+}
+
+/// Largest distance of the middle point from the midpoint of its neighbors, doubled.
+fn second_difference(a: Point, b: Point, c: Point) -> f32 {
+    2.0f32
+        .mul_add(-b.x, a.x + c.x)
+        .hypot(2.0f32.mul_add(-b.y, a.y + c.y))
+}
+
+/// Number of segments keeping a curve of the given deviation within the tolerance.
+fn segments(deviation: f32, tolerance: f32) -> usize {
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        reason = "the count is clamped to a small positive range"
+    )]
+    let count = (deviation / tolerance).sqrt().ceil().clamp(1.0, 256.0) as usize;
+    if count == 0 { 1 } else { count }
+}
+
+#[allow(clippy::cast_precision_loss, reason = "at most 256 segments")]
+fn step(i: usize, count: usize) -> f32 {
+    i as f32 / count as f32
+}
+
+fn bezier<const N: usize>(weighted: [(Point, f32); N]) -> Point {
+    weighted
+        .into_iter()
+        .fold(Point::default(), |sum, (point, weight)| {
+            Point::new(
+                point.x.mul_add(weight, sum.x),
+                point.y.mul_add(weight, sum.y),
+            )
+        })
+}
+
+/// Verbs of a rectangle with rounded corners, clockwise from the top left.
+/// Radii are clockwise from the top left as well.
+#[must_use]
+pub fn rounded_rect(rect: Rect, radii: [f32; 4]) -> Vec<PathVerb> {
+    let [top_left, top_right, bottom_right, bottom_left] = radii.map(|r| r.max(0.0));
+    let (left, top, right, bottom) = (rect.x, rect.y, rect.right(), rect.bottom());
+    let mut verbs = vec![PathVerb::MoveTo(Point::new(left + top_left, top))];
+    // Each corner runs from the end of one side to the start of the next, around the vertex.
+    let mut corner = |start: Point, vertex: Point, end: Point, radius: f32| {
+        verbs.push(PathVerb::LineTo(start));
+        if radius > 0.0 {
+            let handle = |from: Point| {
+                Point::new(
+                    (vertex.x - from.x).mul_add(KAPPA, from.x),
+                    (vertex.y - from.y).mul_add(KAPPA, from.y),
+                )
+            };
+            verbs.push(PathVerb::CubicTo(handle(start), handle(end), end));
+        }
+    };
+    corner(
+        Point::new(right - top_right, top),
+        Point::new(right, top),
+        Point::new(right, top + top_right),
+        top_right,
+    );
+    corner(
+        Point::new(right, bottom - bottom_right),
+        Point::new(right, bottom),
+        Point::new(right - bottom_right, bottom),
+        bottom_right,
+    );
+    corner(
+        Point::new(left + bottom_left, bottom),
+        Point::new(left, bottom),
+        Point::new(left, bottom - bottom_left),
+        bottom_left,
+    );
+    corner(
+        Point::new(left, top + top_left),
+        Point::new(left, top),
+        Point::new(left + top_left, top),
+        top_left,
+    );
+    verbs.push(PathVerb::Close);
+    verbs
+}
+
+/// Verbs of a rectangle, clockwise from the top left.
+#[must_use]
+pub fn rect(rect: Rect) -> Vec<PathVerb> {
+    rounded_rect(rect, [0.0; 4])
+}
+
+/// A line segment of a filled outline, pointing down.
+#[derive(Clone, Copy, Debug)]
+struct Edge {
+    top: Point,
+    bottom: Point,
+    /// `1` if the outline runs down along the edge, `-1` if it runs up.
+    winding: i32,
+}
+
+impl Edge {
+    fn x_at(&self, y: f32) -> f32 {
+        let t = (y - self.top.y) / (self.bottom.y - self.top.y);
+        (self.bottom.x - self.top.x).mul_add(t, self.top.x)
+    }
+}
+
+/// Coverage of device pixels by a filled outline, from `0` to `1`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mask {
+    x: i32,
+    y: i32,
+    width: usize,
+    height: usize,
+    values: Vec<f32>,
+}
+
+impl Mask {
+    /// Rasterize an outline in device pixels, limited to the pixels `area` touches.
+    #[must_use]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss,
+        reason = "device coordinates are far below 2^24"
+    )]
+    pub fn fill(outline: &Outline, rule: FillRule, area: Rect) -> Self {
+        let Some(bounds) = outline.bounds() else {
+            return Self::empty();
+        };
+        let area = area.intersection(bounds);
+        let (x, y) = (area.x.floor(), area.y.floor());
+        let width = (area.right().ceil() - x).max(0.0) as usize;
+        let height = (area.bottom().ceil() - y).max(0.0) as usize;
+        let mut mask = Self {
+            x: x as i32,
+            y: y as i32,
+            width,
+            height,
+            values: vec![0.0; width * height],
+        };
+        if width == 0 || height == 0 {
+            return mask;
+        }
+
+        let mut edges: Vec<Edge> = outline
+            .polylines
+            .iter()
+            .filter(|polyline| polyline.points.len() > 2)
+            .flat_map(|polyline| {
+                let points = &polyline.points;
+                points.iter().zip(points.iter().cycle().skip(1))
+            })
+            .filter(|(a, b)| a.y.total_cmp(&b.y).is_ne())
+            .map(|(&a, &b)| {
+                if a.y < b.y {
+                    Edge {
+                        top: a,
+                        bottom: b,
+                        winding: 1,
+                    }
+                } else {
+                    Edge {
+                        top: b,
+                        bottom: a,
+                        winding: -1,
+                    }
+                }
+            })
+            .collect();
+        edges.sort_by(|a, b| a.top.y.total_cmp(&b.top.y));
+
+        let weight = 1.0 / SUBSAMPLES as f32;
+        let mut next = 0;
+        let mut active: Vec<Edge> = Vec::new();
+        let mut crossings: Vec<(f32, i32)> = Vec::new();
+        for (row, values) in mask.values.chunks_exact_mut(width).enumerate() {
+            for sample in 0..SUBSAMPLES {
+                let scanline = (sample as f32 + 0.5).mul_add(weight, y + row as f32);
+                while next < edges.len() && edges[next].top.y <= scanline {
+                    active.push(edges[next]);
+                    next += 1;
+                }
+                active.retain(|edge| edge.bottom.y > scanline);
+                crossings.clear();
+                crossings.extend(
+                    active
+                        .iter()
+                        .map(|edge| (edge.x_at(scanline), edge.winding)),
+                );
+                crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+                let mut winding = 0;
+                for pair in crossings.windows(2) {
+                    winding += pair[0].1;
+                    let inside = match rule {
+                        FillRule::NonZero => winding != 0,
+                        FillRule::EvenOdd => winding % 2 != 0,
+                    };
+                    if inside {
+                        add_span(values, pair[0].0 - x, pair[1].0 - x, weight);
+                    }
+                }
+            }
+        }
+        for value in &mut mask.values {
+            *value = value.clamp(0.0, 1.0);
+        }
+        mask
+    }
+
+    const fn empty() -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0,
+            values: Vec::new(),
+        }
+    }
+
+    /// Covered area in device pixels.
+    #[must_use]
+    #[allow(
+        clippy::cast_precision_loss,
+        reason = "device coordinates are far below 2^24"
+    )]
+    pub const fn bounds(&self) -> Rect {
+        Rect::new(
+            self.x as f32,
+            self.y as f32,
+            self.width as f32,
+            self.height as f32,
+        )
+    }
+
+    /// Coverage of the pixel at device coordinates, zero outside the mask.
+    #[must_use]
+    pub fn get(&self, x: i32, y: i32) -> f32 {
+        let column = usize::try_from(i64::from(x) - i64::from(self.x)).ok();
+        let row = usize::try_from(i64::from(y) - i64::from(self.y)).ok();
+        match (column, row) {
+            (Some(column), Some(row)) if column < self.width && row < self.height => {
+                self.values[row * self.width + column]
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Coverage by both masks.
+    #[must_use]
+    pub fn intersect(&self, other: &Self) -> Self {
+        let mut mask = self.clone();
+        for (index, value) in mask.values.iter_mut().enumerate() {
+            let (column, row) = self.position(index);
+            *value *= other.get(column, row);
+        }
+        mask
+    }
+
+    /// Device coordinates of the pixel at an index.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        reason = "device coordinates are far below 2^31"
+    )]
+    const fn position(&self, index: usize) -> (i32, i32) {
+        (
+            self.x + (index % self.width) as i32,
+            self.y + (index / self.width) as i32,
+        )
+    }
+
+    /// Covered pixels with their device coordinates.
+    pub fn covered(&self) -> impl Iterator<Item = (i32, i32, f32)> {
+        self.values
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| **value > 0.0)
+            .map(|(index, value)| {
+                let (x, y) = self.position(index);
+                (x, y, *value)
+            })
+    }
+}
+
+/// Add coverage of a horizontal span to a row of pixels starting at zero.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss,
+    reason = "the span is clamped to the row, which is far shorter than 2^24"
+)]
+fn add_span(row: &mut [f32], start: f32, end: f32, weight: f32) {
+    let len = row.len() as f32;
+    let (start, end) = (start.clamp(0.0, len), end.clamp(0.0, len));
+    if end <= start {
+        return;
+    }
+    let (first, last) = (start.floor() as usize, end.floor() as usize);
+    if first == last {
+        row[first] += (end - start) * weight;
+        return;
+    }
+    row[first] += (first as f32 + 1.0 - start) * weight;
+    for value in &mut row[first + 1..last] {
+        *value += weight;
+    }
+    if let Some(value) = row.get_mut(last) {
+        *value += (end - last as f32) * weight;
+    }
+}
+
+/// Draw a paint over the target where the mask covers it, faded by the clip.
+/// The paint is sampled at pixel centers in device coordinates.
+pub fn paint(
+    target: &mut Pixmap,
+    mask: &Mask,
+    clip: Option<&Mask>,
+    paint: impl Fn(Point) -> LinearRgba,
+) {
+    for (x, y, coverage) in mask.covered() {
+        let coverage = clip.map_or(coverage, |clip| coverage * clip.get(x, y));
+        if coverage <= 0.0 {
+            continue;
+        }
+        #[allow(
+            clippy::cast_precision_loss,
+            reason = "device coordinates are far below 2^24"
+        )]
+        let center = Point::new(x as f32 + 0.5, y as f32 + 0.5);
+        target.blend(x, y, paint(center).scaled(coverage));
+    }
+}
+
+/// Draw another pixmap over the target where they overlap, faded by the clip.
+pub fn composite(target: &mut Pixmap, layer: &Pixmap, clip: Option<&Mask>) {
+    let Some(clip) = clip else {
+        target.composite(layer, 1.0);
+        return;
+    };
+    let width = usize::try_from(layer.width()).unwrap_or_default().max(1);
+    for (row, pixels) in (layer.y()..).zip(layer.pixels().chunks_exact(width)) {
+        for (column, pixel) in (layer.x()..).zip(pixels) {
+            let coverage = clip.get(column, row);
+            if coverage > 0.0 {
+                target.blend(column, row, pixel.scaled(coverage));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: Rect = Rect::new(0.0, 0.0, 16.0, 16.0);
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-3, "{actual} != {expected}");
+    }
+
+    fn fill(verbs: &[PathVerb], rule: FillRule) -> Mask {
+        Mask::fill(&Outline::of(verbs, TOLERANCE), rule, AREA)
+    }
+
+    #[test]
+    fn test_rect_coverage() {
+        let aligned = fill(&rect(Rect::new(2.0, 2.0, 4.0, 3.0)), FillRule::NonZero);
+        assert_eq!(aligned.bounds(), Rect::new(2.0, 2.0, 4.0, 3.0));
+        assert_eq!(aligned.covered().count(), 12);
+        assert!(
+            aligned
+                .covered()
+                .all(|(_, _, coverage)| (coverage - 1.0).abs() < 1e-6)
+        );
+        assert_close(aligned.get(1, 2), 0.0);
+
+        // Edges halfway through a pixel cover half of it, corners a quarter.
+        let offset = fill(&rect(Rect::new(2.5, 2.5, 2.0, 2.0)), FillRule::NonZero);
+        assert_close(offset.get(2, 3), 0.5);
+        assert_close(offset.get(3, 3), 1.0);
+        assert_close(offset.get(2, 2), 0.25);
+        assert_close(offset.get(4, 4), 0.25);
+        let total: f32 = offset.covered().map(|(_, _, coverage)| coverage).sum();
+        assert_close(total, 4.0);
+
+        // Only the area is rasterized.
+        let clipped = Mask::fill(
+            &Outline::of(&rect(Rect::new(-8.0, -8.0, 64.0, 64.0)), TOLERANCE),
+            FillRule::NonZero,
+            AREA,
+        );
+        assert_eq!(clipped.bounds(), AREA);
+    }
+
+    #[test]
+    fn test_fill_rules() {
+        // Two squares wound the same way, one inside the other.
+        let mut verbs = rect(Rect::new(0.0, 0.0, 8.0, 8.0));
+        verbs.extend(rect(Rect::new(2.0, 2.0, 4.0, 4.0)));
+        let non_zero = fill(&verbs, FillRule::NonZero);
+        let even_odd = fill(&verbs, FillRule::EvenOdd);
+        assert_close(non_zero.get(4, 4), 1.0);
+        assert_close(even_odd.get(4, 4), 0.0);
+        assert_close(even_odd.get(1, 1), 1.0);
+    }
+
+    #[test]
+    fn test_rounded_rect() {
+        let radius = 4.0;
+        let outline = Outline::of(
+            &rounded_rect(Rect::new(0.0, 0.0, 8.0, 8.0), [radius; 4]),
+            TOLERANCE,
+        );
+        // Every flattened point lies on the circle within the tolerance.
+        for point in &outline.polylines[0].points {
+            let distance = (point.x - 4.0).hypot(point.y - 4.0);
+            assert!((distance - radius).abs() < 0.02, "{point:?}");
+        }
+        // The covered area matches the area of the flattened polygon.
+        let points = &outline.polylines[0].points;
+        let polygon = points
+            .iter()
+            .zip(points.iter().cycle().skip(1))
+            .map(|(a, b)| a.x.mul_add(b.y, -(b.x * a.y)))
+            .sum::<f32>()
+            / 2.0;
+        let mask = Mask::fill(&outline, FillRule::NonZero, AREA);
+        let area: f32 = mask.covered().map(|(_, _, coverage)| coverage).sum();
+        assert!((area - polygon.abs()).abs() < 0.2, "{area} != {polygon}");
+        assert_close(mask.get(0, 0), 0.0);
+    }
+
+    #[test]
+    fn test_paint_clip() {
+        let mut target = Pixmap::new(0, 0, 4, 1);
+        let mask = fill(&rect(Rect::new(0.0, 0.0, 4.0, 1.0)), FillRule::NonZero);
+        let clip = fill(&rect(Rect::new(1.0, 0.0, 1.5, 1.0)), FillRule::NonZero);
+        let red = LinearRgba {
+            r: 1.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        };
+        paint(&mut target, &mask, Some(&clip), |_| red);
+        let alphas: Vec<f32> = target.pixels().iter().map(|pixel| pixel.a).collect();
+        assert_eq!(alphas, [0.0, 1.0, 0.5, 0.0]);
+        let clipped = mask.intersect(&clip);
+        assert_eq!(clipped.bounds(), mask.bounds());
+        assert_close(clipped.get(2, 0), 0.5);
+        assert_close(clipped.get(3, 0), 0.0);
+    }
+}
@@ -0,0 +1,419 @@
+use std::f32::consts::PI;
+
+use chipbox_scene::{LineCap, LineJoin, Point, Stroke};
+
+use super::{Outline, Polyline};
+
+/// The outline covered by a stroke along every polyline of `outline`,
+/// to be filled with [`chipbox_scene::FillRule::NonZero`].
+///
+/// The stroke is made of a polygon per segment, join and cap, all wound the same way
+/// so that they merge where they overlap. Widths are in the space of the outline,
+/// and round parts are flattened within `tolerance`.
+#[must_use]
+pub fn stroke(outline: &Outline, stroke: &Stroke, tolerance: f32) -> Outline {
+    let mut stroker = Stroker {
+        half: stroke.width / 2.0,
+        stroke,
+        tolerance,
+        outline: Outline::default(),
+    };
+    if stroker.half <= 0.0 {
+        return stroker.outline;
+    }
+    for polyline in &outline.polylines {
+        if stroke.dash.is_empty() {
+            stroker.polyline(polyline);
+        } else {
+            for dash in dash(polyline, &stroke.dash, stroke.dash_offset) {
+                stroker.polyline(&dash);
+            }
+        }
+    }
+    stroker.outline
+}
+
+/// Split a polyline into the dashes of a pattern of alternating dash and gap lengths,
+/// starting `offset` into the pattern. Patterns of odd length repeat twice, as in SVG.
+fn dash(polyline: &Polyline, pattern: &[f32], offset: f32) -> Vec<Polyline> {
+    let pattern: Vec<f32> = if pattern.len() % 2 == 1 {
+        pattern.iter().chain(pattern).copied().collect()
+    } else {
+        pattern.to_vec()
+    };
+    let total: f32 = pattern.iter().sum();
+    if total <= 0.0 || !total.is_finite() {
+        return vec![polyline.clone()];
+    }
+    let mut index = 0;
+    let mut remaining = pattern[0];
+    let mut phase = offset.rem_euclid(total);
+    loop {
+        if phase <= 0.0 {
+            break;
+        }
+        if phase >= remaining {
+            phase -= remaining;
+            index = (index + 1) % pattern.len();
+            remaining = pattern[index];
+        } else {
+            remaining -= phase;
+            break;
+        }
+    }
+
+    let mut points = polyline.points.clone();
+    if polyline.closed {
+        points.extend(polyline.points.first().copied());
+    }
+    let mut dashes = Vec::new();
+    let mut current = Vec::new();
+    if index % 2 == 0 {
+        current.extend(points.first().copied());
+    }
+    for segment in points.windows(2) {
+        let (start, end) = (segment[0], segment[1]);
+        let length = (end.x - start.x).hypot(end.y - start.y);
+        if length <= 0.0 {
+            continue;
+        }
+        let direction = Point::new((end.x - start.x) / length, (end.y - start.y) / length);
+        let mut travelled = 0.0;
+        loop {
+            if length - travelled <= remaining {
+                break;
+            }
+            travelled += remaining;
+            let split = Point::new(
+                direction.x.mul_add(travelled, start.x),
+                direction.y.mul_add(travelled, start.y),
+            );
+            if index % 2 == 0 {
+                current.push(split);
+                dashes.push(Polyline {
+                    points: std::mem::take(&mut current),
+                    closed: false,
+                });
+            } else {
+                current = vec![split];
+            }
+            index = (index + 1) % pattern.len();
+            remaining = pattern[index];
+        }
+        remaining -= length - travelled;
+        if index % 2 == 0 {
+            current.push(end);
+        }
+    }
+    if index % 2 == 0 && !current.is_empty() {
+        dashes.push(Polyline {
+            points: current,
+            closed: false,
+        });
+    }
+    dashes
+}
+
+struct Stroker<'a> {
+    half: f32,
+    stroke: &'a Stroke,
+    tolerance: f32,
+    outline: Outline,
+}
+
+impl Stroker<'_> {
+    fn polyline(&mut self, polyline: &Polyline) {
+        let mut points = polyline.points.clone();
+        points.dedup_by(|a, b| (a.x - b.x).hypot(a.y - b.y) < 1e-4);
+        if polyline.closed && points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        let [first, ..] = points[..] else {
+            return;
+        };
+        if points.len() == 1 {
+            // A segment of zero length draws its caps facing right, as in SVG.
+            if !polyline.closed {
+                self.cap(first, Point::new(1.0, 0.0));
+                self.cap(first, Point::new(-1.0, 0.0));
+            }
+            return;
+        }
+
+        let closed = polyline.closed && points.len() > 2;
+        let count = if closed {
+            points.len()
+        } else {
+            points.len() - 1
+        };
+        let segment = |i: usize| (points[i], points[(i + 1) % points.len()]);
+        for i in 0..count {
+            let (start, end) = segment(i);
+            let normal = self.normal(direction(start, end));
+            self.polygon(vec![
+                offset(start, normal, 1.0),
+                offset(end, normal, 1.0),
+                offset(end, normal, -1.0),
+                offset(start, normal, -1.0),
+            ]);
+        }
+        for i in 1..count {
+            let (before, vertex) = segment(i - 1);
+            let (_, after) = segment(i);
+            self.join(vertex, direction(before, vertex), direction(vertex, after));
+        }
+        if closed {
+            let (before, vertex) = segment(count - 1);
+            let (_, after) = segment(0);
+            self.join(vertex, direction(before, vertex), direction(vertex, after));
+        } else {
+            let last = points.len() - 1;
+            self.cap(first, direction(points[1], first));
+            self.cap(points[last], direction(points[last - 1], points[last]));
+        }
+    }
+
+    /// The normal of a direction, scaled to half the stroke width.
+    fn normal(&self, direction: Point) -> Point {
+        Point::new(-direction.y * self.half, direction.x * self.half)
+    }
+
+    /// Connect two segments meeting at `vertex` on the outer side of the turn.
+    fn join(&mut self, vertex: Point, before: Point, after: Point) {
+        let cross = before.x.mul_add(after.y, -(before.y * after.x));
+        let dot = before.x.mul_add(after.x, before.y * after.y);
+        if cross.abs() < 1e-6 && dot > 0.0 {
+            return;
+        }
+        if self.stroke.join == LineJoin::Round {
+            self.circle(vertex);
+            return;
+        }
+        // With y pointing down, a positive cross product turns towards the normal.
+        let side = if cross > 0.0 { -1.0 } else { 1.0 };
+        let (from, to) = (
+            offset(vertex, self.normal(before), side),
+            offset(vertex, self.normal(after), side),
+        );
+        if self.stroke.join == LineJoin::Miter {
+            // The ratio of the miter length to the stroke width.
+            let cos_half = f32::midpoint(1.0, dot).max(0.0).sqrt();
+            let ratio = cos_half.recip();
+            if ratio <= self.stroke.miter_limit {
+                let (mx, my) = (
+                    vertex.x.mul_add(-2.0, from.x + to.x),
+                    vertex.y.mul_add(-2.0, from.y + to.y),
+                );
+                let scale = self.half * ratio / mx.hypot(my);
+                let miter = Point::new(mx.mul_add(scale, vertex.x), my.mul_add(scale, vertex.y));
+                self.polygon(vec![vertex, from, miter, to]);
+                return;
+            }
+        }
+        self.polygon(vec![vertex, from, to]);
+    }
+
+    /// Close the end of an open polyline, which points along `direction`.
+    fn cap(&mut self, end: Point, direction: Point) {
+        match self.stroke.cap {
+            LineCap::Butt => {}
+            LineCap::Round => self.circle(end),
+            LineCap::Square => {
+                let normal = self.normal(direction);
+                let tip = Point::new(
+                    direction.x.mul_add(self.half, end.x),
+                    direction.y.mul_add(self.half, end.y),
+                );
+                self.polygon(vec![
+                    offset(end, normal, 1.0),
+                    offset(tip, normal, 1.0),
+                    offset(tip, normal, -1.0),
+                    offset(end, normal, -1.0),
+                ]);
+            }
+        }
+    }
+
+    fn circle(&mut self, center: Point) {
+        let radius = self.half;
+        // Chords of this angle stay within the tolerance of the circle.
+        let angle = 2.0 * (1.0 - self.tolerance / radius).clamp(-1.0, 1.0).acos();
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            reason = "the count is clamped to a small positive range"
+        )]
+        let count = (2.0 * PI / angle).ceil().clamp(8.0, 256.0) as usize;
+        self.polygon(
+            (0..count)
+                .map(|i| {
+                    #[allow(clippy::cast_precision_loss, reason = "at most 256 points")]
+                    let (sin, cos) = (2.0 * PI * i as f32 / count as f32).sin_cos();
+                    Point::new(cos.mul_add(radius, center.x), sin.mul_add(radius, center.y))
+                })
+                .collect(),
+        );
+    }
+
+    /// Add a polygon, reversed if needed so that every polygon winds the same way.
+    fn polygon(&mut self, mut points: Vec<Point>) {
+        let area: f32 = points
+            .iter()
+            .zip(points.iter().cycle().skip(1))
+            .map(|(a, b)| a.x.mul_add(b.y, -(b.x * a.y)))
+            .sum();
+        if area < 0.0 {
+            points.reverse();
+        }
+        self.outline.polylines.push(Polyline {
+            points,
+            closed: true,
+        });
+    }
+}
+
+fn direction(from: Point, to: Point) -> Point {
+    let length = (to.x - from.x).hypot(to.y - from.y);
+    Point::new((to.x - from.x) / length, (to.y - from.y) / length)
+}
+
+const fn offset(point: Point, normal: Point, side: f32) -> Point {
+    Point::new(
+        normal.x.mul_add(side, point.x),
+        normal.y.mul_add(side, point.y),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chipbox_scene::{Fill, FillRule, LinearRgba, Rect};
+
+    use super::*;
+    use crate::raster::{Mask, TOLERANCE};
+
+    fn line(points: &[(f32, f32)], closed: bool) -> Outline {
+        Outline {
+            polylines: vec![Polyline {
+                points: points.iter().map(|&(x, y)| Point::new(x, y)).collect(),
+                closed,
+            }],
+        }
+    }
+
+    fn style(width: f32, cap: LineCap, join: LineJoin, dash: Vec<f32>) -> Stroke {
+        Stroke {
+            width,
+            fill: Fill::Solid(LinearRgba::default()),
+            dash,
+            dash_offset: 0.0,
+            cap,
+            join,
+            miter_limit: 4.0,
+        }
+    }
+
+    fn covered(outline: &Outline) -> Mask {
+        Mask::fill(outline, FillRule::NonZero, Rect::new(0.0, 0.0, 16.0, 16.0))
+    }
+
+    fn area(mask: &Mask) -> f32 {
+        mask.covered().map(|(_, _, coverage)| coverage).sum()
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-2, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn test_stroke_caps() {
+        let segment = line(&[(2.0, 4.0), (10.0, 4.0)], false);
+        let butt = covered(&stroke(
+            &segment,
+            &style(2.0, LineCap::Butt, LineJoin::Miter, Vec::new()),
+            TOLERANCE,
+        ));
+        assert_eq!(butt.bounds(), Rect::new(2.0, 3.0, 8.0, 2.0));
+        assert_close(area(&butt), 16.0);
+        let square = covered(&stroke(
+            &segment,
+            &style(2.0, LineCap::Square, LineJoin::Miter, Vec::new()),
+            TOLERANCE,
+        ));
+        assert_close(area(&square), 20.0);
+        let round = covered(&stroke(
+            &segment,
+            &style(2.0, LineCap::Round, LineJoin::Miter, Vec::new()),
+            TOLERANCE,
+        ));
+        // Round parts are inscribed polygons, within the tolerance of the circle.
+        let round = area(&round);
+        assert!(round < 16.0 + PI, "{round}");
+        assert!(
+            round > 2.0f32.mul_add(-PI * TOLERANCE, 16.0 + PI),
+            "{round}"
+        );
+    }
+
+    #[test]
+    fn test_stroke_joins() {
+        // A right angle, whose joins differ only in the outer corner.
+        let corner = line(&[(2.0, 4.0), (10.0, 4.0), (10.0, 12.0)], false);
+        let join_area = |join| {
+            area(&covered(&stroke(
+                &corner,
+                &style(2.0, LineCap::Butt, join, Vec::new()),
+                TOLERANCE,
+            )))
+        };
+        // Both segments, with the inner corner overlapping.
+        let segments = 16.0 + 16.0 - 1.0;
+        assert_close(join_area(LineJoin::Bevel), segments + 0.5);
+        assert_close(join_area(LineJoin::Miter), segments + 1.0);
+        let round = join_area(LineJoin::Round);
+        assert!(
+            round > segments + 0.5 && round < segments + PI / 4.0,
+            "{round}"
+        );
+
+        // A closed square strokes its corners too.
+        let square = line(&[(4.0, 4.0), (12.0, 4.0), (12.0, 12.0), (4.0, 12.0)], true);
+        let mask = covered(&stroke(
+            &square,
+            &style(2.0, LineCap::Butt, LineJoin::Miter, Vec::new()),
+            TOLERANCE,
+        ));
+        assert_eq!(mask.bounds(), Rect::new(3.0, 3.0, 10.0, 10.0));
+        assert_close(area(&mask), 100.0 - 36.0);
+    }
+
+    #[test]
+    fn test_stroke_dashes() {
+        let dashes = dash(
+            &line(&[(0.0, 0.0), (10.0, 0.0)], false).polylines[0],
+            &[3.0, 1.0],
+            1.0,
+        );
+        let spans: Vec<(f32, f32)> = dashes
+            .iter()
+            .map(|dash| (dash.points[0].x, dash.points[dash.points.len() - 1].x))
+            .collect();
+        assert_eq!(spans, [(0.0, 2.0), (3.0, 6.0), (7.0, 10.0)]);
+
+        // Odd patterns repeat, so dashes and gaps swap every other time.
+        let odd = dash(
+            &line(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0)], false).polylines[0],
+            &[3.0],
+            0.0,
+        );
+        assert_eq!(odd.len(), 2);
+        assert_eq!(odd[1].points, [Point::new(4.0, 2.0), Point::new(4.0, 4.0)]);
+
+        // Dashes of zero length still get caps, the gap ends exactly at the end.
+        let dots = stroke(
+            &line(&[(2.0, 2.0), (10.0, 2.0)], false),
+            &style(2.0, LineCap::Square, LineJoin::Miter, vec![0.0, 4.0]),
+            TOLERANCE,
+        );
+        assert_close(area(&covered(&dots)), 2.0 * 4.0);
+    }
+}
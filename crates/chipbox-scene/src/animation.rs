@@ -0,0 +1,692 @@
+//! Time-based interpolation of the properties targeted by transition elements.
+//!
+//! The host declares the scene as usual and keeps it untouched.
+//! Every frame it advances a [`FrameClock`] and lets an [`Animator`] rewrite a copy of the scene,
+//! replacing animated properties with their in-between values before layout and painting.
+//! The animator notices changes to the declared values and starts a transition from the
+//! currently displayed value, so interrupted transitions continue smoothly.
+//!
+//! Animated values are tracked by the id of the transition's parent, or by its path if it has none.
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use crate::{
+    AnimatedProperty, BehaviorElement, BoxElement, Color, ContentElement, ElementId, ElementNode,
+    ElementPath, LayoutElement, LayoutLength, LengthExpr, LinearRgba, Paint, Scene, Timing,
+    TransformElement,
+};
+
+/// Time of the current frame, advanced by the host.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameClock {
+    now: Duration,
+    frame: u64,
+}
+
+impl FrameClock {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            now: Duration::ZERO,
+            frame: 0,
+        }
+    }
+
+    /// Move on to the next frame, `delta` after the current one.
+    pub fn advance(&mut self, delta: Duration) {
+        self.now += delta;
+        self.frame += 1;
+    }
+
+    /// Time since the clock was created.
+    #[must_use]
+    pub const fn now(&self) -> Duration {
+        self.now
+    }
+
+    /// Number of frames since the clock was created.
+    #[must_use]
+    pub const fn frame(&self) -> u64 {
+        self.frame
+    }
+}
+
+/// Owner of an animated value.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Owner {
+    Id(ElementId),
+    Path(ElementPath),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct TrackKey {
+    owner: Owner,
+    property: AnimatedProperty,
+    /// Index of the sibling holding the value, `None` for properties of the parent itself.
+    slot: Option<usize>,
+}
+
+/// A value of an animated property.
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Length(LayoutLength),
+    Number(f32),
+    Color(Color),
+    Transform(Box<TransformElement>),
+}
+
+impl Value {
+    /// The value a fraction `t` of the way from `self` to `to`.
+    /// `t` may leave `0..=1` when a spring overshoots.
+    fn mix(&self, to: &Self, t: f32) -> Self {
+        match (self, to) {
+            (Self::Length(a), Self::Length(b)) => Self::Length(mix_length(a, b, t)),
+            (Self::Number(a), Self::Number(b)) => Self::Number(lerp(*a, *b, t)),
+            (Self::Color(a), Self::Color(b)) => Self::Color(mix_color(a, b, t)),
+            (Self::Transform(a), Self::Transform(b)) => {
+                Self::Transform(Box::new(mix_transform(a, b, t)))
+            }
+            _ => to.clone(),
+        }
+    }
+
+    /// Signed distance from `self` to `to`, if they can be compared on a line.
+    fn delta(&self, to: &Self) -> Option<f32> {
+        match (self, to) {
+            (Self::Number(a), Self::Number(b)) => Some(b - a),
+            (Self::Length(a), Self::Length(b)) if a.unit() == b.unit() => {
+                Some(b.value()? - a.value()?)
+            }
+            _ => None,
+        }
+    }
+}
+
+const fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    (b - a).mul_add(t, a)
+}
+
+fn mix_length(a: &LayoutLength, b: &LayoutLength, t: f32) -> LayoutLength {
+    if a == b {
+        return b.clone();
+    }
+    if let (Some(unit), Some(x), Some(y)) = (
+        a.unit().filter(|u| Some(*u) == b.unit()),
+        a.value(),
+        b.value(),
+    ) && let Some(length) = LayoutLength::from_unit(lerp(x, y, t), unit)
+    {
+        return length;
+    }
+    // Lengths in different units only become comparable during layout.
+    let expr = |length: &LayoutLength, factor: f32| {
+        let length = match length {
+            LayoutLength::Calc(expr) => expr.as_ref().clone(),
+            length => LengthExpr::Length(length.clone()),
+        };
+        Box::new(LengthExpr::Product(Box::new(length), factor))
+    };
+    LayoutLength::Calc(Arc::new(LengthExpr::Sum(expr(a, 1.0 - t), expr(b, t))))
+}
+
+/// Colors are mixed premultiplied in linear light, so fading from transparent
+/// does not pass through black.
+fn mix_color(a: &Color, b: &Color, t: f32) -> Color {
+    let (a, b) = (a.to_premultiplied(), b.to_premultiplied());
+    let mixed = LinearRgba {
+        r: lerp(a.r, b.r, t),
+        g: lerp(a.g, b.g, t),
+        b: lerp(a.b, b.b, t),
+        a: lerp(a.a, b.a, t).clamp(0.0, 1.0),
+    };
+    if mixed.a <= 0.0 {
+        return Color::TRANSPARENT;
+    }
+    Color::LinearRgb {
+        r: mixed.r / mixed.a,
+        g: mixed.g / mixed.a,
+        b: mixed.b / mixed.a,
+        a: mixed.a,
+    }
+}
+
+fn mix_transform(a: &TransformElement, b: &TransformElement, t: f32) -> TransformElement {
+    let translate = |a: &Option<LayoutLength>, b: &Option<LayoutLength>| match (a, b) {
+        (None, None) => None,
+        (a, b) => {
+            let zero = LayoutLength::Pixel(0.0);
+            Some(mix_length(
+                a.as_ref().unwrap_or(&zero),
+                b.as_ref().unwrap_or(&zero),
+                t,
+            ))
+        }
+    };
+    TransformElement {
+        id: b.id.clone(),
+        translate_x: translate(&a.translate_x, &b.translate_x),
+        translate_y: translate(&a.translate_y, &b.translate_y),
+        rotate: lerp(a.rotate, b.rotate, t),
+        scale_x: lerp(a.scale_x, b.scale_x, t),
+        scale_y: lerp(a.scale_y, b.scale_y, t),
+        skew_x: lerp(a.skew_x, b.skew_x, t),
+        skew_y: lerp(a.skew_y, b.skew_y, t),
+        origin: b.origin,
+    }
+}
+
+/// A property of the scene that a transition reads its target from and writes its value to.
+enum Target<'a> {
+    Length(&'a mut LayoutLength),
+    /// An offset where `None` means zero.
+    Offset(&'a mut Option<LayoutLength>),
+    Number(&'a mut f32),
+    Color(&'a mut Color),
+    Transform(&'a mut TransformElement),
+}
+
+impl Target<'_> {
+    fn read(&self) -> Value {
+        match self {
+            Self::Length(length) => Value::Length((**length).clone()),
+            Self::Offset(offset) => {
+                Value::Length((**offset).clone().unwrap_or(LayoutLength::Pixel(0.0)))
+            }
+            Self::Number(number) => Value::Number(**number),
+            Self::Color(color) => Value::Color((**color).clone()),
+            Self::Transform(transform) => Value::Transform(Box::new((**transform).clone())),
+        }
+    }
+
+    fn write(&mut self, value: Value) {
+        match (self, value) {
+            (Self::Length(length), Value::Length(value)) => **length = value,
+            (Self::Offset(offset), Value::Length(value)) => **offset = Some(value),
+            (Self::Number(number), Value::Number(value)) => **number = value,
+            (Self::Color(color), Value::Color(value)) => **color = value,
+            (Self::Transform(transform), Value::Transform(value)) => **transform = *value,
+            _ => {}
+        }
+    }
+}
+
+/// The target of a transition held by a sibling of the transition element.
+fn sibling_target(node: &mut ElementNode, property: AnimatedProperty) -> Option<Target<'_>> {
+    match (node, property) {
+        (ElementNode::Behavior(BehaviorElement::Texture(e)), AnimatedProperty::Opacity) => {
+            Some(Target::Number(&mut e.opacity))
+        }
+        (ElementNode::Behavior(BehaviorElement::Transform(e)), AnimatedProperty::Transform) => {
+            Some(Target::Transform(e))
+        }
+        (ElementNode::Content(ContentElement::Rect(e)), AnimatedProperty::Fill) => {
+            match &mut e.fill {
+                Some(Paint::Solid { color }) => Some(Target::Color(color)),
+                _ => None,
+            }
+        }
+        (ElementNode::Content(ContentElement::Text(e)), AnimatedProperty::Color) => {
+            Some(Target::Color(&mut e.color))
+        }
+        _ => None,
+    }
+}
+
+/// Progress of one animated value.
+#[derive(Clone, Debug)]
+struct Track {
+    from: Value,
+    to: Value,
+    value: Value,
+    /// When the current transition started.
+    start: Duration,
+    /// Time up to which the spring has been integrated.
+    integrated: Duration,
+    /// Progress from `from` to `to`, and its rate of change per second for springs.
+    position: f32,
+    velocity: f32,
+    done: bool,
+    /// Generation of the animator the track was last used in.
+    seen: u64,
+}
+
+impl Track {
+    fn new(value: Value, now: Duration) -> Self {
+        Self {
+            from: value.clone(),
+            to: value.clone(),
+            value,
+            start: now,
+            integrated: now,
+            position: 1.0,
+            velocity: 0.0,
+            done: true,
+            seen: 0,
+        }
+    }
+
+    /// Start moving from the displayed value to a new target.
+    fn retarget(&mut self, to: Value, now: Duration) {
+        // A spring keeps its speed, rescaled from the old distance to the new one.
+        let speed = self.from.delta(&self.to).map(|delta| delta * self.velocity);
+        self.velocity = match (speed, self.value.delta(&to)) {
+            (Some(speed), Some(delta)) if !self.done && delta.abs() > f32::EPSILON => speed / delta,
+            _ => 0.0,
+        };
+        self.from = self.value.clone();
+        self.to = to;
+        self.start = now;
+        self.integrated = now;
+        self.position = 0.0;
+        self.done = false;
+    }
+
+    fn step(&mut self, timing: &Timing, now: Duration) {
+        if self.done {
+            return;
+        }
+        match *timing {
+            Timing::Tween {
+                duration,
+                delay,
+                ref easing,
+            } => {
+                let elapsed = now.saturating_sub(self.start).as_secs_f32() - delay;
+                let t = if duration > 0.0 {
+                    (elapsed / duration).clamp(0.0, 1.0)
+                } else if elapsed >= 0.0 {
+                    1.0
+                } else {
+                    0.0
+                };
+                self.position = easing.apply(t);
+                self.done = t >= 1.0;
+            }
+            Timing::Spring {
+                stiffness,
+                damping,
+                mass,
+            } => {
+                self.integrate(stiffness, damping, mass, now);
+            }
+        }
+        self.value = if self.done {
+            self.to.clone()
+        } else {
+            self.from.mix(&self.to, self.position)
+        };
+    }
+
+    /// Advance the spring with semi-implicit Euler steps short enough to stay stable.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss,
+        reason = "the number of steps per frame is small"
+    )]
+    fn integrate(&mut self, stiffness: f32, damping: f32, mass: f32, now: Duration) {
+        const MAX_STEP: f32 = 1.0 / 240.0;
+        const REST: f32 = 1e-3;
+
+        let elapsed = now.saturating_sub(self.integrated).as_secs_f32();
+        self.integrated = now;
+        let steps = (elapsed / MAX_STEP).ceil().max(1.0) as u32;
+        let step = elapsed / steps as f32;
+        for _ in 0..steps {
+            let force = stiffness.mul_add(1.0 - self.position, -damping * self.velocity);
+            self.velocity += force / mass * step;
+            self.position += self.velocity * step;
+        }
+        self.done = (self.position - 1.0).abs() < REST && self.velocity.abs() < REST;
+    }
+}
+
+/// Runs the transitions of a scene.
+///
+/// The animator keeps the displayed value of every transitioned property between frames.
+/// Pass it a fresh copy of the declared scene every frame.
+/// Values whose transition element disappears from the scene are forgotten.
+#[derive(Clone, Debug, Default)]
+pub struct Animator {
+    tracks: HashMap<TrackKey, Track>,
+    generation: u64,
+}
+
+impl Animator {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the transitioned properties of the scene with their values at the time of the clock.
+    pub fn animate(&mut self, scene: &mut Scene, clock: &FrameClock) {
+        self.generation += 1;
+        self.nodes(&mut scene.children, &ElementPath::default(), clock.now());
+        let generation = self.generation;
+        self.tracks.retain(|_, track| track.seen == generation);
+    }
+
+    /// Whether any transition is still running,
+    /// in which case the host should keep requesting frames.
+    #[must_use]
+    pub fn is_animating(&self) -> bool {
+        self.tracks.values().any(|track| !track.done)
+    }
+
+    fn nodes(&mut self, nodes: &mut [ElementNode], parent: &ElementPath, now: Duration) {
+        for (index, node) in nodes.iter_mut().enumerate() {
+            // Only layout elements have element children.
+            if let ElementNode::Layout(e) = node {
+                self.layout(e, &parent.child(index), now);
+            }
+        }
+    }
+
+    fn layout(&mut self, element: &mut LayoutElement, path: &ElementPath, now: Duration) {
+        match element {
+            LayoutElement::Box(e) => self.r#box(e, path, now),
+            LayoutElement::Align(e) => {
+                let own = &mut [
+                    (AnimatedProperty::XOffset, Target::Offset(&mut e.x_offset)),
+                    (AnimatedProperty::YOffset, Target::Offset(&mut e.y_offset)),
+                ];
+                self.element(e.id.clone(), path, own, &mut e.children, now);
+            }
+            LayoutElement::Margin(e) => self.element(e.id(), path, &mut [], &mut e.children, now),
+            LayoutElement::ContentBox(e) => {
+                self.element(e.id(), path, &mut [], &mut e.children, now);
+            }
+            LayoutElement::Scroll(e) => self.element(e.id(), path, &mut [], &mut e.children, now),
+            LayoutElement::Array(e) => {
+                for (index, e) in e.children.iter_mut().enumerate() {
+                    self.r#box(e, &path.child(index), now);
+                }
+            }
+            LayoutElement::Grid(e) => {
+                for (index, e) in e.children.iter_mut().enumerate() {
+                    self.element(e.id(), &path.child(index), &mut [], &mut e.children, now);
+                }
+            }
+            LayoutElement::Flex(e) => {
+                for (index, e) in e.children.iter_mut().enumerate() {
+                    self.element(e.id(), &path.child(index), &mut [], &mut e.children, now);
+                }
+            }
+        }
+    }
+
+    fn r#box(&mut self, element: &mut BoxElement, path: &ElementPath, now: Duration) {
+        let own = &mut [
+            (AnimatedProperty::Width, Target::Length(&mut element.width)),
+            (
+                AnimatedProperty::Height,
+                Target::Length(&mut element.height),
+            ),
+        ];
+        self.element(element.id.clone(), path, own, &mut element.children, now);
+    }
+
+    /// Run the transitions among the children of an element, then continue with its descendants.
+    fn element(
+        &mut self,
+        id: Option<ElementId>,
+        path: &ElementPath,
+        own: &mut [(AnimatedProperty, Target<'_>)],
+        children: &mut [ElementNode],
+        now: Duration,
+    ) {
+        let transitions: Vec<_> = children
+            .iter()
+            .filter_map(|child| match child {
+                ElementNode::Behavior(BehaviorElement::Transition(e)) => {
+                    Some((e.property, e.timing.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+        if !transitions.is_empty() {
+            let owner = id.map_or_else(|| Owner::Path(path.clone()), Owner::Id);
+            for (property, timing) in &transitions {
+                let key = |slot| TrackKey {
+                    owner: owner.clone(),
+                    property: *property,
+                    slot,
+                };
+                for (_, target) in own.iter_mut().filter(|(p, _)| p == property) {
+                    self.update(key(None), timing, target, now);
+                }
+                for (index, child) in children.iter_mut().enumerate() {
+                    if let Some(mut target) = sibling_target(child, *property) {
+                        self.update(key(Some(index)), timing, &mut target, now);
+                    }
+                }
+            }
+        }
+        self.nodes(children, path, now);
+    }
+
+    fn update(&mut self, key: TrackKey, timing: &Timing, target: &mut Target<'_>, now: Duration) {
+        let declared = target.read();
+        let track = self
+            .tracks
+            .entry(key)
+            .or_insert_with(|| Track::new(declared.clone(), now));
+        track.seen = self.generation;
+        track.step(timing, now);
+        if track.to != declared {
+            track.retarget(declared, now);
+            track.step(timing, now);
+        }
+        if track.value != track.to {
+            target.write(track.value.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AlignElement, Easing, TextureElement, TransitionElement};
+
+    const FRAME: Duration = Duration::from_millis(50);
+
+    fn transition(property: AnimatedProperty, timing: Timing) -> ElementNode {
+        ElementNode::Behavior(BehaviorElement::Transition(TransitionElement {
+            id: None,
+            property,
+            timing,
+        }))
+    }
+
+    fn linear(duration: f32) -> Timing {
+        Timing::Tween {
+            duration,
+            delay: 0.0,
+            easing: Easing::Linear,
+        }
+    }
+
+    /// A box with a transitioned width and a texture with transitioned opacity.
+    fn scene(width: f32, opacity: f32, timing: Timing) -> Scene {
+        let texture = ElementNode::Behavior(BehaviorElement::Texture(TextureElement {
+            id: None,
+            opacity,
+            shader: None,
+        }));
+        let r#box = BoxElement {
+            id: Some(ElementId("panel".into())),
+            children: vec![
+                transition(AnimatedProperty::Width, timing.clone()),
+                transition(AnimatedProperty::Opacity, timing),
+                texture,
+            ],
+            width: LayoutLength::Pixel(width),
+            height: LayoutLength::Pixel(10.0),
+        };
+        Scene {
+            children: vec![ElementNode::Layout(LayoutElement::Box(r#box))],
+            px_width: 100,
+            px_height: 100,
+            metrics: crate::DisplayMetrics::default(),
+        }
+    }
+
+    /// Animate a copy of the declared scene and read back the width and opacity.
+    fn frame(animator: &mut Animator, declared: &Scene, clock: &FrameClock) -> (f32, f32) {
+        let mut scene = declared.clone();
+        animator.animate(&mut scene, clock);
+        let ElementNode::Layout(LayoutElement::Box(e)) = &scene.children[0] else {
+            panic!("expected box");
+        };
+        let ElementNode::Behavior(BehaviorElement::Texture(texture)) = &e.children[2] else {
+            panic!("expected texture");
+        };
+        let width = e.width.value().expect("plain width");
+        (
+            (width * 100.0).round() / 100.0,
+            (texture.opacity * 100.0).round() / 100.0,
+        )
+    }
+
+    #[test]
+    fn test_tween() {
+        let mut animator = Animator::new();
+        let mut clock = FrameClock::new();
+        assert_eq!(
+            frame(&mut animator, &scene(100.0, 1.0, linear(0.2)), &clock),
+            (100.0, 1.0)
+        );
+        assert!(!animator.is_animating());
+
+        let declared = scene(200.0, 0.0, linear(0.2));
+        let mut values = Vec::new();
+        for _ in 0..5 {
+            values.push(frame(&mut animator, &declared, &clock));
+            clock.advance(FRAME);
+        }
+        assert_eq!(
+            values,
+            [
+                (100.0, 1.0),
+                (125.0, 0.75),
+                (150.0, 0.5),
+                (175.0, 0.25),
+                (200.0, 0.0)
+            ]
+        );
+        assert_eq!(clock.frame(), 5);
+        assert!(!animator.is_animating());
+    }
+
+    #[test]
+    fn test_retarget() {
+        let mut animator = Animator::new();
+        let mut clock = FrameClock::new();
+        frame(&mut animator, &scene(100.0, 1.0, linear(0.2)), &clock);
+        clock.advance(FRAME);
+        frame(&mut animator, &scene(200.0, 1.0, linear(0.2)), &clock);
+        clock.advance(FRAME * 2);
+        // Halfway to 200, the target changes back and the transition starts from 150.
+        let declared = scene(100.0, 1.0, linear(0.2));
+        assert_eq!(frame(&mut animator, &declared, &clock), (150.0, 1.0));
+        clock.advance(FRAME * 2);
+        assert_eq!(frame(&mut animator, &declared, &clock), (125.0, 1.0));
+        assert!(animator.is_animating());
+
+        // Removing the transitions drops their state.
+        let mut plain = scene(300.0, 1.0, linear(0.2));
+        let ElementNode::Layout(LayoutElement::Box(e)) = &mut plain.children[0] else {
+            panic!("expected box");
+        };
+        e.children.drain(..2);
+        let mut animated = plain.clone();
+        animator.animate(&mut animated, &clock);
+        assert_eq!(animated, plain);
+        assert!(!animator.is_animating());
+    }
+
+    #[test]
+    fn test_spring() {
+        let spring = Timing::Spring {
+            stiffness: Timing::default_stiffness(),
+            damping: Timing::default_damping(),
+            mass: Timing::default_mass(),
+        };
+        let mut animator = Animator::new();
+        let mut clock = FrameClock::new();
+        frame(&mut animator, &scene(0.0, 0.0, spring.clone()), &clock);
+        let declared = scene(100.0, 1.0, spring);
+        let mut widths = Vec::new();
+        while animator.is_animating() || widths.is_empty() {
+            clock.advance(Duration::from_millis(16));
+            widths.push(frame(&mut animator, &declared, &clock).0);
+            assert!(widths.len() < 100, "spring did not settle");
+        }
+        assert!(widths.windows(2).all(|pair| pair[0] <= pair[1] + 0.5));
+        assert!(widths[10] > 50.0);
+        assert_eq!(widths.last(), Some(&100.0));
+    }
+
+    #[test]
+    fn test_mix_values() {
+        let length = mix_length(
+            &LayoutLength::Pixel(10.0),
+            &LayoutLength::ParentWidth(1.0),
+            0.25,
+        );
+        assert_eq!(
+            length,
+            LayoutLength::Calc(Arc::new(LengthExpr::Sum(
+                Box::new(LengthExpr::Product(
+                    Box::new(LengthExpr::Length(LayoutLength::Pixel(10.0))),
+                    0.75
+                )),
+                Box::new(LengthExpr::Product(
+                    Box::new(LengthExpr::Length(LayoutLength::ParentWidth(1.0))),
+                    0.25
+                )),
+            )))
+        );
+
+        // Fading in from transparent keeps the color instead of darkening it.
+        let red = Color::LinearRgb {
+            r: 1.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        };
+        assert_eq!(
+            mix_color(&Color::TRANSPARENT, &red, 0.5),
+            red.clone().with_alpha(0.5)
+        );
+
+        let align = AlignElement {
+            id: None,
+            children: vec![transition(AnimatedProperty::XOffset, linear(1.0))],
+            origin: TransformElement::default_origin(),
+            target: TransformElement::default_origin(),
+            x_offset: None,
+            y_offset: None,
+        };
+        let mut declared = Scene {
+            children: vec![ElementNode::Layout(LayoutElement::Align(align))],
+            ..scene(0.0, 0.0, linear(1.0))
+        };
+        let mut animator = Animator::new();
+        let mut clock = FrameClock::new();
+        animator.animate(&mut declared.clone(), &clock);
+        let ElementNode::Layout(LayoutElement::Align(e)) = &mut declared.children[0] else {
+            panic!("expected align");
+        };
+        e.x_offset = Some(LayoutLength::Pixel(40.0));
+        clock.advance(Duration::from_millis(500));
+        animator.animate(&mut declared.clone(), &clock);
+        clock.advance(Duration::from_millis(500));
+        let mut scene = declared.clone();
+        animator.animate(&mut scene, &clock);
+        let ElementNode::Layout(LayoutElement::Align(e)) = &scene.children[0] else {
+            panic!("expected align");
+        };
+        assert_eq!(e.x_offset, Some(LayoutLength::Pixel(20.0)));
+    }
+}
@@ -0,0 +1,498 @@
+//! Decoding and caching of images shown by image elements.
+//!
+//! Decoded images are keyed by a hash of their encoded bytes,
+//! so the same picture loaded from several sources is decoded once.
+//! The least recently used images are evicted when the cache exceeds its byte budget.
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Arc,
+};
+
+use crate::{ImageElement, ImageFit, ImageSource, LengthContext, Rect, Size};
+
+/// Budget of decoded pixel data kept by [`ImageCache::default`].
+pub const DEFAULT_IMAGE_CACHE_BYTES: usize = 256 * 1024 * 1024;
+
+/// Pixels of a decoded image.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedImage {
+    pub width: u32,
+    pub height: u32,
+    /// Rows of straight (not premultiplied) sRGB RGBA bytes, from the top.
+    pub pixels: Arc<[u8]>,
+}
+
+impl DecodedImage {
+    /// Size in device pixels when every image pixel covers a logical pixel.
+    #[must_use]
+    #[allow(
+        clippy::cast_precision_loss,
+        reason = "image dimensions are far below 2^24"
+    )]
+    pub const fn intrinsic_size(&self, ctx: &LengthContext) -> Size {
+        Size::new(
+            ctx.metrics.logical_to_device(self.width as f32),
+            ctx.metrics.logical_to_device(self.height as f32),
+        )
+    }
+}
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum ImageError {
+    #[error("unable to read image {source_name}")]
+    #[diagnostic(code(chipbox_scene::image::io))]
+    Io {
+        source_name: ImageSource,
+        #[source]
+        error: Arc<std::io::Error>,
+    },
+    #[error("no asset registered for image {0}")]
+    #[diagnostic(
+        code(chipbox_scene::image::unknown_asset),
+        help("register the encoded bytes with `ImageCache::register` first")
+    )]
+    UnknownAsset(ImageSource),
+    #[error("image {0} is not a PNG, JPEG or QOI file")]
+    #[diagnostic(code(chipbox_scene::image::format))]
+    UnsupportedFormat(ImageSource),
+    #[error("unable to decode image {source_name}")]
+    #[diagnostic(code(chipbox_scene::image::decode))]
+    Decode {
+        source_name: ImageSource,
+        #[source]
+        error: Arc<::image::ImageError>,
+    },
+}
+
+struct Entry {
+    image: Arc<DecodedImage>,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    /// Content hashes of sources loaded before, so files are read once.
+    sources: HashMap<ImageSource, u64>,
+    decoded: HashMap<u64, Entry>,
+    /// Size of the pixels of every decoded image.
+    bytes: usize,
+    /// Incremented on every access, orders entries by recency.
+    clock: u64,
+}
+
+/// Decoded images by content hash, evicting the least recently used beyond a byte budget.
+pub struct ImageCache {
+    assets: HashMap<Arc<str>, Arc<[u8]>>,
+    capacity: usize,
+    state: RefCell<CacheState>,
+}
+
+impl std::fmt::Debug for ImageCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.borrow();
+        f.debug_struct("ImageCache")
+            .field("assets", &self.assets.len())
+            .field("images", &state.decoded.len())
+            .field("bytes", &state.bytes)
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+impl Default for ImageCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_IMAGE_CACHE_BYTES)
+    }
+}
+
+impl ImageCache {
+    /// An empty cache keeping at most `capacity` bytes of decoded pixels,
+    /// except for the most recently loaded image.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            assets: HashMap::new(),
+            capacity,
+            state: RefCell::default(),
+        }
+    }
+
+    /// Register the encoded bytes of an asset, replacing any previous asset with the same id.
+    pub fn register(&mut self, id: impl Into<Arc<str>>, bytes: impl Into<Arc<[u8]>>) {
+        let id = id.into();
+        self.invalidate(&ImageSource::Asset { id: id.clone() });
+        self.assets.insert(id, bytes.into());
+    }
+
+    /// Forget the content of a source, so it is read again the next time it is loaded.
+    pub fn invalidate(&self, source: &ImageSource) {
+        self.state.borrow_mut().sources.remove(source);
+    }
+
+    /// Bytes of decoded pixels currently cached.
+    #[must_use]
+    pub fn bytes(&self) -> usize {
+        self.state.borrow().bytes
+    }
+
+    /// The decoded image of a source, decoding it if it is not cached.
+    /// ## Errors
+    /// - [`ImageError`] if the source can not be read or decoded.
+    pub fn load(&self, source: &ImageSource) -> Result<Arc<DecodedImage>, ImageError> {
+        let known = self.state.borrow().sources.get(source).copied();
+        if let Some(hash) = known
+            && let Some(image) = self.touch(hash)
+        {
+            return Ok(image);
+        }
+        let bytes = self.read(source)?;
+        let hash = content_hash(&bytes);
+        self.state.borrow_mut().sources.insert(source.clone(), hash);
+        if let Some(image) = self.touch(hash) {
+            return Ok(image);
+        }
+        let image = Arc::new(decode(source, &bytes)?);
+        self.insert(hash, image.clone());
+        Ok(image)
+    }
+
+    /// Mark a decoded image as used.
+    fn touch(&self, hash: u64) -> Option<Arc<DecodedImage>> {
+        let state = &mut *self.state.borrow_mut();
+        state.clock += 1;
+        let entry = state.decoded.get_mut(&hash)?;
+        entry.last_used = state.clock;
+        Some(entry.image.clone())
+    }
+
+    fn insert(&self, hash: u64, image: Arc<DecodedImage>) {
+        let state = &mut *self.state.borrow_mut();
+        state.clock += 1;
+        state.bytes += image.pixels.len();
+        state.decoded.insert(
+            hash,
+            Entry {
+                image,
+                last_used: state.clock,
+            },
+        );
+        while state.bytes > self.capacity && state.decoded.len() > 1 {
+            let Some((&oldest, _)) = state
+                .decoded
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+            else {
+                break;
+            };
+            if let Some(entry) = state.decoded.remove(&oldest) {
+                state.bytes -= entry.image.pixels.len();
+            }
+        }
+    }
+
+    fn read(&self, source: &ImageSource) -> Result<Arc<[u8]>, ImageError> {
+        match source {
+            ImageSource::Path { path } => {
+                std::fs::read(&**path)
+                    .map(Arc::from)
+                    .map_err(|error| ImageError::Io {
+                        source_name: source.clone(),
+                        error: Arc::new(error),
+                    })
+            }
+            ImageSource::Asset { id } => self
+                .assets
+                .get(id)
+                .cloned()
+                .ok_or_else(|| ImageError::UnknownAsset(source.clone())),
+        }
+    }
+}
+
+fn content_hash(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn decode(source: &ImageSource, bytes: &[u8]) -> Result<DecodedImage, ImageError> {
+    use ::image::ImageFormat;
+
+    let format = ::image::guess_format(bytes)
+        .ok()
+        .filter(|format| {
+            matches!(
+                format,
+                ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::Qoi
+            )
+        })
+        .ok_or_else(|| ImageError::UnsupportedFormat(source.clone()))?;
+    let image = ::image::load_from_memory_with_format(bytes, format)
+        .map_err(|error| ImageError::Decode {
+            source_name: source.clone(),
+            error: Arc::new(error),
+        })?
+        .into_rgba8();
+    Ok(DecodedImage {
+        width: image.width(),
+        height: image.height(),
+        pixels: image.into_raw().into(),
+    })
+}
+
+/// An [`ImageElement`] as quads copying parts of the image to device pixels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaintedImage {
+    pub rect: Rect,
+    pub image: Arc<DecodedImage>,
+    /// Parts of the image stretched to the target rects, which never overlap.
+    pub quads: Vec<ImageQuad>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ImageQuad {
+    /// Part of the image in image pixels.
+    pub source: Rect,
+    /// Where the part is drawn in device pixels.
+    pub target: Rect,
+}
+
+impl PaintedImage {
+    /// Resolve an image element laid out at `rect`.
+    /// ## Errors
+    /// - [`ImageError`] if the image can not be loaded.
+    pub fn of(
+        element: &ImageElement,
+        rect: Rect,
+        ctx: &LengthContext,
+        images: &ImageCache,
+    ) -> Result<Self, ImageError> {
+        let image = images.load(&element.source)?;
+        #[allow(
+            clippy::cast_precision_loss,
+            reason = "image dimensions are far below 2^24"
+        )]
+        let (width, height) = (image.width as f32, image.height as f32);
+        let full = Rect::new(0.0, 0.0, width, height);
+        let quads = match element.fit {
+            ImageFit::Fill => vec![ImageQuad {
+                source: full,
+                target: rect,
+            }],
+            ImageFit::Contain => {
+                let scale = (rect.width / width).min(rect.height / height);
+                let size = Size::new(width * scale, height * scale);
+                vec![ImageQuad {
+                    source: full,
+                    target: Rect::from_size(
+                        rect.x + (rect.width - size.width) / 2.0,
+                        rect.y + (rect.height - size.height) / 2.0,
+                        size,
+                    ),
+                }]
+            }
+            ImageFit::Cover => {
+                let scale = (rect.width / width).max(rect.height / height);
+                let size = Size::new(rect.width / scale, rect.height / scale);
+                vec![ImageQuad {
+                    source: Rect::from_size(
+                        (width - size.width) / 2.0,
+                        (height - size.height) / 2.0,
+                        size,
+                    ),
+                    target: rect,
+                }]
+            }
+            ImageFit::NineSlice {
+                top,
+                right,
+                bottom,
+                left,
+            } => nine_slice([top, right, bottom, left], full, rect, ctx),
+        };
+        Ok(Self {
+            rect,
+            image,
+            quads: quads
+                .into_iter()
+                .filter(|quad| quad.target.width > 0.0 && quad.target.height > 0.0)
+                .filter(|quad| quad.source.width > 0.0 && quad.source.height > 0.0)
+                .filter(|quad| quad.target.width.is_finite() && quad.target.height.is_finite())
+                .collect(),
+        })
+    }
+}
+
+/// Split the image and the target into a grid of three by three quads.
+/// Corners are drawn at their logical size, scaled down where opposite corners would overlap.
+#[allow(
+    clippy::cast_precision_loss,
+    reason = "image dimensions are far below 2^24"
+)]
+fn nine_slice(insets: [u32; 4], image: Rect, rect: Rect, ctx: &LengthContext) -> Vec<ImageQuad> {
+    // Insets can not exceed the image, opposite ones share what is left.
+    let clamp = |start: u32, end: u32, size: f32| {
+        let (start, end) = (start as f32, end as f32);
+        let scale = (size / (start + end)).min(1.0);
+        (start * scale, end * scale)
+    };
+    let (top, bottom) = clamp(insets[0], insets[2], image.height);
+    let (left, right) = clamp(insets[3], insets[1], image.width);
+    let device = |length: f32| ctx.metrics.logical_to_device(length);
+    let scale = [
+        (rect.width, device(left) + device(right)),
+        (rect.height, device(top) + device(bottom)),
+    ]
+    .into_iter()
+    .filter(|(_, sum)| *sum > 0.0)
+    .map(|(side, sum)| side / sum)
+    .fold(1.0f32, f32::min);
+    let target = |length: f32| device(length) * scale;
+    let columns = |start: f32, end: f32, size: f32, origin: f32| {
+        [
+            (origin, start),
+            (origin + start, size - start - end),
+            (origin + size - end, end),
+        ]
+    };
+    let source_columns = columns(left, right, image.width, 0.0);
+    let source_rows = columns(top, bottom, image.height, 0.0);
+    let target_columns = columns(target(left), target(right), rect.width, rect.x);
+    let target_rows = columns(target(top), target(bottom), rect.height, rect.y);
+    let mut quads = Vec::with_capacity(9);
+    for (source_row, target_row) in source_rows.into_iter().zip(target_rows) {
+        for (source_column, target_column) in source_columns.into_iter().zip(target_columns) {
+            quads.push(ImageQuad {
+                source: Rect::new(source_column.0, source_row.0, source_column.1, source_row.1),
+                target: Rect::new(target_column.0, target_row.0, target_column.1, target_row.1),
+            });
+        }
+    }
+    quads
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use ::image::{ImageFormat, Rgba, RgbaImage};
+
+    use super::*;
+    use crate::DisplayMetrics;
+
+    fn ctx() -> LengthContext<'static> {
+        let size = Size::new(200.0, 200.0);
+        LengthContext {
+            scene: size,
+            parent: size,
+            metrics: DisplayMetrics::default(),
+            text: None,
+            images: None,
+        }
+    }
+
+    fn encode(width: u32, height: u32, format: ImageFormat) -> Vec<u8> {
+        let image = RgbaImage::from_fn(width, height, |x, _| {
+            Rgba([u8::try_from(x).unwrap_or(u8::MAX), 0, 0, 255])
+        });
+        let mut bytes = Cursor::new(Vec::new());
+        image
+            .write_to(&mut bytes, format)
+            .expect("encode test image");
+        bytes.into_inner()
+    }
+
+    fn asset(id: &str) -> ImageSource {
+        ImageSource::Asset { id: id.into() }
+    }
+
+    #[test]
+    fn test_image_cache() {
+        let png = encode(4, 2, ImageFormat::Png);
+        let mut cache = ImageCache::new(100);
+        cache.register("a", png.clone());
+        cache.register("same", png);
+        cache.register("qoi", encode(3, 3, ImageFormat::Qoi));
+        cache.register("text", b"not an image".as_slice());
+
+        let image = cache.load(&asset("a")).expect("decode png");
+        assert_eq!((image.width, image.height), (4, 2));
+        assert_eq!(&image.pixels[4..8], [1, 0, 0, 255]);
+        // Identical content is decoded once.
+        let same = cache.load(&asset("same")).expect("decode png");
+        assert!(Arc::ptr_eq(&image, &same));
+        assert_eq!(cache.bytes(), 32);
+
+        // Loading a second image exceeds the budget and evicts the least recently used.
+        cache.load(&asset("qoi")).expect("decode qoi");
+        assert_eq!(cache.bytes(), 32 + 36);
+        cache.register("big", encode(4, 4, ImageFormat::Png));
+        cache.load(&asset("big")).expect("decode png");
+        assert_eq!(cache.bytes(), 36 + 64);
+        cache.load(&asset("a")).expect("decode png again");
+        assert_eq!(cache.bytes(), 64 + 32);
+
+        assert!(matches!(
+            cache.load(&asset("text")),
+            Err(ImageError::UnsupportedFormat(_))
+        ));
+        assert!(matches!(
+            cache.load(&asset("missing")),
+            Err(ImageError::UnknownAsset(_))
+        ));
+    }
+
+    #[test]
+    fn test_image_fit() {
+        let mut cache = ImageCache::default();
+        cache.register("wide", encode(40, 20, ImageFormat::Png));
+        let rect = Rect::new(0.0, 0.0, 20.0, 20.0);
+        let quads = |fit: ImageFit| {
+            let element = ImageElement {
+                id: None,
+                source: asset("wide"),
+                fit,
+                width: None,
+                height: None,
+            };
+            PaintedImage::of(&element, rect, &ctx(), &cache)
+                .expect("load image")
+                .quads
+        };
+        assert_eq!(
+            quads(ImageFit::Contain),
+            [ImageQuad {
+                source: Rect::new(0.0, 0.0, 40.0, 20.0),
+                target: Rect::new(0.0, 5.0, 20.0, 10.0),
+            }]
+        );
+        assert_eq!(
+            quads(ImageFit::Cover),
+            [ImageQuad {
+                source: Rect::new(10.0, 0.0, 20.0, 20.0),
+                target: rect,
+            }]
+        );
+        assert_eq!(quads(ImageFit::Fill)[0].target, rect);
+
+        let slices = quads(ImageFit::NineSlice {
+            top: 5,
+            right: 5,
+            bottom: 5,
+            left: 5,
+        });
+        assert_eq!(slices.len(), 9);
+        assert_eq!(slices[0].target, Rect::new(0.0, 0.0, 5.0, 5.0));
+        assert_eq!(
+            slices[4],
+            ImageQuad {
+                source: Rect::new(5.0, 5.0, 30.0, 10.0),
+                target: Rect::new(5.0, 5.0, 10.0, 10.0),
+            }
+        );
+        assert_eq!(slices[8].target, Rect::new(15.0, 15.0, 5.0, 5.0));
+    }
+}
@@ -0,0 +1,600 @@
+//! Offscreen layers of texture elements and how they are composited.
+//!
+//! This is the reference implementation of the compositor on the CPU.
+//! Pixels are premultiplied linear RGBA, effects run on the finished layer
+//! and the opacity of the group is applied last, when the layer is blended onto its parent.
+
+use crate::{
+    BehaviorElement, ElementNode, Fill, LengthContext, LinearRgba, Point, Rect, TextureElement,
+    TextureShader,
+};
+
+/// A rectangle of device pixels, which need not start at the origin.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pixmap {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    pixels: Vec<LinearRgba>,
+}
+
+impl Pixmap {
+    /// A transparent pixmap with its top left pixel at `x`, `y`.
+    #[must_use]
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        let len = usize::try_from(u64::from(width) * u64::from(height)).unwrap_or(usize::MAX);
+        Self {
+            x,
+            y,
+            width,
+            height,
+            pixels: vec![LinearRgba::default(); len],
+        }
+    }
+
+    /// A transparent pixmap covering every pixel the rect touches.
+    #[must_use]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        reason = "device coordinates are far below 2^31"
+    )]
+    pub fn covering(rect: Rect) -> Self {
+        let (x, y) = (rect.x.floor(), rect.y.floor());
+        let width = (rect.right().ceil() - x).max(0.0);
+        let height = (rect.bottom().ceil() - y).max(0.0);
+        Self::new(x as i32, y as i32, width as u32, height as u32)
+    }
+
+    #[must_use]
+    pub const fn x(&self) -> i32 {
+        self.x
+    }
+
+    #[must_use]
+    pub const fn y(&self) -> i32 {
+        self.y
+    }
+
+    #[must_use]
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[must_use]
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Covered area in device pixels.
+    #[must_use]
+    #[allow(
+        clippy::cast_precision_loss,
+        reason = "device coordinates are far below 2^24"
+    )]
+    pub const fn bounds(&self) -> Rect {
+        Rect::new(
+            self.x as f32,
+            self.y as f32,
+            self.width as f32,
+            self.height as f32,
+        )
+    }
+
+    /// Rows of pixels from the top.
+    #[must_use]
+    pub fn pixels(&self) -> &[LinearRgba] {
+        &self.pixels
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        let column = u32::try_from(i64::from(x) - i64::from(self.x)).ok()?;
+        let row = u32::try_from(i64::from(y) - i64::from(self.y)).ok()?;
+        if column >= self.width || row >= self.height {
+            return None;
+        }
+        usize::try_from(u64::from(row) * u64::from(self.width) + u64::from(column)).ok()
+    }
+
+    /// The pixel at device coordinates, transparent outside the pixmap.
+    #[must_use]
+    pub fn pixel(&self, x: i32, y: i32) -> LinearRgba {
+        self.index(x, y)
+            .map_or_else(LinearRgba::default, |index| self.pixels[index])
+    }
+
+    pub fn pixel_mut(&mut self, x: i32, y: i32) -> Option<&mut LinearRgba> {
+        self.index(x, y).map(|index| &mut self.pixels[index])
+    }
+
+    /// Draw a color over the pixel at device coordinates, if it is inside the pixmap.
+    pub fn blend(&mut self, x: i32, y: i32, color: LinearRgba) {
+        if let Some(pixel) = self.pixel_mut(x, y) {
+            *pixel = color.over(*pixel);
+        }
+    }
+
+    /// Draw another pixmap over this one where they overlap, faded by `opacity`.
+    pub fn composite(&mut self, layer: &Self, opacity: f32) {
+        if layer.width == 0 || layer.height == 0 {
+            return;
+        }
+        for (row, pixels) in (layer.y..).zip(layer.pixels.chunks_exact(layer.width as usize)) {
+            for (column, pixel) in (layer.x..).zip(pixels) {
+                self.blend(column, row, pixel.scaled(opacity));
+            }
+        }
+    }
+
+    /// Pixel centers and pixels in device coordinates.
+    fn centers_mut(&mut self) -> impl Iterator<Item = (Point, &mut LinearRgba)> {
+        let (x, y, width) = (self.x, self.y, self.width as usize);
+        self.pixels
+            .iter_mut()
+            .enumerate()
+            .map(move |(index, pixel)| {
+                #[allow(
+                    clippy::cast_precision_loss,
+                    clippy::cast_possible_truncation,
+                    clippy::cast_possible_wrap,
+                    reason = "device coordinates are far below 2^24"
+                )]
+                let center = Point::new(
+                    (x + (index % width) as i32) as f32 + 0.5,
+                    (y + (index / width) as i32) as f32 + 0.5,
+                );
+                (center, pixel)
+            })
+    }
+}
+
+/// A [`TextureShader`] resolved to device pixels.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Effect {
+    /// Gaussian blur with the standard deviation in device pixels.
+    Blur {
+        sigma: f32,
+    },
+    ColorMatrix([f32; 20]),
+    /// `amount` already includes the alpha of the tint.
+    Tint {
+        color: LinearRgba,
+        amount: f32,
+    },
+    Mask(Fill),
+}
+
+impl Effect {
+    /// Resolve a shader of a layer covering `rect`.
+    #[must_use]
+    pub fn of(shader: &TextureShader, rect: Rect, ctx: &LengthContext) -> Self {
+        let ctx = ctx.with_parent(rect.size());
+        match shader {
+            TextureShader::Blur { radius } => Self::Blur {
+                sigma: radius.resolve(&ctx).max(0.0),
+            },
+            TextureShader::ColorMatrix { matrix } => Self::ColorMatrix(*matrix),
+            TextureShader::Tint { color, amount } => {
+                let color = color.to_premultiplied();
+                Self::Tint {
+                    color,
+                    amount: amount.clamp(0.0, 1.0) * color.a,
+                }
+            }
+            TextureShader::Mask { paint } => Self::Mask(Fill::of(paint, rect, &ctx)),
+        }
+    }
+
+    /// How far the effect reaches beyond the drawn pixels.
+    #[must_use]
+    pub fn outset(&self) -> f32 {
+        match self {
+            Self::Blur { sigma } => (sigma * 3.0).ceil(),
+            Self::ColorMatrix(_) | Self::Tint { .. } | Self::Mask(_) => 0.0,
+        }
+    }
+
+    /// Process the pixels of a finished layer.
+    pub fn apply(&self, layer: &mut Pixmap) {
+        match self {
+            Self::Blur { sigma } => blur(layer, &blur_kernel(*sigma)),
+            Self::ColorMatrix(matrix) => {
+                for pixel in &mut layer.pixels {
+                    *pixel = color_matrix(matrix, *pixel);
+                }
+            }
+            Self::Tint { color, amount } => {
+                for pixel in &mut layer.pixels {
+                    *pixel = tint(*color, *amount, *pixel);
+                }
+            }
+            Self::Mask(fill) => {
+                for (center, pixel) in layer.centers_mut() {
+                    *pixel = pixel.scaled(fill.color_at(center).a);
+                }
+            }
+        }
+    }
+}
+
+/// Normalized weights of a gaussian from `-radius` to `radius` pixels,
+/// with the radius three standard deviations rounded up.
+#[must_use]
+pub fn blur_kernel(sigma: f32) -> Vec<f32> {
+    if sigma <= 0.0 || sigma.is_nan() {
+        return vec![1.0];
+    }
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        reason = "blur radii are small and positive"
+    )]
+    let radius = (sigma * 3.0).ceil() as i32;
+    let weights: Vec<f32> = (-radius..=radius)
+        .map(|offset| {
+            #[allow(clippy::cast_precision_loss, reason = "blur radii are small")]
+            let offset = offset as f32;
+            (-offset * offset / (2.0 * sigma * sigma)).exp()
+        })
+        .collect();
+    let sum: f32 = weights.iter().sum();
+    weights.into_iter().map(|weight| weight / sum).collect()
+}
+
+/// Convolve the rows and then the columns with the kernel,
+/// treating pixels outside the pixmap as transparent.
+fn blur(layer: &mut Pixmap, kernel: &[f32]) {
+    if kernel.len() <= 1 {
+        return;
+    }
+    let (width, height) = (layer.width as usize, layer.height as usize);
+    let radius = kernel.len() / 2;
+    let convolve = |pixels: &[LinearRgba], len: usize, stride: usize, start: usize| {
+        (0..len)
+            .map(|i| {
+                let mut sum = LinearRgba::default();
+                for (k, weight) in kernel.iter().enumerate() {
+                    let Some(j) = (i + k).checked_sub(radius).filter(|j| *j < len) else {
+                        continue;
+                    };
+                    let pixel = pixels[start + j * stride];
+                    sum = LinearRgba {
+                        r: pixel.r.mul_add(*weight, sum.r),
+                        g: pixel.g.mul_add(*weight, sum.g),
+                        b: pixel.b.mul_add(*weight, sum.b),
+                        a: pixel.a.mul_add(*weight, sum.a),
+                    };
+                }
+                sum
+            })
+            .collect::<Vec<_>>()
+    };
+    for row in 0..height {
+        let blurred = convolve(&layer.pixels, width, 1, row * width);
+        layer.pixels[row * width..(row + 1) * width].copy_from_slice(&blurred);
+    }
+    for column in 0..width {
+        let blurred = convolve(&layer.pixels, height, width, column);
+        for (row, pixel) in blurred.into_iter().enumerate() {
+            layer.pixels[row * width + column] = pixel;
+        }
+    }
+}
+
+/// Apply the matrix to the straight color, clamping the result.
+fn color_matrix(matrix: &[f32; 20], pixel: LinearRgba) -> LinearRgba {
+    let straight = if pixel.a > 0.0 {
+        [
+            pixel.r / pixel.a,
+            pixel.g / pixel.a,
+            pixel.b / pixel.a,
+            pixel.a,
+        ]
+    } else {
+        [0.0; 4]
+    };
+    let [r, g, b, a] = std::array::from_fn(|row| {
+        let coefficients = &matrix[row * 5..row * 5 + 5];
+        straight
+            .iter()
+            .zip(coefficients)
+            .fold(coefficients[4], |sum, (value, coefficient)| {
+                value.mul_add(*coefficient, sum)
+            })
+            .clamp(0.0, 1.0)
+    });
+    LinearRgba {
+        r: r * a,
+        g: g * a,
+        b: b * a,
+        a,
+    }
+}
+
+/// Mix the color of the pixel towards the tint, keeping its alpha.
+fn tint(color: LinearRgba, amount: f32, pixel: LinearRgba) -> LinearRgba {
+    if color.a <= 0.0 {
+        return pixel;
+    }
+    let target = |component: f32| component / color.a * pixel.a;
+    let mix = |from: f32, to: f32| (to - from).mul_add(amount, from);
+    LinearRgba {
+        r: mix(pixel.r, target(color.r)),
+        g: mix(pixel.g, target(color.g)),
+        b: mix(pixel.b, target(color.b)),
+        a: pixel.a,
+    }
+}
+
+/// The offscreen layer of a [`TextureElement`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Layer {
+    /// Bounds of the parent of the texture element.
+    pub rect: Rect,
+    pub opacity: f32,
+    pub effect: Option<Effect>,
+}
+
+impl Layer {
+    /// Resolve a texture element whose parent is laid out at `rect`.
+    #[must_use]
+    pub fn of(element: &TextureElement, rect: Rect, ctx: &LengthContext) -> Self {
+        Self {
+            rect,
+            opacity: element.opacity.clamp(0.0, 1.0),
+            effect: element
+                .shader
+                .as_ref()
+                .map(|shader| Effect::of(shader, rect, ctx)),
+        }
+    }
+
+    /// Layers of the texture elements among the children of an element laid out at `rect`,
+    /// in the order they are pushed.
+    #[must_use]
+    pub fn of_children(children: &[ElementNode], rect: Rect, ctx: &LengthContext) -> Vec<Self> {
+        children
+            .iter()
+            .filter_map(|child| match child {
+                ElementNode::Behavior(BehaviorElement::Texture(e)) => Some(Self::of(e, rect, ctx)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Area of the offscreen pixmap, which grows with the reach of the effect.
+    #[must_use]
+    pub fn bounds(&self) -> Rect {
+        let outset = self.effect.as_ref().map_or(0.0, Effect::outset);
+        self.rect.inset(-outset, -outset, -outset, -outset)
+    }
+}
+
+/// A stack of offscreen layers on top of the pixmap of the scene.
+///
+/// Content is drawn to [`Self::target`], the topmost layer.
+/// Popping a layer applies its effect and blends it onto the layer below.
+#[derive(Clone, Debug)]
+pub struct Compositor {
+    root: Pixmap,
+    layers: Vec<(Layer, Pixmap)>,
+}
+
+impl Compositor {
+    #[must_use]
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            root: Pixmap::new(0, 0, width, height),
+            layers: Vec::new(),
+        }
+    }
+
+    /// The pixmap content is drawn to.
+    pub fn target(&mut self) -> &mut Pixmap {
+        self.layers
+            .last_mut()
+            .map_or(&mut self.root, |(_, pixmap)| pixmap)
+    }
+
+    /// Number of layers pushed and not yet popped.
+    #[must_use]
+    pub const fn depth(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Start drawing to a new offscreen layer.
+    pub fn push(&mut self, layer: Layer) {
+        let pixmap = Pixmap::covering(layer.bounds());
+        self.layers.push((layer, pixmap));
+    }
+
+    /// Finish the topmost layer and composite it onto the one below.
+    /// ## Panics
+    /// - If no layer was pushed.
+    pub fn pop(&mut self) {
+        let (layer, mut pixmap) = self.layers.pop().expect("a layer to pop");
+        if let Some(effect) = &layer.effect {
+            effect.apply(&mut pixmap);
+        }
+        self.target().composite(&pixmap, layer.opacity);
+    }
+
+    /// Pop every remaining layer and return the pixels of the scene.
+    #[must_use]
+    pub fn finish(mut self) -> Pixmap {
+        while !self.layers.is_empty() {
+            self.pop();
+        }
+        self.root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, DisplayMetrics, LayoutLength, Paint, Size};
+
+    fn ctx() -> LengthContext<'static> {
+        let size = Size::new(200.0, 200.0);
+        LengthContext {
+            scene: size,
+            parent: size,
+            metrics: DisplayMetrics::default(),
+            text: None,
+            images: None,
+        }
+    }
+
+    fn fill(pixmap: &mut Pixmap, rect: Rect, color: LinearRgba) {
+        let rect = Pixmap::covering(rect);
+        for y in rect.y..rect.y + i32::try_from(rect.height).unwrap_or(0) {
+            for x in rect.x..rect.x + i32::try_from(rect.width).unwrap_or(0) {
+                pixmap.blend(x, y, color);
+            }
+        }
+    }
+
+    fn components(color: LinearRgba) -> [f32; 4] {
+        [color.r, color.g, color.b, color.a]
+    }
+
+    fn assert_close(actual: LinearRgba, expected: [f32; 4]) {
+        for (a, b) in components(actual).into_iter().zip(expected) {
+            assert!((a - b).abs() < 1e-4, "{actual:?} != {expected:?}");
+        }
+    }
+
+    fn texture(opacity: f32, shader: Option<TextureShader>) -> TextureElement {
+        TextureElement {
+            id: None,
+            opacity,
+            shader,
+        }
+    }
+
+    const RED: LinearRgba = LinearRgba {
+        r: 1.0,
+        g: 0.0,
+        b: 0.0,
+        a: 1.0,
+    };
+
+    #[test]
+    fn test_group_opacity() {
+        let mut compositor = Compositor::new(4, 1);
+        let rect = Rect::new(0.0, 0.0, 3.0, 1.0);
+        compositor.push(Layer::of(&texture(0.5, None), rect, &ctx()));
+        assert_eq!(compositor.depth(), 1);
+        // Overlapping opaque children stay opaque inside the layer.
+        fill(compositor.target(), Rect::new(0.0, 0.0, 2.0, 1.0), RED);
+        fill(compositor.target(), Rect::new(1.0, 0.0, 2.0, 1.0), RED);
+        // Content outside the layer is clipped.
+        compositor.target().blend(3, 0, RED);
+        let pixmap = compositor.finish();
+        let alphas: Vec<f32> = pixmap.pixels().iter().map(|pixel| pixel.a).collect();
+        assert_eq!(alphas, [0.5, 0.5, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn test_blur_effect() {
+        let kernel = blur_kernel(1.0);
+        assert_eq!(kernel.len(), 7);
+        assert!((kernel.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+        assert_eq!(blur_kernel(0.0), [1.0]);
+
+        let shader = TextureShader::Blur {
+            radius: LayoutLength::Pixel(1.0),
+        };
+        let layer = Layer::of(
+            &texture(1.0, Some(shader)),
+            Rect::new(5.0, 5.0, 1.0, 1.0),
+            &ctx(),
+        );
+        assert_eq!(layer.bounds(), Rect::new(2.0, 2.0, 7.0, 7.0));
+        let mut compositor = Compositor::new(11, 11);
+        compositor.push(layer);
+        compositor.target().blend(5, 5, RED);
+        let pixmap = compositor.finish();
+        // The pixel spreads symmetrically and its total is kept.
+        let total: f32 = pixmap.pixels().iter().map(|pixel| pixel.a).sum();
+        assert!((total - 1.0).abs() < 1e-4, "{total}");
+        assert_close(
+            pixmap.pixel(5, 5),
+            [kernel[3] * kernel[3], 0.0, 0.0, kernel[3] * kernel[3]],
+        );
+        assert_eq!(pixmap.pixel(4, 5), pixmap.pixel(6, 5));
+        assert_eq!(pixmap.pixel(5, 2), pixmap.pixel(2, 5));
+        assert_eq!(pixmap.pixel(1, 5), LinearRgba::default());
+    }
+
+    #[test]
+    fn test_color_effects() {
+        let rect = Rect::new(0.0, 0.0, 2.0, 1.0);
+        let half_red = RED.scaled(0.5);
+        let apply = |shader: TextureShader| {
+            let mut pixmap = Pixmap::covering(rect);
+            fill(&mut pixmap, rect, half_red);
+            Effect::of(&shader, rect, &ctx()).apply(&mut pixmap);
+            pixmap
+        };
+
+        // Swap red and green, and keep alpha.
+        let mut matrix = [0.0; 20];
+        matrix[1] = 1.0;
+        matrix[5] = 1.0;
+        matrix[18] = 1.0;
+        let swapped = apply(TextureShader::ColorMatrix { matrix });
+        assert_close(swapped.pixel(0, 0), [0.0, 0.5, 0.0, 0.5]);
+
+        let tinted = apply(TextureShader::Tint {
+            color: Color::LinearRgb {
+                r: 0.0,
+                g: 0.0,
+                b: 1.0,
+                a: 1.0,
+            },
+            amount: 0.5,
+        });
+        assert_close(tinted.pixel(0, 0), [0.25, 0.0, 0.25, 0.5]);
+
+        // A gradient from opaque on the left to transparent on the right.
+        let masked = apply(TextureShader::Mask {
+            paint: Paint::Linear {
+                angle: 90.0,
+                stops: vec![
+                    crate::ColorStop {
+                        offset: None,
+                        color: Color::BLACK,
+                    },
+                    crate::ColorStop {
+                        offset: None,
+                        color: Color::BLACK.with_alpha(0.0),
+                    },
+                ],
+            },
+        });
+        assert_close(masked.pixel(0, 0), [0.375, 0.0, 0.0, 0.375]);
+        assert_close(masked.pixel(1, 0), [0.125, 0.0, 0.0, 0.125]);
+    }
+
+    #[test]
+    fn test_nested_layers() {
+        let rect = Rect::new(0.0, 0.0, 1.0, 1.0);
+        let children = [
+            ElementNode::Behavior(BehaviorElement::Texture(texture(0.5, None))),
+            ElementNode::Behavior(BehaviorElement::PointerEnter),
+            ElementNode::Behavior(BehaviorElement::Texture(texture(0.5, None))),
+        ];
+        let layers = Layer::of_children(&children, rect, &ctx());
+        assert_eq!(layers.len(), 2);
+        let mut compositor = Compositor::new(1, 1);
+        for layer in layers {
+            compositor.push(layer);
+        }
+        compositor.target().blend(0, 0, RED);
+        compositor.pop();
+        assert_eq!(compositor.depth(), 1);
+        assert_close(compositor.finish().pixel(0, 0), [0.25, 0.0, 0.0, 0.25]);
+    }
+}
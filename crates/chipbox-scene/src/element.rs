@@ -1,31 +1,50 @@
 mod behavior;
 mod content;
+mod kind;
 mod layout;
+mod path;
 
 use std::sync::Arc;
 
 use delegate_match::delegate_match;
 
 pub use self::{
-    behavior::{BehaviorElement, TextureElement},
-    content::{Color, ContentElement, TextElement},
+    behavior::{
+        AnimatedProperty, BehaviorElement, Easing, FocusableElement, StackingElement,
+        TextureElement, TextureShader, Timing, TransformElement, TransitionElement,
+    },
+    content::{
+        Border, BufferId, Color, ColorParseError, ColorParseErrorKind, ColorStop, ContentElement,
+        CornerRadii, FillRule, ImageElement, ImageFit, ImageSource, LineCap, LineJoin, LinearRgba,
+        Paint, PathData, PathElement, PathParseError, PathParseErrorKind, PathSegment, PathStroke,
+        RectElement, Shadow, SpanElement, TextChild, TextElement, TextOverflow, ViewBox,
+        ViewBoxFit, WaveformElement, color_from_str, path_from_str,
+    },
+    kind::ElementKind,
     layout::{
-        ArrayElement, Axis, BoxElement, ContentBoxElement, FlexElement, FlexItemElement,
-        GridElement, LayoutElement, LayoutLength, LinearDirection, MarginElement, SizedElement,
+        AlignElement, Alignment, ArrayElement, Axis, BoxElement, ContentBoxElement, FlexAlign,
+        FlexElement, FlexItemElement, FlexJustify, GridElement, GridItemElement, GridTemplate,
+        GridTrack, GridTrackParseError, HorizontalAlign, LayoutElement, LayoutLength, LengthExpr,
+        LengthParseError, LengthParseErrorKind, LinearDirection, MarginElement, ScrollElement,
+        SizedElement, VerticalAlign, length_from_str,
     },
+    path::ElementPath,
 };
 
-#[derive(specta::Type, Clone)]
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct ElementId(pub Arc<str>);
 
-#[derive(specta::Type)]
+impl std::fmt::Display for ElementId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
 #[serde(untagged, rename_all = "kebab-case")]
 pub enum ElementNode {
-    #[serde(flatten)]
     Layout(LayoutElement),
-    #[serde(flatten)]
     Behavior(BehaviorElement),
-    #[serde(flatten)]
     Content(ContentElement),
 }
 
@@ -36,4 +55,11 @@ impl ElementNode {
             Self::{ Layout, Behavior, Content }(e) => e.id(),
         }}
     }
+
+    #[must_use]
+    pub const fn kind(&self) -> ElementKind {
+        delegate_match! { match self {
+            Self::{ Layout, Behavior, Content }(e) => e.kind(),
+        }}
+    }
 }
@@ -1,15 +1,28 @@
+mod focusable;
+mod stacking;
 mod texture;
+mod transform;
+mod transition;
 
 use delegate_match::delegate_match;
 
-pub use self::texture::TextureElement;
-use crate::ElementId;
+pub use self::{
+    focusable::FocusableElement,
+    stacking::StackingElement,
+    texture::{TextureElement, TextureShader},
+    transform::TransformElement,
+    transition::{AnimatedProperty, Easing, Timing, TransitionElement},
+};
+use crate::{ElementId, ElementKind};
 
 /// Applies behavior to the parent element.
-#[derive(specta::Type)]
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum BehaviorElement {
     Texture(TextureElement),
+    Transform(TransformElement),
+    Stacking(StackingElement),
+    Transition(TransitionElement),
     SelectionContainer,
     SelectionAction,
     Selection,
@@ -18,17 +31,50 @@ pub enum BehaviorElement {
     PointerHoverMove,
     PointerEnter,
     PointerLeave,
+    Focusable(FocusableElement),
+    KeyDown,
+    KeyUp,
+    TextInput,
+    Focus,
+    Blur,
 }
 
 impl BehaviorElement {
     #[must_use]
     pub fn id(&self) -> Option<ElementId> {
         delegate_match! { match self {
-            Self::{ Texture }(e) => e.id(),
+            Self::{ Texture, Transform, Stacking, Transition, Focusable }(e) => e.id(),
             Self::{
                 SelectionContainer, SelectionAction, Selection, Deselection,
-                PointerAction, PointerHoverMove, PointerEnter, PointerLeave
+                PointerAction, PointerHoverMove, PointerEnter, PointerLeave,
+                KeyDown, KeyUp, TextInput, Focus, Blur
             } => None,
         }}
     }
 }
+
+impl BehaviorElement {
+    #[must_use]
+    pub const fn kind(&self) -> ElementKind {
+        match self {
+            Self::Texture(_) => ElementKind::Texture,
+            Self::Transform(_) => ElementKind::Transform,
+            Self::Stacking(_) => ElementKind::Stacking,
+            Self::Transition(_) => ElementKind::Transition,
+            Self::SelectionContainer => ElementKind::SelectionContainer,
+            Self::SelectionAction => ElementKind::SelectionAction,
+            Self::Selection => ElementKind::Selection,
+            Self::Deselection => ElementKind::Deselection,
+            Self::PointerAction => ElementKind::PointerAction,
+            Self::PointerHoverMove => ElementKind::PointerHoverMove,
+            Self::PointerEnter => ElementKind::PointerEnter,
+            Self::PointerLeave => ElementKind::PointerLeave,
+            Self::Focusable(_) => ElementKind::Focusable,
+            Self::KeyDown => ElementKind::KeyDown,
+            Self::KeyUp => ElementKind::KeyUp,
+            Self::TextInput => ElementKind::TextInput,
+            Self::Focus => ElementKind::Focus,
+            Self::Blur => ElementKind::Blur,
+        }
+    }
+}
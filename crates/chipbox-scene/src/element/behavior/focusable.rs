@@ -0,0 +1,21 @@
+use crate::ElementId;
+
+/// A focusable element is a behavior element that lets its parent receive keyboard focus.
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusableElement {
+    #[serde(default)]
+    pub id: Option<ElementId>,
+    /// Position in the tab order.
+    /// Positive indices come first in ascending order, followed by zero in document order.
+    /// Negative indices are skipped by tab traversal, but can still be focused by pointer or programmatically.
+    #[serde(default)]
+    pub tab_index: i32,
+}
+
+impl FocusableElement {
+    #[must_use]
+    pub fn id(&self) -> Option<ElementId> {
+        self.id.clone()
+    }
+}
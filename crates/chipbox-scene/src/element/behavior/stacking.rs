@@ -0,0 +1,23 @@
+use crate::ElementId;
+
+/// A stacking element is a behavior element that orders its parent among its siblings.
+///
+/// Siblings are painted and hit-tested in ascending `z_index`, keeping document order
+/// among equal indices, and elements without a stacking element have index zero.
+/// The parent forms a stacking context: its descendants are ordered among themselves
+/// and never above or below elements outside of it.
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StackingElement {
+    #[serde(default)]
+    pub id: Option<ElementId>,
+    #[serde(default)]
+    pub z_index: i32,
+}
+
+impl StackingElement {
+    #[must_use]
+    pub fn id(&self) -> Option<ElementId> {
+        self.id.clone()
+    }
+}
@@ -1,14 +1,19 @@
-use crate::ElementId;
+use crate::{Color, ElementId, LayoutLength, Paint};
 
 /// A texture element is a behavior element that makes its parent render to a texture.
-#[derive(specta::Type)]
+///
+/// The parent and its children are drawn to an offscreen layer,
+/// which is processed by the shader and then composited with the opacity.
+/// Overlapping children therefore fade as a group instead of showing through each other.
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct TextureElement {
     #[serde(default)]
     pub id: Option<ElementId>,
     #[serde(default = "TextureElement::default_opacity")]
     pub opacity: f32,
-    pub shader: (),
+    #[serde(default)]
+    pub shader: Option<TextureShader>,
 }
 
 impl TextureElement {
@@ -22,3 +27,68 @@ impl TextureElement {
         self.id.clone()
     }
 }
+
+/// A built-in effect applied to the layer of a [`TextureElement`].
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TextureShader {
+    /// Gaussian blur with the radius as its standard deviation, as in CSS `blur()`.
+    /// The layer grows by three times the radius so the blur is not cut off.
+    Blur { radius: LayoutLength },
+    /// A row-major 4x5 matrix mapping straight linear RGBA and a constant one
+    /// to the new RGBA, as in SVG `feColorMatrix`.
+    ColorMatrix { matrix: [f32; 20] },
+    /// Replaces the color of every pixel with the tint, keeping its alpha.
+    /// `amount` mixes between the original color at `0` and the tint at `1`.
+    Tint {
+        color: Color,
+        #[serde(default = "TextureShader::default_amount")]
+        amount: f32,
+    },
+    /// Multiplies the layer by the alpha of the paint, which covers the parent bounds.
+    Mask { paint: Paint },
+}
+
+impl TextureShader {
+    #[must_use]
+    pub const fn default_amount() -> f32 {
+        1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_texture_shader() {
+        let texture: TextureElement = serde_json::from_str(
+            r#"{ "opacity": 0.5, "shader": { "type": "blur", "radius": "4px" } }"#,
+        )
+        .expect("valid texture");
+        assert_eq!(
+            texture.shader,
+            Some(TextureShader::Blur {
+                radius: LayoutLength::Pixel(4.0)
+            })
+        );
+
+        let texture: TextureElement =
+            serde_json::from_str(r##"{ "shader": { "type": "tint", "color": "#f00" } }"##)
+                .expect("valid texture");
+        assert_eq!(
+            (texture.opacity, texture.shader),
+            (
+                TextureElement::default_opacity(),
+                Some(TextureShader::Tint {
+                    color: Color::rgb(255, 0, 0),
+                    amount: 1.0,
+                })
+            )
+        );
+
+        let texture: TextureElement =
+            serde_json::from_str(r#"{ "shader": null }"#).expect("valid texture");
+        assert_eq!(texture.shader, None);
+    }
+}
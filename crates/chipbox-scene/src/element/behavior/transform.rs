@@ -0,0 +1,103 @@
+use crate::{Alignment, ElementId, HorizontalAlign, LayoutLength, VerticalAlign};
+
+/// A transform element is a behavior element that moves, rotates, scales and skews its parent
+/// together with its descendants.
+///
+/// The transform is applied in the order translate, rotate, scale and skew around the origin.
+/// Like in CSS, it does not affect the layout of other elements,
+/// but hit-testing and painting follow it.
+/// Several transform elements on the same parent apply in order, the first one outermost.
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransformElement {
+    #[serde(default)]
+    pub id: Option<ElementId>,
+    /// Lengths relative to the parent refer to the transformed element itself.
+    #[serde(default)]
+    pub translate_x: Option<LayoutLength>,
+    #[serde(default)]
+    pub translate_y: Option<LayoutLength>,
+    /// Rotation in degrees clockwise.
+    #[serde(default)]
+    pub rotate: f32,
+    #[serde(default = "TransformElement::default_scale")]
+    pub scale_x: f32,
+    #[serde(default = "TransformElement::default_scale")]
+    pub scale_y: f32,
+    /// Skew along the x axis in degrees.
+    #[serde(default)]
+    pub skew_x: f32,
+    /// Skew along the y axis in degrees.
+    #[serde(default)]
+    pub skew_y: f32,
+    /// The point of the element that stays in place, the center by default.
+    #[serde(default = "TransformElement::default_origin")]
+    pub origin: Alignment,
+}
+
+impl TransformElement {
+    #[must_use]
+    pub const fn default_scale() -> f32 {
+        1.0
+    }
+
+    #[must_use]
+    pub const fn default_origin() -> Alignment {
+        Alignment {
+            horizontal: HorizontalAlign::Center,
+            vertical: VerticalAlign::Middle,
+        }
+    }
+
+    #[must_use]
+    pub fn id(&self) -> Option<ElementId> {
+        self.id.clone()
+    }
+}
+
+impl Default for TransformElement {
+    fn default() -> Self {
+        Self {
+            id: None,
+            translate_x: None,
+            translate_y: None,
+            rotate: 0.0,
+            scale_x: Self::default_scale(),
+            scale_y: Self::default_scale(),
+            skew_x: 0.0,
+            skew_y: 0.0,
+            origin: Self::default_origin(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_transform() {
+        let transform: TransformElement = serde_json::from_str(
+            r#"{
+                "translateY": "-4px",
+                "rotate": 45,
+                "scaleX": 2,
+                "origin": { "horizontal": "left", "vertical": "top" }
+            }"#,
+        )
+        .expect("valid transform");
+        assert_eq!(
+            transform,
+            TransformElement {
+                translate_y: Some(LayoutLength::Pixel(-4.0)),
+                rotate: 45.0,
+                scale_x: 2.0,
+                origin: Alignment {
+                    horizontal: HorizontalAlign::Left,
+                    vertical: VerticalAlign::Top,
+                },
+                ..TransformElement::default()
+            }
+        );
+    }
+}
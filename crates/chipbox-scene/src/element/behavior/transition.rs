@@ -0,0 +1,249 @@
+use crate::ElementId;
+
+/// A transition element is a behavior element that animates changes to a property of its parent.
+///
+/// Whenever the declared value changes, the displayed value moves from where it currently is
+/// to the new value with the timing of the transition, driven by an [`crate::Animator`].
+/// Properties of the parent's behavior and content children count as properties of the parent,
+/// so a transition on `opacity` animates every sibling texture element.
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransitionElement {
+    #[serde(default)]
+    pub id: Option<ElementId>,
+    pub property: AnimatedProperty,
+    #[serde(default)]
+    pub timing: Timing,
+}
+
+impl TransitionElement {
+    #[must_use]
+    pub fn id(&self) -> Option<ElementId> {
+        self.id.clone()
+    }
+}
+
+/// A property that can be animated by a [`TransitionElement`].
+#[derive(
+    specta::Type, serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum AnimatedProperty {
+    /// Width of a box parent.
+    Width,
+    /// Height of a box parent.
+    Height,
+    /// Horizontal offset of an align parent.
+    XOffset,
+    /// Vertical offset of an align parent.
+    YOffset,
+    /// Opacity of sibling texture elements.
+    Opacity,
+    /// Every component of sibling transform elements, except the origin.
+    Transform,
+    /// Solid fill color of sibling rect elements.
+    Fill,
+    /// Color of sibling text elements.
+    Color,
+}
+
+/// How an animated value approaches its target.
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Timing {
+    /// Follows the easing curve over a fixed duration, as in CSS transitions.
+    Tween {
+        /// Duration in seconds.
+        duration: f32,
+        /// Seconds to wait before starting.
+        #[serde(default)]
+        delay: f32,
+        #[serde(default)]
+        easing: Easing,
+    },
+    /// A damped spring pulling the value towards the target.
+    /// The default parameters settle in about half a second without overshooting noticeably.
+    /// Retargeting a moving spring keeps its velocity.
+    Spring {
+        #[serde(default = "Timing::default_stiffness")]
+        stiffness: f32,
+        #[serde(default = "Timing::default_damping")]
+        damping: f32,
+        #[serde(default = "Timing::default_mass")]
+        mass: f32,
+    },
+}
+
+impl Timing {
+    #[must_use]
+    pub const fn default_stiffness() -> f32 {
+        170.0
+    }
+
+    #[must_use]
+    pub const fn default_damping() -> f32 {
+        26.0
+    }
+
+    #[must_use]
+    pub const fn default_mass() -> f32 {
+        1.0
+    }
+}
+
+impl Default for Timing {
+    fn default() -> Self {
+        Self::Tween {
+            duration: 0.25,
+            delay: 0.0,
+            easing: Easing::default(),
+        }
+    }
+}
+
+/// Maps the linear progress of a tween to the progress of the value, as CSS timing functions do.
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Easing {
+    Linear,
+    #[default]
+    Ease,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    /// A cubic Bézier curve from `(0, 0)` to `(1, 1)` with the given control points.
+    /// The x coordinates must be within `0..=1`.
+    CubicBezier {
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+    },
+}
+
+impl Easing {
+    /// The eased progress at linear progress `t` within `0..=1`.
+    #[must_use]
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        let [x1, y1, x2, y2] = match *self {
+            Self::Linear => return t,
+            Self::Ease => [0.25, 0.1, 0.25, 1.0],
+            Self::EaseIn => [0.42, 0.0, 1.0, 1.0],
+            Self::EaseOut => [0.0, 0.0, 0.58, 1.0],
+            Self::EaseInOut => [0.42, 0.0, 0.58, 1.0],
+            Self::CubicBezier { x1, y1, x2, y2 } => [x1, y1, x2, y2],
+        };
+        bezier(y1, y2, bezier_parameter(x1, x2, t))
+    }
+}
+
+/// One coordinate of a cubic Bézier curve with its end points at zero and one.
+fn bezier(p1: f32, p2: f32, s: f32) -> f32 {
+    let rest = 1.0 - s;
+    (3.0 * rest * rest * s).mul_add(p1, (3.0 * rest * s * s).mul_add(p2, s * s * s))
+}
+
+/// The curve parameter at which the x coordinate reaches `x`.
+/// Newton's method converges in a few steps on typical curves,
+/// bisection takes over where the slope is too flat.
+fn bezier_parameter(x1: f32, x2: f32, x: f32) -> f32 {
+    let mut s = x;
+    for _ in 0..8 {
+        let error = bezier(x1, x2, s) - x;
+        if error.abs() < 1e-6 {
+            return s;
+        }
+        let rest = 1.0 - s;
+        let slope = (3.0 * rest * rest).mul_add(
+            x1,
+            (6.0 * rest * s).mul_add(x2 - x1, 3.0 * s * s * (1.0 - x2)),
+        );
+        if slope.abs() < 1e-6 {
+            break;
+        }
+        s = (s - error / slope).clamp(0.0, 1.0);
+    }
+    let (mut low, mut high) = (0.0_f32, 1.0_f32);
+    s = x;
+    for _ in 0..32 {
+        let value = bezier(x1, x2, s);
+        if (value - x).abs() < 1e-6 {
+            break;
+        }
+        if value < x {
+            low = s;
+        } else {
+            high = s;
+        }
+        s = f32::midpoint(low, high);
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_transition() {
+        let transition: TransitionElement = serde_json::from_str(
+            r#"{
+                "property": "xOffset",
+                "timing": { "type": "tween", "duration": 0.3, "easing": "easeOut" }
+            }"#,
+        )
+        .expect("valid transition");
+        assert_eq!(
+            transition,
+            TransitionElement {
+                id: None,
+                property: AnimatedProperty::XOffset,
+                timing: Timing::Tween {
+                    duration: 0.3,
+                    delay: 0.0,
+                    easing: Easing::EaseOut,
+                },
+            }
+        );
+
+        let transition: TransitionElement = serde_json::from_str(
+            r#"{ "property": "opacity", "timing": { "type": "spring", "damping": 10 } }"#,
+        )
+        .expect("valid transition");
+        assert_eq!(
+            transition.timing,
+            Timing::Spring {
+                stiffness: Timing::default_stiffness(),
+                damping: 10.0,
+                mass: Timing::default_mass(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_easing() {
+        let round = |value: f32| (value * 1000.0).round() / 1000.0;
+        let values: Vec<_> = [
+            Easing::Linear.apply(0.3),
+            Easing::EaseInOut.apply(0.5),
+            // Reference values of the CSS timing functions.
+            Easing::Ease.apply(0.25),
+            Easing::EaseIn.apply(0.5),
+            Easing::EaseOut.apply(0.5),
+            Easing::Ease.apply(0.0),
+            Easing::Ease.apply(1.5),
+        ]
+        .into_iter()
+        .map(round)
+        .collect();
+        assert_eq!(values, [0.3, 0.5, 0.409, 0.315, 0.685, 0.0, 1.0]);
+        let steep = Easing::CubicBezier {
+            x1: 0.0,
+            y1: 1.0,
+            x2: 0.0,
+            y2: 1.0,
+        };
+        assert!(steep.apply(0.01) > 0.2);
+    }
+}
@@ -1,29 +1,53 @@
+mod color;
+mod image;
+mod paint;
+mod path;
+mod rect;
 mod text;
+mod waveform;
 
 use delegate_match::delegate_match;
 
-pub use self::text::TextElement;
-use crate::ElementId;
+pub use self::{
+    color::{Color, ColorParseError, ColorParseErrorKind, LinearRgba, color_from_str},
+    image::{ImageElement, ImageFit, ImageSource},
+    paint::{Border, ColorStop, CornerRadii, Paint, Shadow},
+    path::{
+        FillRule, LineCap, LineJoin, PathData, PathElement, PathParseError, PathParseErrorKind,
+        PathSegment, PathStroke, ViewBox, ViewBoxFit, path_from_str,
+    },
+    rect::RectElement,
+    text::{SpanElement, TextChild, TextElement, TextOverflow},
+    waveform::{BufferId, WaveformElement},
+};
+use crate::{ElementId, ElementKind};
 
-#[derive(specta::Type)]
-#[serde(tag = "type", rename_all = "camelCase")]
-pub enum Color {
-    Rgb { r: u8, g: u8, b: u8 },
-    Hsv { h: f32, s: f32, v: f32 },
-    Hsl { h: f32, s: f32, l: f32 },
-}
-
-#[derive(specta::Type)]
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum ContentElement {
     Text(TextElement),
+    Rect(Box<RectElement>),
+    Path(Box<PathElement>),
+    Waveform(Box<WaveformElement>),
+    Image(Box<ImageElement>),
 }
 
 impl ContentElement {
     #[must_use]
     pub fn id(&self) -> Option<ElementId> {
         delegate_match! { match self {
-            Self::{ Text }(e) => e.id(),
+            Self::{ Text, Rect, Path, Waveform, Image }(e) => e.id(),
         }}
     }
+
+    #[must_use]
+    pub const fn kind(&self) -> ElementKind {
+        match self {
+            Self::Text(_) => ElementKind::Text,
+            Self::Rect(_) => ElementKind::Rect,
+            Self::Path(_) => ElementKind::Path,
+            Self::Waveform(_) => ElementKind::Waveform,
+            Self::Image(_) => ElementKind::Image,
+        }
+    }
 }
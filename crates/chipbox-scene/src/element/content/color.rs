@@ -0,0 +1,560 @@
+mod parse;
+
+use std::sync::Arc;
+
+pub use self::parse::{ColorParseError, ColorParseErrorKind};
+
+/// A color with straight alpha.
+///
+/// Hues are in degrees, every other component except [`Self::Rgb`] channels is within `0..=1`.
+#[derive(specta::Type, serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Color {
+    /// 8-bit sRGB.
+    Rgb {
+        r: u8,
+        g: u8,
+        b: u8,
+        a: f32,
+    },
+    /// Gamma encoded sRGB.
+    Srgb {
+        r: f32,
+        g: f32,
+        b: f32,
+        a: f32,
+    },
+    /// sRGB primaries with linear light intensities.
+    LinearRgb {
+        r: f32,
+        g: f32,
+        b: f32,
+        a: f32,
+    },
+    Hsv {
+        h: f32,
+        s: f32,
+        v: f32,
+        a: f32,
+    },
+    Hsl {
+        h: f32,
+        s: f32,
+        l: f32,
+        a: f32,
+    },
+    /// Cylindrical form of the perceptual Oklab space.
+    /// Steps in lightness and hue look even, which makes it suited for palettes.
+    Oklch {
+        l: f32,
+        c: f32,
+        h: f32,
+        a: f32,
+    },
+    /// A named color of the [`crate::Theme`], written `$name`.
+    /// Tokens the theme does not define are transparent.
+    Token {
+        name: Arc<str>,
+    },
+}
+
+/// Linear light RGB premultiplied by alpha, the form colors are blended in.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LinearRgba {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl LinearRgba {
+    /// Every component multiplied by `factor`, as when fading the color.
+    #[must_use]
+    pub fn scaled(self, factor: f32) -> Self {
+        Self {
+            r: self.r * factor,
+            g: self.g * factor,
+            b: self.b * factor,
+            a: self.a * factor,
+        }
+    }
+
+    /// The color drawn on top of `below` with source over blending.
+    #[must_use]
+    pub fn over(self, below: Self) -> Self {
+        let rest = 1.0 - self.a;
+        Self {
+            r: below.r.mul_add(rest, self.r),
+            g: below.g.mul_add(rest, self.g),
+            b: below.b.mul_add(rest, self.b),
+            a: below.a.mul_add(rest, self.a),
+        }
+    }
+
+    /// Convert straight (not premultiplied) sRGB bytes, as stored in images.
+    #[must_use]
+    pub fn from_srgba8([r, g, b, a]: [u8; 4]) -> Self {
+        let a = f32::from(a) / 255.0;
+        let [r, g, b] = [r, g, b].map(|c| decode(f32::from(c) / 255.0) * a);
+        Self { r, g, b, a }
+    }
+
+    /// Convert to straight sRGB bytes, clamping every component to `0..=1`.
+    #[must_use]
+    pub fn to_srgba8(self) -> [u8; 4] {
+        let a = self.a.clamp(0.0, 1.0);
+        let straight = |c: f32| if a > 0.0 { c / a } else { 0.0 };
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            reason = "components are clamped to 0..=255 before the cast"
+        )]
+        let byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        [
+            byte(encode(straight(self.r))),
+            byte(encode(straight(self.g))),
+            byte(encode(straight(self.b))),
+            byte(a),
+        ]
+    }
+}
+
+const fn opaque() -> f32 {
+    1.0
+}
+
+/// Accepts a CSS-like color string such as `"#ff8000"`, `"rgb(255 128 0 / 50%)"`
+/// or `"oklch(0.7 0.15 60)"`, or the tagged object form `{ "type": "rgb", "r": 255, ... }`.
+/// Alpha defaults to opaque.
+impl<'de> serde::Deserialize<'de> for Color {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        #[derive(serde::Deserialize)]
+        #[serde(remote = "Color", tag = "type", rename_all = "camelCase")]
+        enum Tagged {
+            Rgb {
+                r: u8,
+                g: u8,
+                b: u8,
+                #[serde(default = "opaque")]
+                a: f32,
+            },
+            Srgb {
+                r: f32,
+                g: f32,
+                b: f32,
+                #[serde(default = "opaque")]
+                a: f32,
+            },
+            LinearRgb {
+                r: f32,
+                g: f32,
+                b: f32,
+                #[serde(default = "opaque")]
+                a: f32,
+            },
+            Hsv {
+                h: f32,
+                s: f32,
+                v: f32,
+                #[serde(default = "opaque")]
+                a: f32,
+            },
+            Hsl {
+                h: f32,
+                s: f32,
+                l: f32,
+                #[serde(default = "opaque")]
+                a: f32,
+            },
+            Oklch {
+                l: f32,
+                c: f32,
+                h: f32,
+                #[serde(default = "opaque")]
+                a: f32,
+            },
+            Token {
+                name: Arc<str>,
+            },
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(untagged, expecting = "a color string or a tagged color object")]
+        enum Repr {
+            Literal(String),
+            #[serde(deserialize_with = "Tagged::deserialize")]
+            Tagged(Color),
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Literal(literal) => Self::parse_from(&literal).map_err(D::Error::custom),
+            Repr::Tagged(color) => Ok(color),
+        }
+    }
+}
+
+/// Parse a color from a string.
+/// See [`Color::parse_from`].
+/// ## Errors
+/// - [`ColorParseError`] pointing at the offending token.
+#[specta::specta]
+pub fn color_from_str(input: &str) -> Result<Color, ColorParseError> {
+    Color::parse_from(input)
+}
+
+impl Color {
+    pub const BLACK: Self = Self::rgb(0, 0, 0);
+    pub const WHITE: Self = Self::rgb(255, 255, 255);
+    pub const TRANSPARENT: Self = Self::Rgb {
+        r: 0,
+        g: 0,
+        b: 0,
+        a: 0.0,
+    };
+
+    /// An opaque 8-bit sRGB color.
+    #[must_use]
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::Rgb { r, g, b, a: 1.0 }
+    }
+
+    /// Parse a color from a string.
+    ///
+    /// Accepts hexadecimal colors (`"#rgb"`, `"#rgba"`, `"#rrggbb"`, `"#rrggbbaa"`),
+    /// theme tokens (`"$accent"`) and the functions `rgb(..)`, `hsl(..)`, `hsv(..)` and `oklch(..)`.
+    /// Components are separated by whitespace or commas, alpha follows a `/` or a fourth comma.
+    /// Percentages cover the range of a component, `100%` chroma is `0.4`, and hues accept `deg`.
+    /// ## Errors
+    /// - [`ColorParseError`] pointing at the offending token.
+    pub fn parse_from(input: &str) -> Result<Self, ColorParseError> {
+        parse::parse(input)
+    }
+
+    #[must_use]
+    pub const fn alpha(&self) -> f32 {
+        match self {
+            Self::Rgb { a, .. }
+            | Self::Srgb { a, .. }
+            | Self::LinearRgb { a, .. }
+            | Self::Hsv { a, .. }
+            | Self::Hsl { a, .. }
+            | Self::Oklch { a, .. } => *a,
+            Self::Token { .. } => 0.0,
+        }
+    }
+
+    /// The same color with a different alpha.
+    /// Tokens are returned unchanged.
+    #[must_use]
+    pub const fn with_alpha(mut self, alpha: f32) -> Self {
+        match &mut self {
+            Self::Rgb { a, .. }
+            | Self::Srgb { a, .. }
+            | Self::LinearRgb { a, .. }
+            | Self::Hsv { a, .. }
+            | Self::Hsl { a, .. }
+            | Self::Oklch { a, .. } => *a = alpha,
+            Self::Token { .. } => {}
+        }
+        self
+    }
+
+    /// Convert to gamma encoded sRGB.
+    ///
+    /// Colors outside of the sRGB gamut are clipped,
+    /// except for OKLCH which loses chroma first to keep its lightness and hue.
+    #[must_use]
+    pub fn to_srgb(&self) -> Self {
+        let [r, g, b] = match *self {
+            Self::Oklch { l, c, h, .. } => oklch_to_linear_in_gamut(l, c, h).map(encode),
+            _ => self.srgb_components(),
+        }
+        .map(|c| c.clamp(0.0, 1.0));
+        Self::Srgb {
+            r,
+            g,
+            b,
+            a: self.alpha(),
+        }
+    }
+
+    /// Convert to linear light RGB, clipped to the sRGB gamut.
+    #[must_use]
+    pub fn to_linear_rgb(&self) -> Self {
+        let Self::Srgb { r, g, b, a } = self.to_srgb() else {
+            unreachable!("converted to sRGB");
+        };
+        let [r, g, b] = [r, g, b].map(decode);
+        Self::LinearRgb { r, g, b, a }
+    }
+
+    /// Convert to OKLCH.
+    /// The hue of achromatic colors is zero.
+    #[must_use]
+    pub fn to_oklch(&self) -> Self {
+        if let Self::Oklch { .. } = self {
+            return self.clone();
+        }
+        let [l, green_red, blue_yellow] = linear_to_oklab(self.linear_components());
+        let c = green_red.hypot(blue_yellow);
+        let h = if c < 1e-6 {
+            0.0
+        } else {
+            blue_yellow.atan2(green_red).to_degrees().rem_euclid(360.0)
+        };
+        Self::Oklch {
+            l,
+            c,
+            h,
+            a: self.alpha(),
+        }
+    }
+
+    /// The color as consumed by the renderer.
+    /// Components are clipped to the sRGB gamut and alpha to `0..=1`.
+    #[must_use]
+    pub fn to_premultiplied(&self) -> LinearRgba {
+        let Self::LinearRgb { r, g, b, a } = self.to_linear_rgb() else {
+            unreachable!("converted to linear RGB");
+        };
+        let a = a.clamp(0.0, 1.0);
+        LinearRgba {
+            r: r * a,
+            g: g * a,
+            b: b * a,
+            a,
+        }
+    }
+
+    /// Gamma encoded sRGB components, possibly out of gamut.
+    fn srgb_components(&self) -> [f32; 3] {
+        match *self {
+            Self::Rgb { r, g, b, .. } => [r, g, b].map(|c| f32::from(c) / 255.0),
+            Self::Srgb { r, g, b, .. } => [r, g, b],
+            Self::LinearRgb { r, g, b, .. } => [r, g, b].map(encode),
+            Self::Hsv { h, s, v, .. } => {
+                let chroma = v * s;
+                hue_to_rgb(h, chroma, v - chroma)
+            }
+            Self::Hsl { h, s, l, .. } => {
+                let chroma = (1.0 - 2.0f32.mul_add(l, -1.0).abs()) * s;
+                hue_to_rgb(h, chroma, chroma.mul_add(-0.5, l))
+            }
+            Self::Oklch { l, c, h, .. } => oklch_to_linear(l, c, h).map(encode),
+            Self::Token { .. } => [0.0; 3],
+        }
+    }
+
+    /// Linear light components, possibly out of gamut.
+    fn linear_components(&self) -> [f32; 3] {
+        match *self {
+            Self::LinearRgb { r, g, b, .. } => [r, g, b],
+            Self::Oklch { l, c, h, .. } => oklch_to_linear(l, c, h),
+            _ => self.srgb_components().map(decode),
+        }
+    }
+}
+
+/// RGB of a hue with the given chroma, offset by `min`.
+fn hue_to_rgb(hue: f32, chroma: f32, min: f32) -> [f32; 3] {
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector.rem_euclid(2.0) - 1.0).abs());
+    let [r, g, b] = match sector {
+        s if s < 1.0 => [chroma, x, 0.0],
+        s if s < 2.0 => [x, chroma, 0.0],
+        s if s < 3.0 => [0.0, chroma, x],
+        s if s < 4.0 => [0.0, x, chroma],
+        s if s < 5.0 => [x, 0.0, chroma],
+        _ => [chroma, 0.0, x],
+    };
+    [r + min, g + min, b + min]
+}
+
+/// sRGB transfer function, from linear light to gamma encoded.
+fn encode(c: f32) -> f32 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055f32.mul_add(c.powf(1.0 / 2.4), -0.055)
+    }
+}
+
+/// Inverse of the sRGB transfer function.
+fn decode(c: f32) -> f32 {
+    if c <= 0.040_45 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn dot(row: [f32; 3], v: [f32; 3]) -> f32 {
+    row[0].mul_add(v[0], row[1].mul_add(v[1], row[2] * v[2]))
+}
+
+fn linear_to_oklab(rgb: [f32; 3]) -> [f32; 3] {
+    let lms = [
+        [0.412_221_46, 0.536_332_55, 0.051_445_995],
+        [0.211_903_5, 0.680_699_5, 0.107_396_96],
+        [0.088_302_46, 0.281_718_85, 0.629_978_7],
+    ]
+    .map(|row| dot(row, rgb).cbrt());
+    [
+        [0.210_454_26, 0.793_617_8, -0.004_072_047],
+        [1.977_998_5, -2.428_592_2, 0.450_593_7],
+        [0.025_904_037, 0.782_771_77, -0.808_675_77],
+    ]
+    .map(|row| dot(row, lms))
+}
+
+fn oklch_to_linear(l: f32, c: f32, h: f32) -> [f32; 3] {
+    let (sin, cos) = h.to_radians().sin_cos();
+    let lab = [l, c * cos, c * sin];
+    let lms = [
+        [1.0, 0.396_337_78, 0.215_803_76],
+        [1.0, -0.105_561_346, -0.063_854_17],
+        [1.0, -0.089_484_18, -1.291_485_5],
+    ]
+    .map(|row| dot(row, lab).powi(3));
+    [
+        [4.076_741_7, -3.307_711_6, 0.230_969_94],
+        [-1.268_438, 2.609_757_4, -0.341_319_38],
+        [-0.004_196_086_3, -0.703_418_6, 1.707_614_7],
+    ]
+    .map(|row| dot(row, lms))
+}
+
+/// Reduce the chroma until the color fits the sRGB gamut.
+fn oklch_to_linear_in_gamut(l: f32, c: f32, h: f32) -> [f32; 3] {
+    const EPSILON: f32 = 1e-4;
+    let in_gamut = |rgb: [f32; 3]| rgb.iter().all(|c| (-EPSILON..=1.0 + EPSILON).contains(c));
+    let rgb = oklch_to_linear(l, c, h);
+    if in_gamut(rgb) || !(0.0..=1.0).contains(&l) {
+        return rgb;
+    }
+    let (mut low, mut high) = (0.0, c);
+    for _ in 0..24 {
+        let mid = f32::midpoint(low, high);
+        if in_gamut(oklch_to_linear(l, mid, h)) {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    oklch_to_linear(l, low, h)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: [f32; 4], expected: [f32; 4]) {
+        assert!(
+            actual
+                .iter()
+                .zip(expected)
+                .all(|(a, e)| (a - e).abs() < 1e-3),
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    fn components(color: &Color) -> [f32; 4] {
+        match *color {
+            Color::Srgb { r, g, b, a } | Color::LinearRgb { r, g, b, a } => [r, g, b, a],
+            Color::Oklch { l, c, h, a } => [l, c, h, a],
+            _ => unreachable!("no float RGB or OKLCH"),
+        }
+    }
+
+    #[test]
+    fn test_color_conversions() {
+        let red = Color::rgb(255, 0, 0);
+        assert_close(components(&red.to_oklch()), [0.628, 0.2577, 29.234, 1.0]);
+        assert_close(components(&red.to_oklch().to_srgb()), [1.0, 0.0, 0.0, 1.0]);
+        let white = Color::WHITE.to_oklch();
+        assert_close(components(&white), [1.0, 0.0, 0.0, 1.0]);
+        let green = Color::Hsl {
+            h: 120.0,
+            s: 1.0,
+            l: 0.5,
+            a: 1.0,
+        };
+        assert_close(components(&green.to_srgb()), [0.0, 1.0, 0.0, 1.0]);
+        let yellow = Color::Hsv {
+            h: -300.0,
+            s: 1.0,
+            v: 1.0,
+            a: 0.5,
+        };
+        assert_close(components(&yellow.to_srgb()), [1.0, 1.0, 0.0, 0.5]);
+        let gray = Color::Srgb {
+            r: 0.5,
+            g: 0.5,
+            b: 0.5,
+            a: 1.0,
+        };
+        assert_close(
+            components(&gray.to_linear_rgb()),
+            [0.214, 0.214, 0.214, 1.0],
+        );
+    }
+
+    #[test]
+    fn test_color_premultiplied() {
+        let LinearRgba { r, g, b, a } = Color::rgb(255, 128, 0).with_alpha(0.5).to_premultiplied();
+        assert_close([r, g, b, a], [0.5, 0.216 * 0.5, 0.0, 0.5]);
+        assert_eq!(Color::TRANSPARENT.to_premultiplied(), LinearRgba::default());
+        // Out of gamut OKLCH keeps its lightness and hue.
+        let vivid = Color::Oklch {
+            l: 0.7,
+            c: 0.4,
+            h: 150.0,
+            a: 1.0,
+        };
+        let mapped = vivid.to_srgb();
+        assert!(components(&mapped).iter().all(|c| (0.0..=1.0).contains(c)));
+        let [lightness, chroma, hue, _] = components(&mapped.to_oklch());
+        assert!((lightness - 0.7).abs() < 1e-2, "{lightness}");
+        assert!((hue - 150.0).abs() < 1.0, "{hue}");
+        assert!(chroma < 0.4);
+    }
+
+    #[test]
+    fn test_color_bytes() {
+        let orange = [255, 128, 0, 128];
+        let LinearRgba { r, g, b, a } = LinearRgba::from_srgba8(orange);
+        assert_close([r, g, b, a], [0.502, 0.216 * 0.502, 0.0, 0.502]);
+        assert_eq!(LinearRgba::from_srgba8(orange).to_srgba8(), orange);
+        assert_eq!(LinearRgba::default().to_srgba8(), [0; 4]);
+        // Components above alpha are clamped rather than wrapped.
+        let invalid = LinearRgba {
+            r: 2.0,
+            g: -1.0,
+            b: 0.0,
+            a: 0.5,
+        };
+        assert_eq!(invalid.to_srgba8(), [255, 0, 0, 128]);
+    }
+
+    #[test]
+    fn test_color_deserialize() {
+        let from_json = |json: &str| serde_json::from_str::<Color>(json);
+        assert_eq!(
+            from_json(r#"{ "type": "rgb", "r": 1, "g": 2, "b": 3 }"#).expect("object"),
+            Color::rgb(1, 2, 3)
+        );
+        assert_eq!(
+            from_json(r##""#01020380""##).expect("string"),
+            Color::rgb(1, 2, 3).with_alpha(128.0 / 255.0)
+        );
+        let err = from_json(r#""rgb(1 2)""#).expect_err("invalid string");
+        assert!(err.to_string().contains("expected 3 components"), "{err}");
+        let json = serde_json::to_value(Color::rgb(1, 2, 3)).expect("serialize");
+        assert_eq!(
+            json,
+            serde_json::json!({ "type": "rgb", "r": 1, "g": 2, "b": 3, "a": 1.0 })
+        );
+    }
+}
@@ -0,0 +1,360 @@
+use std::ops::Range;
+
+use miette::SourceSpan;
+
+use crate::Color;
+
+/// What went wrong while parsing a color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum ColorParseErrorKind {
+    #[error("unexpected end of input")]
+    EndOfInput,
+    #[error("unexpected token")]
+    UnexpectedToken,
+    #[error("expected 3, 4, 6 or 8 hexadecimal digits")]
+    HexLength,
+    #[error("invalid hexadecimal digit")]
+    HexDigit,
+    #[error("invalid unit")]
+    InvalidUnit,
+    #[error("unable to recognize float")]
+    RecognizeFloat,
+    #[error("unknown function")]
+    UnknownFunction,
+    #[error("expected 3 components and an optional alpha, found {found}")]
+    ComponentCount { found: usize },
+    #[error("invalid token name")]
+    TokenName,
+}
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+#[error("invalid color: {kind}")]
+pub struct ColorParseError {
+    pub kind: ColorParseErrorKind,
+    #[source_code]
+    pub input: String,
+    /// Location of the offending token in the input.
+    #[label("{kind}")]
+    pub span: SourceSpan,
+}
+
+type ParseResult<T> = Result<T, (ColorParseErrorKind, Range<usize>)>;
+
+/// Unit suffix of a component.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Unit {
+    None,
+    Percent,
+    Degree,
+}
+
+struct Component {
+    value: f32,
+    unit: Unit,
+    span: Range<usize>,
+}
+
+impl Component {
+    /// The value with `100%` mapped to `full`, and plain numbers scaled by `scale`.
+    fn fraction(&self, full: f32, scale: f32) -> ParseResult<f32> {
+        match self.unit {
+            Unit::None => Ok(self.value * scale),
+            Unit::Percent => Ok(self.value / 100.0 * full),
+            Unit::Degree => Err((ColorParseErrorKind::InvalidUnit, self.span.clone())),
+        }
+    }
+
+    fn hue(&self) -> ParseResult<f32> {
+        match self.unit {
+            Unit::None | Unit::Degree => Ok(self.value),
+            Unit::Percent => Err((ColorParseErrorKind::InvalidUnit, self.span.clone())),
+        }
+    }
+}
+
+/// Length of the longest prefix matching the predicate.
+fn prefix_len(input: &str, predicate: impl Fn(char) -> bool) -> usize {
+    input.find(|c| !predicate(c)).unwrap_or(input.len())
+}
+
+/// Parse a color.
+pub fn parse(input: &str) -> Result<Color, ColorParseError> {
+    let start = prefix_len(input, char::is_whitespace);
+    let end = input.trim_end().len().max(start);
+    let result = if input[start..].starts_with('#') {
+        hex(&input[start + 1..end], start + 1)
+    } else if input[start..].starts_with('$') {
+        token(&input[start + 1..end], start + 1)
+    } else {
+        function(input, start, end)
+    };
+    result.map_err(|(kind, span)| ColorParseError {
+        kind,
+        input: input.to_owned(),
+        span: (span.start, span.len()).into(),
+    })
+}
+
+/// Parse the name of a theme token starting at `offset`.
+fn token(name: &str, offset: usize) -> ParseResult<Color> {
+    let len = prefix_len(name, is_token_char);
+    if len == 0 || len < name.len() {
+        let end = name[len..]
+            .chars()
+            .next()
+            .map_or(len, |c| len + c.len_utf8());
+        return Err((ColorParseErrorKind::TokenName, offset + len..offset + end));
+    }
+    Ok(Color::Token { name: name.into() })
+}
+
+/// Whether the character may appear in the name of a theme token.
+const fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '.'
+}
+
+/// Parse the digits of a hexadecimal color starting at `offset`.
+fn hex(digits: &str, offset: usize) -> ParseResult<Color> {
+    if let Some(i) = digits.find(|c: char| !c.is_ascii_hexdigit()) {
+        let len = digits[i..].chars().next().map_or(0, char::len_utf8);
+        return Err((ColorParseErrorKind::HexDigit, offset + i..offset + i + len));
+    }
+    let digit = |i: usize| u8::from_str_radix(&digits[i..=i], 16).expect("checked hex digit");
+    let channels: Vec<u8> = match digits.len() {
+        3 | 4 => (0..digits.len()).map(|i| digit(i) * 17).collect(),
+        6 | 8 => (0..digits.len())
+            .step_by(2)
+            .map(|i| digit(i) * 16 + digit(i + 1))
+            .collect(),
+        _ => {
+            return Err((
+                ColorParseErrorKind::HexLength,
+                offset..offset + digits.len(),
+            ));
+        }
+    };
+    Ok(Color::Rgb {
+        r: channels[0],
+        g: channels[1],
+        b: channels[2],
+        a: channels.get(3).map_or(1.0, |a| f32::from(*a) / 255.0),
+    })
+}
+
+const FUNCTIONS: &[&str] = &["rgb", "rgba", "hsl", "hsla", "hsv", "hsva", "oklch"];
+
+/// Parse a color function such as `rgb(..)` spanning `start..end` of the input.
+fn function(input: &str, start: usize, end: usize) -> ParseResult<Color> {
+    let name_end = start + prefix_len(&input[start..end], |c| c.is_ascii_alphabetic());
+    let name = &input[start..name_end];
+    if name.is_empty() {
+        return Err(input[start..end].chars().next().map_or(
+            (ColorParseErrorKind::EndOfInput, start..start),
+            |c| {
+                (
+                    ColorParseErrorKind::UnexpectedToken,
+                    start..start + c.len_utf8(),
+                )
+            },
+        ));
+    }
+    if !FUNCTIONS.contains(&name) {
+        return Err((ColorParseErrorKind::UnknownFunction, start..name_end));
+    }
+    let mut pos = name_end + prefix_len(&input[name_end..end], char::is_whitespace);
+    match input[pos..end].chars().next() {
+        Some('(') => pos += 1,
+        Some(c) => {
+            return Err((
+                ColorParseErrorKind::UnexpectedToken,
+                pos..pos + c.len_utf8(),
+            ));
+        }
+        None => return Err((ColorParseErrorKind::EndOfInput, end..end)),
+    }
+    let components = components(input, &mut pos, end)?;
+    pos += prefix_len(&input[pos..end], char::is_whitespace);
+    if pos < end {
+        let len = input[pos..].chars().next().map_or(0, char::len_utf8);
+        return Err((ColorParseErrorKind::UnexpectedToken, pos..pos + len));
+    }
+    let [x, y, z, alpha @ ..] = components.as_slice() else {
+        return Err((
+            ColorParseErrorKind::ComponentCount {
+                found: components.len(),
+            },
+            start..end,
+        ));
+    };
+    let a = match alpha {
+        [] => 1.0,
+        [a] => a.fraction(1.0, 1.0)?,
+        _ => {
+            return Err((
+                ColorParseErrorKind::ComponentCount {
+                    found: components.len(),
+                },
+                start..end,
+            ));
+        }
+    };
+    // Plain saturation and lightness are percentage points, as in CSS.
+    Ok(match name {
+        "rgb" | "rgba" => Color::Srgb {
+            r: x.fraction(1.0, 1.0 / 255.0)?,
+            g: y.fraction(1.0, 1.0 / 255.0)?,
+            b: z.fraction(1.0, 1.0 / 255.0)?,
+            a,
+        },
+        "hsl" | "hsla" => Color::Hsl {
+            h: x.hue()?,
+            s: y.fraction(1.0, 0.01)?,
+            l: z.fraction(1.0, 0.01)?,
+            a,
+        },
+        "hsv" | "hsva" => Color::Hsv {
+            h: x.hue()?,
+            s: y.fraction(1.0, 0.01)?,
+            v: z.fraction(1.0, 0.01)?,
+            a,
+        },
+        "oklch" => Color::Oklch {
+            l: x.fraction(1.0, 1.0)?,
+            c: y.fraction(0.4, 1.0)?,
+            h: z.hue()?,
+            a,
+        },
+        _ => unreachable!("function names are checked"),
+    })
+}
+
+/// Parse the components of a function up to and including the closing parenthesis.
+fn components(input: &str, pos: &mut usize, end: usize) -> ParseResult<Vec<Component>> {
+    let mut components = Vec::new();
+    loop {
+        let rest = &input[*pos..end];
+        let Some(c) = rest.chars().next() else {
+            return Err((ColorParseErrorKind::EndOfInput, end..end));
+        };
+        let start = *pos;
+        match c {
+            ')' => {
+                *pos += 1;
+                return Ok(components);
+            }
+            // Separators are interchangeable, only the number of components matters.
+            c if c.is_whitespace() || c == ',' || c == '/' => *pos += c.len_utf8(),
+            '0'..='9' | '.' | '-' | '+' => {
+                let Ok((_, number)) = nom::number::complete::recognize_float::<_, (&str, _)>(rest)
+                else {
+                    let len = prefix_len(rest, |c| c.is_ascii_alphanumeric() || "+-.".contains(c));
+                    return Err((ColorParseErrorKind::RecognizeFloat, start..start + len));
+                };
+                let value = number.parse().expect("recognized a valid float");
+                let unit_start = start + number.len();
+                let unit_len = prefix_len(&input[unit_start..end], |c| {
+                    c.is_ascii_alphabetic() || c == '%'
+                });
+                *pos = unit_start + unit_len;
+                let unit = match &input[unit_start..*pos] {
+                    "" => Unit::None,
+                    "%" => Unit::Percent,
+                    "deg" => Unit::Degree,
+                    _ => return Err((ColorParseErrorKind::InvalidUnit, unit_start..*pos)),
+                };
+                components.push(Component {
+                    value,
+                    unit,
+                    span: start..*pos,
+                });
+            }
+            c => {
+                return Err((
+                    ColorParseErrorKind::UnexpectedToken,
+                    start..start + c.len_utf8(),
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(input: &str) -> (ColorParseErrorKind, usize, usize) {
+        let err = parse(input).expect_err("invalid input");
+        (err.kind, err.span.offset(), err.span.len())
+    }
+
+    #[test]
+    fn test_parse_forms() {
+        assert_eq!(parse("#f80").expect("valid"), Color::rgb(255, 136, 0));
+        assert_eq!(
+            parse(" #FF880080 ").expect("valid"),
+            Color::rgb(255, 136, 0).with_alpha(128.0 / 255.0)
+        );
+        assert_eq!(
+            parse("rgb(255 0 0 / 50%)").expect("valid"),
+            Color::Srgb {
+                r: 1.0,
+                g: 0.0,
+                b: 0.0,
+                a: 0.5
+            }
+        );
+        assert_eq!(
+            parse("rgba(100%, 0, 0, 0.25)").expect("valid"),
+            Color::Srgb {
+                r: 1.0,
+                g: 0.0,
+                b: 0.0,
+                a: 0.25
+            }
+        );
+        assert_eq!(
+            parse("hsl(120deg 50% 25)").expect("valid"),
+            Color::Hsl {
+                h: 120.0,
+                s: 0.5,
+                l: 0.25,
+                a: 1.0
+            }
+        );
+        assert_eq!(
+            parse("oklch(70% 50% -30)").expect("valid"),
+            Color::Oklch {
+                l: 0.7,
+                c: 0.2,
+                h: -30.0,
+                a: 1.0
+            }
+        );
+        assert_eq!(
+            parse("$surface.raised").expect("valid"),
+            Color::Token {
+                name: "surface.raised".into()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_error_spans() {
+        use ColorParseErrorKind as E;
+        assert_eq!(error(""), (E::EndOfInput, 0, 0));
+        assert_eq!(error("#12345"), (E::HexLength, 1, 5));
+        assert_eq!(error("#12g"), (E::HexDigit, 3, 1));
+        assert_eq!(error("lab(1 2 3)"), (E::UnknownFunction, 0, 3));
+        assert_eq!(error("red"), (E::UnknownFunction, 0, 3));
+        assert_eq!(error("rgb 1 2 3"), (E::UnexpectedToken, 4, 1));
+        assert_eq!(error("rgb(1 2 3"), (E::EndOfInput, 9, 0));
+        assert_eq!(error("rgb(1 2 3) x"), (E::UnexpectedToken, 11, 1));
+        assert_eq!(error("rgb(1 2 3px)"), (E::InvalidUnit, 9, 2));
+        assert_eq!(error("hsl(10% 1 1)"), (E::InvalidUnit, 4, 3));
+        assert_eq!(error("rgb(1 2)"), (E::ComponentCount { found: 2 }, 0, 8));
+        assert_eq!(error("rgb(1 ; 2)"), (E::UnexpectedToken, 6, 1));
+        assert_eq!(error("12"), (E::UnexpectedToken, 0, 1));
+        assert_eq!(error("$"), (E::TokenName, 1, 0));
+        assert_eq!(error("$accent!"), (E::TokenName, 7, 1));
+    }
+}
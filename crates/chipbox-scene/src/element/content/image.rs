@@ -0,0 +1,180 @@
+use std::sync::Arc;
+
+use crate::{ElementId, LayoutLength};
+
+/// A bitmap decoded from a PNG, JPEG or QOI file.
+///
+/// Without a width or height, the image takes its intrinsic size,
+/// with every image pixel covering a logical pixel, shrunk to fit the parent.
+/// With only one of them, the other follows the aspect ratio of the image.
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageElement {
+    #[serde(default)]
+    pub id: Option<ElementId>,
+    pub source: ImageSource,
+    #[serde(default)]
+    pub fit: ImageFit,
+    #[serde(default)]
+    pub width: Option<LayoutLength>,
+    #[serde(default)]
+    pub height: Option<LayoutLength>,
+}
+
+impl ImageElement {
+    #[must_use]
+    pub fn id(&self) -> Option<ElementId> {
+        self.id.clone()
+    }
+}
+
+/// Where the encoded image comes from.
+#[derive(specta::Type, serde::Serialize, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ImageSource {
+    /// A file on disk.
+    Path { path: Arc<str> },
+    /// An asset registered with the image cache.
+    Asset { id: Arc<str> },
+}
+
+/// Accepts a string as a path, or the tagged object form.
+impl<'de> serde::Deserialize<'de> for ImageSource {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(remote = "ImageSource", tag = "type", rename_all = "camelCase")]
+        enum Tagged {
+            Path { path: Arc<str> },
+            Asset { id: Arc<str> },
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(untagged, expecting = "a path or a tagged image source object")]
+        enum Repr {
+            Path(Arc<str>),
+            #[serde(deserialize_with = "Tagged::deserialize")]
+            Tagged(ImageSource),
+        }
+
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Path(path) => Self::Path { path },
+            Repr::Tagged(source) => source,
+        })
+    }
+}
+
+impl std::fmt::Display for ImageSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Path { path } => write!(f, "`{path}`"),
+            Self::Asset { id } => write!(f, "asset `{id}`"),
+        }
+    }
+}
+
+/// How the image is scaled to the bounds of the element.
+#[derive(specta::Type, serde::Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ImageFit {
+    /// Scaled uniformly to fit inside the bounds, centered along the other axis.
+    #[default]
+    Contain,
+    /// Scaled uniformly to cover the bounds, cropping the overflowing sides.
+    Cover,
+    /// Stretched to the bounds.
+    Fill,
+    /// Corners keep their size, edges stretch along their length and the center stretches
+    /// in both directions, as in skinned knobs and panels.
+    /// Insets are in image pixels.
+    #[serde(rename_all = "camelCase")]
+    NineSlice {
+        top: u32,
+        right: u32,
+        bottom: u32,
+        left: u32,
+    },
+}
+
+/// Accepts the name of a fit without parameters, or the tagged object form.
+impl<'de> serde::Deserialize<'de> for ImageFit {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(remote = "ImageFit", tag = "type", rename_all = "camelCase")]
+        enum Tagged {
+            Contain,
+            Cover,
+            Fill,
+            NineSlice {
+                top: u32,
+                right: u32,
+                bottom: u32,
+                left: u32,
+            },
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        enum Name {
+            Contain,
+            Cover,
+            Fill,
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(untagged, expecting = "a fit name or a tagged fit object")]
+        enum Repr {
+            Name(Name),
+            #[serde(deserialize_with = "Tagged::deserialize")]
+            Tagged(ImageFit),
+        }
+
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Name(Name::Contain) => Self::Contain,
+            Repr::Name(Name::Cover) => Self::Cover,
+            Repr::Name(Name::Fill) => Self::Fill,
+            Repr::Tagged(fit) => fit,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_image() {
+        let image: ImageElement = serde_json::from_str(
+            r#"{ "source": "skin/knob.png", "fit": "cover", "width": "32px" }"#,
+        )
+        .expect("valid image");
+        assert_eq!(
+            image.source,
+            ImageSource::Path {
+                path: "skin/knob.png".into()
+            }
+        );
+        assert_eq!(image.fit, ImageFit::Cover);
+        assert_eq!(image.width, Some(LayoutLength::Pixel(32.0)));
+        assert_eq!(image.height, None);
+
+        let image: ImageElement = serde_json::from_str(
+            r#"{
+                "source": { "type": "asset", "id": "album-art" },
+                "fit": { "type": "nineSlice", "top": 4, "right": 4, "bottom": 4, "left": 4 }
+            }"#,
+        )
+        .expect("valid image");
+        assert_eq!(
+            image.fit,
+            ImageFit::NineSlice {
+                top: 4,
+                right: 4,
+                bottom: 4,
+                left: 4
+            }
+        );
+        let json = serde_json::to_string(&image).expect("serialize");
+        let round_trip: ImageElement = serde_json::from_str(&json).expect("deserialize serialized");
+        assert_eq!(round_trip, image);
+    }
+}
@@ -0,0 +1,239 @@
+use crate::{Color, LayoutLength};
+
+/// How an area is colored.
+///
+/// Gradient geometry is relative to the painted box,
+/// so parent lengths refer to the box itself.
+#[derive(specta::Type, serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Paint {
+    Solid {
+        color: Color,
+    },
+    /// Colors change along a line through the center of the box,
+    /// at `angle` degrees clockwise from pointing up.
+    /// The line is long enough for the corners to reach the first and last stops.
+    Linear {
+        angle: f32,
+        stops: Vec<ColorStop>,
+    },
+    /// Colors change with the distance from the center,
+    /// reaching the last stop at `radius`, or at the farthest corner if unset.
+    Radial {
+        #[serde(default = "center_x")]
+        x: LayoutLength,
+        #[serde(default = "center_y")]
+        y: LayoutLength,
+        #[serde(default)]
+        radius: Option<LayoutLength>,
+        stops: Vec<ColorStop>,
+    },
+    /// Colors change with the angle around the center,
+    /// starting at `angle` degrees clockwise from pointing up.
+    Sweep {
+        #[serde(default = "center_x")]
+        x: LayoutLength,
+        #[serde(default = "center_y")]
+        y: LayoutLength,
+        #[serde(default)]
+        angle: f32,
+        stops: Vec<ColorStop>,
+    },
+}
+
+/// A color at a position along a gradient.
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ColorStop {
+    /// Position within `0..=1`.
+    /// Stops without an offset are spread evenly between their neighbors.
+    #[serde(default)]
+    pub offset: Option<f32>,
+    pub color: Color,
+}
+
+const fn center_x() -> LayoutLength {
+    LayoutLength::ParentWidth(0.5)
+}
+
+const fn center_y() -> LayoutLength {
+    LayoutLength::ParentHeight(0.5)
+}
+
+pub(super) const fn zero() -> LayoutLength {
+    LayoutLength::SceneUnit(0.0)
+}
+
+/// Accepts a color in any of its forms as a solid paint, or the tagged object form.
+impl<'de> serde::Deserialize<'de> for Paint {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(remote = "Paint", tag = "type", rename_all = "camelCase")]
+        enum Tagged {
+            Solid {
+                color: Color,
+            },
+            Linear {
+                angle: f32,
+                stops: Vec<ColorStop>,
+            },
+            Radial {
+                #[serde(default = "center_x")]
+                x: LayoutLength,
+                #[serde(default = "center_y")]
+                y: LayoutLength,
+                #[serde(default)]
+                radius: Option<LayoutLength>,
+                stops: Vec<ColorStop>,
+            },
+            Sweep {
+                #[serde(default = "center_x")]
+                x: LayoutLength,
+                #[serde(default = "center_y")]
+                y: LayoutLength,
+                #[serde(default)]
+                angle: f32,
+                stops: Vec<ColorStop>,
+            },
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(untagged, expecting = "a color or a tagged paint object")]
+        enum Repr {
+            #[serde(deserialize_with = "Tagged::deserialize")]
+            Tagged(Paint),
+            Color(Color),
+        }
+
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Tagged(paint) => paint,
+            Repr::Color(color) => Self::Solid { color },
+        })
+    }
+}
+
+impl From<Color> for Paint {
+    fn from(color: Color) -> Self {
+        Self::Solid { color }
+    }
+}
+
+impl Paint {
+    /// Color stops of a gradient, empty for solid paint.
+    #[must_use]
+    pub fn stops(&self) -> &[ColorStop] {
+        match self {
+            Self::Solid { .. } => &[],
+            Self::Linear { stops, .. } | Self::Radial { stops, .. } | Self::Sweep { stops, .. } => {
+                stops
+            }
+        }
+    }
+}
+
+/// Radii of the corners of a box.
+#[derive(specta::Type, serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CornerRadii {
+    pub top_left: LayoutLength,
+    pub top_right: LayoutLength,
+    pub bottom_right: LayoutLength,
+    pub bottom_left: LayoutLength,
+}
+
+impl CornerRadii {
+    /// The same radius for every corner.
+    #[must_use]
+    pub fn uniform(radius: &LayoutLength) -> Self {
+        Self {
+            top_left: radius.clone(),
+            top_right: radius.clone(),
+            bottom_right: radius.clone(),
+            bottom_left: radius.clone(),
+        }
+    }
+
+    /// Corners in clockwise order, starting at the top left.
+    #[must_use]
+    pub const fn corners(&self) -> [&LayoutLength; 4] {
+        [
+            &self.top_left,
+            &self.top_right,
+            &self.bottom_right,
+            &self.bottom_left,
+        ]
+    }
+}
+
+impl Default for CornerRadii {
+    fn default() -> Self {
+        Self::uniform(&zero())
+    }
+}
+
+/// Accepts a single length for every corner, or an object with optional corners.
+impl<'de> serde::Deserialize<'de> for CornerRadii {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Corners {
+            #[serde(default = "zero")]
+            top_left: LayoutLength,
+            #[serde(default = "zero")]
+            top_right: LayoutLength,
+            #[serde(default = "zero")]
+            bottom_right: LayoutLength,
+            #[serde(default = "zero")]
+            bottom_left: LayoutLength,
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(untagged, expecting = "a length or an object of corner lengths")]
+        enum Repr {
+            Uniform(LayoutLength),
+            Corners(Corners),
+        }
+
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Uniform(radius) => Self::uniform(&radius),
+            Repr::Corners(c) => Self {
+                top_left: c.top_left,
+                top_right: c.top_right,
+                bottom_right: c.bottom_right,
+                bottom_left: c.bottom_left,
+            },
+        })
+    }
+}
+
+/// A stroke along the edge of a box.
+/// The stroke lies inside the box, so borders never overlap neighbors.
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Border {
+    pub width: LayoutLength,
+    pub paint: Paint,
+    /// Alternating lengths of dashes and gaps, solid if empty.
+    #[serde(default)]
+    pub dash: Vec<LayoutLength>,
+    /// Distance into the dash pattern at the top left corner.
+    #[serde(default = "zero")]
+    pub dash_offset: LayoutLength,
+}
+
+/// A blurred copy of the box outline drawn behind it.
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Shadow {
+    #[serde(default = "zero")]
+    pub x: LayoutLength,
+    #[serde(default = "zero")]
+    pub y: LayoutLength,
+    /// Standard deviation of the gaussian blur.
+    #[serde(default = "zero")]
+    pub blur: LayoutLength,
+    /// Grows the outline before blurring it.
+    #[serde(default = "zero")]
+    pub spread: LayoutLength,
+    pub color: Color,
+}
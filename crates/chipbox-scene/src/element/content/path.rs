@@ -0,0 +1,302 @@
+mod parse;
+
+use std::str::FromStr;
+
+pub use self::parse::{PathParseError, PathParseErrorKind};
+use super::{Paint, paint::zero};
+use crate::{ElementId, LayoutLength};
+
+/// A shape drawn from path data, filling its parent.
+///
+/// Path coordinates are in the [`ViewBox`], which is mapped onto the parent box.
+/// The fill is drawn first, then the stroke on top.
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PathElement {
+    #[serde(default)]
+    pub id: Option<ElementId>,
+    pub data: PathData,
+    #[serde(default)]
+    pub view_box: ViewBox,
+    #[serde(default)]
+    pub fit: ViewBoxFit,
+    #[serde(default)]
+    pub fill: Option<Paint>,
+    #[serde(default)]
+    pub fill_rule: FillRule,
+    #[serde(default)]
+    pub stroke: Option<PathStroke>,
+}
+
+impl PathElement {
+    #[must_use]
+    pub fn id(&self) -> Option<ElementId> {
+        self.id.clone()
+    }
+}
+
+/// The coordinate system of path data.
+/// Defaults to the unit square, so `1` is the full width or height of the parent.
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewBox {
+    #[serde(default)]
+    pub x: f32,
+    #[serde(default)]
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Default for ViewBox {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            width: 1.0,
+            height: 1.0,
+        }
+    }
+}
+
+/// How the view box is mapped onto the parent box.
+#[derive(
+    specta::Type, serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum ViewBoxFit {
+    /// Scaled independently along each axis to cover the parent exactly.
+    #[default]
+    Stretch,
+    /// Scaled uniformly to fit inside the parent, centered along the other axis.
+    Contain,
+}
+
+/// Which areas enclosed by a path are inside it.
+#[derive(
+    specta::Type, serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum FillRule {
+    /// Inside where the outline winds around a point a nonzero number of times.
+    #[default]
+    NonZero,
+    /// Inside where a ray from a point crosses the outline an odd number of times.
+    EvenOdd,
+}
+
+/// A stroke along the outline of a path.
+///
+/// Widths and dashes are lengths in the scene, so they are not affected by the view box.
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PathStroke {
+    pub width: LayoutLength,
+    pub paint: Paint,
+    #[serde(default)]
+    pub cap: LineCap,
+    #[serde(default)]
+    pub join: LineJoin,
+    /// Miter joins longer than this multiple of the width are beveled instead.
+    #[serde(default = "PathStroke::default_miter_limit")]
+    pub miter_limit: f32,
+    /// Alternating lengths of dashes and gaps, solid if empty.
+    #[serde(default)]
+    pub dash: Vec<LayoutLength>,
+    /// Distance into the dash pattern at the start of each subpath.
+    #[serde(default = "zero")]
+    pub dash_offset: LayoutLength,
+}
+
+impl PathStroke {
+    #[must_use]
+    pub const fn default_miter_limit() -> f32 {
+        4.0
+    }
+}
+
+/// Shape of the ends of open subpaths and dashes.
+#[derive(
+    specta::Type, serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum LineCap {
+    #[default]
+    Butt,
+    Round,
+    Square,
+}
+
+/// Shape of the corners between segments.
+#[derive(
+    specta::Type, serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum LineJoin {
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+/// A single segment of a path in absolute view box coordinates.
+/// Shorthand commands of path strings are expanded to the full segments.
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PathSegment {
+    /// Starts a new subpath.
+    MoveTo {
+        x: f32,
+        y: f32,
+    },
+    LineTo {
+        x: f32,
+        y: f32,
+    },
+    QuadTo {
+        x1: f32,
+        y1: f32,
+        x: f32,
+        y: f32,
+    },
+    CubicTo {
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+        x: f32,
+        y: f32,
+    },
+    /// An elliptical arc with radii `rx` and `ry`, rotated by `rotation` degrees.
+    /// Radii too small to reach the end point are scaled up, as in SVG.
+    #[serde(rename_all = "camelCase")]
+    ArcTo {
+        rx: f32,
+        ry: f32,
+        rotation: f32,
+        /// Whether the arc spans more than 180 degrees.
+        large_arc: bool,
+        /// Whether the arc turns clockwise.
+        sweep: bool,
+        x: f32,
+        y: f32,
+    },
+    /// Closes the subpath with a line back to its start.
+    Close,
+}
+
+impl PathSegment {
+    /// The point the segment ends at, or [`None`] for [`Self::Close`].
+    #[must_use]
+    pub const fn end(&self) -> Option<(f32, f32)> {
+        match *self {
+            Self::MoveTo { x, y }
+            | Self::LineTo { x, y }
+            | Self::QuadTo { x, y, .. }
+            | Self::CubicTo { x, y, .. }
+            | Self::ArcTo { x, y, .. } => Some((x, y)),
+            Self::Close => None,
+        }
+    }
+}
+
+/// Segments of a path, written as SVG path data such as `"M0 0 L1 1 Z"`.
+#[derive(specta::Type, serde::Serialize, Clone, Debug, Default, PartialEq)]
+pub struct PathData(pub Vec<PathSegment>);
+
+/// Accepts an SVG path data string, or an array of tagged segment objects.
+impl<'de> serde::Deserialize<'de> for PathData {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        #[derive(serde::Deserialize)]
+        #[serde(
+            untagged,
+            expecting = "a path data string or an array of path segments"
+        )]
+        enum Repr {
+            Literal(String),
+            Segments(Vec<PathSegment>),
+        }
+
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Literal(literal) => literal.parse().map_err(D::Error::custom)?,
+            Repr::Segments(segments) => Self(segments),
+        })
+    }
+}
+
+impl FromStr for PathData {
+    type Err = PathParseError;
+
+    /// Parse SVG path data.
+    ///
+    /// Supports the `M`, `L`, `H`, `V`, `C`, `S`, `Q`, `T`, `A` and `Z` commands,
+    /// in both their absolute (uppercase) and relative (lowercase) forms.
+    /// ## Errors
+    /// - [`PathParseError`] pointing at the offending token.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse::parse(s)
+    }
+}
+
+/// Parse SVG path data into absolute segments.
+/// ## Errors
+/// - [`PathParseError`] pointing at the offending token.
+#[specta::specta]
+pub fn path_from_str(input: &str) -> Result<PathData, PathParseError> {
+    input.parse()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Color;
+
+    #[test]
+    fn test_deserialize_path() {
+        let path: PathElement = serde_json::from_str(
+            r##"{
+                "data": "M0 0 L24 12 Z",
+                "viewBox": { "width": 24, "height": 24 },
+                "fit": "contain",
+                "fillRule": "evenOdd",
+                "stroke": { "width": "2px", "paint": "#000", "join": "round" }
+            }"##,
+        )
+        .expect("valid path");
+        assert_eq!(path.data.0.len(), 3);
+        assert_eq!(
+            path.view_box,
+            ViewBox {
+                x: 0.0,
+                y: 0.0,
+                width: 24.0,
+                height: 24.0,
+            }
+        );
+        assert_eq!(path.fit, ViewBoxFit::Contain);
+        assert_eq!(path.fill_rule, FillRule::EvenOdd);
+        assert_eq!(
+            path.stroke,
+            Some(PathStroke {
+                width: LayoutLength::Pixel(2.0),
+                paint: Paint::from(Color::BLACK),
+                cap: LineCap::Butt,
+                join: LineJoin::Round,
+                miter_limit: PathStroke::default_miter_limit(),
+                dash: Vec::new(),
+                dash_offset: zero(),
+            })
+        );
+
+        // Serialized segments are accepted as well.
+        let json = serde_json::to_string(&path).expect("serialize");
+        let round_trip: PathElement = serde_json::from_str(&json).expect("deserialize serialized");
+        assert_eq!(round_trip, path);
+
+        let err = serde_json::from_str::<PathElement>(r#"{ "data": "M0 0 L1" }"#)
+            .expect_err("invalid path data");
+        assert!(err.to_string().contains("`L` expects 2 numbers"), "{err}");
+    }
+}
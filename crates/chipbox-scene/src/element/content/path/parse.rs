@@ -0,0 +1,451 @@
+use std::ops::Range;
+
+use miette::SourceSpan;
+
+use super::{PathData, PathSegment};
+
+/// What went wrong while parsing path data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum PathParseErrorKind {
+    #[error("unexpected token")]
+    UnexpectedToken,
+    #[error("unknown command")]
+    UnknownCommand,
+    #[error("path data must start with a move command")]
+    MissingMoveTo,
+    #[error("unable to recognize float")]
+    RecognizeFloat,
+    #[error("arc flags must be 0 or 1")]
+    ArcFlag,
+    #[error("`{command}` expects {count} numbers per segment")]
+    ArgumentCount { command: char, count: usize },
+}
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+#[error("invalid path data at offset {}: {kind}", .span.offset())]
+pub struct PathParseError {
+    pub kind: PathParseErrorKind,
+    #[source_code]
+    pub input: String,
+    /// Location of the offending token in the input.
+    #[label("{kind}")]
+    pub span: SourceSpan,
+}
+
+type ParseResult<T> = Result<T, (PathParseErrorKind, Range<usize>)>;
+
+const COMMANDS: &str = "MLHVCSQTAZ";
+
+/// Parse SVG path data.
+pub fn parse(input: &str) -> Result<PathData, PathParseError> {
+    let mut parser = Parser {
+        input,
+        pos: 0,
+        segments: Vec::new(),
+        current: (0.0, 0.0),
+        subpath: (0.0, 0.0),
+    };
+    match parser.path() {
+        Ok(()) => Ok(PathData(parser.segments)),
+        Err((kind, span)) => Err(PathParseError {
+            kind,
+            input: input.to_owned(),
+            span: (span.start, span.len()).into(),
+        }),
+    }
+}
+
+/// The command a segment belongs to, for error reporting.
+#[derive(Clone, Copy)]
+struct Command {
+    name: char,
+    start: usize,
+}
+
+impl Command {
+    /// Number of arguments of each segment, counting arc flags.
+    const fn arguments(self) -> usize {
+        match self.name.to_ascii_uppercase() {
+            'H' | 'V' => 1,
+            'M' | 'L' | 'T' => 2,
+            'Q' | 'S' => 4,
+            'C' => 6,
+            'A' => 7,
+            _ => 0,
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    segments: Vec<PathSegment>,
+    current: (f32, f32),
+    /// Start of the current subpath, where closing returns to.
+    subpath: (f32, f32),
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    /// Skip whitespace and commas.
+    fn skip_separators(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest
+            .find(|c: char| !c.is_whitespace() && c != ',')
+            .unwrap_or(rest.len());
+    }
+
+    /// Span of the character at the current position.
+    fn token(&self) -> Range<usize> {
+        self.pos..self.pos + self.peek().map_or(0, char::len_utf8)
+    }
+
+    /// Whether another number follows, repeating the previous command.
+    fn at_number(&mut self) -> bool {
+        self.skip_separators();
+        matches!(self.peek(), Some('0'..='9' | '.' | '-' | '+'))
+    }
+
+    /// The error for a token where an argument of `command` was expected.
+    fn missing(&self, command: Command) -> (PathParseErrorKind, Range<usize>) {
+        match self.peek() {
+            None | Some('a'..='z' | 'A'..='Z') => (
+                PathParseErrorKind::ArgumentCount {
+                    command: command.name,
+                    count: command.arguments(),
+                },
+                command.start..self.pos,
+            ),
+            Some(_) => (PathParseErrorKind::UnexpectedToken, self.token()),
+        }
+    }
+
+    fn numbers<const N: usize>(&mut self, command: Command) -> ParseResult<[f32; N]> {
+        let mut numbers = [0.0; N];
+        for number in &mut numbers {
+            if !self.at_number() {
+                return Err(self.missing(command));
+            }
+            let rest = &self.input[self.pos..];
+            let Ok((_, literal)) = nom::number::complete::recognize_float::<_, (&str, _)>(rest)
+            else {
+                let len = rest[1..]
+                    .find(|c: char| !c.is_ascii_alphanumeric() && !".+-".contains(c))
+                    .map_or(rest.len(), |len| len + 1);
+                return Err((PathParseErrorKind::RecognizeFloat, self.pos..self.pos + len));
+            };
+            *number = literal.parse().expect("recognized a valid float");
+            self.pos += literal.len();
+        }
+        Ok(numbers)
+    }
+
+    /// A single-digit arc flag, which needs no separator after it.
+    fn flag(&mut self, command: Command) -> ParseResult<bool> {
+        if !self.at_number() {
+            return Err(self.missing(command));
+        }
+        let flag = match self.peek() {
+            Some('0') => false,
+            Some('1') => true,
+            _ => return Err((PathParseErrorKind::ArcFlag, self.token())),
+        };
+        self.pos += 1;
+        Ok(flag)
+    }
+
+    fn path(&mut self) -> ParseResult<()> {
+        loop {
+            self.skip_separators();
+            let Some(name) = self.peek() else {
+                return Ok(());
+            };
+            let command = Command {
+                name,
+                start: self.pos,
+            };
+            if !name.is_ascii_alphabetic() {
+                return Err((PathParseErrorKind::UnexpectedToken, self.token()));
+            }
+            if !COMMANDS.contains(name.to_ascii_uppercase()) {
+                return Err((PathParseErrorKind::UnknownCommand, self.token()));
+            }
+            if self.segments.is_empty() && !name.eq_ignore_ascii_case(&'M') {
+                return Err((PathParseErrorKind::MissingMoveTo, self.token()));
+            }
+            self.pos += 1;
+            let mut first = true;
+            loop {
+                let segment = self.segment(command, first)?;
+                match segment {
+                    PathSegment::MoveTo { x, y } => self.subpath = (x, y),
+                    PathSegment::Close => self.current = self.subpath,
+                    _ => {}
+                }
+                self.current = segment.end().unwrap_or(self.current);
+                self.segments.push(segment);
+                first = false;
+                if name.eq_ignore_ascii_case(&'Z') || !self.at_number() {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// The first control point of a smooth curve, which reflects the last control point
+    /// of a previous curve of the same `kind` around the current point.
+    fn reflected_control(&self, kind: char) -> (f32, f32) {
+        let (cx, cy) = self.current;
+        match (kind, self.segments.last()) {
+            ('C', Some(PathSegment::CubicTo { x2: x, y2: y, .. }))
+            | ('Q', Some(PathSegment::QuadTo { x1: x, y1: y, .. })) => {
+                (2.0f32.mul_add(cx, -x), 2.0f32.mul_add(cy, -y))
+            }
+            _ => (cx, cy),
+        }
+    }
+
+    /// Parse the arguments of one segment of `command`.
+    /// Only the `first` segment of a move command moves, the rest are lines.
+    fn segment(&mut self, command: Command, first: bool) -> ParseResult<PathSegment> {
+        let (cx, cy) = self.current;
+        let (dx, dy) = if command.name.is_ascii_lowercase() {
+            (cx, cy)
+        } else {
+            (0.0, 0.0)
+        };
+        Ok(match command.name.to_ascii_uppercase() {
+            'M' => {
+                let [x, y] = self.numbers(command)?;
+                let (x, y) = (x + dx, y + dy);
+                if first {
+                    PathSegment::MoveTo { x, y }
+                } else {
+                    PathSegment::LineTo { x, y }
+                }
+            }
+            'L' => {
+                let [x, y] = self.numbers(command)?;
+                PathSegment::LineTo {
+                    x: x + dx,
+                    y: y + dy,
+                }
+            }
+            'H' => {
+                let [x] = self.numbers(command)?;
+                PathSegment::LineTo { x: x + dx, y: cy }
+            }
+            'V' => {
+                let [y] = self.numbers(command)?;
+                PathSegment::LineTo { x: cx, y: y + dy }
+            }
+            'C' => {
+                let [x1, y1, x2, y2, x, y] = self.numbers(command)?;
+                PathSegment::CubicTo {
+                    x1: x1 + dx,
+                    y1: y1 + dy,
+                    x2: x2 + dx,
+                    y2: y2 + dy,
+                    x: x + dx,
+                    y: y + dy,
+                }
+            }
+            'S' => {
+                let [x2, y2, x, y] = self.numbers(command)?;
+                let (x1, y1) = self.reflected_control('C');
+                PathSegment::CubicTo {
+                    x1,
+                    y1,
+                    x2: x2 + dx,
+                    y2: y2 + dy,
+                    x: x + dx,
+                    y: y + dy,
+                }
+            }
+            'Q' => {
+                let [x1, y1, x, y] = self.numbers(command)?;
+                PathSegment::QuadTo {
+                    x1: x1 + dx,
+                    y1: y1 + dy,
+                    x: x + dx,
+                    y: y + dy,
+                }
+            }
+            'T' => {
+                let [x, y] = self.numbers(command)?;
+                let (x1, y1) = self.reflected_control('Q');
+                PathSegment::QuadTo {
+                    x1,
+                    y1,
+                    x: x + dx,
+                    y: y + dy,
+                }
+            }
+            'A' => {
+                let [rx, ry, rotation] = self.numbers(command)?;
+                let large_arc = self.flag(command)?;
+                let sweep = self.flag(command)?;
+                let [x, y] = self.numbers(command)?;
+                PathSegment::ArcTo {
+                    rx,
+                    ry,
+                    rotation,
+                    large_arc,
+                    sweep,
+                    x: x + dx,
+                    y: y + dy,
+                }
+            }
+            'Z' => PathSegment::Close,
+            _ => unreachable!("commands are checked"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments(input: &str) -> Vec<PathSegment> {
+        parse(input).expect("valid path data").0
+    }
+
+    fn error(input: &str) -> (PathParseErrorKind, usize, usize) {
+        let err = parse(input).expect_err("invalid input");
+        (err.kind, err.span.offset(), err.span.len())
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        use PathSegment as S;
+        assert_eq!(segments("  "), []);
+        assert_eq!(
+            segments("M1 2 3 4 h2 v-1 Z l1,1"),
+            [
+                S::MoveTo { x: 1.0, y: 2.0 },
+                S::LineTo { x: 3.0, y: 4.0 },
+                S::LineTo { x: 5.0, y: 4.0 },
+                S::LineTo { x: 5.0, y: 3.0 },
+                S::Close,
+                S::LineTo { x: 2.0, y: 3.0 },
+            ]
+        );
+        // Numbers need no separators where the next one starts with a sign or a second point.
+        assert_eq!(
+            segments("m1-1l.5.5"),
+            [S::MoveTo { x: 1.0, y: -1.0 }, S::LineTo { x: 1.5, y: -0.5 }]
+        );
+        assert_eq!(
+            segments("M0 0 C0 1 1 1 1 0 s2 -1 2 0"),
+            [
+                S::MoveTo { x: 0.0, y: 0.0 },
+                S::CubicTo {
+                    x1: 0.0,
+                    y1: 1.0,
+                    x2: 1.0,
+                    y2: 1.0,
+                    x: 1.0,
+                    y: 0.0
+                },
+                S::CubicTo {
+                    x1: 1.0,
+                    y1: -1.0,
+                    x2: 3.0,
+                    y2: -1.0,
+                    x: 3.0,
+                    y: 0.0
+                },
+            ]
+        );
+        assert_eq!(
+            segments("M0 0 Q1 1 2 0 T4 0"),
+            [
+                S::MoveTo { x: 0.0, y: 0.0 },
+                S::QuadTo {
+                    x1: 1.0,
+                    y1: 1.0,
+                    x: 2.0,
+                    y: 0.0
+                },
+                S::QuadTo {
+                    x1: 3.0,
+                    y1: -1.0,
+                    x: 4.0,
+                    y: 0.0
+                },
+            ]
+        );
+        assert_eq!(
+            segments("M1 1 a2 3 45 1 0 4 5 A1 1 0 0110 10"),
+            [
+                S::MoveTo { x: 1.0, y: 1.0 },
+                S::ArcTo {
+                    rx: 2.0,
+                    ry: 3.0,
+                    rotation: 45.0,
+                    large_arc: true,
+                    sweep: false,
+                    x: 5.0,
+                    y: 6.0
+                },
+                S::ArcTo {
+                    rx: 1.0,
+                    ry: 1.0,
+                    rotation: 0.0,
+                    large_arc: false,
+                    sweep: true,
+                    x: 10.0,
+                    y: 10.0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_error_spans() {
+        use PathParseErrorKind as E;
+        assert_eq!(error("L1 1"), (E::MissingMoveTo, 0, 1));
+        assert_eq!(error("M0 0 X1"), (E::UnknownCommand, 5, 1));
+        assert_eq!(error("10 10"), (E::UnexpectedToken, 0, 1));
+        assert_eq!(
+            error("M0 0 C1 2 3"),
+            (
+                E::ArgumentCount {
+                    command: 'C',
+                    count: 6
+                },
+                5,
+                6
+            )
+        );
+        assert_eq!(
+            error("M0 0 L1 Z"),
+            (
+                E::ArgumentCount {
+                    command: 'L',
+                    count: 2
+                },
+                5,
+                3
+            )
+        );
+        assert_eq!(error("M0 0 L1 ;"), (E::UnexpectedToken, 8, 1));
+        assert_eq!(error("M0 0 L1 -x"), (E::RecognizeFloat, 8, 2));
+        assert_eq!(error("M0 0 A1 1 0 2 0 1 1"), (E::ArcFlag, 12, 1));
+        assert_eq!(
+            error("M0 0 a1 1 0 0 1"),
+            (
+                E::ArgumentCount {
+                    command: 'a',
+                    count: 7
+                },
+                5,
+                10
+            )
+        );
+        assert_eq!(error("M0 0 Z 1"), (E::UnexpectedToken, 7, 1));
+    }
+}
@@ -0,0 +1,73 @@
+use super::{Border, CornerRadii, Paint, Shadow};
+use crate::ElementId;
+
+/// A painted box filling its parent.
+///
+/// Shadows are drawn first, then the fill and the border on top.
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RectElement {
+    #[serde(default)]
+    pub id: Option<ElementId>,
+    #[serde(default)]
+    pub fill: Option<Paint>,
+    #[serde(default)]
+    pub radius: CornerRadii,
+    #[serde(default)]
+    pub border: Option<Border>,
+    #[serde(default)]
+    pub shadows: Vec<Shadow>,
+}
+
+impl RectElement {
+    #[must_use]
+    pub fn id(&self) -> Option<ElementId> {
+        self.id.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, ColorStop, LayoutLength};
+
+    #[test]
+    fn test_deserialize_rect() {
+        let rect: RectElement = serde_json::from_str(
+            r##"{
+                "fill": { "type": "linear", "angle": 90, "stops": [
+                    { "color": "#000" },
+                    { "offset": 1, "color": "#fff" }
+                ] },
+                "radius": { "topLeft": "4px" },
+                "border": { "width": "1px", "paint": "#ff0000", "dash": ["2px", "2px"] }
+            }"##,
+        )
+        .expect("valid rect");
+        assert_eq!(
+            rect.fill,
+            Some(Paint::Linear {
+                angle: 90.0,
+                stops: vec![
+                    ColorStop {
+                        offset: None,
+                        color: Color::BLACK,
+                    },
+                    ColorStop {
+                        offset: Some(1.0),
+                        color: Color::WHITE,
+                    },
+                ],
+            })
+        );
+        assert_eq!(rect.radius.top_left, LayoutLength::Pixel(4.0));
+        assert_eq!(rect.radius.bottom_right, LayoutLength::SceneUnit(0.0));
+        let border = rect.border.expect("border");
+        assert_eq!(border.paint, Paint::from(Color::rgb(255, 0, 0)));
+        assert_eq!(border.dash.len(), 2);
+        assert!(rect.shadows.is_empty());
+
+        let uniform: CornerRadii = serde_json::from_str("3").expect("uniform radius");
+        assert_eq!(uniform, CornerRadii::uniform(&LayoutLength::SceneUnit(3.0)));
+    }
+}
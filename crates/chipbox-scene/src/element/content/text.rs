@@ -2,18 +2,115 @@ use std::sync::Arc;
 
 use crate::{Color, ElementId};
 
-#[derive(specta::Type)]
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct TextElement {
     #[serde(default)]
     pub id: Option<ElementId>,
     #[serde(default)]
-    pub children: Vec<Arc<str>>,
+    pub children: Vec<TextChild>,
+    /// A font family, a comma-separated stack of families of which the first installed one
+    /// is used, or a font token of the [`crate::Theme`] such as `"$body"`.
     pub font: Arc<str>,
     pub weight: u16,
     /// Defines em square size.
     pub size: f32,
     pub color: Color,
+    #[serde(default)]
+    pub italic: bool,
+    #[serde(default)]
+    pub underline: bool,
+    #[serde(default)]
+    pub overflow: TextOverflow,
+}
+
+/// A piece of text, either plain or styled.
+#[derive(specta::Type, serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum TextChild {
+    Text(Arc<str>),
+    Span(SpanElement),
+}
+
+/// Accepts a string as plain text, or a span object tagged with `"type": "span"`.
+impl<'de> serde::Deserialize<'de> for TextChild {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(tag = "type", rename_all = "camelCase")]
+        enum Tagged {
+            Span(SpanElement),
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(
+            untagged,
+            expecting = "a string or a span object with `\"type\": \"span\"`"
+        )]
+        enum Repr {
+            Text(Arc<str>),
+            Tagged(Tagged),
+        }
+
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Text(text) => Self::Text(text),
+            Repr::Tagged(Tagged::Span(span)) => Self::Span(span),
+        })
+    }
+}
+
+impl From<&str> for TextChild {
+    fn from(text: &str) -> Self {
+        Self::Text(Arc::from(text))
+    }
+}
+
+/// Styled run of text inside a [`TextElement`].
+///
+/// Unset properties are inherited from the enclosing span or text element.
+/// Spans only restyle glyphs, lines are broken across them as if the text was plain.
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(tag = "type", rename = "span", rename_all = "camelCase")]
+pub struct SpanElement {
+    #[serde(default)]
+    pub id: Option<ElementId>,
+    #[serde(default)]
+    pub children: Vec<TextChild>,
+    #[serde(default)]
+    pub font: Option<Arc<str>>,
+    #[serde(default)]
+    pub weight: Option<u16>,
+    #[serde(default)]
+    pub italic: Option<bool>,
+    #[serde(default)]
+    pub color: Option<Color>,
+    #[serde(default)]
+    pub underline: Option<bool>,
+    /// Raises the glyphs above the baseline, or lowers them when negative.
+    /// Lengths add up in nested spans.
+    #[serde(default)]
+    pub baseline_shift: Option<f32>,
+}
+
+/// How text that does not fit the width of its parent is handled.
+#[derive(
+    specta::Type,
+    serde::Serialize,
+    serde::Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum TextOverflow {
+    /// Break lines between words, or between characters of words that do not fit on their own.
+    #[default]
+    Wrap,
+    /// Keep every line on a single line and truncate it with an ellipsis.
+    Ellipsis,
 }
 
 impl TextElement {
@@ -22,3 +119,47 @@ impl TextElement {
         self.id.clone()
     }
 }
+
+impl SpanElement {
+    #[must_use]
+    pub fn id(&self) -> Option<ElementId> {
+        self.id.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_text_children() {
+        let children: Vec<TextChild> = serde_json::from_str(
+            r#"["Hello ", { "type": "span", "weight": 700, "children": ["world"] }]"#,
+        )
+        .expect("valid children");
+        assert_eq!(
+            children,
+            [
+                TextChild::from("Hello "),
+                TextChild::Span(SpanElement {
+                    weight: Some(700),
+                    children: vec![TextChild::from("world")],
+                    ..SpanElement::default()
+                })
+            ]
+        );
+        let json = serde_json::to_string(&children).expect("serialize");
+        let round_trip: Vec<TextChild> =
+            serde_json::from_str(&json).expect("deserialize serialized");
+        assert_eq!(round_trip, children);
+
+        // Objects that are not spans are rejected instead of read as empty spans.
+        for child in [
+            r#"{ "children": ["untagged"] }"#,
+            r#"{ "type": "box", "width": 10 }"#,
+            "{}",
+        ] {
+            assert!(serde_json::from_str::<TextChild>(child).is_err(), "{child}");
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use super::Paint;
+use crate::ElementId;
+
+/// Identifies a sample buffer registered with the renderer.
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BufferId(pub Arc<str>);
+
+impl std::fmt::Display for BufferId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The waveform of a range of a sample buffer, filling its parent.
+///
+/// Every device pixel column shows the minimum and maximum of the samples it covers,
+/// with their root mean square drawn on top.
+/// Amplitudes of `-1` and `1` reach the bottom and top of the lane.
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WaveformElement {
+    #[serde(default)]
+    pub id: Option<ElementId>,
+    pub buffer: BufferId,
+    /// The channel to draw, or every channel in its own lane from top to bottom if unset.
+    #[serde(default)]
+    pub channel: Option<u32>,
+    /// First visible frame, which may be fractional when zoomed in past single samples.
+    #[serde(default)]
+    pub start: f64,
+    /// Frame at the right edge, the end of the buffer if unset.
+    #[serde(default)]
+    pub end: Option<f64>,
+    /// Multiplies every amplitude.
+    #[serde(default = "WaveformElement::default_gain")]
+    pub gain: f32,
+    pub fill: Paint,
+    #[serde(default)]
+    pub rms_fill: Option<Paint>,
+}
+
+impl WaveformElement {
+    #[must_use]
+    pub const fn default_gain() -> f32 {
+        1.0
+    }
+
+    #[must_use]
+    pub fn id(&self) -> Option<ElementId> {
+        self.id.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Color;
+
+    #[test]
+    fn test_deserialize_waveform() {
+        let waveform: WaveformElement = serde_json::from_str(
+            r##"{ "buffer": "clip-1", "end": 48000, "fill": "#fff", "rmsFill": "#888" }"##,
+        )
+        .expect("valid waveform");
+        assert_eq!(
+            waveform,
+            WaveformElement {
+                id: None,
+                buffer: BufferId("clip-1".into()),
+                channel: None,
+                start: 0.0,
+                end: Some(48000.0),
+                gain: 1.0,
+                fill: Paint::from(Color::WHITE),
+                rms_fill: Some(Paint::from(Color::rgb(136, 136, 136))),
+            }
+        );
+    }
+}
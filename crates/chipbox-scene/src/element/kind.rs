@@ -0,0 +1,157 @@
+/// The type of an element, as named by its `type` tag.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum ElementKind {
+    Box,
+    Margin,
+    ContentBox,
+    Array,
+    Align,
+    Grid,
+    GridItem,
+    Flex,
+    FlexItem,
+    Scroll,
+    Text,
+    Span,
+    Rect,
+    Path,
+    Waveform,
+    Image,
+    Texture,
+    Transform,
+    Stacking,
+    Transition,
+    SelectionContainer,
+    SelectionAction,
+    Selection,
+    Deselection,
+    PointerAction,
+    PointerHoverMove,
+    PointerEnter,
+    PointerLeave,
+    Focusable,
+    KeyDown,
+    KeyUp,
+    TextInput,
+    Focus,
+    Blur,
+}
+
+impl ElementKind {
+    pub const ALL: [Self; 34] = [
+        Self::Box,
+        Self::Margin,
+        Self::ContentBox,
+        Self::Array,
+        Self::Align,
+        Self::Grid,
+        Self::GridItem,
+        Self::Flex,
+        Self::FlexItem,
+        Self::Scroll,
+        Self::Text,
+        Self::Span,
+        Self::Rect,
+        Self::Path,
+        Self::Waveform,
+        Self::Image,
+        Self::Texture,
+        Self::Transform,
+        Self::Stacking,
+        Self::Transition,
+        Self::SelectionContainer,
+        Self::SelectionAction,
+        Self::Selection,
+        Self::Deselection,
+        Self::PointerAction,
+        Self::PointerHoverMove,
+        Self::PointerEnter,
+        Self::PointerLeave,
+        Self::Focusable,
+        Self::KeyDown,
+        Self::KeyUp,
+        Self::TextInput,
+        Self::Focus,
+        Self::Blur,
+    ];
+
+    /// The `type` tag of the element.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Box => "box",
+            Self::Margin => "margin",
+            Self::ContentBox => "content-box",
+            Self::Array => "array",
+            Self::Align => "align",
+            Self::Grid => "grid",
+            Self::GridItem => "grid-item",
+            Self::Flex => "flex",
+            Self::FlexItem => "flex-item",
+            Self::Scroll => "scroll",
+            Self::Text => "text",
+            Self::Span => "span",
+            Self::Rect => "rect",
+            Self::Path => "path",
+            Self::Waveform => "waveform",
+            Self::Image => "image",
+            Self::Texture => "texture",
+            Self::Transform => "transform",
+            Self::Stacking => "stacking",
+            Self::Transition => "transition",
+            Self::SelectionContainer => "selection-container",
+            Self::SelectionAction => "selection-action",
+            Self::Selection => "selection",
+            Self::Deselection => "deselection",
+            Self::PointerAction => "pointer-action",
+            Self::PointerHoverMove => "pointer-hover-move",
+            Self::PointerEnter => "pointer-enter",
+            Self::PointerLeave => "pointer-leave",
+            Self::Focusable => "focusable",
+            Self::KeyDown => "key-down",
+            Self::KeyUp => "key-up",
+            Self::TextInput => "text-input",
+            Self::Focus => "focus",
+            Self::Blur => "blur",
+        }
+    }
+
+    /// Look up an element type by its `type` tag.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    /// Whether this is a behavior element.
+    #[must_use]
+    pub const fn is_behavior(self) -> bool {
+        matches!(
+            self,
+            Self::Texture
+                | Self::Transform
+                | Self::Stacking
+                | Self::Transition
+                | Self::SelectionContainer
+                | Self::SelectionAction
+                | Self::Selection
+                | Self::Deselection
+                | Self::PointerAction
+                | Self::PointerHoverMove
+                | Self::PointerEnter
+                | Self::PointerLeave
+                | Self::Focusable
+                | Self::KeyDown
+                | Self::KeyUp
+                | Self::TextInput
+                | Self::Focus
+                | Self::Blur
+        )
+    }
+}
+
+impl std::fmt::Display for ElementKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
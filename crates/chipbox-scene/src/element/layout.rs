@@ -2,105 +2,24 @@ mod align;
 mod array;
 mod flex;
 mod grid;
+mod length;
+mod scroll;
 mod sized;
 
 use delegate_match::delegate_match;
 
 pub use self::{
-    align::AlignElement,
+    align::{AlignElement, Alignment, HorizontalAlign, VerticalAlign},
     array::ArrayElement,
-    flex::{FlexElement, FlexItemElement},
-    grid::GridElement,
+    flex::{FlexAlign, FlexElement, FlexItemElement, FlexJustify},
+    grid::{GridElement, GridItemElement, GridTemplate, GridTrack, GridTrackParseError},
+    length::{LayoutLength, LengthExpr, LengthParseError, LengthParseErrorKind, length_from_str},
+    scroll::ScrollElement,
     sized::{BoxElement, ContentBoxElement, MarginElement},
 };
-use crate::ElementId;
+use crate::{ElementId, ElementKind};
 
-#[derive(specta::Type)]
-#[serde(tag = "type", rename_all = "camelCase")]
-pub enum LayoutLength {
-    #[serde(rename = "su")]
-    SceneUnit(f32),
-    #[serde(rename = "sw")]
-    SceneWidth(f32),
-    #[serde(rename = "sh")]
-    SceneHeight(f32),
-    #[serde(rename = "pw")]
-    ParentWidth(f32),
-    #[serde(rename = "ph")]
-    ParentHeight(f32),
-    #[serde(rename = "px")]
-    Pixel(f32),
-    #[serde(rename = "mm")]
-    Millimeter(f32),
-    #[serde(rename = "cm")]
-    Centimeter(f32),
-    #[serde(rename = "in")]
-    Inch(f32),
-    #[serde(rename = "pt")]
-    Point(f32),
-}
-
-#[specta::specta]
-pub fn length_from_str(input: &str) -> Result<LayoutLength, LayoutUnitParseError<&str>> {
-    LayoutLength::parse_from(input)
-}
-
-impl LayoutLength {
-    /// Parse a layout unit from a string.
-    /// ## Errors
-    /// - [`EndOfInput`] if the input is empty
-    /// - [`RecognizeFloat`] if the input is not a valid float.
-    /// - [`Incomplete`] if the input is incomplete.
-    /// ## Panics
-    /// - If the input is a valid float but could not be parsed.
-    ///   This would be indicative of a bug in this implementation.
-    ///
-    /// [`EndOfInput`]: LayoutUnitParseError::EndOfInput
-    /// [`RecognizeFloat`]: LayoutUnitParseError::RecognizeFloat
-    /// [`Incomplete`]: LayoutUnitParseError::Incomplete
-    pub fn parse_from(input: &str) -> Result<Self, LayoutUnitParseError<&str>> {
-        // Parse initial float.
-        let (rest, value) = match nom::number::complete::recognize_float(input) {
-            Ok((rest, value)) => {
-                let float = value.parse().expect("recognized a valid float");
-                Ok((rest, float))
-            }
-            Err(nom::Err::Error((i, _)) | nom::Err::Failure((i, _))) => {
-                Err(LayoutUnitParseError::RecognizeFloat(i))
-            }
-            Err(nom::Err::Incomplete(_)) => Err(LayoutUnitParseError::Incomplete),
-        }?;
-        // Parse unit. If empty, assume scene units.
-        let unit = match rest {
-            "" | "su" => Self::SceneUnit(value),
-            "sw" => Self::SceneWidth(value),
-            "sh" => Self::SceneHeight(value),
-            "pw" => Self::ParentWidth(value),
-            "ph" => Self::ParentHeight(value),
-            "px" => Self::Pixel(value),
-            "mm" => Self::Millimeter(value),
-            "cm" => Self::Centimeter(value),
-            "in" => Self::Inch(value),
-            "pt" => Self::Point(value),
-            _ => return Err(LayoutUnitParseError::InvalidUnit(rest)),
-        };
-        Ok(unit)
-    }
-}
-
-#[derive(Debug, thiserror::Error)]
-pub enum LayoutUnitParseError<I> {
-    #[error("end of input")]
-    EndOfInput,
-    #[error("invalid unit")]
-    InvalidUnit(I),
-    #[error("unable to recognize float")]
-    RecognizeFloat(I),
-    #[error("input is incomplete")]
-    Incomplete,
-}
-
-#[derive(specta::Type)]
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum Axis {
     #[serde(alias = "x")]
@@ -109,7 +28,9 @@ pub enum Axis {
     Vertical,
 }
 
-#[derive(specta::Type, Default)]
+#[derive(
+    specta::Type, serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
 #[serde(rename_all = "camelCase")]
 pub enum LinearDirection {
     #[default]
@@ -117,7 +38,7 @@ pub enum LinearDirection {
     Backward,
 }
 
-#[derive(specta::Type)]
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum SizedElement {
     Box(BoxElement),
@@ -134,7 +55,7 @@ impl SizedElement {
     }
 }
 
-#[derive(specta::Type)]
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum LayoutElement {
     Box(BoxElement),
@@ -144,13 +65,28 @@ pub enum LayoutElement {
     Align(AlignElement),
     Grid(GridElement),
     Flex(FlexElement),
+    Scroll(ScrollElement),
 }
 
 impl LayoutElement {
     #[must_use]
     pub fn id(&self) -> Option<ElementId> {
         delegate_match! { match self {
-            Self::{ Box, Margin, ContentBox, Array, Grid, Flex, Align }(e) => e.id(),
+            Self::{ Box, Margin, ContentBox, Array, Grid, Flex, Align, Scroll }(e) => e.id(),
         }}
     }
+
+    #[must_use]
+    pub const fn kind(&self) -> ElementKind {
+        match self {
+            Self::Box(_) => ElementKind::Box,
+            Self::Margin(_) => ElementKind::Margin,
+            Self::ContentBox(_) => ElementKind::ContentBox,
+            Self::Array(_) => ElementKind::Array,
+            Self::Align(_) => ElementKind::Align,
+            Self::Grid(_) => ElementKind::Grid,
+            Self::Flex(_) => ElementKind::Flex,
+            Self::Scroll(_) => ElementKind::Scroll,
+        }
+    }
 }
@@ -2,7 +2,7 @@ use crate::{ElementId, ElementNode, LayoutLength};
 
 /// An align element is a container for other elements.
 /// It is a layout element that aligns its children inside its bounds.
-#[derive(specta::Type)]
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct AlignElement {
     #[serde(default)]
@@ -58,14 +58,14 @@ impl AlignElement {
     }
 }
 
-#[derive(specta::Type)]
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct Alignment {
     pub horizontal: HorizontalAlign,
     pub vertical: VerticalAlign,
 }
 
-#[derive(specta::Type)]
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 pub enum HorizontalAlign {
     Left,
@@ -73,7 +73,7 @@ pub enum HorizontalAlign {
     Right,
 }
 
-#[derive(specta::Type)]
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 pub enum VerticalAlign {
     Top,
@@ -1,6 +1,6 @@
 use crate::{Axis, BoxElement, ElementId, LinearDirection};
 
-#[derive(specta::Type)]
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ArrayElement {
     #[serde(default)]
@@ -1,14 +1,14 @@
 mod item;
 
 pub use self::item::FlexItemElement;
-use crate::{Axis, ElementId, LinearDirection};
+use crate::{Axis, ElementId, LayoutLength, LinearDirection};
 
-#[derive(specta::Type)]
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct FlexElement {
     #[serde(default)]
     pub id: Option<ElementId>,
-    /// May only contain flex item elements (for proportional distribution).
+    /// May only contain flex item elements, which are sized by their flex factors.
     #[serde(default)]
     pub children: Vec<FlexItemElement>,
     /// On which axis the elements are distributed.
@@ -17,6 +17,48 @@ pub struct FlexElement {
     /// The direction in which the elements are distributed along the axis.
     #[serde(default)]
     pub direction: LinearDirection,
+    /// Space between adjacent items along the main axis.
+    #[serde(default)]
+    pub gap: Option<LayoutLength>,
+    /// How space left over after sizing the items is distributed along the main axis.
+    #[serde(default)]
+    pub justify: FlexJustify,
+    /// How items are placed along the cross axis.
+    #[serde(default)]
+    pub align: FlexAlign,
+}
+
+/// Distribution of free space along the main axis.
+/// Start and end follow the direction of the flex element.
+#[derive(
+    specta::Type, serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum FlexJustify {
+    #[default]
+    Start,
+    End,
+    Center,
+    /// Free space goes between items, none at the edges.
+    SpaceBetween,
+    /// Every item gets equal space on both of its sides.
+    SpaceAround,
+    /// Gaps between items and at the edges are all equal.
+    SpaceEvenly,
+}
+
+/// Placement of items along the cross axis.
+#[derive(
+    specta::Type, serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum FlexAlign {
+    Start,
+    Center,
+    End,
+    /// Items without a cross size fill the whole cross extent.
+    #[default]
+    Stretch,
 }
 
 impl FlexElement {
@@ -1,20 +1,46 @@
-use crate::element::{ElementId, ElementNode};
+use crate::{
+    FlexAlign, LayoutLength,
+    element::{ElementId, ElementNode},
+};
 
-#[derive(specta::Type)]
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct FlexItemElement {
     #[serde(default)]
     pub id: Option<ElementId>,
     #[serde(default)]
     pub children: Vec<ElementNode>,
-    /// The proportion of the flex item in relation to the rest.
-    #[serde(default = "FlexItemElement::default_proportion")]
-    pub proportion: f32,
+    /// Share of the free space the item grows by, relative to its siblings.
+    #[serde(default = "FlexItemElement::default_grow", alias = "proportion")]
+    pub grow: f32,
+    /// Share of the overflow the item shrinks by, weighted by its basis.
+    #[serde(default = "FlexItemElement::default_shrink")]
+    pub shrink: f32,
+    /// Main size of the item before growing or shrinking. Defaults to zero.
+    #[serde(default)]
+    pub basis: Option<LayoutLength>,
+    /// Lower bound of the main size.
+    #[serde(default)]
+    pub min: Option<LayoutLength>,
+    /// Upper bound of the main size.
+    #[serde(default)]
+    pub max: Option<LayoutLength>,
+    /// Size along the cross axis. Defaults to the cross extent of the flex element.
+    #[serde(default)]
+    pub cross: Option<LayoutLength>,
+    /// Overrides the cross-axis alignment of the flex element for this item.
+    #[serde(default)]
+    pub align: Option<FlexAlign>,
 }
 
 impl FlexItemElement {
     #[must_use]
-    pub const fn default_proportion() -> f32 {
+    pub const fn default_grow() -> f32 {
+        1.0
+    }
+
+    #[must_use]
+    pub const fn default_shrink() -> f32 {
         1.0
     }
 
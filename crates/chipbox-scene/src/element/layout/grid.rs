@@ -1,16 +1,24 @@
+mod item;
+mod track;
+
 use std::num::NonZeroUsize;
 
-use crate::{Axis, BoxElement, ElementId, LinearDirection};
+pub use self::{
+    item::GridItemElement,
+    track::{GridTemplate, GridTrack, GridTrackParseError},
+};
+use crate::{Axis, ElementId, LayoutLength, LinearDirection};
 
 /// A grid element is a container for other elements.
 /// It is a layout element that distributes its children in a grid.
-#[derive(specta::Type)]
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct GridElement {
     #[serde(default)]
     pub id: Option<ElementId>,
+    /// May only contain grid item elements, which are placed in order.
     #[serde(default)]
-    pub children: Vec<BoxElement>,
+    pub children: Vec<GridItemElement>,
     /// The main axis along which the elements are distributed.
     #[serde(default = "GridElement::default_axis")]
     pub axis: Axis,
@@ -31,6 +39,23 @@ pub struct GridElement {
     pub array_direction: LinearDirection,
     /// How many items can fit perpendicular to the main axis.
     pub array_limit: NonZeroUsize,
+    /// Sizes of the columns, in the order they are filled.
+    /// Columns without a template share the remaining space equally.
+    #[serde(default)]
+    pub columns: GridTemplate,
+    /// Sizes of the rows, in the order they are filled.
+    /// Rows without a template share the remaining space equally.
+    #[serde(default)]
+    pub rows: GridTemplate,
+    /// Space between adjacent tracks, unless overridden per axis.
+    #[serde(default)]
+    pub gap: Option<LayoutLength>,
+    /// Space between adjacent rows.
+    #[serde(default)]
+    pub row_gap: Option<LayoutLength>,
+    /// Space between adjacent columns.
+    #[serde(default)]
+    pub column_gap: Option<LayoutLength>,
 }
 
 impl GridElement {
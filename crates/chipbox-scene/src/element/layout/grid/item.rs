@@ -0,0 +1,30 @@
+use std::num::NonZeroUsize;
+
+use crate::element::{ElementId, ElementNode};
+
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GridItemElement {
+    #[serde(default)]
+    pub id: Option<ElementId>,
+    #[serde(default)]
+    pub children: Vec<ElementNode>,
+    /// How many columns the item covers.
+    #[serde(default = "GridItemElement::default_span")]
+    pub column_span: NonZeroUsize,
+    /// How many rows the item covers.
+    #[serde(default = "GridItemElement::default_span")]
+    pub row_span: NonZeroUsize,
+}
+
+impl GridItemElement {
+    #[must_use]
+    pub const fn default_span() -> NonZeroUsize {
+        NonZeroUsize::MIN
+    }
+
+    #[must_use]
+    pub fn id(&self) -> Option<ElementId> {
+        self.id.clone()
+    }
+}
@@ -0,0 +1,236 @@
+use std::str::FromStr;
+
+use crate::{LayoutLength, LengthParseError};
+
+/// Sizing function of a single grid track.
+#[derive(specta::Type, serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", content = "value", rename_all = "camelCase")]
+pub enum GridTrack {
+    /// A fixed size.
+    Length(LayoutLength),
+    /// A share of the space left over by the other tracks, written as `1fr`.
+    /// Fractional tracks never shrink below zero, nor grow to fit their content.
+    #[serde(rename = "fr")]
+    Fraction(f32),
+    /// Sized to fit the content of its items.
+    Auto,
+    /// A size between a minimum and a maximum sizing function, written as `minmax(100px, 1fr)`.
+    /// A fractional minimum is treated as [`Self::Auto`].
+    #[serde(rename = "minmax")]
+    MinMax { min: Box<Self>, max: Box<Self> },
+}
+
+/// Mirror of the tagged object form of [`GridTrack`].
+#[derive(serde::Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "camelCase")]
+enum Tagged {
+    Length(LayoutLength),
+    #[serde(rename = "fr")]
+    Fraction(f32),
+    Auto,
+    #[serde(rename = "minmax")]
+    MinMax {
+        min: GridTrack,
+        max: GridTrack,
+    },
+}
+
+/// Accepts a number (scene units), a track string such as `"1fr"` or `"minmax(48px, auto)"`,
+/// or the tagged object form `{ "type": "fr", "value": 1 }`.
+#[derive(serde::Deserialize)]
+#[serde(
+    untagged,
+    expecting = "a number, a track string or a tagged track object"
+)]
+enum TrackRepr {
+    Number(f32),
+    Literal(String),
+    Tagged(Tagged),
+}
+
+impl<'de> serde::Deserialize<'de> for GridTrack {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        Ok(match TrackRepr::deserialize(deserializer)? {
+            TrackRepr::Number(value) => Self::Length(LayoutLength::SceneUnit(value)),
+            TrackRepr::Literal(literal) => literal.parse().map_err(D::Error::custom)?,
+            TrackRepr::Tagged(Tagged::Length(length)) => Self::Length(length),
+            TrackRepr::Tagged(Tagged::Fraction(fraction)) => Self::Fraction(fraction),
+            TrackRepr::Tagged(Tagged::Auto) => Self::Auto,
+            TrackRepr::Tagged(Tagged::MinMax { min, max }) => Self::MinMax {
+                min: Box::new(min),
+                max: Box::new(max),
+            },
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum GridTrackParseError {
+    #[error("invalid track length")]
+    #[diagnostic(transparent)]
+    Length(#[from] LengthParseError),
+    #[error("invalid fraction `{0}`")]
+    #[diagnostic(code(chipbox_scene::grid::fraction))]
+    Fraction(String),
+    #[error("`minmax` expects two arguments, found `{0}`")]
+    #[diagnostic(code(chipbox_scene::grid::minmax))]
+    MinMax(String),
+}
+
+impl FromStr for GridTrack {
+    type Err = GridTrackParseError;
+
+    /// Parse a single track: `auto`, a fraction such as `1fr`, `minmax(min, max)`,
+    /// or a [`LayoutLength`].
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        if input == "auto" {
+            return Ok(Self::Auto);
+        }
+        if let Some(fraction) = input.strip_suffix("fr") {
+            return fraction
+                .trim()
+                .parse()
+                .map(Self::Fraction)
+                .map_err(|_| GridTrackParseError::Fraction(input.to_owned()));
+        }
+        if let Some(args) = input
+            .strip_prefix("minmax(")
+            .and_then(|args| args.strip_suffix(')'))
+        {
+            let [min, max]: [&str; 2] =
+                split_top_level(args, ',')
+                    .collect::<Vec<_>>()
+                    .try_into()
+                    .map_err(|_| GridTrackParseError::MinMax(args.to_owned()))?;
+            return Ok(Self::MinMax {
+                min: Box::new(min.parse()?),
+                max: Box::new(max.parse()?),
+            });
+        }
+        Ok(Self::Length(LayoutLength::parse_from(input)?))
+    }
+}
+
+/// Split at the separator, ignoring separators nested in parentheses.
+/// Empty parts are skipped.
+fn split_top_level(input: &str, separator: char) -> impl Iterator<Item = &str> {
+    let mut depth = 0_usize;
+    input
+        .split(move |c: char| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            depth == 0 && (c == separator || (separator == ' ' && c.is_whitespace()))
+        })
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+}
+
+/// A list of track sizes along one axis of a grid.
+///
+/// Deserializes from a space-separated string such as `"48px 1fr 1fr"`,
+/// or from a list of tracks. Expressions containing spaces must be parenthesized.
+#[derive(specta::Type, serde::Serialize, Clone, Debug, Default, PartialEq)]
+pub struct GridTemplate(pub Vec<GridTrack>);
+
+#[derive(serde::Deserialize)]
+#[serde(untagged, expecting = "a track template string or a list of tracks")]
+enum TemplateRepr {
+    Literal(String),
+    Tracks(Vec<GridTrack>),
+}
+
+impl<'de> serde::Deserialize<'de> for GridTemplate {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        match TemplateRepr::deserialize(deserializer)? {
+            TemplateRepr::Literal(literal) => literal.parse().map_err(D::Error::custom),
+            TemplateRepr::Tracks(tracks) => Ok(Self(tracks)),
+        }
+    }
+}
+
+impl FromStr for GridTemplate {
+    type Err = GridTrackParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        split_top_level(input, ' ')
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+impl GridTemplate {
+    /// Size of the track at the given index.
+    /// Tracks past the end of the template share the remaining space equally.
+    #[must_use]
+    pub fn track(&self, index: usize) -> &GridTrack {
+        const IMPLICIT: GridTrack = GridTrack::Fraction(1.0);
+        self.0.get(index).unwrap_or(&IMPLICIT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_template() {
+        let template: GridTemplate = "48px 1fr minmax(2in, auto) (1pw - 24px)"
+            .parse()
+            .expect("valid template");
+        assert_eq!(
+            template.0[..3],
+            [
+                GridTrack::Length(LayoutLength::Pixel(48.0)),
+                GridTrack::Fraction(1.0),
+                GridTrack::MinMax {
+                    min: Box::new(GridTrack::Length(LayoutLength::Inch(2.0))),
+                    max: Box::new(GridTrack::Auto),
+                },
+            ]
+        );
+        assert!(matches!(
+            template.0[3],
+            GridTrack::Length(LayoutLength::Calc(_))
+        ));
+        let json: GridTemplate =
+            serde_json::from_str(r#"["auto", 12, { "type": "fr", "value": 2 }]"#).expect("list");
+        assert_eq!(
+            json.0,
+            [
+                GridTrack::Auto,
+                GridTrack::Length(LayoutLength::SceneUnit(12.0)),
+                GridTrack::Fraction(2.0),
+            ]
+        );
+        let round_trip = serde_json::to_string(&template).expect("serialize");
+        assert_eq!(
+            serde_json::from_str::<GridTemplate>(&round_trip).expect("tagged"),
+            template
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            "xfr".parse::<GridTrack>(),
+            Err(GridTrackParseError::Fraction(_))
+        ));
+        assert!(matches!(
+            "minmax(1px)".parse::<GridTrack>(),
+            Err(GridTrackParseError::MinMax(_))
+        ));
+        assert!(matches!(
+            "12qx".parse::<GridTrack>(),
+            Err(GridTrackParseError::Length(_))
+        ));
+    }
+}
@@ -0,0 +1,283 @@
+mod expr;
+mod parse;
+
+use std::sync::Arc;
+
+pub use self::{
+    expr::LengthExpr,
+    parse::{LengthParseError, LengthParseErrorKind},
+};
+
+/// A length resolved to device pixels during layout.
+#[derive(specta::Type, serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", content = "value", rename_all = "camelCase")]
+pub enum LayoutLength {
+    /// Scene units, see [`crate::DisplayMetrics::scale`].
+    #[serde(rename = "su")]
+    SceneUnit(f32),
+    /// Fraction of the scene width.
+    #[serde(rename = "sw")]
+    SceneWidth(f32),
+    /// Fraction of the scene height.
+    #[serde(rename = "sh")]
+    SceneHeight(f32),
+    /// Fraction of the parent width.
+    #[serde(rename = "pw")]
+    ParentWidth(f32),
+    /// Fraction of the parent height.
+    #[serde(rename = "ph")]
+    ParentHeight(f32),
+    /// Logical pixels.
+    #[serde(rename = "px")]
+    Pixel(f32),
+    #[serde(rename = "mm")]
+    Millimeter(f32),
+    #[serde(rename = "cm")]
+    Centimeter(f32),
+    #[serde(rename = "in")]
+    Inch(f32),
+    #[serde(rename = "pt")]
+    Point(f32),
+    /// An arithmetic expression over lengths, evaluated during layout.
+    #[serde(rename = "calc")]
+    Calc(Arc<LengthExpr>),
+    /// A named length of the [`crate::Theme`], written `$name`.
+    /// Tokens the theme does not define resolve to zero.
+    #[serde(rename = "token")]
+    Token(Arc<str>),
+}
+
+/// Accepts a number (scene units), a string such as `"12px"`, `"$gap"` or `"1pw - 24px"`,
+/// or the tagged object form `{ "type": "px", "value": 12 }`.
+impl<'de> serde::Deserialize<'de> for LayoutLength {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Value {
+            Number(f32),
+            Name(Arc<str>),
+            Expr(LengthExpr),
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(
+            untagged,
+            expecting = "a number, a length string or a tagged length object"
+        )]
+        enum Repr {
+            Number(f32),
+            Literal(String),
+            Tagged {
+                #[serde(rename = "type")]
+                unit: String,
+                value: Value,
+            },
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Number(value) => Ok(Self::SceneUnit(value)),
+            Repr::Literal(literal) => Self::parse_from(&literal).map_err(D::Error::custom),
+            Repr::Tagged {
+                unit,
+                value: Value::Expr(expr),
+            } if unit == "calc" => Ok(Self::Calc(Arc::new(expr))),
+            Repr::Tagged {
+                unit,
+                value: Value::Name(name),
+            } if unit == "token" => Ok(Self::Token(name)),
+            Repr::Tagged {
+                unit,
+                value: Value::Number(value),
+            } if !matches!(unit.as_str(), "" | "calc" | "token") => {
+                Self::from_unit(value, &unit).ok_or_else(|| D::Error::unknown_variant(&unit, UNITS))
+            }
+            Repr::Tagged { unit, .. } if !UNITS.contains(&unit.as_str()) => {
+                Err(D::Error::unknown_variant(&unit, UNITS))
+            }
+            Repr::Tagged { unit, .. } => {
+                let expected = match unit.as_str() {
+                    "calc" => r#"an expression object such as `{ "op": "sum", "args": [..] }`"#,
+                    "token" => "the name of a theme length",
+                    _ => "a number",
+                };
+                Err(D::Error::custom(format_args!(
+                    "invalid value for length of type `{unit}`, expected {expected}"
+                )))
+            }
+        }
+    }
+}
+
+/// Tags of the tagged object form.
+const UNITS: &[&str] = &[
+    "su", "sw", "sh", "pw", "ph", "px", "mm", "cm", "in", "pt", "calc", "token",
+];
+
+/// Parse a layout length from a string.
+/// See [`LayoutLength::parse_from`].
+/// ## Errors
+/// - [`LengthParseError`] pointing at the offending token.
+#[specta::specta]
+pub fn length_from_str(input: &str) -> Result<LayoutLength, LengthParseError> {
+    LayoutLength::parse_from(input)
+}
+
+impl LayoutLength {
+    /// Parse a layout length from a string.
+    ///
+    /// Accepts a single number with an optional unit (`"12px"`, scene units if omitted),
+    /// a theme token (`"$gap"`), or an arithmetic expression over such lengths,
+    /// e.g. `"100pw - 2 * $gap"`.
+    /// Expressions support `+`, `-`, `*`, `/`, parentheses and
+    /// the `min(..)`, `max(..)` and `clamp(min, value, max)` functions.
+    /// Plain lengths are returned as-is, expressions as [`Self::Calc`].
+    /// ## Errors
+    /// - [`LengthParseError`] pointing at the offending token.
+    pub fn parse_from(input: &str) -> Result<Self, LengthParseError> {
+        let expr = parse::parse(input)?;
+        Ok(match expr {
+            LengthExpr::Length(length) => length,
+            expr => Self::Calc(Arc::new(expr)),
+        })
+    }
+
+    /// Construct a length from a number and its unit suffix.
+    /// An empty unit is interpreted as scene units.
+    #[must_use]
+    pub fn from_unit(value: f32, unit: &str) -> Option<Self> {
+        let length = match unit {
+            "" | "su" => Self::SceneUnit(value),
+            "sw" => Self::SceneWidth(value),
+            "sh" => Self::SceneHeight(value),
+            "pw" => Self::ParentWidth(value),
+            "ph" => Self::ParentHeight(value),
+            "px" => Self::Pixel(value),
+            "mm" => Self::Millimeter(value),
+            "cm" => Self::Centimeter(value),
+            "in" => Self::Inch(value),
+            "pt" => Self::Point(value),
+            _ => return None,
+        };
+        Some(length)
+    }
+
+    /// The numeric value of a plain length.
+    /// Returns `None` for expressions and tokens.
+    #[must_use]
+    pub const fn value(&self) -> Option<f32> {
+        match self {
+            Self::SceneUnit(v)
+            | Self::SceneWidth(v)
+            | Self::SceneHeight(v)
+            | Self::ParentWidth(v)
+            | Self::ParentHeight(v)
+            | Self::Pixel(v)
+            | Self::Millimeter(v)
+            | Self::Centimeter(v)
+            | Self::Inch(v)
+            | Self::Point(v) => Some(*v),
+            Self::Calc(_) | Self::Token(_) => None,
+        }
+    }
+
+    /// The unit suffix of a plain length.
+    /// Returns `None` for expressions and tokens.
+    #[must_use]
+    pub const fn unit(&self) -> Option<&'static str> {
+        let unit = match self {
+            Self::SceneUnit(_) => "su",
+            Self::SceneWidth(_) => "sw",
+            Self::SceneHeight(_) => "sh",
+            Self::ParentWidth(_) => "pw",
+            Self::ParentHeight(_) => "ph",
+            Self::Pixel(_) => "px",
+            Self::Millimeter(_) => "mm",
+            Self::Centimeter(_) => "cm",
+            Self::Inch(_) => "in",
+            Self::Point(_) => "pt",
+            Self::Calc(_) | Self::Token(_) => return None,
+        };
+        Some(unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_json(json: &str) -> serde_json::Result<LayoutLength> {
+        serde_json::from_str(json)
+    }
+
+    #[test]
+    fn test_deserialize_forms() {
+        let expected = LayoutLength::Pixel(12.0);
+        assert_eq!(from_json(r#""12px""#).expect("string"), expected);
+        assert_eq!(
+            from_json(r#"{ "type": "px", "value": 12 }"#).expect("object"),
+            expected
+        );
+        assert_eq!(
+            from_json("3").expect("number"),
+            LayoutLength::SceneUnit(3.0)
+        );
+        let calc = from_json(r#""1pw - 24px""#).expect("expression");
+        assert!(matches!(calc, LayoutLength::Calc(_)));
+        let json = serde_json::to_string(&calc).expect("serialize");
+        assert_eq!(from_json(&json).expect("calc object"), calc);
+    }
+
+    #[test]
+    fn test_deserialize_errors() {
+        let err = from_json(r#""12qx""#).expect_err("invalid unit");
+        assert!(err.to_string().contains("invalid unit"), "{err}");
+        let err = from_json(r#"{ "type": "qx", "value": 12 }"#).expect_err("invalid tag");
+        assert!(err.to_string().contains("unknown variant `qx`"), "{err}");
+        let err = from_json(r#"{ "type": "px", "value": { "op": "min", "args": [] } }"#)
+            .expect_err("mismatched value");
+        assert!(err.to_string().contains("`px`, expected a number"), "{err}");
+        let err = from_json(r#"{ "type": "calc", "value": 3 }"#).expect_err("calc of a number");
+        assert!(
+            err.to_string()
+                .contains(r#"expression object such as `{ "op""#),
+            "{err}"
+        );
+        from_json("true").expect_err("invalid type");
+    }
+
+    #[test]
+    fn test_deserialize_divisor() {
+        let quotient = |divisor: &str| {
+            let args = format!(r#"[{{ "op": "length", "args": "1pw" }}, {divisor}]"#);
+            serde_json::from_str::<LengthExpr>(&format!(
+                r#"{{ "op": "quotient", "args": {args} }}"#
+            ))
+        };
+        assert_eq!(
+            quotient("2").expect("non-zero divisor"),
+            LengthExpr::Quotient(
+                Box::new(LengthExpr::Length(LayoutLength::ParentWidth(1.0))),
+                2.0
+            )
+        );
+        let err = quotient("0").expect_err("zero divisor");
+        assert!(err.to_string().contains("invalid divisor 0"), "{err}");
+        // Overflows to infinity.
+        quotient("1e39").expect_err("infinite divisor");
+        // Nested expressions and calc lengths are checked as well.
+        let nested = r#"{ "op": "min", "args": [
+            { "op": "quotient", "args": [{ "op": "length", "args": 1 }, 0] }
+        ] }"#;
+        serde_json::from_str::<LengthExpr>(nested).expect_err("nested zero divisor");
+        from_json(&format!(r#"{{ "type": "calc", "value": {nested} }}"#))
+            .expect_err("calc with a zero divisor");
+    }
+
+    #[test]
+    fn test_serialize_tagged() {
+        let json = serde_json::to_value(LayoutLength::ParentWidth(0.5)).expect("serialize");
+        assert_eq!(json, serde_json::json!({ "type": "pw", "value": 0.5 }));
+    }
+}
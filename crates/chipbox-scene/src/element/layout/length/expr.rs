@@ -0,0 +1,65 @@
+use crate::LayoutLength;
+
+/// An arithmetic expression over layout lengths.
+///
+/// Lengths of mixed units can only be combined once the parent size is known,
+/// so expressions are kept structured and evaluated by the layout solver.
+#[derive(specta::Type, serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "op", content = "args", rename_all = "camelCase")]
+pub enum LengthExpr {
+    Length(LayoutLength),
+    Sum(Box<Self>, Box<Self>),
+    Difference(Box<Self>, Box<Self>),
+    /// Scales a length by a unitless factor.
+    Product(Box<Self>, f32),
+    /// Divides a length by a unitless, non-zero divisor.
+    Quotient(Box<Self>, f32),
+    Min(Vec<Self>),
+    Max(Vec<Self>),
+    /// Clamps a value between a lower and an upper bound.
+    /// The lower bound wins if the bounds overlap.
+    Clamp {
+        min: Box<Self>,
+        value: Box<Self>,
+        max: Box<Self>,
+    },
+}
+
+/// Rejects a quotient by zero or by a non-finite divisor,
+/// which the string form reports as [`crate::LengthParseErrorKind::DivisionByZero`].
+impl<'de> serde::Deserialize<'de> for LengthExpr {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        #[derive(serde::Deserialize)]
+        #[serde(
+            remote = "LengthExpr",
+            tag = "op",
+            content = "args",
+            rename_all = "camelCase"
+        )]
+        enum Tagged {
+            Length(LayoutLength),
+            Sum(Box<LengthExpr>, Box<LengthExpr>),
+            Difference(Box<LengthExpr>, Box<LengthExpr>),
+            Product(Box<LengthExpr>, f32),
+            Quotient(Box<LengthExpr>, f32),
+            Min(Vec<LengthExpr>),
+            Max(Vec<LengthExpr>),
+            Clamp {
+                min: Box<LengthExpr>,
+                value: Box<LengthExpr>,
+                max: Box<LengthExpr>,
+            },
+        }
+
+        match Tagged::deserialize(deserializer)? {
+            Self::Quotient(_, divisor) if divisor == 0.0 || !divisor.is_finite() => {
+                Err(D::Error::custom(format_args!(
+                    "invalid divisor {divisor}, expected a finite, non-zero number"
+                )))
+            }
+            expr => Ok(expr),
+        }
+    }
+}
@@ -0,0 +1,491 @@
+use std::ops::Range;
+
+use miette::SourceSpan;
+
+use crate::{LayoutLength, LengthExpr};
+
+/// What went wrong while parsing a layout length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum LengthParseErrorKind {
+    #[error("unexpected end of input")]
+    EndOfInput,
+    #[error("unexpected token")]
+    UnexpectedToken,
+    #[error("invalid unit")]
+    InvalidUnit,
+    #[error("unable to recognize float")]
+    RecognizeFloat,
+    #[error("unknown function")]
+    UnknownFunction,
+    #[error("expected {expected} arguments, found {found}")]
+    ArgumentCount { expected: usize, found: usize },
+    #[error("cannot multiply two lengths")]
+    LengthProduct,
+    #[error("cannot divide by a length")]
+    LengthDivisor,
+    #[error("division by zero")]
+    DivisionByZero,
+    #[error("invalid token name")]
+    TokenName,
+}
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+#[error("invalid layout length: {kind}")]
+pub struct LengthParseError {
+    pub kind: LengthParseErrorKind,
+    #[source_code]
+    pub input: String,
+    /// Location of the offending token in the input.
+    #[label("{kind}")]
+    pub span: SourceSpan,
+}
+
+type ParseResult<T> = Result<T, (LengthParseErrorKind, Range<usize>)>;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token<'a> {
+    /// A unitless number.
+    Number(f32),
+    Length(LayoutLength),
+    Ident(&'a str),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Open,
+    Close,
+    Comma,
+}
+
+#[derive(Debug)]
+struct Spanned<'a> {
+    token: Token<'a>,
+    span: Range<usize>,
+}
+
+/// Parse a length expression.
+pub fn parse(input: &str) -> Result<LengthExpr, LengthParseError> {
+    let result = lex(input).and_then(|tokens| {
+        let mut parser = Parser {
+            tokens: &tokens,
+            pos: 0,
+            end: input.len(),
+        };
+        let (operand, _) = parser.expr()?;
+        if let Some(spanned) = parser.next() {
+            return Err((LengthParseErrorKind::UnexpectedToken, spanned.span.clone()));
+        }
+        Ok(operand.into_length())
+    });
+    result.map_err(|(kind, span)| LengthParseError {
+        kind,
+        input: input.to_owned(),
+        span: (span.start, span.len()).into(),
+    })
+}
+
+/// Length of the longest prefix matching the predicate.
+fn prefix_len(input: &str, predicate: impl Fn(char) -> bool) -> usize {
+    input.find(|c| !predicate(c)).unwrap_or(input.len())
+}
+
+fn lex(input: &str) -> ParseResult<Vec<Spanned<'_>>> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while let Some(c) = input[pos..].chars().next() {
+        let start = pos;
+        let rest = &input[pos..];
+        let token = match c {
+            c if c.is_whitespace() => {
+                pos += c.len_utf8();
+                continue;
+            }
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            '*' => Token::Star,
+            '/' => Token::Slash,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            ',' => Token::Comma,
+            // Signs are handled by the parser, so that `a -b` is not read as two operands.
+            '0'..='9' | '.' => {
+                let Ok((_, number)) = nom::number::complete::recognize_float::<_, (&str, _)>(rest)
+                else {
+                    let len = prefix_len(rest, |c| c.is_ascii_alphanumeric() || c == '.');
+                    return Err((LengthParseErrorKind::RecognizeFloat, start..start + len));
+                };
+                let value = number.parse().expect("recognized a valid float");
+                let unit_start = start + number.len();
+                let unit_len = prefix_len(&input[unit_start..], |c| c.is_ascii_alphabetic());
+                let unit = &input[unit_start..unit_start + unit_len];
+                pos = unit_start + unit_len;
+                let token = if unit.is_empty() {
+                    Token::Number(value)
+                } else {
+                    Token::Length(
+                        LayoutLength::from_unit(value, unit)
+                            .ok_or((LengthParseErrorKind::InvalidUnit, unit_start..pos))?,
+                    )
+                };
+                tokens.push(Spanned {
+                    token,
+                    span: start..pos,
+                });
+                continue;
+            }
+            '$' => {
+                let len = prefix_len(&rest[1..], |c| {
+                    c.is_ascii_alphanumeric() || matches!(c, '_' | '.')
+                });
+                if len == 0 {
+                    let end = rest[1..].chars().next().map_or(1, |c| 1 + c.len_utf8());
+                    return Err((LengthParseErrorKind::TokenName, start + 1..start + end));
+                }
+                pos += 1 + len;
+                tokens.push(Spanned {
+                    token: Token::Length(LayoutLength::Token(input[start + 1..pos].into())),
+                    span: start..pos,
+                });
+                continue;
+            }
+            c if c.is_ascii_alphabetic() => {
+                pos += prefix_len(rest, |c| c.is_ascii_alphanumeric() || c == '_');
+                tokens.push(Spanned {
+                    token: Token::Ident(&input[start..pos]),
+                    span: start..pos,
+                });
+                continue;
+            }
+            c => {
+                return Err((
+                    LengthParseErrorKind::UnexpectedToken,
+                    start..start + c.len_utf8(),
+                ));
+            }
+        };
+        pos += c.len_utf8();
+        tokens.push(Spanned {
+            token,
+            span: start..pos,
+        });
+    }
+    Ok(tokens)
+}
+
+/// Intermediate parse result.
+/// Unitless numbers are kept apart, so they can act as factors and divisors.
+enum Operand {
+    Scalar(f32),
+    Length(LengthExpr),
+}
+
+impl Operand {
+    /// Unitless numbers are interpreted as scene units wherever a length is expected.
+    fn into_length(self) -> LengthExpr {
+        match self {
+            Self::Scalar(v) => LengthExpr::Length(LayoutLength::SceneUnit(v)),
+            Self::Length(expr) => expr,
+        }
+    }
+
+    fn negate(self) -> Self {
+        match self {
+            Self::Scalar(v) => Self::Scalar(-v),
+            Self::Length(LengthExpr::Length(length)) => match (length.value(), length.unit()) {
+                (Some(value), Some(unit)) => Self::Length(LengthExpr::Length(
+                    LayoutLength::from_unit(-value, unit).expect("unit of a valid length"),
+                )),
+                _ => Self::Length(LengthExpr::Product(
+                    Box::new(LengthExpr::Length(length)),
+                    -1.0,
+                )),
+            },
+            Self::Length(expr) => Self::Length(LengthExpr::Product(Box::new(expr), -1.0)),
+        }
+    }
+}
+
+struct Parser<'t, 'a> {
+    tokens: &'t [Spanned<'a>],
+    pos: usize,
+    /// Length of the input, used to point at its end.
+    end: usize,
+}
+
+impl<'t, 'a> Parser<'t, 'a> {
+    fn peek(&self) -> Option<&'t Spanned<'a>> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&'t Spanned<'a>> {
+        let spanned = self.peek()?;
+        self.pos += 1;
+        Some(spanned)
+    }
+
+    /// Consume the next token, which is expected to exist.
+    fn next_required(&mut self) -> ParseResult<&'t Spanned<'a>> {
+        self.next()
+            .ok_or((LengthParseErrorKind::EndOfInput, self.end..self.end))
+    }
+
+    /// Consume the next token if it is the given one.
+    fn eat(&mut self, token: &Token<'_>) -> Option<Range<usize>> {
+        let spanned = self.peek().filter(|spanned| spanned.token == *token)?;
+        self.pos += 1;
+        Some(spanned.span.clone())
+    }
+
+    fn expect(&mut self, token: &Token<'_>) -> ParseResult<Range<usize>> {
+        let spanned = self.next_required()?;
+        if spanned.token == *token {
+            Ok(spanned.span.clone())
+        } else {
+            Err((LengthParseErrorKind::UnexpectedToken, spanned.span.clone()))
+        }
+    }
+
+    /// `expr := term (('+' | '-') term)*`
+    fn expr(&mut self) -> ParseResult<(Operand, Range<usize>)> {
+        let (mut lhs, mut span) = self.term()?;
+        loop {
+            let negate = if self.eat(&Token::Plus).is_some() {
+                false
+            } else if self.eat(&Token::Minus).is_some() {
+                true
+            } else {
+                return Ok((lhs, span));
+            };
+            let (rhs, rhs_span) = self.term()?;
+            lhs = match (lhs, rhs) {
+                (Operand::Scalar(a), Operand::Scalar(b)) => {
+                    Operand::Scalar(if negate { a - b } else { a + b })
+                }
+                (lhs, rhs) => {
+                    let (a, b) = (Box::new(lhs.into_length()), Box::new(rhs.into_length()));
+                    Operand::Length(if negate {
+                        LengthExpr::Difference(a, b)
+                    } else {
+                        LengthExpr::Sum(a, b)
+                    })
+                }
+            };
+            span = span.start..rhs_span.end;
+        }
+    }
+
+    /// `term := unary (('*' | '/') unary)*`
+    fn term(&mut self) -> ParseResult<(Operand, Range<usize>)> {
+        let (mut lhs, mut span) = self.unary()?;
+        loop {
+            if let Some(op_span) = self.eat(&Token::Star) {
+                let (rhs, rhs_span) = self.unary()?;
+                lhs = match (lhs, rhs) {
+                    (Operand::Scalar(a), Operand::Scalar(b)) => Operand::Scalar(a * b),
+                    (Operand::Scalar(factor), Operand::Length(expr))
+                    | (Operand::Length(expr), Operand::Scalar(factor)) => {
+                        Operand::Length(LengthExpr::Product(Box::new(expr), factor))
+                    }
+                    (Operand::Length(_), Operand::Length(_)) => {
+                        return Err((LengthParseErrorKind::LengthProduct, op_span));
+                    }
+                };
+                span = span.start..rhs_span.end;
+            } else if self.eat(&Token::Slash).is_some() {
+                let (rhs, rhs_span) = self.unary()?;
+                let divisor = match rhs {
+                    Operand::Scalar(0.0) => {
+                        return Err((LengthParseErrorKind::DivisionByZero, rhs_span));
+                    }
+                    Operand::Scalar(divisor) => divisor,
+                    Operand::Length(_) => {
+                        return Err((LengthParseErrorKind::LengthDivisor, rhs_span));
+                    }
+                };
+                lhs = match lhs {
+                    Operand::Scalar(a) => Operand::Scalar(a / divisor),
+                    Operand::Length(expr) => {
+                        Operand::Length(LengthExpr::Quotient(Box::new(expr), divisor))
+                    }
+                };
+                span = span.start..rhs_span.end;
+            } else {
+                return Ok((lhs, span));
+            }
+        }
+    }
+
+    /// `unary := '-' unary | primary`
+    fn unary(&mut self) -> ParseResult<(Operand, Range<usize>)> {
+        if let Some(op_span) = self.eat(&Token::Minus) {
+            let (operand, span) = self.unary()?;
+            return Ok((operand.negate(), op_span.start..span.end));
+        }
+        self.primary()
+    }
+
+    /// `primary := number | length | '(' expr ')' | ident '(' expr (',' expr)* ')'`
+    fn primary(&mut self) -> ParseResult<(Operand, Range<usize>)> {
+        let spanned = self.next_required()?;
+        let span = spanned.span.clone();
+        match &spanned.token {
+            Token::Number(v) => Ok((Operand::Scalar(*v), span)),
+            Token::Length(length) => {
+                Ok((Operand::Length(LengthExpr::Length(length.clone())), span))
+            }
+            Token::Open => {
+                let (operand, _) = self.expr()?;
+                let close = self.expect(&Token::Close)?;
+                Ok((operand, span.start..close.end))
+            }
+            Token::Ident(name) => self.function(name, span),
+            _ => Err((LengthParseErrorKind::UnexpectedToken, span)),
+        }
+    }
+
+    fn function(
+        &mut self,
+        name: &str,
+        name_span: Range<usize>,
+    ) -> ParseResult<(Operand, Range<usize>)> {
+        if !matches!(name, "min" | "max" | "clamp") {
+            return Err((LengthParseErrorKind::UnknownFunction, name_span));
+        }
+        self.expect(&Token::Open)?;
+        let mut args = vec![self.expr()?.0.into_length()];
+        while self.eat(&Token::Comma).is_some() {
+            args.push(self.expr()?.0.into_length());
+        }
+        let close = self.expect(&Token::Close)?;
+        let span = name_span.start..close.end;
+        let expr = match name {
+            "min" => LengthExpr::Min(args),
+            "max" => LengthExpr::Max(args),
+            _ => {
+                let found = args.len();
+                let Ok([min, value, max]) = <[LengthExpr; 3]>::try_from(args) else {
+                    return Err((
+                        LengthParseErrorKind::ArgumentCount { expected: 3, found },
+                        span,
+                    ));
+                };
+                LengthExpr::Clamp {
+                    min: Box::new(min),
+                    value: Box::new(value),
+                    max: Box::new(max),
+                }
+            }
+        };
+        Ok((Operand::Length(expr), span))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[allow(clippy::unnecessary_box_returns, reason = "operands are boxed")]
+    fn length(length: LayoutLength) -> Box<LengthExpr> {
+        Box::new(LengthExpr::Length(length))
+    }
+
+    fn error(input: &str) -> (LengthParseErrorKind, usize, usize) {
+        let err = parse(input).expect_err("invalid input");
+        (err.kind, err.span.offset(), err.span.len())
+    }
+
+    #[test]
+    fn test_plain_lengths() {
+        assert_eq!(
+            LayoutLength::parse_from("12px").expect("valid"),
+            LayoutLength::Pixel(12.0)
+        );
+        assert_eq!(
+            LayoutLength::parse_from("0.5").expect("valid"),
+            LayoutLength::SceneUnit(0.5)
+        );
+        assert_eq!(
+            LayoutLength::parse_from(" -3.5e1mm ").expect("valid"),
+            LayoutLength::Millimeter(-35.0)
+        );
+        assert_eq!(
+            LayoutLength::parse_from("$spacing.md").expect("valid"),
+            LayoutLength::Token("spacing.md".into())
+        );
+        assert_eq!(
+            parse("$gap * 2").expect("valid"),
+            LengthExpr::Product(length(LayoutLength::Token("gap".into())), 2.0)
+        );
+    }
+
+    #[test]
+    fn test_precedence() {
+        let expr = parse("100pw - 24px * 2 / 4").expect("valid");
+        assert_eq!(
+            expr,
+            LengthExpr::Difference(
+                length(LayoutLength::ParentWidth(100.0)),
+                Box::new(LengthExpr::Quotient(
+                    Box::new(LengthExpr::Product(length(LayoutLength::Pixel(24.0)), 2.0)),
+                    4.0
+                )),
+            )
+        );
+        // Unitless arithmetic is folded and defaults to scene units.
+        assert_eq!(
+            parse("(1 + 2) * 3").expect("valid"),
+            LengthExpr::Length(LayoutLength::SceneUnit(9.0))
+        );
+    }
+
+    #[test]
+    fn test_functions() {
+        assert_eq!(
+            parse("max(0.5sw, 300px)").expect("valid"),
+            LengthExpr::Max(vec![
+                LengthExpr::Length(LayoutLength::SceneWidth(0.5)),
+                LengthExpr::Length(LayoutLength::Pixel(300.0)),
+            ])
+        );
+        assert_eq!(
+            parse("clamp(10px, -min(1pw), 2in)").expect("valid"),
+            LengthExpr::Clamp {
+                min: length(LayoutLength::Pixel(10.0)),
+                value: Box::new(LengthExpr::Product(
+                    Box::new(LengthExpr::Min(vec![LengthExpr::Length(
+                        LayoutLength::ParentWidth(1.0)
+                    )])),
+                    -1.0
+                )),
+                max: length(LayoutLength::Inch(2.0)),
+            }
+        );
+    }
+
+    #[test]
+    fn test_error_spans() {
+        use LengthParseErrorKind as E;
+        assert_eq!(error(""), (E::EndOfInput, 0, 0));
+        assert_eq!(error("12px +"), (E::EndOfInput, 6, 0));
+        assert_eq!(error("12qx"), (E::InvalidUnit, 2, 2));
+        assert_eq!(error("1pw 2px"), (E::UnexpectedToken, 4, 3));
+        assert_eq!(error("10px * 2pw"), (E::LengthProduct, 5, 1));
+        assert_eq!(error("10px / (1px + 1)"), (E::LengthDivisor, 7, 9));
+        assert_eq!(error("10px / 0"), (E::DivisionByZero, 7, 1));
+        assert_eq!(error("avg(1px)"), (E::UnknownFunction, 0, 3));
+        assert_eq!(
+            error("clamp(1px, 2px)"),
+            (
+                E::ArgumentCount {
+                    expected: 3,
+                    found: 2
+                },
+                0,
+                15
+            )
+        );
+        assert_eq!(error("1px # 2"), (E::UnexpectedToken, 4, 1));
+        assert_eq!(error("1px + $"), (E::TokenName, 7, 0));
+        assert_eq!(error("$-gap"), (E::TokenName, 1, 1));
+    }
+}
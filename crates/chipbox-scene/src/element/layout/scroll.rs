@@ -0,0 +1,41 @@
+use crate::{Axis, ElementId, ElementNode, LayoutLength};
+
+/// A scroll element is a container for other elements.
+/// It fills its parent and clips its content, which may be larger than itself.
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScrollElement {
+    #[serde(default)]
+    pub id: Option<ElementId>,
+    #[serde(default)]
+    pub children: Vec<ElementNode>,
+    /// The only axis that can be scrolled, or both if unset.
+    #[serde(default)]
+    pub axis: Option<Axis>,
+    /// Horizontal scroll position, clamped to the scrollable range.
+    #[serde(default)]
+    pub offset_x: Option<LayoutLength>,
+    /// Vertical scroll position, clamped to the scrollable range.
+    #[serde(default)]
+    pub offset_y: Option<LayoutLength>,
+    /// Extent of every child along [`Self::axis`], vertical if unset.
+    ///
+    /// When set, the children are stacked along the axis
+    /// and only the ones intersecting the viewport are laid out.
+    /// Behavior children are not rows and are always laid out.
+    #[serde(default)]
+    pub item_extent: Option<LayoutLength>,
+}
+
+impl ScrollElement {
+    #[must_use]
+    pub fn id(&self) -> Option<ElementId> {
+        self.id.clone()
+    }
+
+    /// Whether the content can be scrolled along the given axis.
+    #[must_use]
+    pub fn scrolls(&self, axis: Axis) -> bool {
+        self.axis.is_none_or(|scroll| scroll == axis)
+    }
+}
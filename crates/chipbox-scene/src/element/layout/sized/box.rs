@@ -2,7 +2,7 @@ use crate::{ElementId, ElementNode, LayoutLength};
 
 /// A box element is a container for other elements.
 /// It is a sized element &mdash; it defines its own width and height.
-#[derive(specta::Type)]
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct BoxElement {
     #[serde(default)]
@@ -3,7 +3,7 @@ use crate::{ElementId, ElementNode};
 /// A content box element is a container for other elements.
 /// It is a sized element &mdash;
 /// it calculates its size based on its descendant's content size.
-#[derive(specta::Type)]
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ContentBoxElement {
     #[serde(default)]
@@ -2,7 +2,7 @@ use crate::{ElementId, ElementNode, LayoutLength};
 
 /// A margin element is a container for other elements.
 /// It is a sized element &mdash; it defines its size in relation to its parent.
-#[derive(specta::Type)]
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct MarginElement {
     #[serde(default)]
@@ -0,0 +1,61 @@
+use crate::{LayoutNode, LayoutTree};
+
+/// Location of an element in the scene, as a list of child indices from the root.
+///
+/// Children of every element are indexed in declaration order,
+/// which matches the order of [`LayoutNode::children`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ElementPath(pub Vec<usize>);
+
+impl ElementPath {
+    /// The path of the child at the given index.
+    #[must_use]
+    pub fn child(&self, index: usize) -> Self {
+        let mut path = self.0.clone();
+        path.push(index);
+        Self(path)
+    }
+
+    /// The path of the parent element, or `None` for the root.
+    #[must_use]
+    pub fn parent(&self) -> Option<Self> {
+        let (_, parent) = self.0.split_last()?;
+        Some(Self(parent.to_vec()))
+    }
+
+    /// The element at the path and all of its ancestors, outermost first.
+    /// Stops at the first element missing from the layout, such as one culled by virtualization.
+    #[must_use]
+    pub fn ancestors<'a>(&self, tree: &'a LayoutTree) -> Vec<(Self, &'a LayoutNode)> {
+        let mut chain: Vec<(Self, &LayoutNode)> = Vec::with_capacity(self.0.len());
+        for (depth, &index) in self.0.iter().enumerate() {
+            let node = match chain.last() {
+                Some((_, parent)) => parent.child(index),
+                None => tree.children.get(index),
+            };
+            let Some(node) = node else {
+                break;
+            };
+            chain.push((Self(self.0[..=depth].to_vec()), node));
+        }
+        chain
+    }
+
+    /// Whether this path is an ancestor of, or equal to, the other path.
+    #[must_use]
+    pub fn contains(&self, other: &Self) -> bool {
+        other.0.starts_with(&self.0)
+    }
+}
+
+impl std::fmt::Display for ElementPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for index in &self.0 {
+            write!(f, "/{index}")?;
+        }
+        if self.0.is_empty() {
+            f.write_str("/")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+//! Layout trees and event summaries shared by the input tests.
+
+use std::sync::Arc;
+
+use crate::{
+    ElementId, ElementKind, KeyEvent, KeyEventKind, LayoutNode, PointerEvent, PointerEventKind,
+    Rect, SelectionEvent, SelectionEventKind,
+};
+
+pub fn id(name: &str) -> ElementId {
+    ElementId(Arc::from(name))
+}
+
+/// A box with behavior children of the given kinds, followed by the given children.
+pub fn element(
+    name: &str,
+    rect: Rect,
+    behaviors: &[ElementKind],
+    children: Vec<LayoutNode>,
+) -> LayoutNode {
+    LayoutNode::new(
+        Some(id(name)),
+        ElementKind::Box,
+        rect,
+        behaviors
+            .iter()
+            .map(|&kind| LayoutNode::new(None, kind, rect, Vec::new()))
+            .chain(children)
+            .collect(),
+    )
+}
+
+/// An event reduced to its kind and the id of the receiving element.
+pub trait Summary {
+    type Kind;
+
+    fn summary(&self) -> (Self::Kind, &str);
+}
+
+/// Events reduced to their kinds and receiving element ids, empty for elements without one.
+pub fn summary<E: Summary>(events: &[E]) -> Vec<(E::Kind, &str)> {
+    events.iter().map(Summary::summary).collect()
+}
+
+fn name(id: Option<&ElementId>) -> &str {
+    id.map_or("", |id| &id.0)
+}
+
+impl Summary for PointerEvent {
+    type Kind = PointerEventKind;
+
+    fn summary(&self) -> (Self::Kind, &str) {
+        (self.kind, name(self.id.as_ref()))
+    }
+}
+
+impl Summary for SelectionEvent {
+    type Kind = SelectionEventKind;
+
+    fn summary(&self) -> (Self::Kind, &str) {
+        (self.kind, &self.id.0)
+    }
+}
+
+impl Summary for KeyEvent {
+    type Kind = KeyEventKind;
+
+    fn summary(&self) -> (Self::Kind, &str) {
+        (self.kind.clone(), name(self.id.as_ref()))
+    }
+}
@@ -0,0 +1,481 @@
+mod shortcut;
+
+use std::sync::Arc;
+
+pub use self::shortcut::{Modifiers, Shortcut, ShortcutMap, ShortcutParseError};
+use crate::{ElementId, ElementKind, ElementPath, LayoutNode, LayoutTree};
+
+/// Keyboard input, with keys named as by the DOM `KeyboardEvent.key` property.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeyInput {
+    Down {
+        key: Arc<str>,
+        modifiers: Modifiers,
+        repeat: bool,
+    },
+    Up {
+        key: Arc<str>,
+        modifiers: Modifiers,
+    },
+    /// Text produced by one or more key presses, after keyboard layout and composition.
+    Text(Arc<str>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeyEventKind {
+    /// Fired for an element with a [`ElementKind::Focus`] behavior when it receives focus.
+    Focus,
+    /// Fired for an element with a [`ElementKind::Blur`] behavior when it loses focus.
+    Blur,
+    /// Fired for the focused element and its ancestors with a [`ElementKind::KeyDown`] behavior.
+    KeyDown {
+        key: Arc<str>,
+        modifiers: Modifiers,
+        repeat: bool,
+    },
+    /// Fired for the focused element and its ancestors with a [`ElementKind::KeyUp`] behavior.
+    KeyUp { key: Arc<str>, modifiers: Modifiers },
+    /// Fired for the focused element and its ancestors with a [`ElementKind::TextInput`] behavior.
+    TextInput(Arc<str>),
+    /// A global shortcut registered in the [`ShortcutMap`] under the given name.
+    Shortcut(Arc<str>),
+}
+
+/// An event routed to an element, or to the global shortcut layer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyEvent {
+    pub kind: KeyEventKind,
+    /// The receiving element, `None` for shortcuts.
+    pub target: Option<ElementPath>,
+    pub id: Option<ElementId>,
+}
+
+/// Tracks keyboard focus across frames and routes keyboard input to the focused element.
+///
+/// Key and text events bubble from the focused element to its ancestors, innermost first.
+/// Without a focused element they go straight to the shortcut layer.
+#[derive(Clone, Debug, Default)]
+pub struct FocusManager {
+    /// The focused element, tracked by id if it has one and by path otherwise.
+    focused: Option<(ElementPath, Option<ElementId>)>,
+}
+
+impl FocusManager {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Path of the focused element.
+    #[must_use]
+    pub fn focused(&self) -> Option<&ElementPath> {
+        self.focused.as_ref().map(|(path, _)| path)
+    }
+
+    /// Apply keyboard input to the given layout and return the resulting events.
+    ///
+    /// `Tab` and `Shift+Tab` move the focus along the tab order instead of producing key events.
+    /// Shortcuts are matched after the key bubbled through the focused elements,
+    /// except for shortcuts without command modifiers while a text input is focused.
+    pub fn dispatch(
+        &mut self,
+        tree: &LayoutTree,
+        shortcuts: &ShortcutMap,
+        input: KeyInput,
+    ) -> Vec<KeyEvent> {
+        let mut events = Vec::new();
+        self.relayout(tree);
+        match input {
+            KeyInput::Down { key, modifiers, .. }
+                if key.eq_ignore_ascii_case("tab") && !modifiers.is_command() =>
+            {
+                events.extend(self.traverse(tree, !modifiers.shift));
+            }
+            KeyInput::Down {
+                key,
+                modifiers,
+                repeat,
+            } => {
+                let chain = self.chain(tree);
+                let shortcut = shortcuts.find(&key, modifiers).filter(|_| {
+                    modifiers.is_command()
+                        || !chain
+                            .iter()
+                            .any(|(_, node)| node.has_behavior(ElementKind::TextInput))
+                });
+                let kind = KeyEventKind::KeyDown {
+                    key,
+                    modifiers,
+                    repeat,
+                };
+                bubble(&chain, ElementKind::KeyDown, &kind, &mut events);
+                events.extend(shortcut.map(|name| KeyEvent {
+                    kind: KeyEventKind::Shortcut(name.clone()),
+                    target: None,
+                    id: None,
+                }));
+            }
+            KeyInput::Up { key, modifiers } => {
+                let kind = KeyEventKind::KeyUp { key, modifiers };
+                bubble(&self.chain(tree), ElementKind::KeyUp, &kind, &mut events);
+            }
+            KeyInput::Text(text) => {
+                let kind = KeyEventKind::TextInput(text);
+                bubble(
+                    &self.chain(tree),
+                    ElementKind::TextInput,
+                    &kind,
+                    &mut events,
+                );
+            }
+        }
+        events
+    }
+
+    /// Focus the element at the given path, or clear the focus.
+    /// Elements without a [`ElementKind::Focusable`] behavior cannot be focused.
+    pub fn focus(&mut self, tree: &LayoutTree, path: Option<&ElementPath>) -> Vec<KeyEvent> {
+        let mut events = Vec::new();
+        self.relayout(tree);
+        let focused = path.and_then(|path| {
+            let node = tree.node(path).filter(|node| focusable(node).is_some())?;
+            Some((path.clone(), node.id.clone()))
+        });
+        if path.is_some() && focused.is_none() {
+            return events;
+        }
+        self.replace(tree, focused, &mut events);
+        events
+    }
+
+    /// Focus the innermost focusable element at a point in scene pixel space,
+    /// or clear the focus if there is none, as on a click.
+    pub fn focus_at(&mut self, tree: &LayoutTree, x: f32, y: f32) -> Vec<KeyEvent> {
+        let mut events = Vec::new();
+        self.relayout(tree);
+        let focused = tree.hit_test(x, y).and_then(|path| {
+            path.ancestors(tree)
+                .into_iter()
+                .rev()
+                .find(|(_, node)| focusable(node).is_some())
+                .map(|(path, node)| (path, node.id.clone()))
+        });
+        self.replace(tree, focused, &mut events);
+        events
+    }
+
+    /// Move the focus to the next or previous element in tab order, wrapping around.
+    pub fn traverse(&mut self, tree: &LayoutTree, forward: bool) -> Vec<KeyEvent> {
+        let mut events = Vec::new();
+        self.relayout(tree);
+        let order = tab_order(tree);
+        if order.is_empty() {
+            return events;
+        }
+        let current = self
+            .focused
+            .as_ref()
+            .and_then(|(path, _)| order.iter().position(|(p, _)| p == path));
+        let next = match (current, forward) {
+            (Some(index), true) => (index + 1) % order.len(),
+            (Some(index), false) => (index + order.len() - 1) % order.len(),
+            (None, true) => 0,
+            (None, false) => order.len() - 1,
+        };
+        let (path, node) = &order[next];
+        self.replace(tree, Some((path.clone(), node.id.clone())), &mut events);
+        events
+    }
+
+    /// Re-resolve the focused element against a new layout.
+    ///
+    /// Elements with an id keep the focus when they move. If the focused element
+    /// is removed or no longer focusable, the focus is cleared without a blur event.
+    pub fn relayout(&mut self, tree: &LayoutTree) {
+        self.focused = self.focused.take().and_then(|(path, id)| {
+            let path = match &id {
+                Some(id) => find(tree, id)?,
+                None => path,
+            };
+            tree.node(&path)
+                .filter(|node| focusable(node).is_some() && node.id == id)?;
+            Some((path, id))
+        });
+    }
+
+    fn replace(
+        &mut self,
+        tree: &LayoutTree,
+        focused: Option<(ElementPath, Option<ElementId>)>,
+        events: &mut Vec<KeyEvent>,
+    ) {
+        if focused == self.focused {
+            return;
+        }
+        for ((path, id), kind) in [
+            (self.focused.take(), KeyEventKind::Blur),
+            (focused.clone(), KeyEventKind::Focus),
+        ]
+        .into_iter()
+        .filter_map(|(target, kind)| Some((target?, kind)))
+        {
+            let behavior = match kind {
+                KeyEventKind::Focus => ElementKind::Focus,
+                _ => ElementKind::Blur,
+            };
+            if tree
+                .node(&path)
+                .is_some_and(|node| node.has_behavior(behavior))
+            {
+                events.push(KeyEvent {
+                    kind,
+                    target: Some(path),
+                    id,
+                });
+            }
+        }
+        self.focused = focused;
+    }
+
+    /// The focused element and its ancestors, innermost first.
+    fn chain<'a>(&self, tree: &'a LayoutTree) -> Vec<(ElementPath, &'a LayoutNode)> {
+        self.focused
+            .as_ref()
+            .map(|(path, _)| {
+                let mut chain = path.ancestors(tree);
+                chain.reverse();
+                chain
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// The tab index of a focusable element.
+fn focusable(node: &LayoutNode) -> Option<i32> {
+    node.children
+        .iter()
+        .find(|child| child.kind == ElementKind::Focusable)
+        .map(|child| child.tab_index.unwrap_or_default())
+}
+
+fn bubble(
+    chain: &[(ElementPath, &LayoutNode)],
+    behavior: ElementKind,
+    kind: &KeyEventKind,
+    events: &mut Vec<KeyEvent>,
+) {
+    events.extend(
+        chain
+            .iter()
+            .filter(|(_, node)| node.has_behavior(behavior))
+            .map(|(path, node)| KeyEvent {
+                kind: kind.clone(),
+                target: Some(path.clone()),
+                id: node.id.clone(),
+            }),
+    );
+}
+
+/// Focusable elements reachable by tab traversal, in tab order.
+fn tab_order(tree: &LayoutTree) -> Vec<(ElementPath, &LayoutNode)> {
+    let mut order = Vec::new();
+    walk(tree, &mut |path, node| {
+        if let Some(index) = focusable(node).filter(|index| *index >= 0) {
+            order.push((index, path.clone(), node));
+        }
+        false
+    });
+    // Stable, so equal indices stay in document order.
+    order.sort_by_key(|(index, ..)| if *index > 0 { (0, *index) } else { (1, 0) });
+    order
+        .into_iter()
+        .map(|(_, path, node)| (path, node))
+        .collect()
+}
+
+/// Path of the laid out element with the given id.
+fn find(tree: &LayoutTree, id: &ElementId) -> Option<ElementPath> {
+    let mut found = None;
+    walk(tree, &mut |path, node| {
+        if node.id.as_ref() == Some(id) {
+            found = Some(path.clone());
+        }
+        found.is_some()
+    });
+    found
+}
+
+/// Visit the laid out elements in document order until the visitor returns `true`.
+fn walk<'a>(tree: &'a LayoutTree, visit: &mut impl FnMut(&ElementPath, &'a LayoutNode) -> bool) {
+    fn visit_node<'a>(
+        node: &'a LayoutNode,
+        path: &mut ElementPath,
+        visit: &mut impl FnMut(&ElementPath, &'a LayoutNode) -> bool,
+    ) -> bool {
+        if visit(path, node) {
+            return true;
+        }
+        for (index, child) in node.indexed_children() {
+            path.0.push(index);
+            let done = visit_node(child, path, visit);
+            path.0.pop();
+            if done {
+                return true;
+            }
+        }
+        false
+    }
+
+    let mut path = ElementPath::default();
+    for (index, child) in tree.children.iter().enumerate() {
+        path.0.push(index);
+        let done = visit_node(child, &mut path, visit);
+        path.0.pop();
+        if done {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        Rect,
+        fixtures::{element, summary},
+    };
+
+    fn field(name: &str, x: f32, tab_index: i32) -> LayoutNode {
+        let focusable = LayoutNode {
+            tab_index: Some(tab_index),
+            ..LayoutNode::new(None, ElementKind::Focusable, Rect::default(), Vec::new())
+        };
+        element(
+            name,
+            Rect::new(x, 0.0, 10.0, 10.0),
+            &[
+                ElementKind::Focus,
+                ElementKind::Blur,
+                ElementKind::TextInput,
+            ],
+            vec![focusable],
+        )
+    }
+
+    /// A panel handling key presses, containing text fields `a`, `b`, `c` and `d`.
+    fn tree(fields: Vec<LayoutNode>) -> LayoutTree {
+        LayoutTree {
+            children: vec![element(
+                "panel",
+                Rect::new(0.0, 0.0, 100.0, 10.0),
+                &[ElementKind::KeyDown],
+                fields,
+            )],
+        }
+    }
+
+    fn fields() -> Vec<LayoutNode> {
+        vec![
+            field("a", 0.0, 0),
+            field("b", 20.0, 2),
+            field("c", 40.0, -1),
+            field("d", 60.0, 1),
+        ]
+    }
+
+    fn key(key: &str, modifiers: Modifiers) -> KeyInput {
+        KeyInput::Down {
+            key: Arc::from(key),
+            modifiers,
+            repeat: false,
+        }
+    }
+
+    fn focused(focus: &FocusManager, tree: &LayoutTree) -> Option<ElementId> {
+        focus
+            .focused()
+            .and_then(|path| tree.node(path))
+            .and_then(|node| node.id.clone())
+    }
+
+    #[test]
+    fn test_focus_tab_order() {
+        let tree = tree(fields());
+        let shortcuts = ShortcutMap::new();
+        let mut focus = FocusManager::new();
+        let shift = Modifiers {
+            shift: true,
+            ..Modifiers::default()
+        };
+        let mut order = Vec::new();
+        for _ in 0..4 {
+            focus.dispatch(&tree, &shortcuts, key("Tab", Modifiers::default()));
+            order.extend(focused(&focus, &tree));
+        }
+        focus.dispatch(&tree, &shortcuts, key("Tab", shift));
+        order.extend(focused(&focus, &tree));
+        let order: Vec<_> = order.iter().map(|id| &*id.0).collect();
+        assert_eq!(order, ["d", "b", "a", "d", "a"]);
+
+        let events = focus.focus_at(&tree, 45.0, 5.0);
+        assert_eq!(
+            summary(&events),
+            [(KeyEventKind::Blur, "a"), (KeyEventKind::Focus, "c")]
+        );
+        let events = focus.focus_at(&tree, 90.0, 5.0);
+        assert_eq!(summary(&events), [(KeyEventKind::Blur, "c")]);
+        assert_eq!(focus.focused(), None);
+    }
+
+    #[test]
+    fn test_focus_relayout() {
+        let mut focus = FocusManager::new();
+        focus.focus(&tree(fields()), Some(&ElementPath(vec![0, 2])));
+        assert_eq!(focus.focused(), Some(&ElementPath(vec![0, 2])));
+        let mut moved = fields();
+        moved.swap(0, 1);
+        let moved = tree(moved);
+        focus.relayout(&moved);
+        assert_eq!(focus.focused(), Some(&ElementPath(vec![0, 1])));
+        focus.relayout(&tree(Vec::new()));
+        assert_eq!(focus.focused(), None);
+    }
+
+    #[test]
+    fn test_key_routing() {
+        let tree = tree(fields());
+        let mut shortcuts = ShortcutMap::new();
+        shortcuts.register("Ctrl+S".parse().expect("shortcut"), "save");
+        shortcuts.register("Space".parse().expect("shortcut"), "play");
+        let ctrl = Modifiers {
+            ctrl: true,
+            ..Modifiers::default()
+        };
+        let mut focus = FocusManager::new();
+
+        // The DOM reports the space bar as `" "`, which is what `Space` stands for.
+        for name in [" ", "Space"] {
+            let events = focus.dispatch(&tree, &shortcuts, key(name, Modifiers::default()));
+            assert_eq!(
+                summary(&events),
+                [(KeyEventKind::Shortcut(Arc::from("play")), "")]
+            );
+        }
+
+        focus.focus(&tree, Some(&ElementPath(vec![0, 1])));
+        let events = focus.dispatch(&tree, &shortcuts, key(" ", Modifiers::default()));
+        assert_eq!(events.len(), 1, "{events:?}");
+        assert_eq!(events[0].target, Some(ElementPath(vec![0])));
+        let events = focus.dispatch(&tree, &shortcuts, key("s", ctrl));
+        assert_eq!(events[1].kind, KeyEventKind::Shortcut(Arc::from("save")));
+        let events = focus.dispatch(&tree, &shortcuts, KeyInput::Text(Arc::from("x")));
+        assert_eq!(
+            summary(&events),
+            [(KeyEventKind::TextInput(Arc::from("x")), "a")]
+        );
+        assert!(shortcuts.unregister("save"));
+        assert_eq!(shortcuts.find("s", ctrl), None);
+    }
+}
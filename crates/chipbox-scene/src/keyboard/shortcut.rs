@@ -0,0 +1,185 @@
+use std::{str::FromStr, sync::Arc};
+
+/// Modifier keys held during a key event.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[allow(
+    clippy::struct_excessive_bools,
+    reason = "modifier keys are independent flags"
+)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub meta: bool,
+}
+
+impl Modifiers {
+    /// Whether any modifier other than shift is held,
+    /// meaning the key does not produce text.
+    #[must_use]
+    pub const fn is_command(self) -> bool {
+        self.ctrl || self.alt || self.meta
+    }
+}
+
+/// A key combination, such as `Ctrl+Shift+S`.
+///
+/// Keys use the names of the DOM `KeyboardEvent.key` property and match case-insensitively.
+/// `Space` names the space bar, which the DOM reports as `" "`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    pub modifiers: Modifiers,
+    key: Arc<str>,
+}
+
+impl Shortcut {
+    #[must_use]
+    pub fn new(modifiers: Modifiers, key: &str) -> Self {
+        Self {
+            modifiers,
+            key: Arc::from(normalize(key)),
+        }
+    }
+
+    /// The key, in lowercase, with `Space` as `" "`.
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Whether the key event triggers the shortcut.
+    #[must_use]
+    pub fn matches(&self, key: &str, modifiers: Modifiers) -> bool {
+        self.modifiers == modifiers && *self.key == normalize(key)
+    }
+}
+
+fn normalize(key: &str) -> String {
+    let key = key.to_lowercase();
+    if key == "space" { " ".to_owned() } else { key }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, miette::Diagnostic)]
+pub enum ShortcutParseError {
+    #[error("shortcut `{0}` has no key")]
+    #[diagnostic(
+        code(chipbox_scene::shortcut::key),
+        help("end the shortcut with a key, such as `Ctrl+S`")
+    )]
+    MissingKey(String),
+    #[error("unknown modifier `{0}`")]
+    #[diagnostic(
+        code(chipbox_scene::shortcut::modifier),
+        help("expected one of `Shift`, `Ctrl`, `Alt` or `Meta`")
+    )]
+    Modifier(String),
+}
+
+impl FromStr for Shortcut {
+    type Err = ShortcutParseError;
+
+    /// Parse modifiers and a key joined by `+`, such as `Ctrl+Shift+S` or `Ctrl++`.
+    /// Modifier names are case-insensitive, `Control`, `Option`, `Cmd` and `Super` are accepted as aliases.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        let (modifiers, key) = input.strip_suffix("++").map_or_else(
+            || input.rsplit_once('+').unwrap_or(("", input)),
+            |modifiers| (modifiers, "+"),
+        );
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(ShortcutParseError::MissingKey(input.to_owned()));
+        }
+        let mut parsed = Modifiers::default();
+        for modifier in modifiers
+            .split('+')
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+        {
+            let flag = match modifier.to_lowercase().as_str() {
+                "shift" => &mut parsed.shift,
+                "ctrl" | "control" => &mut parsed.ctrl,
+                "alt" | "option" => &mut parsed.alt,
+                "meta" | "cmd" | "super" => &mut parsed.meta,
+                _ => return Err(ShortcutParseError::Modifier(modifier.to_owned())),
+            };
+            *flag = true;
+        }
+        Ok(Self::new(parsed, key))
+    }
+}
+
+/// Global shortcuts, each identified by a name chosen by the registrant.
+#[derive(Clone, Debug, Default)]
+pub struct ShortcutMap {
+    shortcuts: Vec<(Shortcut, Arc<str>)>,
+}
+
+impl ShortcutMap {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bind a shortcut to a name, replacing any previous binding of the shortcut.
+    /// Returns the name the shortcut was previously bound to.
+    pub fn register(&mut self, shortcut: Shortcut, name: impl Into<Arc<str>>) -> Option<Arc<str>> {
+        let name = name.into();
+        if let Some((_, previous)) = self.shortcuts.iter_mut().find(|(s, _)| *s == shortcut) {
+            return Some(std::mem::replace(previous, name));
+        }
+        self.shortcuts.push((shortcut, name));
+        None
+    }
+
+    /// Remove every shortcut bound to the name.
+    /// Returns whether any shortcut was removed.
+    pub fn unregister(&mut self, name: &str) -> bool {
+        let len = self.shortcuts.len();
+        self.shortcuts.retain(|(_, n)| &**n != name);
+        self.shortcuts.len() != len
+    }
+
+    /// The name of the shortcut triggered by the key event, if any.
+    #[must_use]
+    pub fn find(&self, key: &str, modifiers: Modifiers) -> Option<&Arc<str>> {
+        self.shortcuts
+            .iter()
+            .find(|(shortcut, _)| shortcut.matches(key, modifiers))
+            .map(|(_, name)| name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_shortcut() {
+        let shortcut: Shortcut = "Ctrl+Shift+S".parse().expect("shortcut");
+        let modifiers = Modifiers {
+            shift: true,
+            ctrl: true,
+            ..Modifiers::default()
+        };
+        assert_eq!(shortcut, Shortcut::new(modifiers, "s"));
+        assert!(shortcut.matches("S", modifiers));
+        assert!(!shortcut.matches("S", Modifiers::default()));
+        let plus: Shortcut = "cmd++".parse().expect("plus");
+        assert_eq!(plus.key(), "+");
+        assert!(plus.modifiers.meta);
+        let space: Shortcut = "Space".parse().expect("space");
+        assert_eq!(space.key(), " ");
+        assert!(space.matches(" ", Modifiers::default()));
+        assert!(space.matches("space", Modifiers::default()));
+        assert!(Shortcut::new(Modifiers::default(), "Ä").matches("ä", Modifiers::default()));
+        assert_eq!(
+            "Ctrl+".parse::<Shortcut>(),
+            Err(ShortcutParseError::MissingKey("Ctrl+".into()))
+        );
+        assert_eq!(
+            "Hyper+A".parse::<Shortcut>(),
+            Err(ShortcutParseError::Modifier("Hyper".into()))
+        );
+    }
+}
@@ -1,25 +1,283 @@
+mod animation;
+mod bitmap;
+mod composite;
 mod element;
+#[cfg(test)]
+mod fixtures;
+mod keyboard;
+mod paint;
+mod pointer;
+mod selection;
+mod solver;
+mod text;
+mod theme;
+mod tree;
+mod validate;
+mod waveform;
 
-pub use self::element::{
-    ArrayElement, Axis, BehaviorElement, BoxElement, Color, ContentBoxElement, ContentElement,
-    ElementId, ElementNode, FlexElement, FlexItemElement, GridElement, LayoutElement, LayoutLength,
-    LinearDirection, MarginElement, SizedElement, TextElement, TextureElement,
+pub use self::{
+    animation::{Animator, FrameClock},
+    bitmap::{
+        DEFAULT_IMAGE_CACHE_BYTES, DecodedImage, ImageCache, ImageError, ImageQuad, PaintedImage,
+    },
+    composite::{Compositor, Effect, Layer, Pixmap, blur_kernel},
+    element::{
+        AlignElement, Alignment, AnimatedProperty, ArrayElement, Axis, BehaviorElement, Border,
+        BoxElement, BufferId, Color, ColorParseError, ColorParseErrorKind, ColorStop,
+        ContentBoxElement, ContentElement, CornerRadii, Easing, ElementId, ElementKind,
+        ElementNode, ElementPath, FillRule, FlexAlign, FlexElement, FlexItemElement, FlexJustify,
+        FocusableElement, GridElement, GridItemElement, GridTemplate, GridTrack,
+        GridTrackParseError, HorizontalAlign, ImageElement, ImageFit, ImageSource, LayoutElement,
+        LayoutLength, LengthExpr, LengthParseError, LengthParseErrorKind, LineCap, LineJoin,
+        LinearDirection, LinearRgba, MarginElement, Paint, PathData, PathElement, PathParseError,
+        PathParseErrorKind, PathSegment, PathStroke, RectElement, ScrollElement, Shadow,
+        SizedElement, SpanElement, StackingElement, TextChild, TextElement, TextOverflow,
+        TextureElement, TextureShader, Timing, TransformElement, TransitionElement, VerticalAlign,
+        ViewBox, ViewBoxFit, WaveformElement, color_from_str, length_from_str, path_from_str,
+    },
+    keyboard::{
+        FocusManager, KeyEvent, KeyEventKind, KeyInput, Modifiers, Shortcut, ShortcutMap,
+        ShortcutParseError,
+    },
+    paint::{
+        DropShadow, Fill, Gradient, GradientShape, GradientStop, PaintedPath, PaintedRect,
+        PathVerb, Stroke,
+    },
+    pointer::{PointerButton, PointerDispatcher, PointerEvent, PointerEventKind, PointerInput},
+    selection::{
+        SelectionEvent, SelectionEventKind, SelectionInput, SelectionMode, SelectionModel,
+        SelectionSet,
+    },
+    solver::{
+        Affine, DisplayMetrics, LayoutNode, LayoutTree, LengthContext, Point, Rect, ScrollState,
+        Size,
+    },
+    text::{
+        DEFAULT_LINE_HEIGHT, DEFAULT_TEXT_CACHE_LAYOUTS, FontData, FontId, Glyph, GlyphRun,
+        SpanStyle, TextLayout, TextLine, TextRun, TextStyle, TextSystem,
+    },
+    theme::{Theme, ThemeError},
+    tree::{NodeData, NodeId, Patch, SceneTree, TreeError, diff},
+    validate::{Location, SceneValidationError, ValidationError},
+    waveform::{PaintedWaveform, Peak, PeakCache, SampleBuffer, SampleBuffers, WaveformLane},
 };
 
-#[derive(specta::Type)]
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Scene {
     #[serde(default)]
     pub children: Vec<ElementNode>,
     pub px_width: u32,
     pub px_height: u32,
-    #[serde(default = "Scene::default_scale")]
-    pub scale: f32,
+    #[serde(default)]
+    pub metrics: DisplayMetrics,
 }
 
 impl Scene {
+    /// The size of the scene in device pixels.
     #[must_use]
-    pub const fn default_scale() -> f32 {
-        1.0
+    #[allow(
+        clippy::cast_precision_loss,
+        reason = "scene dimensions are far below 2^24"
+    )]
+    pub const fn px_size(&self) -> Size {
+        Size::new(self.px_width as f32, self.px_height as f32)
+    }
+
+    /// Check the scene for structural mistakes, such as duplicate ids
+    /// or behavior elements without a parent.
+    /// ## Errors
+    /// - [`SceneValidationError`] listing every violated invariant.
+    pub fn validate(&self) -> Result<(), SceneValidationError> {
+        validate::validate(self)
+    }
+
+    /// Resolve the position and size of every element in the scene,
+    /// measuring text and images with the given resources.
+    /// ## Errors
+    /// - [`ImageError`] if an image can not be read or decoded.
+    pub fn layout_with(&self, resources: Resources) -> Result<LayoutTree, ImageError> {
+        solver::solve(self, resources)
+    }
+}
+
+/// What content elements are measured and drawn from, other than the scene itself.
+///
+/// Elements needing a missing resource occupy no space and are not drawn.
+/// A scene should be drawn with the resources it was laid out with.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Resources<'a> {
+    pub text: Option<&'a TextSystem>,
+    pub images: Option<&'a ImageCache>,
+    pub buffers: Option<&'a SampleBuffers>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A scene in the shape produced by the TypeScript renderer.
+    const SCENE: &str = r#"{
+        "pxWidth": 1280,
+        "pxHeight": 720,
+        "metrics": { "dpi": 192, "devicePixelRatio": 2 },
+        "children": [
+            {
+                "type": "margin",
+                "id": "root",
+                "base": "8px",
+                "top": { "type": "sh", "value": 0.1 },
+                "children": [
+                    {
+                        "type": "flex",
+                        "axis": "y",
+                        "children": [
+                            { "type": "flex-item", "proportion": 2, "children": [] },
+                            {
+                                "type": "flex-item",
+                                "children": [
+                                    { "type": "pointer-enter" },
+                                    { "type": "texture", "opacity": 0.5, "shader": null },
+                                    {
+                                        "type": "text",
+                                        "children": ["Track 1"],
+                                        "font": "Inter",
+                                        "weight": 500,
+                                        "size": 14,
+                                        "color": { "type": "hsl", "h": 210, "s": 0.5, "l": 0.4 }
+                                    }
+                                ]
+                            }
+                        ]
+                    },
+                    {
+                        "type": "align",
+                        "origin": { "horizontal": "left", "vertical": "top" },
+                        "xOffset": "max(0.5pw, 300px)",
+                        "children": [
+                            {
+                                "type": "grid",
+                                "arrayLimit": 4,
+                                "arrayDirection": "backward",
+                                "columns": "48px 1fr",
+                                "children": [
+                                    {
+                                        "type": "grid-item",
+                                        "columnSpan": 2,
+                                        "children": [
+                                            { "type": "box", "width": 1, "height": "1in" }
+                                        ]
+                                    }
+                                ]
+                            },
+                            {
+                                "type": "array",
+                                "children": [
+                                    { "type": "box", "width": "24px", "height": "1ph" }
+                                ]
+                            },
+                            { "type": "content-box", "children": [] }
+                        ]
+                    }
+                ]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_scene_round_trip() {
+        let scene: Scene = serde_json::from_str(SCENE).expect("deserialize");
+        assert_eq!(
+            scene.metrics,
+            DisplayMetrics {
+                dpi: 192.0,
+                device_pixel_ratio: 2.0,
+                ..DisplayMetrics::default()
+            }
+        );
+        let ElementNode::Layout(LayoutElement::Margin(margin)) = &scene.children[0] else {
+            panic!("expected margin element");
+        };
+        assert_eq!(margin.id, Some(ElementId("root".into())));
+        assert_eq!(margin.base, Some(LayoutLength::Pixel(8.0)));
+        assert_eq!(margin.top, Some(LayoutLength::SceneHeight(0.1)));
+        assert_eq!(margin.children.len(), 2);
+        let json = serde_json::to_string(&scene).expect("serialize");
+        let round_trip: Scene = serde_json::from_str(&json).expect("deserialize serialized");
+        assert_eq!(round_trip, scene);
+    }
+
+    /// Scenes written against the props of the TypeScript elements.
+    const FIXTURES: &[(&str, &str)] = &[
+        (
+            "layout",
+            include_str!("../../../node/chipbox-frontend/fixtures/layout.json"),
+        ),
+        (
+            "content",
+            include_str!("../../../node/chipbox-frontend/fixtures/content.json"),
+        ),
+        (
+            "behavior",
+            include_str!("../../../node/chipbox-frontend/fixtures/behavior.json"),
+        ),
+    ];
+
+    /// Keys of `written` that are missing from `read` at the same place,
+    /// which serde skipped as unknown fields.
+    /// The tags of children typed as a single element kind are not serialized, so are not listed.
+    fn ignored_keys(
+        written: &serde_json::Value,
+        read: &serde_json::Value,
+        at: &str,
+    ) -> Vec<String> {
+        use serde_json::Value;
+
+        match (written, read) {
+            (Value::Object(written), Value::Object(read)) => written
+                .iter()
+                .filter(|(key, _)| *key != "type")
+                .flat_map(|(key, value)| {
+                    let at = format!("{at}.{key}");
+                    read.get(key)
+                        .map_or_else(|| vec![at.clone()], |read| ignored_keys(value, read, &at))
+                })
+                .collect(),
+            (Value::Array(written), Value::Array(read)) => written
+                .iter()
+                .zip(read)
+                .enumerate()
+                .flat_map(|(index, (written, read))| {
+                    ignored_keys(written, read, &format!("{at}[{index}]"))
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_frontend_fixtures() {
+        for (name, json) in FIXTURES {
+            let scene: Scene =
+                serde_json::from_str(json).unwrap_or_else(|err| panic!("{name}: {err}"));
+            let written: serde_json::Value = serde_json::from_str(json).expect("valid json");
+            let read = serde_json::to_value(&scene).expect("serialize");
+            assert_eq!(ignored_keys(&written, &read, name), Vec::<String>::new());
+            let round_trip: Scene = serde_json::from_value(read)
+                .unwrap_or_else(|err| panic!("{name} serialized: {err}"));
+            assert_eq!(round_trip, scene, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_scene_unknown_element() {
+        let err = serde_json::from_str::<Scene>(
+            r#"{ "pxWidth": 1, "pxHeight": 1, "children": [{ "type": "circle" }] }"#,
+        )
+        .expect_err("unknown element type");
+        assert!(
+            err.to_string().contains("did not match any variant"),
+            "{err}"
+        );
     }
 }
@@ -0,0 +1,485 @@
+//! Paint content resolved to device pixels.
+//!
+//! Colors are converted to premultiplied linear RGBA,
+//! which is also the space gradients are interpolated in.
+
+mod path;
+
+use std::f32::consts::TAU;
+
+pub use self::path::{PaintedPath, PathVerb};
+use crate::{
+    Border, ColorStop, LayoutLength, LengthContext, LineCap, LineJoin, LinearRgba, Paint,
+    PathStroke, Point, Rect, RectElement, Shadow,
+};
+
+/// A [`RectElement`] with every length resolved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaintedRect {
+    pub rect: Rect,
+    /// Corner radii clockwise from the top left,
+    /// scaled down together where adjacent corners would overlap.
+    pub radii: [f32; 4],
+    pub fill: Option<Fill>,
+    pub border: Option<Stroke>,
+    pub shadows: Vec<DropShadow>,
+}
+
+impl PaintedRect {
+    /// Resolve a rect element laid out at `rect`.
+    /// Lengths relative to the parent refer to the rect itself.
+    #[must_use]
+    pub fn of(element: &RectElement, rect: Rect, ctx: &LengthContext) -> Self {
+        let ctx = ctx.with_parent(rect.size());
+        let resolve = |length: &LayoutLength| length.resolve(&ctx);
+        let mut radii = element.radius.corners().map(|r| resolve(r).max(0.0));
+        let [top_left, top_right, bottom_right, bottom_left] = radii;
+        let scale = [
+            (rect.width, top_left + top_right),
+            (rect.height, top_right + bottom_right),
+            (rect.width, bottom_right + bottom_left),
+            (rect.height, bottom_left + top_left),
+        ]
+        .into_iter()
+        .filter(|(_, sum)| *sum > 0.0)
+        .map(|(side, sum)| side / sum)
+        .fold(1.0, f32::min);
+        for radius in &mut radii {
+            *radius *= scale;
+        }
+        Self {
+            rect,
+            radii,
+            fill: element
+                .fill
+                .as_ref()
+                .map(|paint| Fill::of(paint, rect, &ctx)),
+            border: element
+                .border
+                .as_ref()
+                .map(|border| Stroke::of(border, rect, &ctx)),
+            shadows: element
+                .shadows
+                .iter()
+                .map(|shadow| DropShadow::of(shadow, &ctx))
+                .collect(),
+        }
+    }
+}
+
+/// Resolved [`Paint`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Fill {
+    Solid(LinearRgba),
+    Gradient(Gradient),
+}
+
+impl Fill {
+    /// Resolve paint covering `rect`.
+    #[must_use]
+    pub fn of(paint: &Paint, rect: Rect, ctx: &LengthContext) -> Self {
+        let center = |x: &LayoutLength, y: &LayoutLength| {
+            Point::new(rect.x + x.resolve(ctx), rect.y + y.resolve(ctx))
+        };
+        let shape = match paint {
+            Paint::Solid { color } => return Self::Solid(color.to_premultiplied()),
+            Paint::Linear { angle, .. } => {
+                // Directions are clockwise from up, with y pointing down.
+                let (sin, cos) = angle.to_radians().sin_cos();
+                let half = (rect.width * sin)
+                    .abs()
+                    .mul_add(0.5, (rect.height * cos).abs() * 0.5);
+                let middle = Point::new(
+                    rect.width.mul_add(0.5, rect.x),
+                    rect.height.mul_add(0.5, rect.y),
+                );
+                GradientShape::Linear {
+                    start: Point::new(sin.mul_add(-half, middle.x), cos.mul_add(half, middle.y)),
+                    end: Point::new(sin.mul_add(half, middle.x), cos.mul_add(-half, middle.y)),
+                }
+            }
+            Paint::Radial { x, y, radius, .. } => {
+                let center = center(x, y);
+                let radius = radius.as_ref().map_or_else(
+                    || {
+                        let dx = (center.x - rect.x)
+                            .abs()
+                            .max((rect.right() - center.x).abs());
+                        let dy = (center.y - rect.y)
+                            .abs()
+                            .max((rect.bottom() - center.y).abs());
+                        dx.hypot(dy)
+                    },
+                    |radius| radius.resolve(ctx),
+                );
+                GradientShape::Radial { center, radius }
+            }
+            Paint::Sweep { x, y, angle, .. } => GradientShape::Sweep {
+                center: center(x, y),
+                angle: angle.to_radians(),
+            },
+        };
+        Self::Gradient(Gradient {
+            shape,
+            stops: resolve_stops(paint.stops()),
+        })
+    }
+
+    /// The color at a point.
+    #[must_use]
+    pub fn color_at(&self, point: Point) -> LinearRgba {
+        match self {
+            Self::Solid(color) => *color,
+            Self::Gradient(gradient) => gradient.color_at(point),
+        }
+    }
+}
+
+/// A gradient in device pixels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    pub shape: GradientShape,
+    /// Stops with ascending offsets.
+    pub stops: Vec<GradientStop>,
+}
+
+/// How points map to positions along a gradient.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GradientShape {
+    Linear {
+        start: Point,
+        end: Point,
+    },
+    Radial {
+        center: Point,
+        radius: f32,
+    },
+    /// `angle` is in radians, clockwise from pointing up.
+    Sweep {
+        center: Point,
+        angle: f32,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GradientStop {
+    pub offset: f32,
+    pub color: LinearRgba,
+}
+
+impl Gradient {
+    /// Position of a point along the gradient, unclamped.
+    #[must_use]
+    pub fn offset_at(&self, point: Point) -> f32 {
+        match self.shape {
+            GradientShape::Linear { start, end } => {
+                let (dx, dy) = (end.x - start.x, end.y - start.y);
+                let length = dx.mul_add(dx, dy * dy);
+                if length <= 0.0 {
+                    return 0.0;
+                }
+                (point.x - start.x).mul_add(dx, (point.y - start.y) * dy) / length
+            }
+            GradientShape::Radial { center, radius } => {
+                let distance = (point.x - center.x).hypot(point.y - center.y);
+                if radius <= 0.0 {
+                    1.0
+                } else {
+                    distance / radius
+                }
+            }
+            GradientShape::Sweep { center, angle } => {
+                let turn = (point.x - center.x).atan2(center.y - point.y) - angle;
+                turn.rem_euclid(TAU) / TAU
+            }
+        }
+    }
+
+    /// The color at a position along the gradient.
+    /// Positions before the first and after the last stop take the color of that stop.
+    #[must_use]
+    pub fn color_at_offset(&self, offset: f32) -> LinearRgba {
+        let index = self.stops.partition_point(|stop| stop.offset <= offset);
+        match (index.checked_sub(1), self.stops.get(index)) {
+            (Some(i), Some(next)) => {
+                let previous = self.stops[i];
+                let span = next.offset - previous.offset;
+                let t = (offset - previous.offset) / span;
+                let mix = |a: f32, b: f32| (b - a).mul_add(t, a);
+                LinearRgba {
+                    r: mix(previous.color.r, next.color.r),
+                    g: mix(previous.color.g, next.color.g),
+                    b: mix(previous.color.b, next.color.b),
+                    a: mix(previous.color.a, next.color.a),
+                }
+            }
+            (Some(i), None) => self.stops[i].color,
+            (None, Some(first)) => first.color,
+            (None, None) => LinearRgba::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn color_at(&self, point: Point) -> LinearRgba {
+        self.color_at_offset(self.offset_at(point))
+    }
+}
+
+/// Fill in missing offsets and keep them ascending, as CSS does.
+fn resolve_stops(stops: &[ColorStop]) -> Vec<GradientStop> {
+    let last = stops.len().saturating_sub(1);
+    let mut offsets: Vec<Option<f32>> = stops
+        .iter()
+        .enumerate()
+        .map(|(i, stop)| match stop.offset {
+            None if i == 0 => Some(0.0),
+            None if i == last => Some(1.0),
+            offset => offset,
+        })
+        .collect();
+    // Offsets never go back, a stop before a previous one moves up to it.
+    let mut max = f32::NEG_INFINITY;
+    for offset in offsets.iter_mut().flatten() {
+        max = max.max(*offset);
+        *offset = max;
+    }
+    // Spread runs of missing offsets evenly between the known neighbors.
+    let mut i = 0;
+    while i < offsets.len() {
+        if offsets[i].is_some() {
+            i += 1;
+            continue;
+        }
+        let start = i - 1;
+        let end = (i..offsets.len())
+            .find(|j| offsets[*j].is_some())
+            .expect("the last offset is known");
+        let (from, to) = (offsets[start].unwrap_or(0.0), offsets[end].unwrap_or(1.0));
+        #[allow(clippy::cast_precision_loss, reason = "stop counts are small")]
+        let step = (to - from) / (end - start) as f32;
+        for (k, offset) in offsets[i..end].iter_mut().enumerate() {
+            #[allow(clippy::cast_precision_loss, reason = "stop counts are small")]
+            let k = (k + 1) as f32;
+            *offset = Some(step.mul_add(k, from));
+        }
+        i = end;
+    }
+    stops
+        .iter()
+        .zip(offsets)
+        .map(|(stop, offset)| GradientStop {
+            offset: offset.unwrap_or_default(),
+            color: stop.color.to_premultiplied(),
+        })
+        .collect()
+}
+
+/// Resolved [`Border`] or [`PathStroke`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stroke {
+    pub width: f32,
+    pub fill: Fill,
+    /// Alternating lengths of dashes and gaps, solid if empty.
+    pub dash: Vec<f32>,
+    pub dash_offset: f32,
+    pub cap: LineCap,
+    pub join: LineJoin,
+    pub miter_limit: f32,
+}
+
+impl Stroke {
+    /// Borders have square corners where they are not rounded.
+    #[must_use]
+    pub fn of(border: &Border, rect: Rect, ctx: &LengthContext) -> Self {
+        Self {
+            width: border.width.resolve(ctx).max(0.0),
+            fill: Fill::of(&border.paint, rect, ctx),
+            dash: resolve_dash(&border.dash, ctx),
+            dash_offset: border.dash_offset.resolve(ctx),
+            cap: LineCap::Butt,
+            join: LineJoin::Miter,
+            miter_limit: PathStroke::default_miter_limit(),
+        }
+    }
+
+    #[must_use]
+    pub fn of_path(stroke: &PathStroke, rect: Rect, ctx: &LengthContext) -> Self {
+        Self {
+            width: stroke.width.resolve(ctx).max(0.0),
+            fill: Fill::of(&stroke.paint, rect, ctx),
+            dash: resolve_dash(&stroke.dash, ctx),
+            dash_offset: stroke.dash_offset.resolve(ctx),
+            cap: stroke.cap,
+            join: stroke.join,
+            miter_limit: stroke.miter_limit.max(1.0),
+        }
+    }
+}
+
+fn resolve_dash(dash: &[LayoutLength], ctx: &LengthContext) -> Vec<f32> {
+    let mut dash: Vec<f32> = dash
+        .iter()
+        .map(|length| length.resolve(ctx).max(0.0))
+        .collect();
+    // A pattern without any length would never advance.
+    if dash.iter().sum::<f32>() <= 0.0 {
+        dash.clear();
+    }
+    dash
+}
+
+/// Resolved [`Shadow`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DropShadow {
+    pub offset: Point,
+    pub blur: f32,
+    pub spread: f32,
+    pub color: LinearRgba,
+}
+
+impl DropShadow {
+    #[must_use]
+    pub fn of(shadow: &Shadow, ctx: &LengthContext) -> Self {
+        Self {
+            offset: Point::new(shadow.x.resolve(ctx), shadow.y.resolve(ctx)),
+            blur: shadow.blur.resolve(ctx).max(0.0),
+            spread: shadow.spread.resolve(ctx),
+            color: shadow.color.to_premultiplied(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, CornerRadii, DisplayMetrics, Size};
+
+    fn ctx() -> LengthContext<'static> {
+        let size = Size::new(200.0, 200.0);
+        LengthContext {
+            scene: size,
+            parent: size,
+            metrics: DisplayMetrics::default(),
+            text: None,
+            images: None,
+        }
+    }
+
+    fn stops(offsets: &[Option<f32>]) -> Vec<ColorStop> {
+        offsets
+            .iter()
+            .map(|offset| ColorStop {
+                offset: *offset,
+                color: Color::WHITE,
+            })
+            .collect()
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-3, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn test_gradient_stops() {
+        let offsets = |input: &[Option<f32>]| -> Vec<f32> {
+            resolve_stops(&stops(input))
+                .iter()
+                .map(|stop| stop.offset)
+                .collect()
+        };
+        assert_eq!(offsets(&[None, None, None]), [0.0, 0.5, 1.0]);
+        assert_eq!(
+            offsets(&[Some(0.2), None, None, Some(0.8), None]),
+            [0.2, 0.4, 0.6, 0.8, 1.0]
+        );
+        assert_eq!(offsets(&[Some(0.5), Some(0.25), None]), [0.5, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn test_gradient_colors() {
+        let rect = Rect::new(10.0, 10.0, 100.0, 50.0);
+        let paint = Paint::Linear {
+            angle: 90.0,
+            stops: vec![
+                ColorStop {
+                    offset: None,
+                    color: Color::BLACK,
+                },
+                ColorStop {
+                    offset: None,
+                    color: Color::WHITE,
+                },
+            ],
+        };
+        let Fill::Gradient(gradient) = Fill::of(&paint, rect, &ctx()) else {
+            panic!("expected gradient");
+        };
+        let GradientShape::Linear { start, end } = gradient.shape else {
+            panic!("expected linear gradient");
+        };
+        assert_close(start.x, 10.0);
+        assert_close(start.y, 35.0);
+        assert_close(end.x, 110.0);
+        assert_close(gradient.color_at(Point::new(60.0, 0.0)).r, 0.5);
+        assert_close(gradient.color_at(Point::new(0.0, 0.0)).r, 0.0);
+        assert_close(gradient.color_at(Point::new(200.0, 0.0)).r, 1.0);
+
+        // Sweep gradients start pointing up and turn clockwise.
+        let sweep = Gradient {
+            shape: GradientShape::Sweep {
+                center: Point::new(0.0, 0.0),
+                angle: 0.0,
+            },
+            stops: Vec::new(),
+        };
+        assert_close(sweep.offset_at(Point::new(0.0, -1.0)), 0.0);
+        assert_close(sweep.offset_at(Point::new(1.0, 0.0)), 0.25);
+        assert_close(sweep.offset_at(Point::new(-1.0, 0.0)), 0.75);
+
+        // Radial gradients reach the farthest corner by default.
+        let radial = Paint::Radial {
+            x: LayoutLength::Pixel(0.0),
+            y: LayoutLength::Pixel(0.0),
+            radius: None,
+            stops: stops(&[None, None]),
+        };
+        let Fill::Gradient(gradient) = Fill::of(&radial, rect, &ctx()) else {
+            panic!("expected gradient");
+        };
+        assert_close(gradient.offset_at(Point::new(110.0, 60.0)), 1.0);
+    }
+
+    #[test]
+    fn test_painted_rect() {
+        let element = RectElement {
+            fill: Some(Paint::from(Color::rgb(255, 0, 0).with_alpha(0.5))),
+            radius: CornerRadii {
+                top_left: LayoutLength::ParentHeight(1.0),
+                ..CornerRadii::uniform(&LayoutLength::Pixel(10.0))
+            },
+            border: Some(Border {
+                width: LayoutLength::Pixel(2.0),
+                paint: Paint::from(Color::BLACK),
+                dash: vec![LayoutLength::Pixel(0.0)],
+                dash_offset: LayoutLength::Pixel(1.0),
+            }),
+            ..RectElement::default()
+        };
+        let painted = PaintedRect::of(&element, Rect::new(0.0, 0.0, 100.0, 40.0), &ctx());
+        // The left side is too short for both radii.
+        assert_close(painted.radii[0], 40.0 * 0.8);
+        assert_close(painted.radii[3], 10.0 * 0.8);
+        assert_eq!(
+            painted.fill,
+            Some(Fill::Solid(LinearRgba {
+                r: 0.5,
+                g: 0.0,
+                b: 0.0,
+                a: 0.5
+            }))
+        );
+        let border = painted.border.expect("border");
+        assert!(border.dash.is_empty());
+        assert_close(border.width, 2.0);
+    }
+}
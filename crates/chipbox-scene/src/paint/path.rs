@@ -0,0 +1,362 @@
+use std::f32::consts::FRAC_PI_2;
+
+use super::{Fill, Stroke};
+use crate::{FillRule, LengthContext, PathElement, PathSegment, Point, Rect, ViewBoxFit};
+
+/// A [`PathElement`] mapped onto device pixels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaintedPath {
+    pub rect: Rect,
+    /// Every subpath starts with [`PathVerb::MoveTo`].
+    pub verbs: Vec<PathVerb>,
+    pub fill: Option<Fill>,
+    pub fill_rule: FillRule,
+    pub stroke: Option<Stroke>,
+}
+
+/// A path segment in device pixels.
+/// Arcs are approximated by cubic curves, which survive non-uniform scaling.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathVerb {
+    MoveTo(Point),
+    LineTo(Point),
+    QuadTo(Point, Point),
+    CubicTo(Point, Point, Point),
+    Close,
+}
+
+impl PaintedPath {
+    /// Resolve a path element laid out at `rect`, mapping its view box onto the rect.
+    /// Lengths relative to the parent refer to the rect itself.
+    #[must_use]
+    pub fn of(element: &PathElement, rect: Rect, ctx: &LengthContext) -> Self {
+        let ctx = ctx.with_parent(rect.size());
+        let view = element.view_box;
+        let (mut scale_x, mut scale_y) = (rect.width / view.width, rect.height / view.height);
+        let (mut left, mut top) = (rect.x, rect.y);
+        if element.fit == ViewBoxFit::Contain {
+            let scale = scale_x.min(scale_y);
+            left += view.width.mul_add(-scale, rect.width) / 2.0;
+            top += view.height.mul_add(-scale, rect.height) / 2.0;
+            (scale_x, scale_y) = (scale, scale);
+        }
+        let map = |(x, y): (f32, f32)| {
+            Point::new(
+                (x - view.x).mul_add(scale_x, left),
+                (y - view.y).mul_add(scale_y, top),
+            )
+        };
+        // An empty view box has no meaningful mapping, validation reports it.
+        let verbs = if scale_x.is_finite() && scale_y.is_finite() {
+            verbs(&element.data.0, map)
+        } else {
+            Vec::new()
+        };
+        Self {
+            rect,
+            verbs,
+            fill: element
+                .fill
+                .as_ref()
+                .map(|paint| Fill::of(paint, rect, &ctx)),
+            fill_rule: element.fill_rule,
+            stroke: element
+                .stroke
+                .as_ref()
+                .map(|stroke| Stroke::of_path(stroke, rect, &ctx)),
+        }
+    }
+}
+
+/// Convert segments to verbs, mapping every point with `map`.
+fn verbs(segments: &[PathSegment], map: impl Fn((f32, f32)) -> Point) -> Vec<PathVerb> {
+    let mut verbs = Vec::with_capacity(segments.len());
+    let mut current = (0.0, 0.0);
+    let mut subpath = (0.0, 0.0);
+    let mut open = false;
+    for segment in segments {
+        // Drawing after a close continues from the start of the closed subpath.
+        if !open && !matches!(segment, PathSegment::MoveTo { .. } | PathSegment::Close) {
+            verbs.push(PathVerb::MoveTo(map(current)));
+            subpath = current;
+        }
+        open = true;
+        match *segment {
+            PathSegment::MoveTo { x, y } => {
+                verbs.push(PathVerb::MoveTo(map((x, y))));
+                subpath = (x, y);
+            }
+            PathSegment::LineTo { x, y } => verbs.push(PathVerb::LineTo(map((x, y)))),
+            PathSegment::QuadTo { x1, y1, x, y } => {
+                verbs.push(PathVerb::QuadTo(map((x1, y1)), map((x, y))));
+            }
+            PathSegment::CubicTo {
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+            } => verbs.push(PathVerb::CubicTo(map((x1, y1)), map((x2, y2)), map((x, y)))),
+            PathSegment::ArcTo {
+                rx,
+                ry,
+                rotation,
+                large_arc,
+                sweep,
+                x,
+                y,
+            } => {
+                let arc = Arc {
+                    from: current,
+                    to: (x, y),
+                    radii: (rx.abs(), ry.abs()),
+                    rotation: rotation.to_radians(),
+                    large: large_arc,
+                    sweep,
+                };
+                arc.to_cubics(|c1, c2, end| {
+                    verbs.push(PathVerb::CubicTo(map(c1), map(c2), map(end)));
+                });
+            }
+            PathSegment::Close => {
+                verbs.push(PathVerb::Close);
+                current = subpath;
+                open = false;
+            }
+        }
+        current = segment.end().unwrap_or(current);
+    }
+    verbs
+}
+
+/// An elliptical arc in endpoint form, as written in path data.
+struct Arc {
+    from: (f32, f32),
+    to: (f32, f32),
+    radii: (f32, f32),
+    /// Rotation of the ellipse in radians.
+    rotation: f32,
+    large: bool,
+    sweep: bool,
+}
+
+impl Arc {
+    /// Approximate the arc by cubic curves spanning at most a quarter turn each,
+    /// following the SVG implementation notes for converting to center form.
+    fn to_cubics(&self, mut cubic: impl FnMut((f32, f32), (f32, f32), (f32, f32))) {
+        let ((x1, y1), (x2, y2)) = (self.from, self.to);
+        if self.from == self.to {
+            return;
+        }
+        let (mut rx, mut ry) = self.radii;
+        if rx == 0.0 || ry == 0.0 {
+            cubic(self.from, self.to, self.to);
+            return;
+        }
+        let (sin, cos) = self.rotation.sin_cos();
+        // The start point in a frame centered between the end points, aligned with the ellipse.
+        let (mx, my) = ((x1 - x2) / 2.0, (y1 - y2) / 2.0);
+        let px = cos.mul_add(mx, sin * my);
+        let py = (-sin).mul_add(mx, cos * my);
+        // Radii too small to span the end points are scaled up uniformly.
+        let scale = (px / rx).hypot(py / ry).powi(2);
+        if scale > 1.0 {
+            rx *= scale.sqrt();
+            ry *= scale.sqrt();
+        }
+        let (rx2, ry2, px2, py2) = (rx * rx, ry * ry, px * px, py * py);
+        let denominator = rx2.mul_add(py2, ry2 * px2);
+        let numerator = rx2.mul_add(ry2, -denominator).max(0.0);
+        let mut coefficient = (numerator / denominator).sqrt();
+        if self.large == self.sweep {
+            coefficient = -coefficient;
+        }
+        let (ccx, ccy) = (coefficient * rx * py / ry, -coefficient * ry * px / rx);
+        let center = (
+            cos.mul_add(ccx, -sin * ccy) + f32::midpoint(x1, x2),
+            sin.mul_add(ccx, cos * ccy) + f32::midpoint(y1, y2),
+        );
+        let angle = |ux: f32, uy: f32, vx: f32, vy: f32| {
+            ux.mul_add(vy, -uy * vx).atan2(ux.mul_add(vx, uy * vy))
+        };
+        let (ux, uy) = ((px - ccx) / rx, (py - ccy) / ry);
+        let (vx, vy) = ((-px - ccx) / rx, (-py - ccy) / ry);
+        let start = angle(1.0, 0.0, ux, uy);
+        let mut delta = angle(ux, uy, vx, vy);
+        if self.sweep && delta < 0.0 {
+            delta += std::f32::consts::TAU;
+        } else if !self.sweep && delta > 0.0 {
+            delta -= std::f32::consts::TAU;
+        }
+
+        let point = |(x, y): (f32, f32)| {
+            let (x, y) = (x * rx, y * ry);
+            (
+                cos.mul_add(x, -sin * y) + center.0,
+                sin.mul_add(x, cos * y) + center.1,
+            )
+        };
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            reason = "an arc spans at most a full turn"
+        )]
+        let count = (delta.abs() / FRAC_PI_2 - 1e-3).ceil().max(1.0) as usize;
+        #[allow(clippy::cast_precision_loss, reason = "at most four segments")]
+        let step = delta / count as f32;
+        // Distance of the control points along the tangents of a unit circle.
+        let handle = 4.0 / 3.0 * (step / 4.0).tan();
+        for i in 0..count {
+            #[allow(clippy::cast_precision_loss, reason = "at most four segments")]
+            let (sin0, cos0) = step.mul_add(i as f32, start).sin_cos();
+            #[allow(clippy::cast_precision_loss, reason = "at most four segments")]
+            let (sin1, cos1) = step.mul_add((i + 1) as f32, start).sin_cos();
+            let end = if i + 1 == count {
+                self.to
+            } else {
+                point((cos1, sin1))
+            };
+            cubic(
+                point((handle.mul_add(-sin0, cos0), handle.mul_add(cos0, sin0))),
+                point((handle.mul_add(sin1, cos1), handle.mul_add(-cos1, sin1))),
+                end,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, DisplayMetrics, Paint, PathData, Size, ViewBox};
+
+    fn ctx() -> LengthContext<'static> {
+        let size = Size::new(200.0, 200.0);
+        LengthContext {
+            scene: size,
+            parent: size,
+            metrics: DisplayMetrics::default(),
+            text: None,
+            images: None,
+        }
+    }
+
+    fn path(data: &str, view_box: ViewBox, fit: ViewBoxFit) -> PathElement {
+        PathElement {
+            data: data.parse::<PathData>().expect("valid path data"),
+            view_box,
+            fit,
+            fill: Some(Paint::from(Color::WHITE)),
+            ..PathElement::default()
+        }
+    }
+
+    fn assert_close(actual: Point, expected: Point) {
+        assert!(
+            (actual.x - expected.x).abs() < 1e-3 && (actual.y - expected.y).abs() < 1e-3,
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    fn end(verb: PathVerb) -> Point {
+        match verb {
+            PathVerb::MoveTo(point)
+            | PathVerb::LineTo(point)
+            | PathVerb::QuadTo(_, point)
+            | PathVerb::CubicTo(_, _, point) => point,
+            PathVerb::Close => panic!("close has no end point"),
+        }
+    }
+
+    #[test]
+    fn test_view_box_mapping() {
+        let rect = Rect::new(10.0, 20.0, 100.0, 50.0);
+        let stretched = PaintedPath::of(
+            &path("M0 0 L1 1", ViewBox::default(), ViewBoxFit::Stretch),
+            rect,
+            &ctx(),
+        );
+        assert_eq!(
+            stretched.verbs,
+            [
+                PathVerb::MoveTo(Point::new(10.0, 20.0)),
+                PathVerb::LineTo(Point::new(110.0, 70.0)),
+            ]
+        );
+
+        // A square view box fits the height of a wide rect and is centered horizontally.
+        let view_box = ViewBox {
+            x: -12.0,
+            y: -12.0,
+            width: 24.0,
+            height: 24.0,
+        };
+        let contained = PaintedPath::of(
+            &path("M-12 -12 L12 12", view_box, ViewBoxFit::Contain),
+            rect,
+            &ctx(),
+        );
+        assert_close(end(contained.verbs[0]), Point::new(35.0, 20.0));
+        assert_close(end(contained.verbs[1]), Point::new(85.0, 70.0));
+
+        // Drawing after a close starts a new subpath at the start of the closed one.
+        let reopened = PaintedPath::of(
+            &path("M0 0 L1 0 Z L0 1", ViewBox::default(), ViewBoxFit::Stretch),
+            Rect::new(0.0, 0.0, 10.0, 10.0),
+            &ctx(),
+        );
+        assert_eq!(
+            reopened.verbs[2..],
+            [
+                PathVerb::Close,
+                PathVerb::MoveTo(Point::new(0.0, 0.0)),
+                PathVerb::LineTo(Point::new(0.0, 10.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_arc_to_cubics() {
+        let rect = Rect::new(0.0, 0.0, 4.0, 4.0);
+        let view_box = ViewBox {
+            x: 0.0,
+            y: 0.0,
+            width: 4.0,
+            height: 4.0,
+        };
+        // A clockwise half circle from the left to the right through the top.
+        let half = PaintedPath::of(
+            &path("M0 2 A2 2 0 0 1 4 2", view_box, ViewBoxFit::Stretch),
+            rect,
+            &ctx(),
+        );
+        assert_eq!(half.verbs.len(), 3);
+        assert_close(end(half.verbs[1]), Point::new(2.0, 0.0));
+        assert_close(end(half.verbs[2]), Point::new(4.0, 2.0));
+        let handle = 4.0 / 3.0 * (2.0f32.sqrt() - 1.0);
+        let PathVerb::CubicTo(c1, _, _) = half.verbs[1] else {
+            panic!("expected cubic");
+        };
+        assert_close(c1, Point::new(0.0, 2.0f32.mul_add(-handle, 2.0)));
+
+        // Radii too small to reach the end point are scaled up, the other sweep goes through the bottom.
+        let scaled = PaintedPath::of(
+            &path("M0 2 A1 1 0 0 0 4 2", view_box, ViewBoxFit::Stretch),
+            rect,
+            &ctx(),
+        );
+        assert_close(end(scaled.verbs[1]), Point::new(2.0, 4.0));
+
+        // The large arc takes the long way around the other circle through both end points.
+        let large = PaintedPath::of(
+            &path("M2 0 A2 2 0 1 1 4 2", view_box, ViewBoxFit::Stretch),
+            rect,
+            &ctx(),
+        );
+        assert_eq!(large.verbs.len(), 4);
+        assert_close(end(large.verbs[3]), Point::new(4.0, 2.0));
+        assert_close(end(large.verbs[1]), Point::new(4.0, -2.0));
+        assert_close(end(large.verbs[2]), Point::new(6.0, 0.0));
+    }
+}
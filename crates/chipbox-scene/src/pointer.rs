@@ -0,0 +1,327 @@
+mod hit;
+
+use crate::{ElementId, ElementKind, ElementPath, LayoutNode, LayoutTree};
+
+/// Pointer input in scene pixel space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PointerInput {
+    Move {
+        x: f32,
+        y: f32,
+    },
+    Down {
+        x: f32,
+        y: f32,
+        button: PointerButton,
+    },
+    Up {
+        x: f32,
+        y: f32,
+        button: PointerButton,
+    },
+    /// The pointer left the scene.
+    Exit,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PointerButton {
+    Primary,
+    Secondary,
+    Middle,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointerEventKind {
+    /// Fired for elements with a [`ElementKind::PointerEnter`] behavior
+    /// once the pointer moves over them or one of their descendants.
+    Enter,
+    /// Fired for elements with a [`ElementKind::PointerLeave`] behavior
+    /// once the pointer is no longer over them or any of their descendants.
+    Leave,
+    /// Fired for hovered elements with a [`ElementKind::PointerHoverMove`] behavior
+    /// whenever the pointer moves.
+    Move,
+    /// Fired for the innermost element with a [`ElementKind::PointerAction`] behavior
+    /// when a button is pressed and released over it.
+    Action(PointerButton),
+}
+
+/// An event routed to the element owning the behavior that fired it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PointerEvent {
+    pub kind: PointerEventKind,
+    pub target: ElementPath,
+    pub id: Option<ElementId>,
+    /// Pointer position in scene pixel space.
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Identity of an element across frames.
+/// Elements with an id are tracked by id, others by their path.
+#[derive(Clone, Debug, PartialEq)]
+struct Target {
+    path: ElementPath,
+    id: Option<ElementId>,
+}
+
+impl Target {
+    fn is(&self, other: &Self) -> bool {
+        match (&self.id, &other.id) {
+            (Some(a), Some(b)) => a == b,
+            (None, None) => self.path == other.path,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Hovered {
+    target: Target,
+    /// Whether the element had a leave behavior when it was entered.
+    leave: bool,
+}
+
+/// Turns pointer input into [`PointerEvent`]s for the behavior elements of a scene.
+///
+/// Hover state is kept across frames, so the same dispatcher should be
+/// fed every input and every new layout of a scene.
+#[derive(Clone, Debug, Default)]
+pub struct PointerDispatcher {
+    position: Option<(f32, f32)>,
+    /// Hovered elements with enter or leave behaviors, outermost first.
+    hovered: Vec<Hovered>,
+    /// Action targets of the currently pressed buttons.
+    pressed: Vec<(PointerButton, Target)>,
+}
+
+impl PointerDispatcher {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply pointer input to the given layout and return the resulting events.
+    ///
+    /// Leave events are ordered innermost first and precede enter events,
+    /// which are ordered outermost first.
+    pub fn dispatch(&mut self, tree: &LayoutTree, input: PointerInput) -> Vec<PointerEvent> {
+        let mut events = Vec::new();
+        match input {
+            PointerInput::Move { x, y } => {
+                self.position = Some((x, y));
+                let chain = self.hover(tree, &mut events);
+                events.extend(
+                    chain
+                        .iter()
+                        .rev()
+                        .filter(|(_, node)| node.has_behavior(ElementKind::PointerHoverMove))
+                        .map(|(target, _)| event(PointerEventKind::Move, target, x, y)),
+                );
+            }
+            PointerInput::Down { x, y, button } => {
+                self.position = Some((x, y));
+                let chain = self.hover(tree, &mut events);
+                self.pressed.retain(|(pressed, _)| *pressed != button);
+                if let Some(target) = action_target(&chain) {
+                    self.pressed.push((button, target.clone()));
+                }
+            }
+            PointerInput::Up { x, y, button } => {
+                self.position = Some((x, y));
+                let chain = self.hover(tree, &mut events);
+                let pressed = self
+                    .pressed
+                    .iter()
+                    .position(|(pressed, _)| *pressed == button)
+                    .map(|index| self.pressed.remove(index).1);
+                if let Some(target) = action_target(&chain)
+                    && pressed.is_some_and(|pressed| pressed.is(target))
+                {
+                    events.push(event(PointerEventKind::Action(button), target, x, y));
+                }
+            }
+            PointerInput::Exit => {
+                self.hover(&LayoutTree::default(), &mut events);
+                self.position = None;
+                self.pressed.clear();
+            }
+        }
+        events
+    }
+
+    /// Re-evaluate the hover state against a new layout without pointer input,
+    /// so that elements moving under a resting pointer are entered and left.
+    pub fn relayout(&mut self, tree: &LayoutTree) -> Vec<PointerEvent> {
+        let mut events = Vec::new();
+        self.hover(tree, &mut events);
+        events
+    }
+
+    /// Update the hovered elements and return every element under the pointer, outermost first.
+    fn hover<'a>(
+        &mut self,
+        tree: &'a LayoutTree,
+        events: &mut Vec<PointerEvent>,
+    ) -> Vec<(Target, &'a LayoutNode)> {
+        let Some((x, y)) = self.position else {
+            return Vec::new();
+        };
+        let chain: Vec<_> = tree
+            .hit_test(x, y)
+            .map(|path| {
+                path.ancestors(tree)
+                    .into_iter()
+                    .map(|(path, node)| {
+                        let id = node.id.clone();
+                        (Target { path, id }, node)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let hovered = chain
+            .iter()
+            .filter(|(_, node)| {
+                node.has_behavior(ElementKind::PointerEnter)
+                    || node.has_behavior(ElementKind::PointerLeave)
+            })
+            .map(|(target, node)| Hovered {
+                target: target.clone(),
+                leave: node.has_behavior(ElementKind::PointerLeave),
+            })
+            .collect::<Vec<_>>();
+        events.extend(
+            self.hovered
+                .iter()
+                .rev()
+                .filter(|old| old.leave && !hovered.iter().any(|new| new.target.is(&old.target)))
+                .map(|old| event(PointerEventKind::Leave, &old.target, x, y)),
+        );
+        events.extend(
+            chain
+                .iter()
+                .filter(|(target, node)| {
+                    node.has_behavior(ElementKind::PointerEnter)
+                        && !self.hovered.iter().any(|old| old.target.is(target))
+                })
+                .map(|(target, _)| event(PointerEventKind::Enter, target, x, y)),
+        );
+        self.hovered = hovered;
+        chain
+    }
+}
+
+fn action_target<'a>(chain: &'a [(Target, &LayoutNode)]) -> Option<&'a Target> {
+    chain
+        .iter()
+        .rev()
+        .find(|(_, node)| node.has_behavior(ElementKind::PointerAction))
+        .map(|(target, _)| target)
+}
+
+fn event(kind: PointerEventKind, target: &Target, x: f32, y: f32) -> PointerEvent {
+    PointerEvent {
+        kind,
+        target: target.path.clone(),
+        id: target.id.clone(),
+        x,
+        y,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Rect,
+        fixtures::{element, summary},
+    };
+
+    const HOVER: &[ElementKind] = &[
+        ElementKind::PointerEnter,
+        ElementKind::PointerLeave,
+        ElementKind::PointerHoverMove,
+    ];
+
+    fn tree(button_x: f32) -> LayoutTree {
+        LayoutTree {
+            children: vec![element(
+                "panel",
+                Rect::new(0.0, 0.0, 200.0, 100.0),
+                HOVER,
+                vec![element(
+                    "button",
+                    Rect::new(button_x, 0.0, 50.0, 50.0),
+                    &[
+                        ElementKind::PointerEnter,
+                        ElementKind::PointerLeave,
+                        ElementKind::PointerAction,
+                    ],
+                    Vec::new(),
+                )],
+            )],
+        }
+    }
+
+    #[test]
+    fn test_dispatch_hover() {
+        use PointerEventKind::{Enter, Leave, Move};
+
+        let tree = tree(0.0);
+        let mut dispatcher = PointerDispatcher::new();
+        let events = dispatcher.dispatch(&tree, PointerInput::Move { x: 100.0, y: 80.0 });
+        assert_eq!(summary(&events), [(Enter, "panel"), (Move, "panel")]);
+        assert_eq!(events[0].target, ElementPath(vec![0]));
+        let events = dispatcher.dispatch(&tree, PointerInput::Move { x: 10.0, y: 10.0 });
+        assert_eq!(summary(&events), [(Enter, "button"), (Move, "panel")]);
+        assert_eq!(events[0].target, ElementPath(vec![0, 3]));
+        let events = dispatcher.dispatch(&tree, PointerInput::Move { x: 300.0, y: 10.0 });
+        assert_eq!(summary(&events), [(Leave, "button"), (Leave, "panel")]);
+        let events = dispatcher.dispatch(&tree, PointerInput::Move { x: 10.0, y: 10.0 });
+        assert_eq!(
+            summary(&events),
+            [(Enter, "panel"), (Enter, "button"), (Move, "panel")]
+        );
+        let events = dispatcher.dispatch(&tree, PointerInput::Exit);
+        assert_eq!(summary(&events), [(Leave, "button"), (Leave, "panel")]);
+        assert!(dispatcher.dispatch(&tree, PointerInput::Exit).is_empty());
+    }
+
+    #[test]
+    fn test_dispatch_relayout() {
+        use PointerEventKind::{Enter, Leave};
+
+        let mut dispatcher = PointerDispatcher::new();
+        dispatcher.dispatch(&tree(0.0), PointerInput::Move { x: 10.0, y: 10.0 });
+        assert!(dispatcher.relayout(&tree(0.0)).is_empty());
+        let events = dispatcher.relayout(&tree(100.0));
+        assert_eq!(summary(&events), [(Leave, "button")]);
+        let events = dispatcher.relayout(&tree(5.0));
+        assert_eq!(summary(&events), [(Enter, "button")]);
+    }
+
+    #[test]
+    fn test_dispatch_action() {
+        use PointerButton::{Primary, Secondary};
+
+        let tree = tree(0.0);
+        let mut dispatcher = PointerDispatcher::new();
+        let down = |x, button| PointerInput::Down { x, y: 10.0, button };
+        let up = |x, button| PointerInput::Up { x, y: 10.0, button };
+
+        dispatcher.dispatch(&tree, down(10.0, Primary));
+        let events = dispatcher.dispatch(&tree, up(20.0, Primary));
+        assert_eq!(
+            summary(&events),
+            [(PointerEventKind::Action(Primary), "button")]
+        );
+        // Released outside of the pressed element.
+        dispatcher.dispatch(&tree, down(10.0, Primary));
+        let events = dispatcher.dispatch(&tree, up(100.0, Primary));
+        assert_eq!(summary(&events), [(PointerEventKind::Leave, "button")]);
+        // Pressed outside, released inside.
+        dispatcher.dispatch(&tree, down(100.0, Secondary));
+        let events = dispatcher.dispatch(&tree, up(10.0, Secondary));
+        assert_eq!(summary(&events), [(PointerEventKind::Enter, "button")]);
+    }
+}
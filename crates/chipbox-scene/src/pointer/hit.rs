@@ -0,0 +1,146 @@
+use crate::{ElementPath, LayoutNode, LayoutTree, Point};
+
+impl LayoutTree {
+    /// Find the topmost element at a point in scene pixel space.
+    ///
+    /// Siblings are painted in ascending z-index, later siblings above earlier ones
+    /// with the same index, and children above their parent.
+    /// Behavior elements are never hit, as they share the bounds of their parent,
+    /// and descendants of clipping elements are only hit within the clip.
+    /// Bounds and clips are hit as transformed by [`LayoutNode::transform`].
+    #[must_use]
+    pub fn hit_test(&self, x: f32, y: f32) -> Option<ElementPath> {
+        let mut path = ElementPath::default();
+        hit_children(self.stacked_children(), x, y, &mut path).then_some(path)
+    }
+}
+
+fn hit_children(
+    children: Vec<(usize, &LayoutNode)>,
+    x: f32,
+    y: f32,
+    path: &mut ElementPath,
+) -> bool {
+    for (index, child) in children.into_iter().rev() {
+        path.0.push(index);
+        if hit_node(child, x, y, path) {
+            return true;
+        }
+        path.0.pop();
+    }
+    false
+}
+
+fn hit_node(node: &LayoutNode, x: f32, y: f32, path: &mut ElementPath) -> bool {
+    if node.kind.is_behavior() {
+        return false;
+    }
+    // Collapsed elements and their descendants can not be hit.
+    let Some(inverse) = node.transform.invert() else {
+        return false;
+    };
+    let local = inverse.apply(Point::new(x, y));
+    if node
+        .clip()
+        .is_some_and(|clip| !clip.contains(local.x, local.y))
+    {
+        return false;
+    }
+    hit_children(node.stacked_children(), x, y, path) || node.rect.contains(local.x, local.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Affine, ElementKind, Rect, ScrollState, Size};
+
+    fn node(kind: ElementKind, rect: Rect, children: Vec<LayoutNode>) -> LayoutNode {
+        LayoutNode::new(None, kind, rect, children)
+    }
+
+    #[test]
+    fn test_hit_topmost() {
+        let tree = LayoutTree {
+            children: vec![node(
+                ElementKind::Array,
+                Rect::new(0.0, 0.0, 100.0, 100.0),
+                vec![
+                    node(ElementKind::Box, Rect::new(0.0, 0.0, 60.0, 60.0), vec![]),
+                    node(
+                        ElementKind::Box,
+                        Rect::new(40.0, 40.0, 60.0, 60.0),
+                        vec![node(
+                            ElementKind::PointerEnter,
+                            Rect::new(40.0, 40.0, 60.0, 60.0),
+                            vec![],
+                        )],
+                    ),
+                    // Overflows its parent.
+                    node(ElementKind::Box, Rect::new(100.0, 0.0, 20.0, 20.0), vec![]),
+                ],
+            )],
+        };
+        assert_eq!(tree.hit_test(10.0, 10.0), Some(ElementPath(vec![0, 0])));
+        assert_eq!(tree.hit_test(50.0, 50.0), Some(ElementPath(vec![0, 1])));
+        assert_eq!(tree.hit_test(80.0, 10.0), Some(ElementPath(vec![0])));
+        assert_eq!(tree.hit_test(110.0, 10.0), Some(ElementPath(vec![0, 2])));
+        assert_eq!(tree.hit_test(100.0, 50.0), None);
+    }
+
+    #[test]
+    fn test_hit_transformed() {
+        let mut front = node(ElementKind::Box, Rect::new(0.0, 0.0, 50.0, 50.0), vec![]);
+        front.z_index = 1;
+        let mut scaled = node(
+            ElementKind::Box,
+            Rect::new(0.0, 0.0, 50.0, 50.0),
+            vec![node(
+                ElementKind::Box,
+                Rect::new(40.0, 40.0, 10.0, 10.0),
+                vec![],
+            )],
+        );
+        scaled.transform(Affine::scale(2.0, 2.0));
+        let mut collapsed = node(ElementKind::Box, Rect::new(0.0, 0.0, 100.0, 100.0), vec![]);
+        collapsed.transform = Affine::scale(0.0, 1.0);
+        let tree = LayoutTree {
+            children: vec![front, scaled, collapsed],
+        };
+        // The front node is above later siblings with a lower z-index.
+        assert_eq!(tree.hit_test(10.0, 10.0), Some(ElementPath(vec![0])));
+        // Scaled descendants are hit at their transformed position.
+        assert_eq!(tree.hit_test(90.0, 90.0), Some(ElementPath(vec![1, 0])));
+        assert_eq!(tree.hit_test(70.0, 30.0), Some(ElementPath(vec![1])));
+        assert_eq!(tree.hit_test(110.0, 10.0), None);
+    }
+
+    #[test]
+    fn test_hit_scroll_clip() {
+        let mut scroll = node(
+            ElementKind::Scroll,
+            Rect::new(0.0, 0.0, 100.0, 50.0),
+            vec![
+                node(ElementKind::Box, Rect::new(0.0, 0.0, 100.0, 40.0), vec![]),
+                node(ElementKind::Box, Rect::new(0.0, 40.0, 100.0, 40.0), vec![]),
+            ],
+        );
+        scroll.scroll = Some(ScrollState {
+            content: Size::new(100.0, 400.0),
+            offset_x: 0.0,
+            offset_y: 120.0,
+            visible: 3..5,
+            behaviors: Vec::new(),
+        });
+        let tree = LayoutTree {
+            children: vec![scroll],
+        };
+        assert_eq!(tree.hit_test(10.0, 45.0), Some(ElementPath(vec![0, 4])));
+        assert_eq!(tree.hit_test(10.0, 60.0), None);
+        let path = ElementPath(vec![0, 3]);
+        assert_eq!(
+            tree.node(&path).map(|node| node.rect),
+            Some(Rect::new(0.0, 0.0, 100.0, 40.0))
+        );
+        assert_eq!(tree.node(&ElementPath(vec![0, 1])), None);
+    }
+}
@@ -0,0 +1,534 @@
+//! Selection semantics of the selection behavior elements.
+//!
+//! An element with a [`ElementKind::SelectionContainer`] behavior owns a selection set.
+//! Descendants with a [`ElementKind::SelectionAction`] behavior are its selectable items,
+//! unless a nested container is closer. Items are notified of changes through the
+//! [`ElementKind::Selection`] and [`ElementKind::Deselection`] behaviors.
+//!
+//! Containers and items are tracked by id, elements without one are ignored.
+
+use std::collections::HashMap;
+
+use crate::{ElementId, ElementKind, ElementPath, LayoutNode, LayoutTree, Rect};
+
+/// How a selection gesture combines with the current selection.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SelectionMode {
+    /// Replace the selection, as on a plain click.
+    #[default]
+    Replace,
+    /// Extend the selection, as on a shift click.
+    /// Clicks select the range from the anchor item.
+    Extend,
+    /// Toggle the affected items, as on a ctrl click.
+    Toggle,
+}
+
+/// A selection gesture in scene pixel space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SelectionInput {
+    /// Select the item at the point.
+    /// A plain click on a container outside of any item clears its selection.
+    Click { x: f32, y: f32, mode: SelectionMode },
+    /// Rubber-band selection of the visible items intersecting the rectangle spanned by two points.
+    /// Applies to the innermost container at the first point.
+    Rect {
+        x0: f32,
+        y0: f32,
+        x1: f32,
+        y1: f32,
+        mode: SelectionMode,
+    },
+    /// Deselect every item of every container.
+    Clear,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectionEventKind {
+    Selected,
+    Deselected,
+}
+
+/// A change of the selection, routed to the affected item.
+///
+/// Only emitted for items with the matching behavior,
+/// or items not present in the layout, whose behaviors are unknown.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SelectionEvent {
+    pub kind: SelectionEventKind,
+    pub container: ElementId,
+    pub id: ElementId,
+    /// Path of the item, if it is present in the layout.
+    pub target: Option<ElementPath>,
+}
+
+/// Selected items of a single container.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SelectionSet {
+    items: Vec<ElementId>,
+    anchor: Option<ElementId>,
+}
+
+impl SelectionSet {
+    /// Selected items in the order they were selected.
+    #[must_use]
+    pub fn items(&self) -> &[ElementId] {
+        &self.items
+    }
+
+    /// The item range selections extend from.
+    #[must_use]
+    pub const fn anchor(&self) -> Option<&ElementId> {
+        self.anchor.as_ref()
+    }
+
+    #[must_use]
+    pub fn contains(&self, id: &ElementId) -> bool {
+        self.items.contains(id)
+    }
+}
+
+/// The selection sets of all containers of a scene.
+///
+/// Sets are kept across frames, including items culled by virtualization.
+#[derive(Clone, Debug, Default)]
+pub struct SelectionModel {
+    sets: HashMap<ElementId, SelectionSet>,
+}
+
+impl SelectionModel {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The selection of the container with the given id.
+    #[must_use]
+    pub fn get(&self, container: &ElementId) -> Option<&SelectionSet> {
+        self.sets.get(container)
+    }
+
+    /// Whether the item is selected in any container.
+    #[must_use]
+    pub fn is_selected(&self, id: &ElementId) -> bool {
+        self.sets.values().any(|set| set.contains(id))
+    }
+
+    /// Apply a selection gesture to the given layout and return the resulting changes.
+    /// Deselections precede selections.
+    pub fn dispatch(&mut self, tree: &LayoutTree, input: SelectionInput) -> Vec<SelectionEvent> {
+        let containers = containers(tree);
+        let mut events = Vec::new();
+        match input {
+            SelectionInput::Click { x, y, mode } => {
+                let Some(path) = tree.hit_test(x, y) else {
+                    return events;
+                };
+                let item = containers
+                    .iter()
+                    .flat_map(|container| container.items.iter().map(move |item| (container, item)))
+                    .filter(|(_, item)| item.path.contains(&path))
+                    .max_by_key(|(_, item)| item.path.0.len());
+                if let Some((container, item)) = item {
+                    self.click(container, &item.id, mode, &mut events);
+                } else if mode == SelectionMode::Replace
+                    && let Some(container) = innermost(&containers, &path)
+                {
+                    self.update(container, Vec::new(), None, &mut events);
+                }
+            }
+            SelectionInput::Rect {
+                x0,
+                y0,
+                x1,
+                y1,
+                mode,
+            } => {
+                let Some(container) = tree
+                    .hit_test(x0, y0)
+                    .and_then(|path| innermost(&containers, &path))
+                else {
+                    return events;
+                };
+                let rect = Rect::from_points(x0, y0, x1, y1);
+                let hits = container
+                    .items
+                    .iter()
+                    .filter(|item| item.bounds.intersects(rect))
+                    .map(|item| item.id.clone());
+                let current = self.items(&container.id);
+                let items = match mode {
+                    SelectionMode::Replace => hits.collect(),
+                    SelectionMode::Extend => {
+                        let mut items = current;
+                        for id in hits {
+                            if !items.contains(&id) {
+                                items.push(id);
+                            }
+                        }
+                        items
+                    }
+                    SelectionMode::Toggle => {
+                        let hits = hits.collect::<Vec<_>>();
+                        let mut items = current;
+                        items.retain(|id| !hits.contains(id));
+                        items.extend(hits.into_iter().filter(|id| !self.is_in(&container.id, id)));
+                        items
+                    }
+                };
+                let anchor = self.anchor(&container.id);
+                self.update(container, items, anchor, &mut events);
+            }
+            SelectionInput::Clear => {
+                let mut sets = std::mem::take(&mut self.sets)
+                    .into_iter()
+                    .collect::<Vec<_>>();
+                sets.sort_by(|(a, _), (b, _)| a.0.cmp(&b.0));
+                for (container, set) in sets {
+                    let nodes = containers.iter().find(|c| c.id == container);
+                    for id in set.items {
+                        events.extend(event(
+                            SelectionEventKind::Deselected,
+                            &container,
+                            &id,
+                            nodes,
+                        ));
+                    }
+                }
+            }
+        }
+        events
+    }
+
+    fn click(
+        &mut self,
+        container: &Container,
+        id: &ElementId,
+        mode: SelectionMode,
+        events: &mut Vec<SelectionEvent>,
+    ) {
+        let anchor = self.anchor(&container.id);
+        let range = anchor
+            .as_ref()
+            .filter(|_| mode == SelectionMode::Extend)
+            .and_then(|anchor| container.range(anchor, id));
+        let (items, anchor) = match range {
+            Some(range) => (range, anchor),
+            None if mode == SelectionMode::Toggle => {
+                let mut items = self.items(&container.id);
+                if let Some(index) = items.iter().position(|item| item == id) {
+                    items.remove(index);
+                } else {
+                    items.push(id.clone());
+                }
+                (items, Some(id.clone()))
+            }
+            None => (vec![id.clone()], Some(id.clone())),
+        };
+        self.update(container, items, anchor, events);
+    }
+
+    fn items(&self, container: &ElementId) -> Vec<ElementId> {
+        self.sets
+            .get(container)
+            .map(|set| set.items.clone())
+            .unwrap_or_default()
+    }
+
+    fn anchor(&self, container: &ElementId) -> Option<ElementId> {
+        self.sets.get(container).and_then(|set| set.anchor.clone())
+    }
+
+    fn is_in(&self, container: &ElementId, id: &ElementId) -> bool {
+        self.sets.get(container).is_some_and(|set| set.contains(id))
+    }
+
+    /// Replace the selection of a container, emitting events for every change.
+    fn update(
+        &mut self,
+        container: &Container,
+        items: Vec<ElementId>,
+        anchor: Option<ElementId>,
+        events: &mut Vec<SelectionEvent>,
+    ) {
+        let old = self.items(&container.id);
+        for (kind, from, to) in [
+            (SelectionEventKind::Deselected, &old, &items),
+            (SelectionEventKind::Selected, &items, &old),
+        ] {
+            events.extend(
+                from.iter()
+                    .filter(|id| !to.contains(id))
+                    .filter_map(|id| event(kind, &container.id, id, Some(container))),
+            );
+        }
+        if items.is_empty() && anchor.is_none() {
+            self.sets.remove(&container.id);
+        } else {
+            self.sets
+                .insert(container.id.clone(), SelectionSet { items, anchor });
+        }
+    }
+}
+
+struct Container<'a> {
+    id: ElementId,
+    path: ElementPath,
+    /// Selectable items in document order.
+    items: Vec<Item<'a>>,
+}
+
+struct Item<'a> {
+    id: ElementId,
+    path: ElementPath,
+    node: &'a LayoutNode,
+    /// Bounds of the item restricted to the clip of its ancestors.
+    bounds: Rect,
+}
+
+impl Container<'_> {
+    /// The items from the anchor to the given item, both inclusive.
+    fn range(&self, anchor: &ElementId, id: &ElementId) -> Option<Vec<ElementId>> {
+        let start = self.items.iter().position(|item| item.id == *anchor)?;
+        let end = self.items.iter().position(|item| item.id == *id)?;
+        let range = if start <= end {
+            start..=end
+        } else {
+            end..=start
+        };
+        Some(
+            self.items[range]
+                .iter()
+                .map(|item| item.id.clone())
+                .collect(),
+        )
+    }
+}
+
+/// The innermost container containing the element at the path.
+fn innermost<'a, 'b>(
+    containers: &'a [Container<'b>],
+    path: &ElementPath,
+) -> Option<&'a Container<'b>> {
+    containers
+        .iter()
+        .filter(|container| container.path.contains(path))
+        .max_by_key(|container| container.path.0.len())
+}
+
+fn event(
+    kind: SelectionEventKind,
+    container: &ElementId,
+    id: &ElementId,
+    nodes: Option<&Container>,
+) -> Option<SelectionEvent> {
+    let behavior = match kind {
+        SelectionEventKind::Selected => ElementKind::Selection,
+        SelectionEventKind::Deselected => ElementKind::Deselection,
+    };
+    let item = nodes.and_then(|container| container.items.iter().find(|item| item.id == *id));
+    if item.is_some_and(|item| !item.node.has_behavior(behavior)) {
+        return None;
+    }
+    Some(SelectionEvent {
+        kind,
+        container: container.clone(),
+        id: id.clone(),
+        target: item.map(|item| item.path.clone()),
+    })
+}
+
+/// Collect every container of the layout along with its items.
+fn containers(tree: &LayoutTree) -> Vec<Container<'_>> {
+    let mut containers = Vec::new();
+    let mut path = ElementPath::default();
+    for (index, node) in tree.children.iter().enumerate() {
+        path.0.push(index);
+        collect(node, &mut path, None, None, &mut containers);
+        path.0.pop();
+    }
+    containers
+}
+
+fn collect<'a>(
+    node: &'a LayoutNode,
+    path: &mut ElementPath,
+    container: Option<usize>,
+    clip: Option<Rect>,
+    containers: &mut Vec<Container<'a>>,
+) {
+    let mut container = container;
+    if let Some(id) = &node.id {
+        if let Some(index) = container
+            && node.has_behavior(ElementKind::SelectionAction)
+        {
+            containers[index].items.push(Item {
+                id: id.clone(),
+                path: path.clone(),
+                node,
+                bounds: clip.map_or_else(|| node.bounds(), |clip| clip.intersection(node.bounds())),
+            });
+        }
+        if node.has_behavior(ElementKind::SelectionContainer) {
+            containers.push(Container {
+                id: id.clone(),
+                path: path.clone(),
+                items: Vec::new(),
+            });
+            container = Some(containers.len() - 1);
+        }
+    }
+    // Selection rectangles are axis aligned, so transformed clips are approximated by their bounds.
+    let inner = node.clip().map(|inner| node.transform.bounds(inner));
+    let clip = match (clip, inner) {
+        (Some(outer), Some(inner)) => Some(outer.intersection(inner)),
+        (outer, inner) => outer.or(inner),
+    };
+    for (index, child) in node.indexed_children() {
+        path.0.push(index);
+        collect(child, path, container, clip, containers);
+        path.0.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ScrollState,
+        fixtures::{element, id, summary},
+    };
+
+    const ITEM: &[ElementKind] = &[
+        ElementKind::SelectionAction,
+        ElementKind::Selection,
+        ElementKind::Deselection,
+    ];
+
+    /// A container of five rows, 20px high each, followed by 20px of empty space.
+    fn tree() -> LayoutTree {
+        let rows = ["a", "b", "c", "d", "e"]
+            .into_iter()
+            .zip(0u8..)
+            .map(|(name, i)| {
+                element(
+                    name,
+                    Rect::new(0.0, 20.0 * f32::from(i), 100.0, 20.0),
+                    ITEM,
+                    vec![],
+                )
+            })
+            .collect();
+        LayoutTree {
+            children: vec![element(
+                "list",
+                Rect::new(0.0, 0.0, 100.0, 120.0),
+                &[ElementKind::SelectionContainer],
+                rows,
+            )],
+        }
+    }
+
+    fn click(y: f32, mode: SelectionMode) -> SelectionInput {
+        SelectionInput::Click { x: 50.0, y, mode }
+    }
+
+    fn selected(model: &SelectionModel) -> Vec<&str> {
+        model
+            .get(&id("list"))
+            .map(|set| set.items().iter().map(|id| &*id.0).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_selection_click() {
+        use SelectionEventKind::{Deselected, Selected};
+
+        let tree = tree();
+        let mut model = SelectionModel::new();
+        let events = model.dispatch(&tree, click(30.0, SelectionMode::Replace));
+        assert_eq!(summary(&events), [(Selected, "b")]);
+        assert_eq!(events[0].container, id("list"));
+        assert_eq!(events[0].target, Some(ElementPath(vec![0, 2])));
+
+        let events = model.dispatch(&tree, click(70.0, SelectionMode::Extend));
+        assert_eq!(summary(&events), [(Selected, "c"), (Selected, "d")]);
+        assert_eq!(selected(&model), ["b", "c", "d"]);
+        // Ranges extend from the anchor, not the last clicked item.
+        let events = model.dispatch(&tree, click(10.0, SelectionMode::Extend));
+        assert_eq!(
+            summary(&events),
+            [(Deselected, "c"), (Deselected, "d"), (Selected, "a")]
+        );
+
+        let events = model.dispatch(&tree, click(90.0, SelectionMode::Toggle));
+        assert_eq!(summary(&events), [(Selected, "e")]);
+        let events = model.dispatch(&tree, click(30.0, SelectionMode::Toggle));
+        assert_eq!(summary(&events), [(Deselected, "b")]);
+        assert_eq!(selected(&model), ["a", "e"]);
+        assert_eq!(
+            model.get(&id("list")).and_then(SelectionSet::anchor),
+            Some(&id("b"))
+        );
+
+        let events = model.dispatch(&tree, click(110.0, SelectionMode::Replace));
+        assert_eq!(summary(&events), [(Deselected, "a"), (Deselected, "e")]);
+        assert!(!model.is_selected(&id("a")));
+    }
+
+    #[test]
+    fn test_selection_rect() {
+        use SelectionEventKind::{Deselected, Selected};
+
+        let tree = tree();
+        let mut model = SelectionModel::new();
+        let rect = |y0, y1, mode| SelectionInput::Rect {
+            x0: 50.0,
+            y0,
+            x1: 10.0,
+            y1,
+            mode,
+        };
+        let events = model.dispatch(&tree, rect(110.0, 50.0, SelectionMode::Replace));
+        assert_eq!(
+            summary(&events),
+            [(Selected, "c"), (Selected, "d"), (Selected, "e")]
+        );
+        let events = model.dispatch(&tree, rect(15.0, 45.0, SelectionMode::Toggle));
+        assert_eq!(
+            summary(&events),
+            [(Deselected, "c"), (Selected, "a"), (Selected, "b")]
+        );
+        assert_eq!(selected(&model), ["d", "e", "a", "b"]);
+        let events = model.dispatch(&tree, SelectionInput::Clear);
+        assert_eq!(events.len(), 4);
+        assert!(model.get(&id("list")).is_none());
+    }
+
+    #[test]
+    fn test_selection_clipped() {
+        let mut tree = tree();
+        let list = &mut tree.children[0];
+        list.kind = ElementKind::Scroll;
+        list.rect.height = 50.0;
+        list.scroll = Some(ScrollState {
+            content: crate::Size::new(100.0, 120.0),
+            offset_x: 0.0,
+            offset_y: 0.0,
+            visible: 0..6,
+            behaviors: Vec::new(),
+        });
+        let mut model = SelectionModel::new();
+        model.dispatch(
+            &tree,
+            SelectionInput::Rect {
+                x0: 10.0,
+                y0: 30.0,
+                x1: 90.0,
+                y1: 200.0,
+                mode: SelectionMode::Replace,
+            },
+        );
+        assert_eq!(selected(&model), ["b", "c"]);
+    }
+}
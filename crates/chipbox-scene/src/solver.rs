@@ -0,0 +1,1440 @@
+//! Layout pass that resolves every element of a [`Scene`] to pixel-space bounds.
+//!
+//! The solver is pure and deterministic: the same scene always produces the same tree.
+
+mod flex;
+mod geometry;
+mod grid;
+mod length;
+mod metrics;
+mod node;
+
+pub use self::{
+    geometry::{Affine, Point, Rect, Size},
+    length::LengthContext,
+    metrics::DisplayMetrics,
+    node::{LayoutNode, LayoutTree, ScrollState},
+};
+use crate::{
+    AlignElement, Alignment, ArrayElement, Axis, BehaviorElement, BoxElement, ContentBoxElement,
+    ContentElement, ElementId, ElementKind, ElementNode, FlexAlign, FlexElement, GridElement,
+    HorizontalAlign, ImageCache, ImageElement, ImageError, LayoutElement, LayoutLength,
+    LinearDirection, MarginElement, Resources, Scene, ScrollElement, TextRun, TextStyle,
+    TransformElement, VerticalAlign,
+};
+
+/// Resolve the layout of the whole scene.
+/// Images are loaded up front, so that broken images fail the layout
+/// instead of silently occupying no space.
+pub fn solve(scene: &Scene, resources: Resources) -> Result<LayoutTree, ImageError> {
+    if let Some(images) = resources.images {
+        load_images(&scene.children, images)?;
+    }
+    let ctx = LengthContext::for_scene(scene, resources.text).with_images(resources.images);
+    let bounds = Rect::from_size(0.0, 0.0, ctx.scene);
+    Ok(LayoutTree {
+        children: layout_children(&scene.children, bounds, &ctx),
+    })
+}
+
+/// Load the images of the elements and their descendants into the cache.
+fn load_images(children: &[ElementNode], images: &ImageCache) -> Result<(), ImageError> {
+    for child in children {
+        let layout = match child {
+            ElementNode::Content(ContentElement::Image(e)) => {
+                images.load(&e.source)?;
+                continue;
+            }
+            ElementNode::Layout(e) => e,
+            ElementNode::Content(_) | ElementNode::Behavior(_) => continue,
+        };
+        match layout {
+            LayoutElement::Box(e) => load_images(&e.children, images)?,
+            LayoutElement::Margin(e) => load_images(&e.children, images)?,
+            LayoutElement::ContentBox(e) => load_images(&e.children, images)?,
+            LayoutElement::Align(e) => load_images(&e.children, images)?,
+            LayoutElement::Scroll(e) => load_images(&e.children, images)?,
+            LayoutElement::Array(e) => {
+                for item in &e.children {
+                    load_images(&item.children, images)?;
+                }
+            }
+            LayoutElement::Grid(e) => {
+                for item in &e.children {
+                    load_images(&item.children, images)?;
+                }
+            }
+            LayoutElement::Flex(e) => {
+                for item in &e.children {
+                    load_images(&item.children, images)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Lay out a list of children inside the given parent bounds.
+fn layout_children(children: &[ElementNode], bounds: Rect, ctx: &LengthContext) -> Vec<LayoutNode> {
+    let ctx = ctx.with_parent(bounds.size());
+    children
+        .iter()
+        .map(|child| layout_node(child, bounds, &ctx))
+        .collect()
+}
+
+/// Lay out a single node inside the given parent bounds.
+/// The context is expected to describe the parent.
+fn layout_node(node: &ElementNode, bounds: Rect, ctx: &LengthContext) -> LayoutNode {
+    match node {
+        ElementNode::Layout(e) => layout_element(e, bounds, ctx),
+        ElementNode::Behavior(e) => layout_behavior(e, bounds),
+        ElementNode::Content(e) => layout_content(e, bounds, ctx),
+    }
+}
+
+fn layout_element(element: &LayoutElement, bounds: Rect, ctx: &LengthContext) -> LayoutNode {
+    match element {
+        LayoutElement::Box(e) => layout_box(e, bounds.x, bounds.y, ctx),
+        LayoutElement::Margin(e) => layout_margin(e, bounds, ctx),
+        LayoutElement::ContentBox(e) => layout_content_box(e, bounds, ctx),
+        LayoutElement::Array(e) => layout_array(e, bounds, ctx),
+        LayoutElement::Align(e) => layout_align(e, bounds, ctx),
+        LayoutElement::Grid(e) => layout_grid(e, bounds, ctx),
+        LayoutElement::Flex(e) => layout_flex(e, bounds, ctx),
+        LayoutElement::Scroll(e) => layout_scroll(e, bounds, ctx),
+    }
+}
+
+/// Resolve the size of a box element.
+/// Negative lengths are clamped to zero.
+fn box_size(element: &BoxElement, ctx: &LengthContext) -> Size {
+    Size::new(
+        element.width.resolve(ctx).max(0.0),
+        element.height.resolve(ctx).max(0.0),
+    )
+}
+
+fn layout_box(element: &BoxElement, x: f32, y: f32, ctx: &LengthContext) -> LayoutNode {
+    let rect = Rect::from_size(x, y, box_size(element, ctx));
+    stack(
+        LayoutNode::new(
+            element.id(),
+            ElementKind::Box,
+            rect,
+            layout_children(&element.children, rect, ctx),
+        ),
+        &element.children,
+        ctx,
+    )
+}
+
+fn layout_margin(element: &MarginElement, bounds: Rect, ctx: &LengthContext) -> LayoutNode {
+    let side = |side: &Option<LayoutLength>, axis: &Option<LayoutLength>| {
+        side.as_ref()
+            .or(axis.as_ref())
+            .or(element.base.as_ref())
+            .map_or(0.0, |length| length.resolve(ctx))
+    };
+    let rect = bounds.inset(
+        side(&element.left, &element.horizontal),
+        side(&element.top, &element.vertical),
+        side(&element.right, &element.horizontal),
+        side(&element.bottom, &element.vertical),
+    );
+    stack(
+        LayoutNode::new(
+            element.id(),
+            ElementKind::Margin,
+            rect,
+            layout_children(&element.children, rect, ctx),
+        ),
+        &element.children,
+        ctx,
+    )
+}
+
+fn layout_content_box(
+    element: &ContentBoxElement,
+    bounds: Rect,
+    ctx: &LengthContext,
+) -> LayoutNode {
+    let mut children = layout_children(&element.children, bounds, ctx);
+    // Size to the extent of the content, starting at the parent origin.
+    let rect = content_extent(&children, bounds.x, bounds.y);
+    // Behavior elements apply to the content box itself.
+    children
+        .iter_mut()
+        .filter(|child| child.kind.is_behavior())
+        .for_each(|child| child.rect = rect);
+    stack(
+        LayoutNode::new(element.id(), ElementKind::ContentBox, rect, children),
+        &element.children,
+        ctx,
+    )
+}
+
+fn layout_array(element: &ArrayElement, bounds: Rect, ctx: &LengthContext) -> LayoutNode {
+    let axis = element.axis;
+    let ctx = ctx.with_parent(bounds.size());
+    let mut cursor = 0.0;
+    let children = element
+        .children
+        .iter()
+        .map(|child| {
+            let extent = box_size(child, &ctx).main(axis);
+            let main = match element.direction {
+                LinearDirection::Forward => bounds.start(axis) + cursor,
+                LinearDirection::Backward => {
+                    bounds.start(axis) + bounds.size().main(axis) - cursor - extent
+                }
+            };
+            cursor += extent;
+            let origin = Rect::from_axes(axis, main, bounds.cross_start(axis), Size::ZERO);
+            layout_box(child, origin.x, origin.y, &ctx)
+        })
+        .collect();
+    LayoutNode::new(element.id(), ElementKind::Array, bounds, children)
+}
+
+/// Relative position of an alignment point inside an area of the given size.
+const fn anchor(alignment: Alignment, size: Size) -> (f32, f32) {
+    let x = match alignment.horizontal {
+        HorizontalAlign::Left => 0.0,
+        HorizontalAlign::Center => 0.5,
+        HorizontalAlign::Right => 1.0,
+    };
+    let y = match alignment.vertical {
+        VerticalAlign::Top => 0.0,
+        VerticalAlign::Middle => 0.5,
+        VerticalAlign::Bottom => 1.0,
+    };
+    (x * size.width, y * size.height)
+}
+
+fn layout_align(element: &AlignElement, bounds: Rect, ctx: &LengthContext) -> LayoutNode {
+    let ctx = ctx.with_parent(bounds.size());
+    let offset =
+        |length: &Option<LayoutLength>| length.as_ref().map_or(0.0, |length| length.resolve(&ctx));
+    let (target_x, target_y) = anchor(element.target, bounds.size());
+    let (offset_x, offset_y) = (offset(&element.x_offset), offset(&element.y_offset));
+    let children = element
+        .children
+        .iter()
+        .map(|child| {
+            let mut node = layout_node(child, bounds, &ctx);
+            if !node.kind.is_behavior() {
+                let (origin_x, origin_y) = anchor(element.origin, node.rect.size());
+                let x = bounds.x + target_x - origin_x + offset_x;
+                let y = bounds.y + target_y - origin_y + offset_y;
+                node.translate(x - node.rect.x, y - node.rect.y);
+            }
+            node
+        })
+        .collect();
+    stack(
+        LayoutNode::new(element.id(), ElementKind::Align, bounds, children),
+        &element.children,
+        &ctx,
+    )
+}
+
+#[allow(
+    clippy::cast_precision_loss,
+    reason = "element counts are far below 2^24"
+)]
+const fn count(n: usize) -> f32 {
+    n as f32
+}
+
+/// Extent of the non-behavior nodes, starting at the given origin.
+fn content_extent(children: &[LayoutNode], x: f32, y: f32) -> Rect {
+    children
+        .iter()
+        .filter(|child| !child.kind.is_behavior())
+        .fold(Rect::from_size(x, y, Size::ZERO), |rect, child| {
+            rect.union(child.rect)
+        })
+}
+
+/// Lay out a grid or flex item at its resolved bounds.
+fn layout_item(
+    id: Option<ElementId>,
+    kind: ElementKind,
+    rect: Rect,
+    children: &[ElementNode],
+    ctx: &LengthContext,
+) -> LayoutNode {
+    stack(
+        LayoutNode::new(id, kind, rect, layout_children(children, rect, ctx)),
+        children,
+        ctx,
+    )
+}
+
+fn layout_grid(element: &GridElement, bounds: Rect, ctx: &LengthContext) -> LayoutNode {
+    let axis = element.axis;
+    let limit = element.array_limit.get();
+    let size = bounds.size();
+    let ctx = ctx.with_parent(size);
+    // Lines run along the main axis, so vertical grids fill rows.
+    let (main_template, cross_template, main_gap, cross_gap) = match axis {
+        Axis::Vertical => (
+            &element.rows,
+            &element.columns,
+            &element.row_gap,
+            &element.column_gap,
+        ),
+        Axis::Horizontal => (
+            &element.columns,
+            &element.rows,
+            &element.column_gap,
+            &element.row_gap,
+        ),
+    };
+    let gap = |gap: &Option<LayoutLength>| {
+        gap.as_ref()
+            .or(element.gap.as_ref())
+            .map_or(0.0, |gap| gap.resolve(&ctx).max(0.0))
+    };
+    let (main_gap, cross_gap) = (gap(main_gap), gap(cross_gap));
+    let spans = element.children.iter().map(|child| match axis {
+        Axis::Vertical => (child.row_span.get(), child.column_span.get()),
+        Axis::Horizontal => (child.column_span.get(), child.row_span.get()),
+    });
+    let placements = grid::place(spans, limit);
+    let lines = placements
+        .iter()
+        .map(|p| p.line + p.main_span)
+        .max()
+        .unwrap_or(0)
+        .max(main_template.0.len());
+    // Content is measured without a parent size, like a content box.
+    let contents: Vec<Size> = element
+        .children
+        .iter()
+        .map(|child| {
+            let zero = Rect::from_size(0.0, 0.0, Size::ZERO);
+            content_extent(&layout_children(&child.children, zero, &ctx), 0.0, 0.0).size()
+        })
+        .collect();
+    let tracks = |main: bool, template, count, extent, gap| {
+        let items: Vec<_> = placements
+            .iter()
+            .zip(&contents)
+            .map(|(p, content)| grid::TrackItem {
+                start: if main { p.line } else { p.slot },
+                span: if main { p.main_span } else { p.cross_span },
+                content: if main {
+                    content.main(axis)
+                } else {
+                    content.cross(axis)
+                },
+            })
+            .collect();
+        grid::Tracks::new(template, count, &items, extent, gap, &ctx)
+    };
+    let main_tracks = tracks(true, main_template, lines, size.main(axis), main_gap);
+    let cross_tracks = tracks(false, cross_template, limit, size.cross(axis), cross_gap);
+    let children = element
+        .children
+        .iter()
+        .zip(&placements)
+        .map(|(child, p)| {
+            let (main, main_extent) = main_tracks.span(element.direction, p.line, p.main_span);
+            let (cross, cross_extent) =
+                cross_tracks.span(element.array_direction, p.slot, p.cross_span);
+            let rect = Rect::from_axes(
+                axis,
+                bounds.start(axis) + main,
+                bounds.cross_start(axis) + cross,
+                Size::from_axes(axis, main_extent, cross_extent),
+            );
+            layout_item(
+                child.id(),
+                ElementKind::GridItem,
+                rect,
+                &child.children,
+                &ctx,
+            )
+        })
+        .collect();
+    LayoutNode::new(element.id(), ElementKind::Grid, bounds, children)
+}
+
+fn layout_flex(element: &FlexElement, bounds: Rect, ctx: &LengthContext) -> LayoutNode {
+    let axis = element.axis;
+    let size = bounds.size();
+    let ctx = ctx.with_parent(size);
+    let resolve = |length: &Option<LayoutLength>, default: f32| {
+        length
+            .as_ref()
+            .map_or(default, |length| length.resolve(&ctx))
+    };
+    let gap = resolve(&element.gap, 0.0).max(0.0);
+    let available = size.main(axis) - gap * count(element.children.len().saturating_sub(1));
+    let factors: Vec<_> = element
+        .children
+        .iter()
+        .map(|child| flex::FlexFactors {
+            grow: child.grow.max(0.0),
+            shrink: child.shrink.max(0.0),
+            basis: resolve(&child.basis, 0.0).max(0.0),
+            min: resolve(&child.min, 0.0).max(0.0),
+            max: resolve(&child.max, f32::INFINITY),
+        })
+        .collect();
+    let extents = flex::resolve_sizes(&factors, available);
+    let free = available - extents.iter().sum::<f32>();
+    let (leading, spacing) = flex::justify(element.justify, free, extents.len());
+    let mut cursor = leading;
+    let children = element
+        .children
+        .iter()
+        .zip(extents)
+        .map(|(child, extent)| {
+            let main = match element.direction {
+                LinearDirection::Forward => cursor,
+                LinearDirection::Backward => size.main(axis) - cursor - extent,
+            };
+            cursor += extent + gap + spacing;
+            let cross_extent = size.cross(axis);
+            let cross = resolve(&child.cross, cross_extent).max(0.0);
+            let cross_offset = match child.align.unwrap_or(element.align) {
+                FlexAlign::Start | FlexAlign::Stretch => 0.0,
+                FlexAlign::Center => (cross_extent - cross) / 2.0,
+                FlexAlign::End => cross_extent - cross,
+            };
+            let rect = Rect::from_axes(
+                axis,
+                bounds.start(axis) + main,
+                bounds.cross_start(axis) + cross_offset,
+                Size::from_axes(axis, extent, cross),
+            );
+            layout_item(
+                child.id(),
+                ElementKind::FlexItem,
+                rect,
+                &child.children,
+                &ctx,
+            )
+        })
+        .collect();
+    LayoutNode::new(element.id(), ElementKind::Flex, bounds, children)
+}
+
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    reason = "callers clamp the value to a non-negative element count"
+)]
+const fn index(value: f32) -> usize {
+    value as usize
+}
+
+/// Clamp a scroll offset to the range in which the viewport stays inside the content.
+fn clamp_offset(offset: f32, content: f32, viewport: f32) -> f32 {
+    offset.clamp(0.0, (content - viewport).max(0.0))
+}
+
+fn layout_scroll(element: &ScrollElement, bounds: Rect, ctx: &LengthContext) -> LayoutNode {
+    let ctx = ctx.with_parent(bounds.size());
+    let offset = |axis, length: &Option<LayoutLength>| {
+        length
+            .as_ref()
+            .filter(|_| element.scrolls(axis))
+            .map_or(0.0, |length| length.resolve(&ctx))
+    };
+    let offset_x = offset(Axis::Horizontal, &element.offset_x);
+    let offset_y = offset(Axis::Vertical, &element.offset_y);
+    let (children, scroll) = element.item_extent.as_ref().map_or_else(
+        || scroll_content(element, bounds, &ctx, offset_x, offset_y),
+        |item| scroll_rows(element, item, bounds, &ctx, offset_x, offset_y),
+    );
+    stack(
+        LayoutNode {
+            scroll: Some(scroll),
+            ..LayoutNode::new(element.id(), ElementKind::Scroll, bounds, children)
+        },
+        &element.children,
+        &ctx,
+    )
+}
+
+/// Lay out the content of a scroll element at the given offset.
+fn scroll_content(
+    element: &ScrollElement,
+    bounds: Rect,
+    ctx: &LengthContext,
+    offset_x: f32,
+    offset_y: f32,
+) -> (Vec<LayoutNode>, ScrollState) {
+    let viewport = bounds.size();
+    // Lay out the content unscrolled first, so that the offset can be clamped to it.
+    let mut children = layout_children(&element.children, bounds, ctx);
+    let extent = content_extent(&children, bounds.x, bounds.y);
+    let content = Size::new(
+        extent.width.max(viewport.width),
+        extent.height.max(viewport.height),
+    );
+    let offset_x = clamp_offset(offset_x, content.width, viewport.width);
+    let offset_y = clamp_offset(offset_y, content.height, viewport.height);
+    children
+        .iter_mut()
+        .filter(|child| !child.kind.is_behavior())
+        .for_each(|child| child.translate(-offset_x, -offset_y));
+    let scroll = ScrollState {
+        content,
+        offset_x,
+        offset_y,
+        visible: 0..children.len(),
+        behaviors: Vec::new(),
+    };
+    (children, scroll)
+}
+
+/// Lay out the children of a scroll element as uniformly sized rows,
+/// skipping the ones outside of the viewport.
+/// Behavior children are not rows, they apply to the scroll element and are always laid out.
+fn scroll_rows(
+    element: &ScrollElement,
+    item: &LayoutLength,
+    bounds: Rect,
+    ctx: &LengthContext,
+    offset_x: f32,
+    offset_y: f32,
+) -> (Vec<LayoutNode>, ScrollState) {
+    let viewport = bounds.size();
+    let axis = element.axis.unwrap_or(Axis::Vertical);
+    let item = item.resolve(ctx).max(0.0);
+    let mut behaviors = Vec::new();
+    let mut rows = Vec::new();
+    for (index, child) in element.children.iter().enumerate() {
+        match child {
+            ElementNode::Behavior(e) => behaviors.push((index, e)),
+            _ => rows.push(child),
+        }
+    }
+    let len = rows.len();
+    let content = Size::from_axes(
+        axis,
+        (item * count(len)).max(viewport.main(axis)),
+        viewport.cross(axis),
+    );
+    let offset = match axis {
+        Axis::Horizontal => offset_x,
+        Axis::Vertical => offset_y,
+    };
+    let offset = clamp_offset(offset, content.main(axis), viewport.main(axis));
+    let visible = if item > 0.0 {
+        let first = index((offset / item).floor()).min(len);
+        let last = index(((offset + viewport.main(axis)) / item).ceil()).min(len);
+        first..last
+    } else {
+        0..0
+    };
+    let rows = visible.clone().map(|i| {
+        let row = Rect::from_axes(
+            axis,
+            bounds.start(axis) + item * count(i) - offset,
+            bounds.cross_start(axis),
+            Size::from_axes(axis, item, viewport.cross(axis)),
+        );
+        layout_node(rows[i], row, &ctx.with_parent(row.size()))
+    });
+    let children = behaviors
+        .iter()
+        .map(|(_, e)| layout_behavior(e, bounds))
+        .chain(rows)
+        .collect();
+    let (offset_x, offset_y) = match axis {
+        Axis::Horizontal => (offset, 0.0),
+        Axis::Vertical => (0.0, offset),
+    };
+    let scroll = ScrollState {
+        content,
+        offset_x,
+        offset_y,
+        visible,
+        behaviors: behaviors.into_iter().map(|(index, _)| index).collect(),
+    };
+    (children, scroll)
+}
+
+/// Apply the transform and stacking elements among the children of an element to its node.
+/// The context is expected to describe the parent of the element.
+fn stack(mut node: LayoutNode, children: &[ElementNode], ctx: &LengthContext) -> LayoutNode {
+    let ctx = ctx.with_parent(node.rect.size());
+    let mut transform = Affine::IDENTITY;
+    for child in children {
+        match child {
+            ElementNode::Behavior(BehaviorElement::Transform(e)) => {
+                transform = local_transform(e, node.rect, &ctx).then(transform);
+            }
+            ElementNode::Behavior(BehaviorElement::Stacking(e)) => node.z_index = e.z_index,
+            _ => {}
+        }
+    }
+    if transform != Affine::IDENTITY {
+        node.transform(transform);
+    }
+    node
+}
+
+/// The transformation of a transform element whose parent is laid out at `rect`.
+fn local_transform(element: &TransformElement, rect: Rect, ctx: &LengthContext) -> Affine {
+    let length =
+        |length: &Option<LayoutLength>| length.as_ref().map_or(0.0, |length| length.resolve(ctx));
+    let (origin_x, origin_y) = anchor(element.origin, rect.size());
+    let (origin_x, origin_y) = (rect.x + origin_x, rect.y + origin_y);
+    Affine::translate(-origin_x, -origin_y)
+        .then(Affine::skew(
+            element.skew_x.to_radians(),
+            element.skew_y.to_radians(),
+        ))
+        .then(Affine::scale(element.scale_x, element.scale_y))
+        .then(Affine::rotate(element.rotate.to_radians()))
+        .then(Affine::translate(
+            origin_x + length(&element.translate_x),
+            origin_y + length(&element.translate_y),
+        ))
+}
+
+/// Behavior elements apply to their parent, so they share its bounds.
+fn layout_behavior(element: &BehaviorElement, bounds: Rect) -> LayoutNode {
+    LayoutNode {
+        tab_index: match element {
+            BehaviorElement::Focusable(e) => Some(e.tab_index),
+            _ => None,
+        },
+        ..LayoutNode::new(element.id(), element.kind(), bounds, Vec::new())
+    }
+}
+
+/// Content is placed at the parent origin. Text is sized to fit the parent width,
+/// images take their intrinsic or requested size, rects, paths and waveforms fill the parent.
+/// Without a text system or image cache, text and images occupy no space.
+fn layout_content(element: &ContentElement, bounds: Rect, ctx: &LengthContext) -> LayoutNode {
+    let size = match element {
+        ContentElement::Text(e) => ctx.text.map_or(Size::ZERO, |text| {
+            let style = TextStyle::of(e, ctx);
+            text.layout(&TextRun::of(e, ctx), &style, Some(bounds.width))
+                .size()
+        }),
+        ContentElement::Image(e) => image_size(e, bounds, ctx),
+        ContentElement::Rect(_) | ContentElement::Path(_) | ContentElement::Waveform(_) => {
+            bounds.size()
+        }
+    };
+    LayoutNode::new(
+        element.id(),
+        element.kind(),
+        Rect::from_size(bounds.x, bounds.y, size),
+        Vec::new(),
+    )
+}
+
+/// The size of an image element, following the aspect ratio of the image for unset sides.
+/// Without any size set, the image is shrunk uniformly to fit the parent.
+fn image_size(element: &ImageElement, bounds: Rect, ctx: &LengthContext) -> Size {
+    let width = element.width.as_ref().map(|width| width.resolve(ctx));
+    let height = element.height.as_ref().map(|height| height.resolve(ctx));
+    if let (Some(width), Some(height)) = (width, height) {
+        return Size::new(width, height);
+    }
+    // Every image was loaded by `solve`, so this only fails without an image cache.
+    let Some(image) = ctx
+        .images
+        .and_then(|images| images.load(&element.source).ok())
+    else {
+        return Size::ZERO;
+    };
+    let intrinsic = image.intrinsic_size(ctx);
+    if intrinsic.width <= 0.0 || intrinsic.height <= 0.0 {
+        return Size::ZERO;
+    }
+    let aspect = intrinsic.width / intrinsic.height;
+    match (width, height) {
+        (Some(width), _) => Size::new(width, width / aspect),
+        (_, Some(height)) => Size::new(height * aspect, height),
+        (None, None) => {
+            let scale = (bounds.width / intrinsic.width)
+                .min(bounds.height / intrinsic.height)
+                .clamp(0.0, 1.0);
+            Size::new(intrinsic.width * scale, intrinsic.height * scale)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{num::NonZeroUsize, sync::Arc};
+
+    use super::*;
+    use crate::{
+        ElementId, ElementPath, FlexItemElement, FlexJustify, GridItemElement, GridTemplate,
+        SpanElement, TextChild, TextElement, TextOverflow, TextSystem, TextureElement,
+    };
+
+    fn scene(children: Vec<ElementNode>) -> Scene {
+        Scene {
+            children,
+            px_width: 800,
+            px_height: 600,
+            metrics: DisplayMetrics {
+                scale: 2.0,
+                ..DisplayMetrics::default()
+            },
+        }
+    }
+
+    /// Lay out a scene without measuring text or images.
+    fn layout(scene: &Scene) -> LayoutTree {
+        scene.layout_with(Resources::default()).expect("layout")
+    }
+
+    fn id(s: &str) -> ElementId {
+        ElementId(Arc::from(s))
+    }
+
+    fn r#box(width: LayoutLength, height: LayoutLength) -> BoxElement {
+        BoxElement {
+            id: None,
+            children: Vec::new(),
+            width,
+            height,
+        }
+    }
+
+    fn node(element: BoxElement) -> ElementNode {
+        ElementNode::Layout(LayoutElement::Box(element))
+    }
+
+    fn text() -> ElementNode {
+        ElementNode::Content(ContentElement::Text(TextElement {
+            id: None,
+            children: Vec::new(),
+            font: Arc::from("sans"),
+            weight: 400,
+            size: 16.0,
+            color: crate::Color::BLACK,
+            italic: false,
+            underline: false,
+            overflow: TextOverflow::Wrap,
+        }))
+    }
+
+    #[test]
+    fn test_box_units() {
+        let mut outer = r#box(
+            LayoutLength::ParentWidth(0.5),
+            LayoutLength::SceneUnit(100.0),
+        );
+        outer.children.push(node(r#box(
+            LayoutLength::ParentWidth(0.5),
+            LayoutLength::ParentHeight(0.25),
+        )));
+        outer.children.push(node(r#box(
+            LayoutLength::Inch(1.0),
+            LayoutLength::Point(72.0),
+        )));
+        let tree = layout(&scene(vec![node(outer)]));
+        let outer = &tree.children[0];
+        assert_eq!(outer.rect, Rect::new(0.0, 0.0, 400.0, 200.0));
+        assert_eq!(outer.children[0].rect, Rect::new(0.0, 0.0, 200.0, 50.0));
+        assert_eq!(outer.children[1].rect, Rect::new(0.0, 0.0, 96.0, 96.0));
+    }
+
+    #[test]
+    fn test_calc_lengths() {
+        let mut outer = r#box(
+            LayoutLength::parse_from("1pw - 24px * 2").expect("valid"),
+            LayoutLength::parse_from("clamp(10px, 0.5sh, 200px)").expect("valid"),
+        );
+        outer.children.push(node(r#box(
+            LayoutLength::parse_from("max(0.5pw, 300px)").expect("valid"),
+            LayoutLength::parse_from("min(1ph, 1in) / 2").expect("valid"),
+        )));
+        let tree = layout(&scene(vec![node(outer)]));
+        let outer = &tree.children[0];
+        assert_eq!(outer.rect, Rect::new(0.0, 0.0, 752.0, 200.0));
+        assert_eq!(outer.children[0].rect, Rect::new(0.0, 0.0, 376.0, 48.0));
+    }
+
+    #[test]
+    fn test_hidpi_physical_size() {
+        let lengths = [
+            LayoutLength::Inch(1.0),
+            LayoutLength::Centimeter(2.54),
+            LayoutLength::Millimeter(25.4),
+            LayoutLength::Point(72.0),
+            LayoutLength::Pixel(96.0),
+            LayoutLength::SceneUnit(48.0),
+        ];
+        let mut scene = scene(Vec::new());
+        for (metrics, expected) in [
+            (DisplayMetrics::default(), 96.0),
+            (DisplayMetrics::from_scale_factor(2.0), 192.0),
+        ] {
+            scene.metrics = DisplayMetrics {
+                scale: 2.0,
+                ..metrics
+            };
+            let ctx = LengthContext::for_scene(&scene, None);
+            for length in &lengths {
+                let px = length.resolve(&ctx);
+                assert!((px - expected).abs() < 1e-3, "{length:?} resolved to {px}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_margin_overrides() {
+        let margin = MarginElement {
+            id: Some(id("margin")),
+            children: vec![text()],
+            base: Some(LayoutLength::Pixel(10.0)),
+            horizontal: Some(LayoutLength::Pixel(20.0)),
+            left: None,
+            right: Some(LayoutLength::Pixel(30.0)),
+            vertical: None,
+            top: Some(LayoutLength::SceneHeight(0.1)),
+            bottom: None,
+        };
+        let root = ElementNode::Layout(LayoutElement::Margin(margin));
+        let tree = layout(&scene(vec![root]));
+        let margin = &tree.children[0];
+        assert_eq!(margin.id, Some(id("margin")));
+        assert_eq!(margin.rect, Rect::new(20.0, 60.0, 750.0, 530.0));
+        assert_eq!(margin.children[0].rect, Rect::new(20.0, 60.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_array_backward() {
+        let array = ArrayElement {
+            id: None,
+            children: vec![
+                r#box(LayoutLength::Pixel(100.0), LayoutLength::Pixel(10.0)),
+                r#box(LayoutLength::Pixel(50.0), LayoutLength::ParentHeight(1.0)),
+            ],
+            axis: crate::Axis::Horizontal,
+            direction: LinearDirection::Backward,
+        };
+        let root = ElementNode::Layout(LayoutElement::Array(array));
+        let tree = layout(&scene(vec![root]));
+        let rects: Vec<_> = tree.children[0].children.iter().map(|c| c.rect).collect();
+        assert_eq!(
+            rects,
+            [
+                Rect::new(700.0, 0.0, 100.0, 10.0),
+                Rect::new(650.0, 0.0, 50.0, 600.0)
+            ]
+        );
+    }
+
+    fn item(grow: f32) -> FlexItemElement {
+        FlexItemElement {
+            id: None,
+            children: Vec::new(),
+            grow,
+            shrink: FlexItemElement::default_shrink(),
+            basis: None,
+            min: None,
+            max: None,
+            cross: None,
+            align: None,
+        }
+    }
+
+    fn flex_element(axis: crate::Axis, children: Vec<FlexItemElement>) -> FlexElement {
+        FlexElement {
+            id: None,
+            children,
+            axis,
+            direction: LinearDirection::Forward,
+            gap: None,
+            justify: FlexJustify::Start,
+            align: FlexAlign::Stretch,
+        }
+    }
+
+    fn flex_rects(flex: FlexElement) -> Vec<Rect> {
+        let tree = layout(&scene(vec![ElementNode::Layout(LayoutElement::Flex(flex))]));
+        tree.children[0].children.iter().map(|c| c.rect).collect()
+    }
+
+    #[test]
+    fn test_flex_proportions() {
+        let flex = flex_element(crate::Axis::Vertical, vec![item(1.0), item(2.0), item(1.0)]);
+        assert_eq!(
+            flex_rects(flex),
+            [
+                Rect::new(0.0, 0.0, 800.0, 150.0),
+                Rect::new(0.0, 150.0, 800.0, 300.0),
+                Rect::new(0.0, 450.0, 800.0, 150.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flex_basis_and_gap() {
+        let mut fixed = item(0.0);
+        fixed.basis = Some(LayoutLength::Pixel(200.0));
+        let mut flex = flex_element(crate::Axis::Horizontal, vec![fixed, item(1.0), item(3.0)]);
+        flex.gap = Some(LayoutLength::Pixel(20.0));
+        flex.direction = LinearDirection::Backward;
+        // 800 - 200 - 2 * 20 = 560 is split 1:3.
+        assert_eq!(
+            flex_rects(flex),
+            [
+                Rect::new(600.0, 0.0, 200.0, 600.0),
+                Rect::new(440.0, 0.0, 140.0, 600.0),
+                Rect::new(0.0, 0.0, 420.0, 600.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flex_overflow_shrink() {
+        let sized = |basis, shrink| {
+            let mut item = item(1.0);
+            item.basis = Some(LayoutLength::Pixel(basis));
+            item.shrink = shrink;
+            item
+        };
+        // 200px of overflow is taken from the shrinkable items, weighted by their basis.
+        let flex = flex_element(
+            crate::Axis::Horizontal,
+            vec![sized(600.0, 1.0), sized(200.0, 1.0), sized(200.0, 0.0)],
+        );
+        assert_eq!(
+            flex_rects(flex),
+            [
+                Rect::new(0.0, 0.0, 450.0, 600.0),
+                Rect::new(450.0, 0.0, 150.0, 600.0),
+                Rect::new(600.0, 0.0, 200.0, 600.0),
+            ]
+        );
+        // Without shrinkable items, the last item overflows the flex element.
+        let flex = flex_element(
+            crate::Axis::Horizontal,
+            vec![sized(600.0, 0.0), sized(300.0, 0.0)],
+        );
+        assert_eq!(flex_rects(flex)[1], Rect::new(600.0, 0.0, 300.0, 600.0));
+    }
+
+    #[test]
+    fn test_flex_min_max_clamp() {
+        let mut min = item(1.0);
+        min.basis = Some(LayoutLength::Pixel(400.0));
+        min.min = Some(LayoutLength::Pixel(350.0));
+        let mut shrinking = item(1.0);
+        shrinking.basis = Some(LayoutLength::Pixel(600.0));
+        // The minimum is hit first, so the remaining overflow goes to the other item.
+        let flex = flex_element(crate::Axis::Horizontal, vec![min, shrinking]);
+        assert_eq!(
+            flex_rects(flex),
+            [
+                Rect::new(0.0, 0.0, 350.0, 600.0),
+                Rect::new(350.0, 0.0, 450.0, 600.0),
+            ]
+        );
+        let mut capped = item(1.0);
+        capped.max = Some(LayoutLength::ParentWidth(0.25));
+        let flex = flex_element(crate::Axis::Horizontal, vec![capped, item(1.0)]);
+        assert_eq!(
+            flex_rects(flex),
+            [
+                Rect::new(0.0, 0.0, 200.0, 600.0),
+                Rect::new(200.0, 0.0, 600.0, 600.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flex_justify_align() {
+        let fixed = |align| {
+            let mut item = item(0.0);
+            item.basis = Some(LayoutLength::Pixel(100.0));
+            item.cross = Some(LayoutLength::Pixel(50.0));
+            item.align = align;
+            item
+        };
+        let mut flex = flex_element(
+            crate::Axis::Horizontal,
+            vec![fixed(None), fixed(Some(FlexAlign::End)), fixed(None)],
+        );
+        flex.align = FlexAlign::Center;
+        flex.justify = FlexJustify::SpaceBetween;
+        assert_eq!(
+            flex_rects(flex.clone()),
+            [
+                Rect::new(0.0, 275.0, 100.0, 50.0),
+                Rect::new(350.0, 550.0, 100.0, 50.0),
+                Rect::new(700.0, 275.0, 100.0, 50.0),
+            ]
+        );
+        flex.justify = FlexJustify::SpaceEvenly;
+        let xs: Vec<_> = flex_rects(flex.clone()).iter().map(|r| r.x).collect();
+        assert_eq!(xs, [125.0, 350.0, 575.0]);
+        flex.justify = FlexJustify::Center;
+        let xs: Vec<_> = flex_rects(flex).iter().map(|r| r.x).collect();
+        assert_eq!(xs, [250.0, 350.0, 450.0]);
+    }
+
+    fn grid_item(children: Vec<ElementNode>, column_span: usize) -> GridItemElement {
+        GridItemElement {
+            id: None,
+            children,
+            column_span: NonZeroUsize::new(column_span).expect("non-zero"),
+            row_span: GridItemElement::default_span(),
+        }
+    }
+
+    fn grid(children: Vec<GridItemElement>, limit: usize) -> GridElement {
+        GridElement {
+            id: None,
+            children,
+            axis: crate::Axis::Vertical,
+            direction: LinearDirection::Forward,
+            array_direction: LinearDirection::Forward,
+            array_limit: NonZeroUsize::new(limit).expect("non-zero"),
+            columns: GridTemplate::default(),
+            rows: GridTemplate::default(),
+            gap: None,
+            row_gap: None,
+            column_gap: None,
+        }
+    }
+
+    fn grid_rects(grid: GridElement) -> Vec<Rect> {
+        let tree = layout(&scene(vec![ElementNode::Layout(LayoutElement::Grid(grid))]));
+        tree.children[0].children.iter().map(|c| c.rect).collect()
+    }
+
+    #[test]
+    fn test_grid_cells() {
+        let cell = || grid_item(Vec::new(), 1);
+        let mut grid = grid(vec![cell(), cell(), cell()], 2);
+        grid.array_direction = LinearDirection::Backward;
+        assert_eq!(
+            grid_rects(grid),
+            [
+                Rect::new(400.0, 0.0, 400.0, 300.0),
+                Rect::new(0.0, 0.0, 400.0, 300.0),
+                Rect::new(400.0, 300.0, 400.0, 300.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_grid_tracks() {
+        let fill = || {
+            node(r#box(
+                LayoutLength::ParentWidth(1.0),
+                LayoutLength::ParentHeight(1.0),
+            ))
+        };
+        let content = node(r#box(LayoutLength::Pixel(128.0), LayoutLength::Pixel(40.0)));
+        let mut grid = grid(
+            vec![
+                grid_item(vec![fill()], 1),
+                grid_item(vec![content], 1),
+                grid_item(Vec::new(), 1),
+                grid_item(Vec::new(), 1),
+                grid_item(vec![fill()], 2),
+            ],
+            4,
+        );
+        grid.columns = "48px auto 1fr 2fr".parse().expect("valid template");
+        grid.rows = "auto".parse().expect("valid template");
+        grid.gap = Some(LayoutLength::Pixel(8.0));
+        // 800 - 3 * 8 - 48 - 128 = 600 is split 1:2, the implicit second row takes the rest.
+        assert_eq!(
+            grid_rects(grid.clone()),
+            [
+                Rect::new(0.0, 0.0, 48.0, 40.0),
+                Rect::new(56.0, 0.0, 128.0, 40.0),
+                Rect::new(192.0, 0.0, 200.0, 40.0),
+                Rect::new(400.0, 0.0, 400.0, 40.0),
+                Rect::new(0.0, 48.0, 184.0, 552.0),
+            ]
+        );
+        let tree = layout(&scene(vec![ElementNode::Layout(LayoutElement::Grid(grid))]));
+        assert_eq!(
+            tree.children[0].children[4].children[0].rect,
+            Rect::new(0.0, 48.0, 184.0, 552.0)
+        );
+    }
+
+    #[test]
+    fn test_grid_minmax() {
+        let mut grid = grid(vec![grid_item(Vec::new(), 1), grid_item(Vec::new(), 1)], 2);
+        grid.columns = "minmax(100px, 1fr) minmax(50px, 200px)"
+            .parse()
+            .expect("valid template");
+        let widths: Vec<_> = grid_rects(grid.clone()).iter().map(|r| r.width).collect();
+        assert_eq!(widths, [600.0, 200.0]);
+        // Fixed minimums overflow the grid instead of shrinking.
+        grid.columns = "minmax(500px, 1fr) minmax(400px, 1fr)"
+            .parse()
+            .expect("valid template");
+        let widths: Vec<_> = grid_rects(grid).iter().map(|r| r.width).collect();
+        assert_eq!(widths, [500.0, 400.0]);
+    }
+
+    fn scroll(children: Vec<ElementNode>) -> ScrollElement {
+        ScrollElement {
+            id: None,
+            children,
+            axis: None,
+            offset_x: None,
+            offset_y: None,
+            item_extent: None,
+        }
+    }
+
+    fn scroll_layout(scroll: ScrollElement) -> LayoutNode {
+        let root = ElementNode::Layout(LayoutElement::Scroll(scroll));
+        let mut tree = layout(&scene(vec![root]));
+        tree.children.remove(0)
+    }
+
+    #[test]
+    fn test_scroll_clamp_offset() {
+        let mut scroll = scroll(vec![
+            node(r#box(
+                LayoutLength::Pixel(400.0),
+                LayoutLength::Pixel(1000.0),
+            )),
+            ElementNode::Behavior(BehaviorElement::PointerEnter),
+        ]);
+        scroll.offset_x = Some(LayoutLength::Pixel(50.0));
+        scroll.offset_y = Some(LayoutLength::Pixel(1000.0));
+        let node = scroll_layout(scroll);
+        let state = node.scroll.as_ref().expect("scroll state");
+        assert_eq!(state.content, Size::new(800.0, 1000.0));
+        assert_eq!((state.offset_x, state.offset_y), (0.0, 400.0));
+        assert_eq!(node.clip(), Some(Rect::new(0.0, 0.0, 800.0, 600.0)));
+        assert_eq!(node.children[0].rect, Rect::new(0.0, -400.0, 400.0, 1000.0));
+        assert_eq!(node.children[1].rect, node.rect);
+    }
+
+    #[test]
+    fn test_scroll_virtualized() {
+        let row = || {
+            node(r#box(
+                LayoutLength::ParentWidth(1.0),
+                LayoutLength::ParentHeight(1.0),
+            ))
+        };
+        let mut scroll = scroll((0..10_000).map(|_| row()).collect());
+        scroll.axis = Some(crate::Axis::Vertical);
+        scroll.item_extent = Some(LayoutLength::Pixel(24.0));
+        scroll.offset_x = Some(LayoutLength::Pixel(50.0));
+        scroll.offset_y = Some(LayoutLength::Pixel(2412.0));
+        let node = scroll_layout(scroll.clone());
+        let state = node.scroll.as_ref().expect("scroll state");
+        assert_eq!(state.content, Size::new(800.0, 240_000.0));
+        assert_eq!((state.offset_x, state.offset_y), (0.0, 2412.0));
+        assert_eq!(state.visible, 100..126);
+        assert_eq!(node.children.len(), 26);
+        assert_eq!(node.children[0].rect, Rect::new(0.0, -12.0, 800.0, 24.0));
+        // Scrolling past the end shows the last rows.
+        scroll.offset_y = Some(LayoutLength::Pixel(1e9));
+        let node = scroll_layout(scroll.clone());
+        let state = node.scroll.as_ref().expect("scroll state");
+        assert_eq!(state.visible, 9975..10_000);
+        assert_eq!(
+            node.children.last().map(|child| child.rect),
+            Some(Rect::new(0.0, 576.0, 800.0, 24.0))
+        );
+
+        // Behavior children are not rows and are never culled.
+        let mut children: Vec<_> = (0..100).map(|_| row()).collect();
+        children.insert(0, ElementNode::Behavior(BehaviorElement::PointerEnter));
+        children.insert(50, ElementNode::Behavior(BehaviorElement::PointerAction));
+        scroll.children = children;
+        scroll.offset_y = Some(LayoutLength::Pixel(1080.0));
+        let node = scroll_layout(scroll);
+        let state = node.scroll.as_ref().expect("scroll state");
+        assert_eq!(state.content, Size::new(800.0, 2400.0));
+        assert_eq!(state.visible, 45..70);
+        assert_eq!(state.behaviors, [0, 50]);
+        assert_eq!(node.children.len(), 27);
+        assert!(node.has_behavior(ElementKind::PointerEnter));
+        assert!(node.has_behavior(ElementKind::PointerAction));
+        assert_eq!(node.child(0).map(|child| child.rect), Some(node.rect));
+        assert_eq!(
+            node.child(50).map(|child| child.kind),
+            Some(ElementKind::PointerAction)
+        );
+        // Rows skip the behaviors, so the first visible row is element 46
+        // and the rows on either side of the second behavior are adjacent.
+        assert_eq!(
+            node.child(46).map(|child| child.rect),
+            Some(Rect::new(0.0, 0.0, 800.0, 24.0))
+        );
+        assert_eq!(node.child(45), None);
+        assert_eq!(
+            node.child(49).map(|child| child.rect),
+            Some(Rect::new(0.0, 72.0, 800.0, 24.0))
+        );
+        assert_eq!(
+            node.child(51).map(|child| child.rect),
+            Some(Rect::new(0.0, 96.0, 800.0, 24.0))
+        );
+        let indices: Vec<_> = node.indexed_children().map(|(index, _)| index).collect();
+        assert_eq!(indices[..5], [0, 50, 46, 47, 48]);
+        assert_eq!(indices[5..7], [49, 51]);
+        assert_eq!(indices.last(), Some(&71));
+    }
+
+    #[test]
+    fn test_align_with_offset() {
+        let align = AlignElement {
+            id: None,
+            children: vec![node(r#box(
+                LayoutLength::Pixel(100.0),
+                LayoutLength::Pixel(50.0),
+            ))],
+            origin: Alignment {
+                horizontal: HorizontalAlign::Right,
+                vertical: VerticalAlign::Bottom,
+            },
+            target: AlignElement::default_align(),
+            x_offset: Some(LayoutLength::Pixel(-10.0)),
+            y_offset: None,
+        };
+        let root = ElementNode::Layout(LayoutElement::Align(align));
+        let tree = layout(&scene(vec![root]));
+        assert_eq!(
+            tree.children[0].children[0].rect,
+            Rect::new(290.0, 250.0, 100.0, 50.0)
+        );
+    }
+
+    #[test]
+    fn test_transform_stacking() {
+        let mut knob = r#box(LayoutLength::Pixel(20.0), LayoutLength::Pixel(10.0));
+        knob.children = vec![
+            ElementNode::Behavior(BehaviorElement::Transform(TransformElement {
+                rotate: 90.0,
+                translate_x: Some(LayoutLength::ParentWidth(0.5)),
+                ..TransformElement::default()
+            })),
+            ElementNode::Behavior(BehaviorElement::Stacking(crate::StackingElement {
+                id: None,
+                z_index: 1,
+            })),
+        ];
+        let align = AlignElement {
+            id: None,
+            children: vec![
+                node(knob),
+                node(r#box(
+                    LayoutLength::Pixel(800.0),
+                    LayoutLength::Pixel(600.0),
+                )),
+            ],
+            origin: AlignElement::default_align(),
+            target: AlignElement::default_align(),
+            x_offset: None,
+            y_offset: None,
+        };
+        let root = ElementNode::Layout(LayoutElement::Align(align));
+        let tree = layout(&scene(vec![root]));
+        let knob = &tree.children[0].children[0];
+        // Laid out centered, then rotated around its center and moved by half its width.
+        assert_eq!(knob.rect, Rect::new(390.0, 295.0, 20.0, 10.0));
+        let bounds = knob.bounds();
+        let round = |rect: Rect| {
+            Rect::new(
+                rect.x.round(),
+                rect.y.round(),
+                rect.width.round(),
+                rect.height.round(),
+            )
+        };
+        assert_eq!(round(bounds), Rect::new(405.0, 290.0, 10.0, 20.0));
+        // Behavior children follow the transform of their parent.
+        assert_eq!(knob.children[0].transform, knob.transform);
+        assert_eq!(knob.z_index, 1);
+
+        // The knob is hit where it is drawn, above its later sibling.
+        assert_eq!(tree.hit_test(408.0, 305.0), Some(ElementPath(vec![0, 0])));
+        assert_eq!(tree.hit_test(392.0, 297.0), Some(ElementPath(vec![0, 1])));
+    }
+
+    #[test]
+    fn test_content_box_extent() {
+        let mut inner = r#box(LayoutLength::Pixel(30.0), LayoutLength::Pixel(40.0));
+        inner
+            .children
+            .push(ElementNode::Behavior(BehaviorElement::PointerEnter));
+        let content_box = ContentBoxElement {
+            id: None,
+            children: vec![
+                node(inner),
+                node(r#box(LayoutLength::Pixel(60.0), LayoutLength::Pixel(10.0))),
+                ElementNode::Behavior(BehaviorElement::Texture(TextureElement {
+                    id: None,
+                    opacity: 1.0,
+                    shader: None,
+                })),
+            ],
+        };
+        let margin = MarginElement {
+            id: None,
+            children: vec![ElementNode::Layout(LayoutElement::ContentBox(content_box))],
+            base: Some(LayoutLength::Pixel(5.0)),
+            horizontal: None,
+            left: None,
+            right: None,
+            vertical: None,
+            top: None,
+            bottom: None,
+        };
+        let root = ElementNode::Layout(LayoutElement::Margin(margin));
+        let tree = layout(&scene(vec![root]));
+        let content_box = &tree.children[0].children[0];
+        assert_eq!(content_box.rect, Rect::new(5.0, 5.0, 60.0, 40.0));
+        assert_eq!(
+            content_box.children[0].children[0].rect,
+            Rect::new(5.0, 5.0, 30.0, 40.0)
+        );
+        assert_eq!(content_box.children[2].kind, ElementKind::Texture);
+        assert_eq!(content_box.children[2].rect, content_box.rect);
+    }
+
+    #[test]
+    fn test_rect_fills_parent() {
+        let mut parent = r#box(LayoutLength::Pixel(30.0), LayoutLength::Pixel(40.0));
+        parent
+            .children
+            .push(ElementNode::Content(ContentElement::Rect(Box::default())));
+        let tree = layout(&scene(vec![node(parent)]));
+        assert_eq!(tree.children[0].children[0].kind, ElementKind::Rect);
+        assert_eq!(
+            tree.children[0].children[0].rect,
+            Rect::new(0.0, 0.0, 30.0, 40.0)
+        );
+    }
+
+    #[test]
+    fn test_image_sizes() {
+        let mut png = std::io::Cursor::new(Vec::new());
+        ::image::RgbaImage::new(40, 20)
+            .write_to(&mut png, ::image::ImageFormat::Png)
+            .expect("encode test image");
+        let mut images = ImageCache::default();
+        images.register("knob", png.into_inner());
+        let with_images = Resources {
+            images: Some(&images),
+            ..Resources::default()
+        };
+        let image = |width: Option<f32>, height: Option<f32>| {
+            ElementNode::Content(ContentElement::Image(Box::new(ImageElement {
+                id: None,
+                source: crate::ImageSource::Asset { id: "knob".into() },
+                fit: crate::ImageFit::Contain,
+                width: width.map(LayoutLength::Pixel),
+                height: height.map(LayoutLength::Pixel),
+            })))
+        };
+        let size = |image: ElementNode, parent: f32| {
+            let mut parent = r#box(LayoutLength::Pixel(parent), LayoutLength::Pixel(parent));
+            parent.children = vec![ElementNode::Layout(LayoutElement::ContentBox(
+                ContentBoxElement {
+                    id: None,
+                    children: vec![image],
+                },
+            ))];
+            let tree = scene(vec![node(parent)])
+                .layout_with(with_images)
+                .expect("load images");
+            tree.children[0].children[0].rect.size()
+        };
+        // Content boxes size around the intrinsic size, shrunk to fit the parent.
+        assert_eq!(size(image(None, None), 100.0), Size::new(40.0, 20.0));
+        assert_eq!(size(image(None, None), 10.0), Size::new(10.0, 5.0));
+        // A single side follows the aspect ratio.
+        assert_eq!(size(image(Some(10.0), None), 100.0), Size::new(10.0, 5.0));
+        assert_eq!(size(image(None, Some(30.0)), 100.0), Size::new(60.0, 30.0));
+        assert_eq!(
+            size(image(Some(7.0), Some(9.0)), 100.0),
+            Size::new(7.0, 9.0)
+        );
+        // Images that can not be loaded fail the layout.
+        let missing = ElementNode::Content(ContentElement::Image(Box::new(ImageElement {
+            id: None,
+            source: crate::ImageSource::Asset {
+                id: "missing".into(),
+            },
+            fit: crate::ImageFit::Contain,
+            width: Some(LayoutLength::Pixel(10.0)),
+            height: Some(LayoutLength::Pixel(10.0)),
+        })));
+        let mut parent = r#box(LayoutLength::Pixel(10.0), LayoutLength::Pixel(10.0));
+        parent.children = vec![missing];
+        let broken = scene(vec![node(parent)]).layout_with(with_images);
+        assert!(
+            matches!(broken, Err(ImageError::UnknownAsset(_))),
+            "{broken:?}"
+        );
+        // Without an image cache, images occupy no space.
+        let tree = layout(&scene(vec![image(None, None)]));
+        assert_eq!(tree.children[0].kind, ElementKind::Image);
+        assert_eq!(tree.children[0].rect.size(), Size::ZERO);
+    }
+
+    #[test]
+    fn test_content_box_text() {
+        let ElementNode::Content(ContentElement::Text(mut element)) = text() else {
+            unreachable!();
+        };
+        element.children = vec![
+            TextChild::from("Hello "),
+            TextChild::Span(SpanElement {
+                children: vec![TextChild::from("world")],
+                weight: Some(700),
+                ..SpanElement::default()
+            }),
+        ];
+        let content_box = |element: TextElement| ContentBoxElement {
+            id: None,
+            children: vec![ElementNode::Content(ContentElement::Text(element))],
+        };
+        let text = TextSystem::bundled();
+        let with_text = Resources {
+            text: Some(&text),
+            ..Resources::default()
+        };
+        let scene_one = scene(vec![ElementNode::Layout(LayoutElement::ContentBox(
+            content_box(element.clone()),
+        ))]);
+        let ctx = LengthContext::for_scene(&scene_one, None);
+        let runs = TextRun::of(&element, &ctx);
+        assert_eq!(runs.len(), 2);
+        let expected = text
+            .layout(&runs, &TextStyle::of(&element, &ctx), None)
+            .size();
+        let tree = scene_one.layout_with(with_text).expect("layout");
+        assert_eq!(tree.children[0].rect, Rect::from_size(0.0, 0.0, expected));
+        assert_eq!(tree.children[0].children[0].rect, tree.children[0].rect);
+        // Without a text system, text is not measured.
+        assert_eq!(layout(&scene_one).children[0].rect.size(), Size::ZERO);
+
+        // Narrow parents wrap the text.
+        let mut parent = r#box(LayoutLength::Pixel(100.0), LayoutLength::Pixel(100.0));
+        parent.children = vec![ElementNode::Layout(LayoutElement::ContentBox(content_box(
+            element,
+        )))];
+        let tree = scene(vec![node(parent)])
+            .layout_with(with_text)
+            .expect("layout");
+        let wrapped = tree.children[0].children[0].rect;
+        assert!(wrapped.width <= 100.0, "{wrapped:?}");
+        assert!(wrapped.height > expected.height, "{wrapped:?}");
+    }
+}
@@ -0,0 +1,116 @@
+//! Resolution of flexible lengths along the main axis of a flex element.
+//!
+//! Follows the CSS flexible box algorithm for a single line,
+//! except that the automatic minimum size of an item is zero since content is not measured.
+
+use super::count;
+use crate::FlexJustify;
+
+/// Main axis constraints of a flex item, in device pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FlexFactors {
+    pub grow: f32,
+    pub shrink: f32,
+    pub basis: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl FlexFactors {
+    /// Clamp a main size to the bounds of the item.
+    /// The minimum wins over the maximum.
+    const fn clamp(self, size: f32) -> f32 {
+        size.min(self.max).max(self.min)
+    }
+}
+
+/// Resolve the main size of every item so that they fill the available space
+/// as closely as their constraints allow.
+pub fn resolve_sizes(items: &[FlexFactors], available: f32) -> Vec<f32> {
+    let mut sizes: Vec<f32> = items.iter().map(|item| item.clamp(item.basis)).collect();
+    let growing = sizes.iter().sum::<f32>() < available;
+    // Items that cannot flex in the needed direction keep their hypothetical size.
+    let mut frozen: Vec<bool> = items
+        .iter()
+        .zip(&sizes)
+        .map(|(item, size)| {
+            let factor = if growing { item.grow } else { item.shrink };
+            factor <= 0.0 || (growing && item.basis > *size) || (!growing && item.basis < *size)
+        })
+        .collect();
+    let mut targets = vec![0.0; items.len()];
+    while frozen.contains(&false) {
+        let used: f32 = items
+            .iter()
+            .zip(&sizes)
+            .zip(&frozen)
+            .map(|((item, size), frozen)| if *frozen { *size } else { item.basis })
+            .sum();
+        let free = available - used;
+        // Shrinking is weighted by the basis, so large items give up more space.
+        let weight = |item: &FlexFactors| {
+            if growing {
+                item.grow
+            } else {
+                item.shrink * item.basis
+            }
+        };
+        let total: f32 = items
+            .iter()
+            .zip(&frozen)
+            .filter(|(_, frozen)| !**frozen)
+            .map(|(item, _)| weight(item))
+            .sum();
+        let mut violation = 0.0;
+        for (i, item) in items.iter().enumerate().filter(|(i, _)| !frozen[*i]) {
+            targets[i] = if total > 0.0 {
+                item.basis + free * weight(item) / total
+            } else {
+                item.basis
+            };
+            sizes[i] = item.clamp(targets[i]);
+            violation += sizes[i] - targets[i];
+        }
+        // Freeze the items clamped in the direction of the total violation, then retry the rest.
+        let violated = |i: usize| {
+            if violation > 0.0 {
+                sizes[i] > targets[i]
+            } else if violation < 0.0 {
+                sizes[i] < targets[i]
+            } else {
+                true
+            }
+        };
+        let freeze: Vec<usize> = (0..items.len())
+            .filter(|i| !frozen[*i] && violated(*i))
+            .collect();
+        if freeze.is_empty() {
+            break;
+        }
+        for i in freeze {
+            frozen[i] = true;
+        }
+    }
+    sizes
+}
+
+/// Offset of the first item and extra space between items
+/// for the free space left on the main axis.
+pub fn justify(justify: FlexJustify, free: f32, items: usize) -> (f32, f32) {
+    // Overflowing items are never spread out.
+    let spare = free.max(0.0);
+    match justify {
+        FlexJustify::End => (free, 0.0),
+        FlexJustify::Center => (free / 2.0, 0.0),
+        FlexJustify::SpaceBetween if items > 1 => (0.0, spare / count(items - 1)),
+        FlexJustify::SpaceAround if items > 0 => {
+            let around = spare / count(items);
+            (around / 2.0, around)
+        }
+        FlexJustify::SpaceEvenly => {
+            let evenly = spare / count(items + 1);
+            (evenly, evenly)
+        }
+        FlexJustify::Start | FlexJustify::SpaceBetween | FlexJustify::SpaceAround => (0.0, 0.0),
+    }
+}
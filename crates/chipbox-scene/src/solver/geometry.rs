@@ -0,0 +1,337 @@
+use crate::Axis;
+
+/// A two-dimensional size in pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Size {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Size {
+    pub const ZERO: Self = Self::new(0.0, 0.0);
+
+    #[must_use]
+    pub const fn new(width: f32, height: f32) -> Self {
+        Self { width, height }
+    }
+
+    /// Construct a size from its extents along the main and cross axis.
+    #[must_use]
+    pub const fn from_axes(axis: Axis, main: f32, cross: f32) -> Self {
+        match axis {
+            Axis::Horizontal => Self::new(main, cross),
+            Axis::Vertical => Self::new(cross, main),
+        }
+    }
+
+    /// The extent along the given axis.
+    #[must_use]
+    pub const fn main(self, axis: Axis) -> f32 {
+        match axis {
+            Axis::Horizontal => self.width,
+            Axis::Vertical => self.height,
+        }
+    }
+
+    /// The extent perpendicular to the given axis.
+    #[must_use]
+    pub const fn cross(self, axis: Axis) -> f32 {
+        match axis {
+            Axis::Horizontal => self.height,
+            Axis::Vertical => self.width,
+        }
+    }
+}
+
+/// A position in pixel space.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Point {
+    #[must_use]
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+}
+
+/// An axis-aligned rectangle in pixel space.
+/// The origin is the top-left corner of the scene.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    #[must_use]
+    pub const fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Construct a rectangle at the given position with the given size.
+    #[must_use]
+    pub const fn from_size(x: f32, y: f32, size: Size) -> Self {
+        Self::new(x, y, size.width, size.height)
+    }
+
+    #[must_use]
+    pub const fn size(self) -> Size {
+        Size::new(self.width, self.height)
+    }
+
+    #[must_use]
+    pub const fn right(self) -> f32 {
+        self.x + self.width
+    }
+
+    #[must_use]
+    pub const fn bottom(self) -> f32 {
+        self.y + self.height
+    }
+
+    /// The start coordinate along the given axis.
+    #[must_use]
+    pub const fn start(self, axis: Axis) -> f32 {
+        match axis {
+            Axis::Horizontal => self.x,
+            Axis::Vertical => self.y,
+        }
+    }
+
+    /// The start coordinate perpendicular to the given axis.
+    #[must_use]
+    pub const fn cross_start(self, axis: Axis) -> f32 {
+        match axis {
+            Axis::Horizontal => self.y,
+            Axis::Vertical => self.x,
+        }
+    }
+
+    /// Construct a rectangle from its position and extents along the main and cross axis.
+    #[must_use]
+    pub const fn from_axes(axis: Axis, main: f32, cross: f32, size: Size) -> Self {
+        match axis {
+            Axis::Horizontal => Self::from_size(main, cross, size),
+            Axis::Vertical => Self::from_size(cross, main, size),
+        }
+    }
+
+    /// Shrink the rectangle by the given insets.
+    /// The resulting size is clamped to zero.
+    #[must_use]
+    pub const fn inset(self, left: f32, top: f32, right: f32, bottom: f32) -> Self {
+        Self::new(
+            self.x + left,
+            self.y + top,
+            (self.width - left - right).max(0.0),
+            (self.height - top - bottom).max(0.0),
+        )
+    }
+
+    /// Whether the point lies within the rectangle.
+    /// The right and bottom edges are exclusive, so adjacent rectangles never overlap.
+    #[must_use]
+    pub const fn contains(self, x: f32, y: f32) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+
+    /// Whether the rectangles share any area.
+    #[must_use]
+    pub const fn intersects(self, other: Self) -> bool {
+        let shared = self.intersection(other);
+        shared.width > 0.0 && shared.height > 0.0
+    }
+
+    /// The area shared by both rectangles.
+    /// The resulting size is clamped to zero.
+    #[must_use]
+    pub const fn intersection(self, other: Self) -> Self {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        Self::new(
+            x,
+            y,
+            (self.right().min(other.right()) - x).max(0.0),
+            (self.bottom().min(other.bottom()) - y).max(0.0),
+        )
+    }
+
+    /// The rectangle spanned by two corner points, in any order.
+    #[must_use]
+    pub const fn from_points(x0: f32, y0: f32, x1: f32, y1: f32) -> Self {
+        let x = x0.min(x1);
+        let y = y0.min(y1);
+        Self::new(x, y, x0.max(x1) - x, y0.max(y1) - y)
+    }
+
+    /// The smallest rectangle containing both rectangles.
+    #[must_use]
+    pub const fn union(self, other: Self) -> Self {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Self::new(
+            x,
+            y,
+            self.right().max(other.right()) - x,
+            self.bottom().max(other.bottom()) - y,
+        )
+    }
+}
+
+/// A 2D affine transformation in pixel space.
+///
+/// Maps `(x, y)` to `(a * x + c * y + e, b * x + d * y + f)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Affine {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Default for Affine {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Affine {
+    pub const IDENTITY: Self = Self::scale(1.0, 1.0);
+
+    #[must_use]
+    pub const fn translate(x: f32, y: f32) -> Self {
+        Self {
+            a: 1.0,
+            b: 0.0,
+            c: 0.0,
+            d: 1.0,
+            e: x,
+            f: y,
+        }
+    }
+
+    #[must_use]
+    pub const fn scale(x: f32, y: f32) -> Self {
+        Self {
+            a: x,
+            b: 0.0,
+            c: 0.0,
+            d: y,
+            e: 0.0,
+            f: 0.0,
+        }
+    }
+
+    /// Rotation by an angle in radians, clockwise as y points down.
+    #[must_use]
+    pub fn rotate(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self {
+            a: cos,
+            b: sin,
+            c: -sin,
+            d: cos,
+            e: 0.0,
+            f: 0.0,
+        }
+    }
+
+    /// Skew by angles in radians along the x and y axis.
+    #[must_use]
+    pub fn skew(x: f32, y: f32) -> Self {
+        Self {
+            a: 1.0,
+            b: y.tan(),
+            c: x.tan(),
+            d: 1.0,
+            e: 0.0,
+            f: 0.0,
+        }
+    }
+
+    /// The transformation applying `self` first and `then` after it.
+    #[must_use]
+    pub fn then(self, then: Self) -> Self {
+        Self {
+            a: then.a.mul_add(self.a, then.c * self.b),
+            b: then.b.mul_add(self.a, then.d * self.b),
+            c: then.a.mul_add(self.c, then.c * self.d),
+            d: then.b.mul_add(self.c, then.d * self.d),
+            e: then.a.mul_add(self.e, then.c.mul_add(self.f, then.e)),
+            f: then.b.mul_add(self.e, then.d.mul_add(self.f, then.f)),
+        }
+    }
+
+    /// The same transformation in a space moved by `x`, `y`.
+    #[must_use]
+    pub fn translated(self, x: f32, y: f32) -> Self {
+        if self == Self::IDENTITY {
+            return self;
+        }
+        Self::translate(-x, -y)
+            .then(self)
+            .then(Self::translate(x, y))
+    }
+
+    /// The transformation undoing this one.
+    /// Returns [`None`] if it collapses the plane, as a scale of zero does.
+    #[must_use]
+    pub fn invert(self) -> Option<Self> {
+        let determinant = self.a.mul_add(self.d, -self.b * self.c);
+        if determinant == 0.0 || !determinant.is_finite() {
+            return None;
+        }
+        let (a, b, c, d) = (
+            self.d / determinant,
+            -self.b / determinant,
+            -self.c / determinant,
+            self.a / determinant,
+        );
+        Some(Self {
+            a,
+            b,
+            c,
+            d,
+            e: -a.mul_add(self.e, c * self.f),
+            f: -b.mul_add(self.e, d * self.f),
+        })
+    }
+
+    #[must_use]
+    pub const fn apply(self, point: Point) -> Point {
+        Point::new(
+            self.a.mul_add(point.x, self.c.mul_add(point.y, self.e)),
+            self.b.mul_add(point.x, self.d.mul_add(point.y, self.f)),
+        )
+    }
+
+    /// The smallest axis-aligned rectangle containing the transformed rectangle.
+    #[must_use]
+    pub fn bounds(self, rect: Rect) -> Rect {
+        let corners = [
+            (rect.x, rect.y),
+            (rect.right(), rect.y),
+            (rect.x, rect.bottom()),
+            (rect.right(), rect.bottom()),
+        ]
+        .map(|(x, y)| self.apply(Point::new(x, y)));
+        let (mut min, mut max) = (corners[0], corners[0]);
+        for corner in &corners[1..] {
+            min = Point::new(min.x.min(corner.x), min.y.min(corner.y));
+            max = Point::new(max.x.max(corner.x), max.y.max(corner.y));
+        }
+        Rect::from_points(min.x, min.y, max.x, max.y)
+    }
+}
@@ -0,0 +1,306 @@
+//! Item placement and track sizing of grid elements.
+//!
+//! Tracks are sized along each axis independently, following a simplified version
+//! of the CSS grid track sizing algorithm.
+
+use super::{LengthContext, count};
+use crate::{GridTemplate, GridTrack, LinearDirection};
+
+/// Position of an item in grid coordinates along the main and cross axis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Placement {
+    pub line: usize,
+    pub slot: usize,
+    pub main_span: usize,
+    pub cross_span: usize,
+}
+
+/// Place items of the given main and cross spans in order,
+/// filling every line up to the limit before starting the next one.
+/// Cells covered by earlier items are skipped.
+pub fn place(spans: impl IntoIterator<Item = (usize, usize)>, limit: usize) -> Vec<Placement> {
+    let mut occupied: Vec<Vec<bool>> = Vec::new();
+    let is_free = |occupied: &Vec<Vec<bool>>, line: usize, slot: usize| {
+        occupied.get(line).is_none_or(|cells| !cells[slot])
+    };
+    let (mut line, mut slot) = (0, 0);
+    spans
+        .into_iter()
+        .map(|(main_span, cross_span)| {
+            let cross_span = cross_span.min(limit);
+            loop {
+                if slot + cross_span > limit {
+                    line += 1;
+                    slot = 0;
+                } else if (line..line + main_span)
+                    .all(|l| (slot..slot + cross_span).all(|s| is_free(&occupied, l, s)))
+                {
+                    break;
+                } else {
+                    slot += 1;
+                }
+            }
+            if occupied.len() < line + main_span {
+                occupied.resize(line + main_span, vec![false; limit]);
+            }
+            for cells in &mut occupied[line..line + main_span] {
+                cells[slot..slot + cross_span].fill(true);
+            }
+            let placement = Placement {
+                line,
+                slot,
+                main_span,
+                cross_span,
+            };
+            slot += cross_span;
+            placement
+        })
+        .collect()
+}
+
+/// An item as seen by the track sizing of one axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrackItem {
+    pub start: usize,
+    pub span: usize,
+    /// Extent of the content of the item along the axis.
+    pub content: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MinSize {
+    Fixed(f32),
+    Auto,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MaxSize {
+    Fixed(f32),
+    Auto,
+    Flex(f32),
+}
+
+fn min_size(track: &GridTrack, ctx: &LengthContext) -> MinSize {
+    match track {
+        GridTrack::Length(length) => MinSize::Fixed(length.resolve(ctx).max(0.0)),
+        GridTrack::Fraction(_) | GridTrack::Auto => MinSize::Auto,
+        GridTrack::MinMax { min, .. } => min_size(min, ctx),
+    }
+}
+
+fn max_size(track: &GridTrack, ctx: &LengthContext) -> MaxSize {
+    match track {
+        GridTrack::Length(length) => MaxSize::Fixed(length.resolve(ctx).max(0.0)),
+        GridTrack::Fraction(flex) => MaxSize::Flex(flex.max(0.0)),
+        GridTrack::Auto => MaxSize::Auto,
+        GridTrack::MinMax { max, .. } => max_size(max, ctx),
+    }
+}
+
+/// Resolved tracks along one axis of a grid.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tracks {
+    pub sizes: Vec<f32>,
+    /// Offset of every track from the start of the grid.
+    pub offsets: Vec<f32>,
+    /// Extent of the grid along the axis.
+    pub extent: f32,
+}
+
+impl Tracks {
+    /// Resolve the first `tracks` tracks of the template,
+    /// so that they fill the extent of the grid as closely as their sizing functions allow.
+    pub fn new(
+        template: &GridTemplate,
+        tracks: usize,
+        items: &[TrackItem],
+        extent: f32,
+        gap: f32,
+        ctx: &LengthContext,
+    ) -> Self {
+        let sizes = size_tracks(template, tracks, items, extent, gap, ctx);
+        let offsets = sizes
+            .iter()
+            .scan(0.0, |offset, size| {
+                let start = *offset;
+                *offset += size + gap;
+                Some(start)
+            })
+            .collect();
+        Self {
+            sizes,
+            offsets,
+            extent,
+        }
+    }
+
+    /// Offset and size of a span of tracks, given the direction of distribution.
+    pub fn span(&self, direction: LinearDirection, start: usize, span: usize) -> (f32, f32) {
+        let end = start + span - 1;
+        let offset = self.offsets[start];
+        let size = self.offsets[end] + self.sizes[end] - offset;
+        match direction {
+            LinearDirection::Forward => (offset, size),
+            LinearDirection::Backward => (self.extent - offset - size, size),
+        }
+    }
+}
+
+fn size_tracks(
+    template: &GridTemplate,
+    tracks: usize,
+    items: &[TrackItem],
+    extent: f32,
+    gap: f32,
+    ctx: &LengthContext,
+) -> Vec<f32> {
+    let functions: Vec<_> = (0..tracks)
+        .map(|i| {
+            let track = template.track(i);
+            // A plain fraction may shrink down to zero, regardless of its content.
+            let min = match track {
+                GridTrack::Fraction(_) => MinSize::Fixed(0.0),
+                track => min_size(track, ctx),
+            };
+            (min, max_size(track, ctx))
+        })
+        .collect();
+    let mut base: Vec<f32> = functions
+        .iter()
+        .map(|(min, _)| match min {
+            MinSize::Fixed(size) => *size,
+            MinSize::Auto => 0.0,
+        })
+        .collect();
+    let mut limit: Vec<f32> = functions
+        .iter()
+        .map(|(_, max)| match max {
+            MaxSize::Fixed(size) => *size,
+            MaxSize::Auto => 0.0,
+            MaxSize::Flex(_) => f32::INFINITY,
+        })
+        .collect();
+
+    // Fit content-sized tracks to their items, starting with the narrowest spans.
+    let mut items: Vec<_> = items
+        .iter()
+        .filter(|item| item.start + item.span <= tracks)
+        .collect();
+    items.sort_by_key(|item| item.span);
+    for item in items {
+        let span = item.start..item.start + item.span;
+        if item.span == 1 {
+            let i = item.start;
+            if functions[i].0 == MinSize::Auto {
+                base[i] = base[i].max(item.content);
+            }
+            if functions[i].1 == MaxSize::Auto {
+                limit[i] = limit[i].max(item.content);
+            }
+            continue;
+        }
+        let auto: Vec<usize> = span
+            .clone()
+            .filter(|i| functions[*i].0 == MinSize::Auto)
+            .collect();
+        let gaps = gap * count(item.span - 1);
+        let needed = item.content - base[span].iter().sum::<f32>() - gaps;
+        if needed > 0.0 && !auto.is_empty() {
+            for i in &auto {
+                base[*i] += needed / count(auto.len());
+            }
+        }
+    }
+    for (limit, base) in limit.iter_mut().zip(&base) {
+        *limit = limit.max(*base);
+    }
+
+    let gaps = gap * count(tracks.saturating_sub(1));
+    let available = extent - gaps;
+    let mut sizes = base;
+    grow_to_limits(&mut sizes, &limit, available);
+
+    let flexible: Vec<(usize, f32)> = functions
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (_, max))| match max {
+            MaxSize::Flex(flex) => Some((i, *flex)),
+            _ => None,
+        })
+        .collect();
+    if flexible.is_empty() {
+        // Without fractional tracks, content-sized tracks take up the remaining space.
+        let stretch: Vec<usize> = (0..tracks)
+            .filter(|i| functions[*i].1 == MaxSize::Auto)
+            .collect();
+        let free = available - sizes.iter().sum::<f32>();
+        if free > 0.0 && !stretch.is_empty() {
+            for i in &stretch {
+                sizes[*i] += free / count(stretch.len());
+            }
+        }
+    } else {
+        let fraction = fraction_size(&sizes, &flexible, available);
+        for (i, flex) in flexible {
+            sizes[i] = sizes[i].max(fraction * flex);
+        }
+    }
+    sizes
+}
+
+/// Grow tracks towards their limits, sharing the free space equally.
+fn grow_to_limits(sizes: &mut [f32], limit: &[f32], available: f32) {
+    loop {
+        let free = available - sizes.iter().sum::<f32>();
+        let growing: Vec<usize> = (0..sizes.len())
+            .filter(|i| sizes[*i] < limit[*i] && limit[*i].is_finite())
+            .collect();
+        if free <= 0.0 || growing.is_empty() {
+            break;
+        }
+        let share = free / count(growing.len());
+        let mut changed = false;
+        for i in growing {
+            let size = (sizes[i] + share).min(limit[i]);
+            changed |= size > sizes[i];
+            sizes[i] = size;
+        }
+        if !changed {
+            break;
+        }
+    }
+}
+
+/// Size of `1fr`, treating tracks whose base size exceeds their share as inflexible.
+fn fraction_size(sizes: &[f32], flexible: &[(usize, f32)], available: f32) -> f32 {
+    let mut flexible = flexible.to_vec();
+    loop {
+        let inflexible: f32 = (0..sizes.len())
+            .filter(|i| !flexible.iter().any(|(j, _)| i == j))
+            .map(|i| sizes[i])
+            .sum();
+        // Fractions summing to less than one leave part of the space unused.
+        let total = flexible.iter().map(|(_, flex)| flex).sum::<f32>().max(1.0);
+        let fraction = (available - inflexible).max(0.0) / total;
+        let before = flexible.len();
+        flexible.retain(|(i, flex)| sizes[*i] <= fraction * flex);
+        if flexible.len() == before {
+            return fraction;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_place_spans() {
+        let placements: Vec<_> = place([(1, 2), (1, 2), (1, 1), (2, 1), (1, 1), (1, 5)], 3)
+            .iter()
+            .map(|p| (p.line, p.slot))
+            .collect();
+        // The gap left in the first line is not backfilled.
+        assert_eq!(placements, [(0, 0), (1, 0), (1, 2), (2, 0), (2, 1), (4, 0)]);
+    }
+}
@@ -0,0 +1,93 @@
+use crate::{DisplayMetrics, ImageCache, LayoutLength, LengthExpr, Scene, Size, TextSystem};
+
+/// Everything needed to resolve a [`LayoutLength`] to device pixels
+/// and to measure content.
+#[derive(Clone, Copy, Debug)]
+pub struct LengthContext<'a> {
+    /// The size of the scene in device pixels.
+    pub scene: Size,
+    /// The size of the parent element in device pixels.
+    pub parent: Size,
+    pub metrics: DisplayMetrics,
+    /// Measures text content, which occupies no space without it.
+    pub text: Option<&'a TextSystem>,
+    /// Measures images, which occupy no space without it.
+    pub images: Option<&'a ImageCache>,
+}
+
+impl<'a> LengthContext<'a> {
+    /// Root context of the scene.
+    /// The parent of a root element is the scene itself.
+    #[must_use]
+    pub const fn for_scene(scene: &Scene, text: Option<&'a TextSystem>) -> Self {
+        let size = scene.px_size();
+        Self {
+            scene: size,
+            parent: size,
+            metrics: scene.metrics,
+            text,
+            images: None,
+        }
+    }
+
+    /// Derive a context for the children of an element of the given size.
+    #[must_use]
+    pub const fn with_parent(self, parent: Size) -> Self {
+        Self { parent, ..self }
+    }
+
+    /// Derive a context measuring images with the given cache.
+    #[must_use]
+    pub const fn with_images(self, images: Option<&'a ImageCache>) -> Self {
+        Self { images, ..self }
+    }
+}
+
+impl LayoutLength {
+    /// Resolve the length to device pixels.
+    #[must_use]
+    pub fn resolve(&self, ctx: &LengthContext) -> f32 {
+        let metrics = &ctx.metrics;
+        match *self {
+            Self::SceneUnit(v) => metrics.scene_units_to_device(v),
+            Self::SceneWidth(v) => v * ctx.scene.width,
+            Self::SceneHeight(v) => v * ctx.scene.height,
+            Self::ParentWidth(v) => v * ctx.parent.width,
+            Self::ParentHeight(v) => v * ctx.parent.height,
+            Self::Pixel(v) => metrics.logical_to_device(v),
+            Self::Millimeter(v) => metrics.inches_to_device(v / 25.4),
+            Self::Centimeter(v) => metrics.inches_to_device(v / 2.54),
+            Self::Inch(v) => metrics.inches_to_device(v),
+            Self::Point(v) => metrics.inches_to_device(v / 72.0),
+            Self::Calc(ref expr) => expr.resolve(ctx),
+            Self::Token(_) => 0.0,
+        }
+    }
+}
+
+impl LengthExpr {
+    /// Evaluate the expression to device pixels.
+    #[must_use]
+    pub fn resolve(&self, ctx: &LengthContext) -> f32 {
+        let fold = |exprs: &[Self], f: fn(f32, f32) -> f32| {
+            exprs
+                .iter()
+                .map(|expr| expr.resolve(ctx))
+                .reduce(f)
+                .unwrap_or(0.0)
+        };
+        match self {
+            Self::Length(length) => length.resolve(ctx),
+            Self::Sum(a, b) => a.resolve(ctx) + b.resolve(ctx),
+            Self::Difference(a, b) => a.resolve(ctx) - b.resolve(ctx),
+            Self::Product(expr, factor) => expr.resolve(ctx) * factor,
+            Self::Quotient(expr, divisor) => expr.resolve(ctx) / divisor,
+            Self::Min(exprs) => fold(exprs, f32::min),
+            Self::Max(exprs) => fold(exprs, f32::max),
+            Self::Clamp { min, value, max } => value
+                .resolve(ctx)
+                .min(max.resolve(ctx))
+                .max(min.resolve(ctx)),
+        }
+    }
+}
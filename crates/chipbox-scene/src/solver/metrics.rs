@@ -0,0 +1,77 @@
+/// Reference pixel density of a display with a device pixel ratio of 1.
+const REFERENCE_DPI: f32 = 96.0;
+
+/// Describes the display a scene is rendered to.
+///
+/// Scene dimensions and resolved lengths are in device pixels.
+/// Pixel and scene unit lengths are logical and scale with [`Self::device_pixel_ratio`],
+/// physical lengths are derived from [`Self::dpi`].
+#[derive(specta::Type, serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DisplayMetrics {
+    /// Physical pixel density in device pixels per inch.
+    #[serde(default = "DisplayMetrics::default_dpi")]
+    pub dpi: f32,
+    /// Device pixels per logical pixel.
+    #[serde(default = "DisplayMetrics::default_device_pixel_ratio")]
+    pub device_pixel_ratio: f32,
+    /// Logical pixels per scene unit.
+    #[serde(default = "DisplayMetrics::default_scale")]
+    pub scale: f32,
+}
+
+impl Default for DisplayMetrics {
+    fn default() -> Self {
+        Self {
+            dpi: Self::default_dpi(),
+            device_pixel_ratio: Self::default_device_pixel_ratio(),
+            scale: Self::default_scale(),
+        }
+    }
+}
+
+impl DisplayMetrics {
+    #[must_use]
+    pub const fn default_dpi() -> f32 {
+        REFERENCE_DPI
+    }
+
+    #[must_use]
+    pub const fn default_device_pixel_ratio() -> f32 {
+        1.0
+    }
+
+    #[must_use]
+    pub const fn default_scale() -> f32 {
+        1.0
+    }
+
+    /// Metrics for a display with the given scale factor, as reported by the windowing system.
+    /// The pixel density is assumed to scale along with it.
+    #[must_use]
+    pub const fn from_scale_factor(scale_factor: f32) -> Self {
+        Self {
+            dpi: REFERENCE_DPI * scale_factor,
+            device_pixel_ratio: scale_factor,
+            scale: Self::default_scale(),
+        }
+    }
+
+    /// Convert logical pixels to device pixels.
+    #[must_use]
+    pub const fn logical_to_device(&self, px: f32) -> f32 {
+        px * self.device_pixel_ratio
+    }
+
+    /// Convert scene units to device pixels.
+    #[must_use]
+    pub const fn scene_units_to_device(&self, su: f32) -> f32 {
+        self.logical_to_device(su * self.scale)
+    }
+
+    /// Convert inches to device pixels.
+    #[must_use]
+    pub const fn inches_to_device(&self, inches: f32) -> f32 {
+        inches * self.dpi
+    }
+}
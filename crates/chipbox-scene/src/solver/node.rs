@@ -0,0 +1,193 @@
+use std::ops::Range;
+
+use crate::{Affine, ElementId, ElementKind, ElementPath, Rect, Size};
+
+/// The resolved layout of a single element.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayoutNode {
+    pub id: Option<ElementId>,
+    pub kind: ElementKind,
+    /// Bounds of the element in scene pixel space.
+    /// Behavior elements share the bounds of their parent.
+    pub rect: Rect,
+    pub children: Vec<Self>,
+    /// Set for scroll elements, whose children are clipped to [`Self::rect`].
+    pub scroll: Option<ScrollState>,
+    /// Set for focusable behavior elements, see [`crate::FocusableElement::tab_index`].
+    pub tab_index: Option<i32>,
+    /// Maps [`Self::rect`] to scene pixel space, composed of the transforms
+    /// of the element and all of its ancestors.
+    pub transform: Affine,
+    /// Orders the element among its siblings, see [`crate::StackingElement::z_index`].
+    pub z_index: i32,
+}
+
+/// Resolved scroll position and content of a scroll element.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScrollState {
+    /// Size of the scrolled content, at least the size of the viewport.
+    pub content: Size,
+    /// Horizontal scroll position, clamped to the scrollable range.
+    pub offset_x: f32,
+    /// Vertical scroll position, clamped to the scrollable range.
+    pub offset_y: f32,
+    /// Rows present in [`LayoutNode::children`], counting every element child
+    /// that is not listed in [`Self::behaviors`].
+    /// Rows outside of the range were culled by virtualization.
+    pub visible: Range<usize>,
+    /// Element indices of the behavior children of a virtualized scroll element, in order.
+    /// They are never culled and precede the visible rows in [`LayoutNode::children`].
+    pub behaviors: Vec<usize>,
+}
+
+impl ScrollState {
+    /// Position in [`LayoutNode::children`] of the element child at the given index.
+    fn position(&self, index: usize) -> Option<usize> {
+        if let Ok(position) = self.behaviors.binary_search(&index) {
+            return Some(position);
+        }
+        let row = index - self.behaviors.partition_point(|&behavior| behavior < index);
+        if !self.visible.contains(&row) {
+            return None;
+        }
+        Some(self.behaviors.len() + row - self.visible.start)
+    }
+
+    /// Element index of the child at the given position in [`LayoutNode::children`].
+    fn index(&self, position: usize) -> usize {
+        let Some(row) = position.checked_sub(self.behaviors.len()) else {
+            return self.behaviors[position];
+        };
+        self.behaviors
+            .iter()
+            .fold(self.visible.start + row, |index, &behavior| {
+                if behavior <= index { index + 1 } else { index }
+            })
+    }
+}
+
+impl LayoutNode {
+    /// A node that is not scrolled, focusable, transformed or stacked.
+    #[must_use]
+    pub const fn new(
+        id: Option<ElementId>,
+        kind: ElementKind,
+        rect: Rect,
+        children: Vec<Self>,
+    ) -> Self {
+        Self {
+            id,
+            kind,
+            rect,
+            children,
+            scroll: None,
+            tab_index: None,
+            transform: Affine::IDENTITY,
+            z_index: 0,
+        }
+    }
+
+    /// The area that descendants are clipped to, if any.
+    #[must_use]
+    pub const fn clip(&self) -> Option<Rect> {
+        match self.scroll {
+            Some(_) => Some(self.rect),
+            None => None,
+        }
+    }
+
+    /// Whether the element has a behavior child of the given kind.
+    #[must_use]
+    pub fn has_behavior(&self, kind: ElementKind) -> bool {
+        self.children.iter().any(|child| child.kind == kind)
+    }
+
+    /// The layout of the element child at the given index.
+    /// Returns `None` for children culled by virtualization.
+    #[must_use]
+    pub fn child(&self, index: usize) -> Option<&Self> {
+        let position = match &self.scroll {
+            Some(scroll) => scroll.position(index)?,
+            None => index,
+        };
+        self.children.get(position)
+    }
+
+    /// Iterate over the laid out children along with their element indices.
+    #[must_use]
+    pub fn indexed_children(&self) -> impl DoubleEndedIterator<Item = (usize, &Self)> {
+        let scroll = self.scroll.as_ref();
+        self.children
+            .iter()
+            .enumerate()
+            .map(move |(position, child)| {
+                let index = scroll.map_or(position, |scroll| scroll.index(position));
+                (index, child)
+            })
+    }
+
+    /// Iterate over the laid out children along with their element indices,
+    /// in the order they are painted.
+    #[must_use]
+    pub fn stacked_children(&self) -> Vec<(usize, &Self)> {
+        stacked(self.indexed_children())
+    }
+
+    /// The smallest area in scene pixel space containing the transformed bounds.
+    #[must_use]
+    pub fn bounds(&self) -> Rect {
+        self.transform.bounds(self.rect)
+    }
+
+    /// Move the node and all of its descendants by the given offset.
+    pub fn translate(&mut self, dx: f32, dy: f32) {
+        self.rect.x += dx;
+        self.rect.y += dy;
+        self.transform = self.transform.translated(dx, dy);
+        for child in &mut self.children {
+            child.translate(dx, dy);
+        }
+    }
+
+    /// Apply a transformation after the current one, to the node and all of its descendants.
+    pub fn transform(&mut self, transform: Affine) {
+        self.transform = self.transform.then(transform);
+        for child in &mut self.children {
+            child.transform(transform);
+        }
+    }
+}
+
+/// Sort children by their z-index, keeping document order among equal indices.
+fn stacked<'a>(
+    children: impl Iterator<Item = (usize, &'a LayoutNode)>,
+) -> Vec<(usize, &'a LayoutNode)> {
+    let mut children: Vec<_> = children.collect();
+    children.sort_by_key(|(_, child)| child.z_index);
+    children
+}
+
+/// The resolved layout of a whole scene.
+/// Mirrors the structure of the element tree.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LayoutTree {
+    pub children: Vec<LayoutNode>,
+}
+
+impl LayoutTree {
+    /// The layout of the element at the given path.
+    /// Returns `None` if the path does not exist or the element was culled.
+    #[must_use]
+    pub fn node(&self, path: &ElementPath) -> Option<&LayoutNode> {
+        let (first, rest) = path.0.split_first()?;
+        rest.iter()
+            .try_fold(self.children.get(*first)?, |node, &index| node.child(index))
+    }
+
+    /// Iterate over the root nodes along with their element indices,
+    /// in the order they are painted.
+    #[must_use]
+    pub fn stacked_children(&self) -> Vec<(usize, &LayoutNode)> {
+        stacked(self.children.iter().enumerate())
+    }
+}
@@ -0,0 +1,120 @@
+//! Font management, shaping and line breaking of text.
+//!
+//! Shaping applies kerning and ligatures, runs of mixed direction are reordered
+//! with the Unicode bidirectional algorithm, and characters missing from the requested
+//! font fall back to other installed fonts. Text is made of styled runs
+//! that are shaped together, so lines break across runs as in plain text.
+
+mod layout;
+mod system;
+
+use std::sync::Arc;
+
+pub use self::{
+    layout::{Glyph, GlyphRun, TextLayout, TextLine},
+    system::{DEFAULT_TEXT_CACHE_LAYOUTS, FontData, FontId, TextSystem},
+};
+use crate::{Color, LengthContext, SpanElement, TextChild, TextElement, TextOverflow};
+
+/// Line height relative to the font size, used when a style does not specify one.
+pub const DEFAULT_LINE_HEIGHT: f32 = 1.2;
+
+/// Properties shared by all runs of a piece of text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextStyle {
+    /// Size of the em square in device pixels.
+    pub size: f32,
+    /// Line height relative to [`Self::size`].
+    pub line_height: f32,
+    pub overflow: TextOverflow,
+}
+
+impl TextStyle {
+    /// The style of a text element, with its size resolved to device pixels.
+    #[must_use]
+    pub const fn of(element: &TextElement, ctx: &LengthContext) -> Self {
+        Self {
+            size: ctx.metrics.scene_units_to_device(element.size),
+            line_height: DEFAULT_LINE_HEIGHT,
+            overflow: element.overflow,
+        }
+    }
+}
+
+/// Style of a single run of text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpanStyle {
+    /// Font family name, falling back to the bundled sans-serif font.
+    pub family: Arc<str>,
+    pub weight: u16,
+    pub italic: bool,
+    pub color: Color,
+    pub underline: bool,
+    /// Offset of the glyphs above the baseline in device pixels.
+    pub baseline_shift: f32,
+}
+
+/// Text with a uniform style.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextRun {
+    pub text: Arc<str>,
+    pub style: SpanStyle,
+}
+
+impl TextRun {
+    /// The runs of a text element in logical order, with span styles inherited
+    /// and lengths resolved to device pixels.
+    #[must_use]
+    pub fn of(element: &TextElement, ctx: &LengthContext) -> Vec<Self> {
+        let style = SpanStyle {
+            family: element.font.clone(),
+            weight: element.weight,
+            italic: element.italic,
+            color: element.color.clone(),
+            underline: element.underline,
+            baseline_shift: 0.0,
+        };
+        let mut runs = Vec::new();
+        collect_runs(&element.children, &style, ctx, &mut runs);
+        runs
+    }
+}
+
+fn collect_runs(
+    children: &[TextChild],
+    style: &SpanStyle,
+    ctx: &LengthContext,
+    runs: &mut Vec<TextRun>,
+) {
+    for child in children {
+        match child {
+            TextChild::Text(text) => match runs.last_mut() {
+                // Adjacent text with the same style is shaped as a single run.
+                Some(last) if last.style == *style => {
+                    last.text = Arc::from(format!("{}{text}", last.text));
+                }
+                _ => runs.push(TextRun {
+                    text: text.clone(),
+                    style: style.clone(),
+                }),
+            },
+            TextChild::Span(span) => {
+                collect_runs(&span.children, &span_style(span, style, ctx), ctx, runs);
+            }
+        }
+    }
+}
+
+fn span_style(span: &SpanElement, parent: &SpanStyle, ctx: &LengthContext) -> SpanStyle {
+    let shift = span
+        .baseline_shift
+        .map_or(0.0, |shift| ctx.metrics.scene_units_to_device(shift));
+    SpanStyle {
+        family: span.font.clone().unwrap_or_else(|| parent.family.clone()),
+        weight: span.weight.unwrap_or(parent.weight),
+        italic: span.italic.unwrap_or(parent.italic),
+        color: span.color.clone().unwrap_or_else(|| parent.color.clone()),
+        underline: span.underline.unwrap_or(parent.underline),
+        baseline_shift: parent.baseline_shift + shift,
+    }
+}
@@ -0,0 +1,66 @@
+use std::ops::Range;
+
+use super::FontId;
+use crate::Size;
+
+/// Shaped and broken text, positioned relative to the top left corner of its bounds.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TextLayout {
+    /// Visual lines from top to bottom.
+    pub lines: Vec<TextLine>,
+    /// Width of the widest line.
+    pub width: f32,
+    /// Sum of the line heights.
+    pub height: f32,
+}
+
+impl TextLayout {
+    #[must_use]
+    pub const fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+}
+
+/// A single visual line of text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextLine {
+    /// Index of the paragraph the line belongs to.
+    /// Paragraphs are separated by line breaks in the source text.
+    pub paragraph: usize,
+    /// Top of the line box.
+    pub top: f32,
+    /// Position of the baseline.
+    pub baseline: f32,
+    pub height: f32,
+    pub width: f32,
+    /// Whether the base direction of the paragraph is right-to-left.
+    pub rtl: bool,
+    /// Glyphs in visual order, split wherever the font or the text run changes.
+    pub runs: Vec<GlyphRun>,
+}
+
+/// Consecutive glyphs of a line sharing a font and a text run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphRun {
+    pub font: FontId,
+    /// Index of the [`TextRun`](super::TextRun) the glyphs were shaped from.
+    pub span: usize,
+    /// Size of the em square in device pixels.
+    pub size: f32,
+    pub glyphs: Vec<Glyph>,
+}
+
+/// A positioned glyph.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Glyph {
+    /// Index of the glyph within its font.
+    pub id: u16,
+    /// Horizontal position of the glyph origin, relative to the start of the line.
+    pub x: f32,
+    /// Vertical offset from the baseline, including the baseline shift of the run.
+    pub y: f32,
+    pub advance: f32,
+    /// Bytes of the paragraph the glyph was shaped from.
+    /// Ligatures cover several characters.
+    pub cluster: Range<usize>,
+}
@@ -0,0 +1,633 @@
+use std::{borrow::Cow, cell::RefCell, collections::HashMap, path::Path, sync::Arc};
+
+use cosmic_text::{
+    Attrs, Buffer, Family, FontSystem, Metrics, Shaping, Style, Weight, Wrap, fontdb,
+};
+use lru::LruCache;
+
+use super::{Glyph, GlyphRun, SpanStyle, TextLayout, TextLine, TextRun, TextStyle};
+use crate::TextOverflow;
+
+/// Font shipped with the application, so text renders without any installed fonts.
+const BUNDLED_FONT: &[u8] = include_bytes!("../../fonts/Inter-Regular.ttf");
+const BUNDLED_FAMILY: &str = "Inter";
+/// Locale used to pick fallback fonts.
+const LOCALE: &str = "en-US";
+const ELLIPSIS: &str = "\u{2026}";
+/// Number of layouts kept by a [`TextSystem`] unless configured otherwise.
+pub const DEFAULT_TEXT_CACHE_LAYOUTS: usize = 4096;
+
+/// Identifies a font face loaded into a [`TextSystem`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FontId(fontdb::ID);
+
+/// The contents of a font file, as needed to rasterize its glyphs.
+#[derive(Clone, Debug)]
+pub struct FontData {
+    font: Arc<cosmic_text::Font>,
+    index: u32,
+}
+
+impl FontData {
+    /// The contents of the font file.
+    #[must_use]
+    pub fn data(&self) -> &[u8] {
+        self.font.data()
+    }
+
+    /// Index of the face within a font collection.
+    #[must_use]
+    pub const fn index(&self) -> u32 {
+        self.index
+    }
+}
+
+/// Font database and shaping service.
+///
+/// Laid out text is cached until the set of fonts changes or [`Self::clear_cache`] is called.
+/// The least recently used layouts are evicted beyond the capacity of the cache.
+pub struct TextSystem {
+    fonts: RefCell<FontSystem>,
+    capacity: usize,
+    cache: RefCell<LayoutCache>,
+}
+
+struct LayoutCache(LruCache<CacheKey, Arc<TextLayout>>);
+
+impl Default for LayoutCache {
+    fn default() -> Self {
+        Self(LruCache::unbounded())
+    }
+}
+
+impl LayoutCache {
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    fn get(&mut self, key: &CacheKey) -> Option<Arc<TextLayout>> {
+        self.0.get(key).cloned()
+    }
+
+    fn insert(&mut self, key: CacheKey, layout: Arc<TextLayout>, capacity: usize) {
+        self.0.put(key, layout);
+        while self.0.len() > capacity.max(1) {
+            self.0.pop_lru();
+        }
+    }
+}
+
+impl std::fmt::Debug for TextSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TextSystem")
+            .field("faces", &self.fonts.borrow().db().len())
+            .field("layouts", &self.cache.borrow().0.len())
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+impl Default for TextSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextSystem {
+    /// Load the bundled font and every font installed on the system.
+    #[must_use]
+    pub fn new() -> Self {
+        let mut db = bundled_database();
+        db.load_system_fonts();
+        Self::from_database(db)
+    }
+
+    /// Load only the bundled font, so the output does not depend on the system.
+    #[must_use]
+    pub fn bundled() -> Self {
+        Self::from_database(bundled_database())
+    }
+
+    fn from_database(db: fontdb::Database) -> Self {
+        Self {
+            fonts: RefCell::new(FontSystem::new_with_locale_and_db(LOCALE.to_owned(), db)),
+            capacity: DEFAULT_TEXT_CACHE_LAYOUTS,
+            cache: RefCell::default(),
+        }
+    }
+
+    /// Keep at most `capacity` layouts cached, except for the most recently laid out text.
+    #[must_use]
+    pub const fn with_cache_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Load a font file, or every face of a font collection, from memory.
+    pub fn load_font_data(&mut self, data: Vec<u8>) {
+        self.fonts.get_mut().db_mut().load_font_data(data);
+        self.cache.get_mut().clear();
+    }
+
+    /// Load every font file in a directory and its subdirectories.
+    /// Files that cannot be read or parsed are skipped.
+    pub fn load_fonts_dir(&mut self, path: impl AsRef<Path>) {
+        self.fonts.get_mut().db_mut().load_fonts_dir(path);
+        self.cache.get_mut().clear();
+    }
+
+    /// The data of a font referenced by a [`GlyphRun`].
+    #[must_use]
+    pub fn font_data(&self, id: FontId) -> Option<FontData> {
+        let mut fonts = self.fonts.borrow_mut();
+        let index = fonts.db().face(id.0)?.index;
+        let font = fonts.get_font(id.0)?;
+        Some(FontData { font, index })
+    }
+
+    /// Drop all cached layouts.
+    pub fn clear_cache(&self) {
+        self.cache.borrow_mut().clear();
+    }
+
+    /// Shape text runs and break them into lines no wider than `max_width`, if given.
+    ///
+    /// Depending on [`TextStyle::overflow`], lines are either wrapped
+    /// or truncated with an ellipsis. Words that do not fit on a line
+    /// of their own are broken between characters, even when they span several runs.
+    #[must_use]
+    pub fn layout(
+        &self,
+        runs: &[TextRun],
+        style: &TextStyle,
+        max_width: Option<f32>,
+    ) -> Arc<TextLayout> {
+        let key = CacheKey::new(runs, style, max_width);
+        if let Some(layout) = self.cache.borrow_mut().get(&key) {
+            return layout;
+        }
+        let fonts = &mut *self.fonts.borrow_mut();
+        let runs = &*resolve_stacks(fonts.db(), runs);
+        let pieces: Vec<_> = runs
+            .iter()
+            .enumerate()
+            .map(|(span, run)| (&*run.text, span))
+            .collect();
+        let layout = Arc::new(match (style.overflow, max_width) {
+            (TextOverflow::Ellipsis, Some(max_width)) => {
+                let pieces = truncate(fonts, &pieces, runs, style, max_width);
+                shape(fonts, &pieces, runs, style, None)
+            }
+            _ => shape(fonts, &pieces, runs, style, max_width),
+        });
+        self.cache
+            .borrow_mut()
+            .insert(key, layout.clone(), self.capacity);
+        layout
+    }
+}
+
+fn bundled_database() -> fontdb::Database {
+    let mut db = fontdb::Database::new();
+    db.load_font_data(BUNDLED_FONT.to_vec());
+    db.set_sans_serif_family(BUNDLED_FAMILY);
+    db
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    runs: Vec<RunKey>,
+    size: u32,
+    line_height: u32,
+    overflow: TextOverflow,
+    max_width: Option<u32>,
+}
+
+/// The parts of a run that affect its layout.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct RunKey {
+    text: Arc<str>,
+    family: Arc<str>,
+    weight: u16,
+    italic: bool,
+    baseline_shift: u32,
+}
+
+impl CacheKey {
+    fn new(runs: &[TextRun], style: &TextStyle, max_width: Option<f32>) -> Self {
+        Self {
+            runs: runs
+                .iter()
+                .map(|run| RunKey {
+                    text: run.text.clone(),
+                    family: run.style.family.clone(),
+                    weight: run.style.weight,
+                    italic: run.style.italic,
+                    baseline_shift: run.style.baseline_shift.to_bits(),
+                })
+                .collect(),
+            size: style.size.to_bits(),
+            line_height: style.line_height.to_bits(),
+            overflow: style.overflow,
+            max_width: max_width.map(f32::to_bits),
+        }
+    }
+}
+
+/// The runs with font stacks replaced by one of their families.
+fn resolve_stacks<'r>(db: &fontdb::Database, runs: &'r [TextRun]) -> Cow<'r, [TextRun]> {
+    if !runs.iter().any(|run| run.style.family.contains(',')) {
+        return Cow::Borrowed(runs);
+    }
+    let runs = runs.iter().map(|run| {
+        let mut run = run.clone();
+        run.style.family = pick_family(db, &run.style.family);
+        run
+    });
+    Cow::Owned(runs.collect())
+}
+
+/// The first installed family of a comma-separated font stack,
+/// or the last family of the stack, which then falls back as usual.
+fn pick_family(db: &fontdb::Database, stack: &str) -> Arc<str> {
+    let mut last = stack;
+    for family in stack.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+        let installed = db.faces().find_map(|face| {
+            face.families
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(family))
+        });
+        if let Some((name, _)) = installed {
+            return Arc::from(name.as_str());
+        }
+        last = family;
+    }
+    Arc::from(last)
+}
+
+fn attrs(style: &SpanStyle) -> Attrs<'_> {
+    Attrs::new()
+        .family(Family::Name(&style.family))
+        .weight(Weight(style.weight))
+        .style(if style.italic {
+            Style::Italic
+        } else {
+            Style::Normal
+        })
+}
+
+/// Shape pieces of text, each tagged with the index of the run it belongs to.
+fn shape(
+    fonts: &mut FontSystem,
+    pieces: &[(&str, usize)],
+    runs: &[TextRun],
+    style: &TextStyle,
+    max_width: Option<f32>,
+) -> TextLayout {
+    let metrics = Metrics::new(style.size, style.size * style.line_height);
+    let mut buffer = Buffer::new(fonts, metrics);
+    let wrap = match style.overflow {
+        TextOverflow::Wrap => Wrap::WordOrGlyph,
+        TextOverflow::Ellipsis => Wrap::None,
+    };
+    buffer.set_wrap(fonts, wrap);
+    buffer.set_size(fonts, max_width, None);
+    buffer.set_rich_text(
+        fonts,
+        pieces
+            .iter()
+            .map(|&(text, span)| (text, attrs(&runs[span].style).metadata(span))),
+        Attrs::new(),
+        Shaping::Advanced,
+    );
+    buffer.shape_until_scroll(fonts, false);
+
+    let mut layout = TextLayout::default();
+    for run in buffer.layout_runs() {
+        let mut glyph_runs = Vec::<GlyphRun>::new();
+        for glyph in run.glyphs {
+            let font = FontId(glyph.font_id);
+            let span = glyph.metadata;
+            let shift = runs.get(span).map_or(0.0, |run| run.style.baseline_shift);
+            let shaped = Glyph {
+                id: glyph.glyph_id,
+                x: glyph.font_size.mul_add(glyph.x_offset, glyph.x),
+                y: glyph.font_size.mul_add(-glyph.y_offset, glyph.y) - shift,
+                advance: glyph.w,
+                cluster: glyph.start..glyph.end,
+            };
+            match glyph_runs.last_mut() {
+                Some(last) if last.font == font && last.span == span => {
+                    last.glyphs.push(shaped);
+                }
+                _ => glyph_runs.push(GlyphRun {
+                    font,
+                    span,
+                    size: glyph.font_size,
+                    glyphs: vec![shaped],
+                }),
+            }
+        }
+        layout.width = layout.width.max(run.line_w);
+        layout.height = layout.height.max(run.line_top + run.line_height);
+        layout.lines.push(TextLine {
+            paragraph: run.line_i,
+            top: run.line_top,
+            baseline: run.line_y,
+            height: run.line_height,
+            width: run.line_w,
+            rtl: run.rtl,
+            runs: glyph_runs,
+        });
+    }
+    layout
+}
+
+/// Cut every paragraph that does not fit the width, and end it with an ellipsis
+/// styled like the last character that was kept.
+/// Paragraphs are left empty when not even the ellipsis fits.
+fn truncate<'a>(
+    fonts: &mut FontSystem,
+    pieces: &[(&'a str, usize)],
+    runs: &[TextRun],
+    style: &TextStyle,
+    max_width: f32,
+) -> Vec<(&'a str, usize)> {
+    let layout = shape(fonts, pieces, runs, style, None);
+    let text: String = pieces.iter().map(|(text, _)| *text).collect();
+    let mut starts = vec![0];
+    starts.extend(text.match_indices('\n').map(|(i, _)| i + 1));
+    let mut ellipsis_widths = HashMap::new();
+    // Removed byte ranges of the text and the run of the ellipsis replacing them, if any.
+    let mut cuts = Vec::new();
+    for line in layout.lines.iter().filter(|line| line.width > max_width) {
+        // Keep the longest logical prefix that fits, regardless of its visual order.
+        let mut glyphs = line
+            .runs
+            .iter()
+            .flat_map(|run| run.glyphs.iter().map(move |glyph| (glyph, run.span)))
+            .collect::<Vec<_>>();
+        glyphs.sort_by_key(|(glyph, _)| glyph.cluster.start);
+        let Some(mut ellipsis_span) = glyphs.first().map(|(_, span)| *span) else {
+            continue;
+        };
+        let mut width = 0.0;
+        let mut end = 0;
+        for (glyph, span) in glyphs {
+            width += glyph.advance;
+            let ellipsis = *ellipsis_widths
+                .entry(span)
+                .or_insert_with(|| shape(fonts, &[(ELLIPSIS, span)], runs, style, None).width);
+            if width + ellipsis > max_width {
+                break;
+            }
+            end = end.max(glyph.cluster.end);
+            ellipsis_span = span;
+        }
+        let start = starts[line.paragraph];
+        let stop = starts
+            .get(line.paragraph + 1)
+            .map_or(text.len(), |next| next - 1);
+        let prefix = text.get(start..start + end).unwrap_or_default().trim_end();
+        let ellipsis = ellipsis_widths
+            .get(&ellipsis_span)
+            .is_none_or(|width| *width <= max_width)
+            .then_some(ellipsis_span);
+        cuts.push((start + prefix.len()..stop, ellipsis));
+    }
+
+    let mut cuts = cuts.into_iter().peekable();
+    let mut truncated = Vec::new();
+    let mut offset = 0;
+    let mut removed_until = 0;
+    for &(piece, span) in pieces {
+        let end = offset + piece.len();
+        let mut position = offset.max(removed_until).min(end);
+        loop {
+            match cuts.peek() {
+                Some((cut, ellipsis_span)) if cut.start <= end => {
+                    truncated.push((&piece[position - offset..cut.start - offset], span));
+                    if let Some(ellipsis_span) = ellipsis_span {
+                        truncated.push((ELLIPSIS, *ellipsis_span));
+                    }
+                    removed_until = cut.end;
+                    position = cut.end.min(end);
+                    cuts.next();
+                }
+                _ => {
+                    truncated.push((&piece[position - offset..], span));
+                    break;
+                }
+            }
+        }
+        offset = end;
+    }
+    truncated.retain(|(text, _)| !text.is_empty());
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Color;
+
+    fn style(overflow: TextOverflow) -> TextStyle {
+        TextStyle {
+            size: 20.0,
+            line_height: 1.5,
+            overflow,
+        }
+    }
+
+    fn span() -> SpanStyle {
+        SpanStyle {
+            family: Arc::from(BUNDLED_FAMILY),
+            weight: 400,
+            italic: false,
+            color: Color::BLACK,
+            underline: false,
+            baseline_shift: 0.0,
+        }
+    }
+
+    fn plain(text: &str) -> Vec<TextRun> {
+        vec![TextRun {
+            text: Arc::from(text),
+            style: span(),
+        }]
+    }
+
+    fn glyph_ids(layout: &TextLayout) -> Vec<u16> {
+        layout
+            .lines
+            .iter()
+            .flat_map(|line| &line.runs)
+            .flat_map(|run| &run.glyphs)
+            .map(|glyph| glyph.id)
+            .collect()
+    }
+
+    #[test]
+    fn test_text_wrap() {
+        let text = TextSystem::bundled();
+        let style = style(TextOverflow::Wrap);
+        let line = text.layout(&plain("Hello wrapped world"), &style, None);
+        assert_eq!(line.lines.len(), 1);
+        assert!((line.height - 30.0).abs() < 1e-3);
+        assert!(line.width > 100.0, "{}", line.width);
+
+        let max_width = text.layout(&plain("Hello wrapped"), &style, None).width + 1.0;
+        let wrapped = text.layout(&plain("Hello wrapped world"), &style, Some(max_width));
+        assert_eq!(wrapped.lines.len(), 2);
+        assert!((wrapped.height - 60.0).abs() < 1e-3);
+        assert!(wrapped.width <= max_width);
+        assert!((wrapped.lines[1].top - 30.0).abs() < 1e-3);
+        assert!(wrapped.lines[1].baseline > 30.0);
+        // The second line starts at the word that did not fit.
+        let second = &wrapped.lines[1].runs[0].glyphs[0];
+        assert_eq!(second.cluster.start, "Hello wrapped ".len());
+        assert!(second.x.abs() < 1e-3);
+
+        let paragraphs = text.layout(&plain("one\ntwo"), &style, None);
+        let indices: Vec<_> = paragraphs.lines.iter().map(|line| line.paragraph).collect();
+        assert_eq!(indices, [0, 1]);
+    }
+
+    #[test]
+    fn test_text_ellipsis() {
+        let text = TextSystem::bundled();
+        let style = style(TextOverflow::Ellipsis);
+        let runs = plain("Truncated track name");
+        let full = text.layout(&runs, &style, None);
+        let max_width = full.width / 2.0;
+        let truncated = text.layout(&runs, &style, Some(max_width));
+        assert_eq!(truncated.lines.len(), 1);
+        assert!(truncated.width <= max_width);
+        let ellipsis = text.layout(&plain(ELLIPSIS), &style, None);
+        assert_eq!(glyph_ids(&truncated).last(), glyph_ids(&ellipsis).last());
+        // Fitting text is left untouched.
+        let fits = text.layout(&runs, &style, Some(full.width));
+        assert_eq!(fits, full);
+        // Nothing is drawn when not even the ellipsis fits.
+        let empty = text.layout(&runs, &style, Some(ellipsis.width / 2.0));
+        assert!(glyph_ids(&empty).is_empty());
+        assert!(empty.width <= ellipsis.width / 2.0);
+    }
+
+    #[test]
+    fn test_text_cache_capacity() {
+        let text = TextSystem::bundled().with_cache_capacity(2);
+        let style = style(TextOverflow::Wrap);
+        let one = text.layout(&plain("one"), &style, None);
+        let two = text.layout(&plain("two"), &style, None);
+        // Using the first layout makes the second the least recently used.
+        assert!(Arc::ptr_eq(&one, &text.layout(&plain("one"), &style, None)));
+        let _three = text.layout(&plain("three"), &style, None);
+        assert_eq!(text.cache.borrow().0.len(), 2);
+        assert!(Arc::ptr_eq(&one, &text.layout(&plain("one"), &style, None)));
+        assert!(!Arc::ptr_eq(
+            &two,
+            &text.layout(&plain("two"), &style, None)
+        ));
+    }
+
+    #[test]
+    fn test_text_shaping() {
+        let text = TextSystem::bundled();
+        let style = style(TextOverflow::Wrap);
+        let width = |s| text.layout(&plain(s), &style, None).width;
+        // Kerning pulls the pair together.
+        assert!(width("AV") < width("A") + width("V"));
+        // Unknown families fall back to the bundled font.
+        let fallback = vec![TextRun {
+            text: Arc::from("abc"),
+            style: SpanStyle {
+                family: Arc::from("Missing Family"),
+                ..span()
+            },
+        }];
+        let layout = text.layout(&fallback, &style, None);
+        assert!(glyph_ids(&layout).iter().all(|id| *id != 0));
+        let font = layout.lines[0].runs[0].font;
+        assert!(!text.font_data(font).expect("font data").data().is_empty());
+        // Right-to-left paragraphs are detected by the bidi algorithm.
+        assert!(
+            text.layout(&plain("\u{5e9}\u{5dc}\u{5d5}\u{5dd}"), &style, None)
+                .lines[0]
+                .rtl
+        );
+        assert!(!layout.lines[0].rtl);
+        // Layouts are cached.
+        assert!(Arc::ptr_eq(&layout, &text.layout(&fallback, &style, None)));
+        // Font stacks use their first installed family.
+        let fonts = text.fonts.borrow();
+        assert_eq!(
+            &*pick_family(fonts.db(), "Missing Family, inter, Other"),
+            BUNDLED_FAMILY
+        );
+        assert_eq!(&*pick_family(fonts.db(), "Missing, Other "), "Other");
+    }
+
+    #[test]
+    fn test_text_spans() {
+        let text = TextSystem::bundled();
+        let style = style(TextOverflow::Wrap);
+        let run = |text: &str, style: SpanStyle| TextRun {
+            text: Arc::from(text),
+            style,
+        };
+        let raised = SpanStyle {
+            baseline_shift: 5.0,
+            ..span()
+        };
+        let underlined = SpanStyle {
+            underline: true,
+            ..span()
+        };
+        // Words split across runs are shaped and broken as a whole.
+        let runs = [
+            run("Hel", span()),
+            run("lo wrap", raised),
+            run("ped world", underlined),
+        ];
+        let spans = |line: &TextLine| line.runs.iter().map(|run| run.span).collect::<Vec<_>>();
+        let line = text.layout(&runs, &style, None);
+        let plain_width = text
+            .layout(&plain("Hello wrapped world"), &style, None)
+            .width;
+        assert!((line.width - plain_width).abs() < 1e-3);
+        assert_eq!(spans(&line.lines[0]), [0, 1, 2]);
+        for run in &line.lines[0].runs {
+            let shift = if run.span == 1 { -5.0 } else { 0.0 };
+            assert!(
+                run.glyphs
+                    .iter()
+                    .all(|glyph| (glyph.y - shift).abs() < 1e-3)
+            );
+        }
+
+        let max_width = text.layout(&plain("Hello wrapped"), &style, None).width + 1.0;
+        let wrapped = text.layout(&runs, &style, Some(max_width));
+        assert_eq!(wrapped.lines.len(), 2);
+        assert_eq!(spans(&wrapped.lines[0]), [0, 1, 2]);
+        assert_eq!(spans(&wrapped.lines[1]), [2]);
+        let second = &wrapped.lines[1].runs[0].glyphs[0];
+        assert_eq!(second.cluster.start, "Hello wrapped ".len());
+
+        // The ellipsis takes the style of the last character that was kept.
+        let ellipsis = format!("Hello wrap{ELLIPSIS}");
+        let max_width = text.layout(&plain(&ellipsis), &style, None).width + 0.5;
+        let truncate = TextStyle {
+            overflow: TextOverflow::Ellipsis,
+            ..style
+        };
+        let truncated = text.layout(&runs, &truncate, Some(max_width));
+        assert_eq!(truncated.lines.len(), 1);
+        assert!(truncated.width <= max_width);
+        assert_eq!(spans(&truncated.lines[0]), [0, 1]);
+        assert_eq!(
+            glyph_ids(&truncated).last(),
+            glyph_ids(&text.layout(&plain(&ellipsis), &style, None)).last()
+        );
+    }
+}
@@ -0,0 +1,685 @@
+//! Named style tokens and the themes defining them.
+//!
+//! Elements refer to tokens by name: colors and lengths are written `$name`,
+//! and fonts name a token as their family, such as `"$body"`.
+//! The scene keeps these references, so the host switches themes by applying another theme
+//! to a copy of the declared scene every frame, without the frontend rebuilding anything.
+//! Applying the theme before the [`crate::Animator`] lets transitions animate a theme switch.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    BehaviorElement, Border, Color, ContentElement, CornerRadii, ElementNode, GridTrack,
+    LayoutElement, LayoutLength, LengthExpr, Paint, PathStroke, Scene, Shadow, TextChild,
+    TextureShader,
+};
+
+/// How many tokens may refer to each other in a chain before giving up,
+/// which also stops cycles.
+const MAX_DEPTH: usize = 16;
+
+/// A set of named colors, font stacks and lengths.
+///
+/// Token values may refer to other tokens, e.g. `accent = "$blue.500"`.
+/// Length tokens are looked up in [`Self::spacing`] first and [`Self::radii`] second.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Theme {
+    /// Display name, such as `"Dark"`.
+    #[serde(default)]
+    pub name: Option<Arc<str>>,
+    #[serde(default)]
+    pub colors: HashMap<Arc<str>, Color>,
+    /// Font stacks: comma-separated family names of which the first installed one is used.
+    #[serde(default)]
+    pub fonts: HashMap<Arc<str>, Arc<str>>,
+    #[serde(default)]
+    pub spacing: HashMap<Arc<str>, LayoutLength>,
+    #[serde(default)]
+    pub radii: HashMap<Arc<str>, LayoutLength>,
+}
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum ThemeError {
+    #[error("unable to read theme {}", .path.display())]
+    #[diagnostic(code(chipbox_scene::theme::io))]
+    Io {
+        path: PathBuf,
+        #[source]
+        error: std::io::Error,
+    },
+    #[error("theme {} is not a JSON or TOML file", .0.display())]
+    #[diagnostic(code(chipbox_scene::theme::format))]
+    UnsupportedFormat(PathBuf),
+    #[error("invalid JSON theme")]
+    #[diagnostic(code(chipbox_scene::theme::json))]
+    Json(#[source] serde_json::Error),
+    #[error("invalid TOML theme")]
+    #[diagnostic(code(chipbox_scene::theme::toml))]
+    Toml(#[source] toml::de::Error),
+    #[error("length token `{0}` is defined in both spacing and radii")]
+    #[diagnostic(
+        code(chipbox_scene::theme::ambiguous_length),
+        help("spacing and radii share the names of length tokens")
+    )]
+    AmbiguousLength(Arc<str>),
+}
+
+impl Theme {
+    /// Parse a theme from JSON.
+    /// ## Errors
+    /// - [`ThemeError::Json`] if the theme is malformed.
+    /// - [`ThemeError::AmbiguousLength`] if a length token is both spacing and a radius.
+    pub fn from_json(json: &str) -> Result<Self, ThemeError> {
+        serde_json::from_str::<Self>(json)
+            .map_err(ThemeError::Json)?
+            .checked()
+    }
+
+    /// Parse a theme from TOML.
+    /// ## Errors
+    /// - [`ThemeError::Toml`] if the theme is malformed.
+    /// - [`ThemeError::AmbiguousLength`] if a length token is both spacing and a radius.
+    pub fn from_toml(toml: &str) -> Result<Self, ThemeError> {
+        toml::from_str::<Self>(toml)
+            .map_err(ThemeError::Toml)?
+            .checked()
+    }
+
+    /// Load a theme from a `.json` or `.toml` file.
+    /// ## Errors
+    /// - [`ThemeError::Io`] if the file cannot be read.
+    /// - [`ThemeError::UnsupportedFormat`] for other file extensions.
+    /// - The errors of [`Self::from_json`] and [`Self::from_toml`].
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ThemeError> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+        let parse = match extension.as_deref() {
+            Some("json") => Self::from_json,
+            Some("toml") => Self::from_toml,
+            _ => return Err(ThemeError::UnsupportedFormat(path.to_owned())),
+        };
+        let source = std::fs::read_to_string(path).map_err(|error| ThemeError::Io {
+            path: path.to_owned(),
+            error,
+        })?;
+        parse(&source)
+    }
+
+    fn checked(self) -> Result<Self, ThemeError> {
+        if let Some(name) = self
+            .spacing
+            .keys()
+            .find(|name| self.radii.contains_key(*name))
+        {
+            return Err(ThemeError::AmbiguousLength(name.clone()));
+        }
+        Ok(self)
+    }
+
+    /// Override tokens with those of another theme, as when a custom skin builds on a base theme.
+    pub fn extend(&mut self, overrides: Self) {
+        if overrides.name.is_some() {
+            self.name = overrides.name;
+        }
+        self.colors.extend(overrides.colors);
+        self.fonts.extend(overrides.fonts);
+        self.spacing.extend(overrides.spacing);
+        self.radii.extend(overrides.radii);
+    }
+
+    /// The value of a color token, with references to other tokens resolved.
+    #[must_use]
+    pub fn color(&self, name: &str) -> Option<Color> {
+        let mut color = Color::Token { name: name.into() };
+        Resolver::new(self).color(&mut color).then_some(color)
+    }
+
+    /// The value of a length token, with references to other tokens resolved.
+    #[must_use]
+    pub fn length(&self, name: &str) -> Option<LayoutLength> {
+        let mut length = LayoutLength::Token(name.into());
+        Resolver::new(self).length(&mut length).then_some(length)
+    }
+
+    /// The font stack of a font token, with references to other tokens resolved.
+    #[must_use]
+    pub fn font(&self, name: &str) -> Option<Arc<str>> {
+        let mut font = Arc::from(format!("${name}"));
+        Resolver::new(self).font(&mut font).then_some(font)
+    }
+
+    /// Replace every token in the scene with its value.
+    ///
+    /// Tokens the theme does not define are left in place and their names are returned,
+    /// so that the host can report them.
+    pub fn apply(&self, scene: &mut Scene) -> Vec<Arc<str>> {
+        let mut resolver = Resolver::new(self);
+        resolver.nodes(&mut scene.children);
+        resolver.missing.into_iter().collect()
+    }
+}
+
+/// Walks the scene, replacing tokens.
+struct Resolver<'a> {
+    theme: &'a Theme,
+    missing: BTreeSet<Arc<str>>,
+    depth: usize,
+}
+
+impl<'a> Resolver<'a> {
+    const fn new(theme: &'a Theme) -> Self {
+        Self {
+            theme,
+            missing: BTreeSet::new(),
+            depth: 0,
+        }
+    }
+
+    /// Replace a token with a copy of its value and resolve the tokens within.
+    /// Returns whether every token was found.
+    fn token<T: Clone>(
+        &mut self,
+        target: &mut T,
+        name: &Arc<str>,
+        value: Option<&T>,
+        resolve: impl FnOnce(&mut Self, &mut T) -> bool,
+    ) -> bool {
+        let Some(value) = value.filter(|_| self.depth < MAX_DEPTH) else {
+            self.missing.insert(name.clone());
+            return false;
+        };
+        let mut value = value.clone();
+        self.depth += 1;
+        let found = resolve(self, &mut value);
+        self.depth -= 1;
+        *target = value;
+        found
+    }
+
+    fn color(&mut self, color: &mut Color) -> bool {
+        let Color::Token { name } = color else {
+            return true;
+        };
+        let name = name.clone();
+        let theme = self.theme;
+        self.token(color, &name, theme.colors.get(&name), Self::color)
+    }
+
+    fn length(&mut self, length: &mut LayoutLength) -> bool {
+        match length {
+            LayoutLength::Token(name) => {
+                let name = name.clone();
+                let theme = self.theme;
+                let value = theme.spacing.get(&name).or_else(|| theme.radii.get(&name));
+                self.token(length, &name, value, Self::length)
+            }
+            LayoutLength::Calc(expr) => self.expr(Arc::make_mut(expr)),
+            _ => true,
+        }
+    }
+
+    fn expr(&mut self, expr: &mut LengthExpr) -> bool {
+        match expr {
+            LengthExpr::Length(length) => self.length(length),
+            LengthExpr::Sum(a, b) | LengthExpr::Difference(a, b) => {
+                let a = self.expr(a);
+                self.expr(b) && a
+            }
+            LengthExpr::Product(expr, _) | LengthExpr::Quotient(expr, _) => self.expr(expr),
+            LengthExpr::Min(exprs) | LengthExpr::Max(exprs) => {
+                let mut found = true;
+                for expr in exprs {
+                    found &= self.expr(expr);
+                }
+                found
+            }
+            LengthExpr::Clamp { min, value, max } => {
+                let found = self.expr(min);
+                let found = self.expr(value) && found;
+                self.expr(max) && found
+            }
+        }
+    }
+
+    fn font(&mut self, font: &mut Arc<str>) -> bool {
+        let Some(name) = font.strip_prefix('$').map(Arc::<str>::from) else {
+            return true;
+        };
+        let theme = self.theme;
+        self.token(font, &name, theme.fonts.get(&name), Self::font)
+    }
+
+    fn optional(&mut self, length: Option<&mut LayoutLength>) {
+        if let Some(length) = length {
+            self.length(length);
+        }
+    }
+
+    fn lengths<'l>(&mut self, lengths: impl IntoIterator<Item = &'l mut LayoutLength>) {
+        for length in lengths {
+            self.length(length);
+        }
+    }
+
+    fn paint(&mut self, paint: &mut Paint) {
+        match paint {
+            Paint::Solid { color } => {
+                self.color(color);
+            }
+            Paint::Linear { stops, .. } => {
+                for stop in stops {
+                    self.color(&mut stop.color);
+                }
+            }
+            Paint::Radial {
+                x,
+                y,
+                radius,
+                stops,
+            } => {
+                self.lengths([x, y]);
+                self.optional(radius.as_mut());
+                for stop in stops {
+                    self.color(&mut stop.color);
+                }
+            }
+            Paint::Sweep { x, y, stops, .. } => {
+                self.lengths([x, y]);
+                for stop in stops {
+                    self.color(&mut stop.color);
+                }
+            }
+        }
+    }
+
+    fn track(&mut self, track: &mut GridTrack) {
+        match track {
+            GridTrack::Length(length) => {
+                self.length(length);
+            }
+            GridTrack::MinMax { min, max } => {
+                self.track(min);
+                self.track(max);
+            }
+            GridTrack::Fraction(_) | GridTrack::Auto => {}
+        }
+    }
+
+    fn nodes(&mut self, nodes: &mut [ElementNode]) {
+        for node in nodes {
+            match node {
+                ElementNode::Layout(e) => self.layout(e),
+                ElementNode::Behavior(e) => self.behavior(e),
+                ElementNode::Content(e) => self.content(e),
+            }
+        }
+    }
+
+    fn layout(&mut self, element: &mut LayoutElement) {
+        match element {
+            LayoutElement::Box(e) => {
+                self.lengths([&mut e.width, &mut e.height]);
+                self.nodes(&mut e.children);
+            }
+            LayoutElement::Margin(e) => {
+                for length in [
+                    &mut e.base,
+                    &mut e.horizontal,
+                    &mut e.left,
+                    &mut e.right,
+                    &mut e.vertical,
+                    &mut e.top,
+                    &mut e.bottom,
+                ] {
+                    self.optional(length.as_mut());
+                }
+                self.nodes(&mut e.children);
+            }
+            LayoutElement::ContentBox(e) => self.nodes(&mut e.children),
+            LayoutElement::Align(e) => {
+                self.optional(e.x_offset.as_mut());
+                self.optional(e.y_offset.as_mut());
+                self.nodes(&mut e.children);
+            }
+            LayoutElement::Scroll(e) => {
+                for length in [&mut e.offset_x, &mut e.offset_y, &mut e.item_extent] {
+                    self.optional(length.as_mut());
+                }
+                self.nodes(&mut e.children);
+            }
+            LayoutElement::Array(e) => {
+                for e in &mut e.children {
+                    self.lengths([&mut e.width, &mut e.height]);
+                    self.nodes(&mut e.children);
+                }
+            }
+            LayoutElement::Grid(e) => {
+                for track in e.columns.0.iter_mut().chain(&mut e.rows.0) {
+                    self.track(track);
+                }
+                for gap in [&mut e.gap, &mut e.row_gap, &mut e.column_gap] {
+                    self.optional(gap.as_mut());
+                }
+                for e in &mut e.children {
+                    self.nodes(&mut e.children);
+                }
+            }
+            LayoutElement::Flex(e) => {
+                self.optional(e.gap.as_mut());
+                for e in &mut e.children {
+                    for length in [&mut e.basis, &mut e.min, &mut e.max, &mut e.cross] {
+                        self.optional(length.as_mut());
+                    }
+                    self.nodes(&mut e.children);
+                }
+            }
+        }
+    }
+
+    fn behavior(&mut self, element: &mut BehaviorElement) {
+        match element {
+            BehaviorElement::Texture(e) => match &mut e.shader {
+                Some(TextureShader::Blur { radius }) => {
+                    self.length(radius);
+                }
+                Some(TextureShader::Tint { color, .. }) => {
+                    self.color(color);
+                }
+                Some(TextureShader::Mask { paint }) => self.paint(paint),
+                Some(TextureShader::ColorMatrix { .. }) | None => {}
+            },
+            BehaviorElement::Transform(e) => {
+                self.optional(e.translate_x.as_mut());
+                self.optional(e.translate_y.as_mut());
+            }
+            _ => {}
+        }
+    }
+
+    fn content(&mut self, element: &mut ContentElement) {
+        match element {
+            ContentElement::Text(e) => {
+                self.font(&mut e.font);
+                self.color(&mut e.color);
+                self.text_children(&mut e.children);
+            }
+            ContentElement::Rect(e) => {
+                if let Some(fill) = &mut e.fill {
+                    self.paint(fill);
+                }
+                self.radii(&mut e.radius);
+                if let Some(border) = &mut e.border {
+                    self.border(border);
+                }
+                for shadow in &mut e.shadows {
+                    self.shadow(shadow);
+                }
+            }
+            ContentElement::Path(e) => {
+                if let Some(fill) = &mut e.fill {
+                    self.paint(fill);
+                }
+                if let Some(stroke) = &mut e.stroke {
+                    self.stroke(stroke);
+                }
+            }
+            ContentElement::Waveform(e) => {
+                self.paint(&mut e.fill);
+                if let Some(fill) = &mut e.rms_fill {
+                    self.paint(fill);
+                }
+            }
+            ContentElement::Image(e) => {
+                self.optional(e.width.as_mut());
+                self.optional(e.height.as_mut());
+            }
+        }
+    }
+
+    fn text_children(&mut self, children: &mut [TextChild]) {
+        for child in children {
+            let TextChild::Span(span) = child else {
+                continue;
+            };
+            if let Some(font) = &mut span.font {
+                self.font(font);
+            }
+            if let Some(color) = &mut span.color {
+                self.color(color);
+            }
+            self.text_children(&mut span.children);
+        }
+    }
+
+    fn radii(&mut self, radii: &mut CornerRadii) {
+        self.lengths([
+            &mut radii.top_left,
+            &mut radii.top_right,
+            &mut radii.bottom_right,
+            &mut radii.bottom_left,
+        ]);
+    }
+
+    fn border(&mut self, border: &mut Border) {
+        self.length(&mut border.width);
+        self.paint(&mut border.paint);
+        self.lengths(&mut border.dash);
+        self.length(&mut border.dash_offset);
+    }
+
+    fn shadow(&mut self, shadow: &mut Shadow) {
+        self.lengths([
+            &mut shadow.x,
+            &mut shadow.y,
+            &mut shadow.blur,
+            &mut shadow.spread,
+        ]);
+        self.color(&mut shadow.color);
+    }
+
+    fn stroke(&mut self, stroke: &mut PathStroke) {
+        self.length(&mut stroke.width);
+        self.paint(&mut stroke.paint);
+        self.lengths(&mut stroke.dash);
+        self.length(&mut stroke.dash_offset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        AnimatedProperty, Animator, BoxElement, DisplayMetrics, Easing, FrameClock, RectElement,
+        TextElement, TextOverflow, Timing, TransitionElement,
+    };
+
+    const DARK: &str = r##"
+        name = "Dark"
+
+        [colors]
+        "gray.900" = "#101010"
+        surface = "$gray.900"
+        text = "#f0f0f0"
+
+        [fonts]
+        body = "Inter, Noto Sans"
+        label = "$body"
+
+        [spacing]
+        md = "8px"
+        panel = "$md * 3"
+
+        [radii]
+        "panel.radius" = "4px"
+    "##;
+
+    const LIGHT: &str = r##"{
+        "name": "Light",
+        "colors": { "surface": "#fafafa", "text": "#202020" },
+        "spacing": { "md": "6px" }
+    }"##;
+
+    fn themed_box() -> Scene {
+        let rect = RectElement {
+            id: None,
+            fill: Some(Paint::from(Color::parse_from("$surface").expect("token"))),
+            radius: CornerRadii::uniform(&LayoutLength::Token("panel.radius".into())),
+            border: None,
+            shadows: Vec::new(),
+        };
+        let text = TextElement {
+            id: None,
+            children: vec![TextChild::Text("Mixer".into())],
+            font: "$label".into(),
+            weight: 400,
+            size: 12.0,
+            color: Color::parse_from("$text").expect("token"),
+            italic: false,
+            underline: false,
+            overflow: TextOverflow::default(),
+        };
+        let r#box = BoxElement {
+            id: None,
+            children: vec![
+                ElementNode::Behavior(BehaviorElement::Transition(TransitionElement {
+                    id: None,
+                    property: AnimatedProperty::Fill,
+                    timing: Timing::Tween {
+                        duration: 1.0,
+                        delay: 0.0,
+                        easing: Easing::Linear,
+                    },
+                })),
+                ElementNode::Content(ContentElement::Rect(Box::new(rect))),
+                ElementNode::Content(ContentElement::Text(text)),
+            ],
+            width: LayoutLength::parse_from("$panel").expect("token"),
+            height: LayoutLength::parse_from("$md + 1px").expect("expression"),
+        };
+        Scene {
+            children: vec![ElementNode::Layout(LayoutElement::Box(r#box))],
+            px_width: 100,
+            px_height: 100,
+            metrics: DisplayMetrics::default(),
+        }
+    }
+
+    #[test]
+    fn test_theme_tokens() {
+        let dark = Theme::from_toml(DARK).expect("valid theme");
+        assert_eq!(dark.name.as_deref(), Some("Dark"));
+        assert_eq!(dark.color("surface"), Some(Color::rgb(16, 16, 16)));
+        assert_eq!(dark.font("label").as_deref(), Some("Inter, Noto Sans"));
+        assert_eq!(
+            dark.length("panel"),
+            Some(LayoutLength::Calc(Arc::new(LengthExpr::Product(
+                Box::new(LengthExpr::Length(LayoutLength::Pixel(8.0))),
+                3.0
+            ))))
+        );
+        assert_eq!(dark.length("panel.radius"), Some(LayoutLength::Pixel(4.0)));
+        assert_eq!(dark.color("missing"), None);
+
+        let mut light = dark.clone();
+        light.extend(Theme::from_json(LIGHT).expect("valid theme"));
+        assert_eq!(light.name.as_deref(), Some("Light"));
+        assert_eq!(light.color("surface"), Some(Color::rgb(250, 250, 250)));
+        assert_eq!(light.font("label"), dark.font("label"));
+
+        let mut cyclic = Theme::default();
+        cyclic
+            .colors
+            .insert("a".into(), Color::parse_from("$b").expect("token"));
+        cyclic
+            .colors
+            .insert("b".into(), Color::parse_from("$a").expect("token"));
+        assert_eq!(cyclic.color("a"), None);
+
+        let err = Theme::from_json(r#"{ "spacing": { "x": 1 }, "radii": { "x": 2 } }"#)
+            .expect_err("ambiguous length");
+        assert!(matches!(err, ThemeError::AmbiguousLength(name) if &*name == "x"));
+        let err = Theme::from_json(r#"{ "colours": {} }"#).expect_err("unknown field");
+        assert!(matches!(err, ThemeError::Json(_)));
+    }
+
+    #[test]
+    fn test_theme_switch() {
+        let dark = Theme::from_toml(DARK).expect("valid theme");
+        let mut light = dark.clone();
+        light.extend(Theme::from_json(LIGHT).expect("valid theme"));
+        let declared = themed_box();
+
+        let mut scene = declared.clone();
+        assert!(dark.apply(&mut scene).is_empty());
+        let layout = scene
+            .layout_with(crate::Resources::default())
+            .expect("layout");
+        assert_eq!(layout.children[0].rect.size(), crate::Size::new(24.0, 9.0));
+        let ElementNode::Layout(LayoutElement::Box(e)) = &scene.children[0] else {
+            panic!("expected box");
+        };
+        let ElementNode::Content(ContentElement::Text(text)) = &e.children[2] else {
+            panic!("expected text");
+        };
+        assert_eq!(
+            (&*text.font, &text.color),
+            ("Inter, Noto Sans", &Color::rgb(240, 240, 240))
+        );
+
+        // Switching themes re-resolves the declared scene, and transitions animate the change.
+        let fill = |scene: &Scene| {
+            let ElementNode::Layout(LayoutElement::Box(e)) = &scene.children[0] else {
+                panic!("expected box");
+            };
+            let ElementNode::Content(ContentElement::Rect(rect)) = &e.children[1] else {
+                panic!("expected rect");
+            };
+            let Some(Paint::Solid { color }) = &rect.fill else {
+                panic!("expected solid fill");
+            };
+            color.clone()
+        };
+        let mut animator = Animator::new();
+        let mut clock = FrameClock::new();
+        let mut frame = |theme: &Theme, clock: &FrameClock| {
+            let mut scene = declared.clone();
+            theme.apply(&mut scene);
+            animator.animate(&mut scene, clock);
+            scene
+        };
+        let close = |a: &Color, b: &Color| {
+            let (a, b) = (a.to_premultiplied(), b.to_premultiplied());
+            [a.r - b.r, a.g - b.g, a.b - b.b, a.a - b.a]
+                .iter()
+                .all(|difference| difference.abs() < 1e-4)
+        };
+        let (from, to) = (Color::rgb(16, 16, 16), Color::rgb(250, 250, 250));
+        frame(&dark, &clock);
+        clock.advance(Duration::from_millis(500));
+        assert!(close(&fill(&frame(&light, &clock)), &from));
+        clock.advance(Duration::from_millis(500));
+        let halfway = fill(&frame(&light, &clock));
+        assert!(!close(&halfway, &from) && !close(&halfway, &to));
+        clock.advance(Duration::from_millis(500));
+        assert!(close(&fill(&frame(&light, &clock)), &to));
+
+        let mut scene = declared;
+        let missing = Theme::from_json(LIGHT)
+            .expect("valid theme")
+            .apply(&mut scene);
+        assert_eq!(
+            missing,
+            ["label", "panel", "panel.radius"].map(Arc::<str>::from)
+        );
+    }
+}
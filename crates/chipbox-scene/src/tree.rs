@@ -0,0 +1,613 @@
+//! Retained, mutable element tree.
+//!
+//! Unlike [`ElementNode`], retained nodes are untyped property bags,
+//! mirroring how the Solid renderer creates an element first and sets its properties later.
+//! The typed element tree is only materialized on demand.
+
+mod diff;
+mod patch;
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use serde_json::{Map, Value};
+
+pub use self::{diff::diff, patch::Patch};
+use crate::{ElementKind, ElementNode};
+
+/// Handle of a node in a [`SceneTree`].
+/// Handles are never reused within a tree.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId(pub u64);
+
+/// Contents of a retained node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NodeData {
+    /// The root of the tree. Its children are the root elements of the scene.
+    Root,
+    Element {
+        kind: ElementKind,
+        /// Element properties, keyed by their serialized names, excluding `type` and `children`.
+        props: Map<String, Value>,
+    },
+    /// A string child of a text element.
+    Text(Arc<str>),
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    data: NodeData,
+}
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum TreeError {
+    #[error("unknown node {0:?}")]
+    UnknownNode(NodeId),
+    #[error("node {0:?} was already created")]
+    DuplicateNode(NodeId),
+    #[error("node {node:?} is not a child of {parent:?}")]
+    NotAChild { parent: NodeId, node: NodeId },
+    #[error("node {0:?} is not attached to the tree")]
+    Detached(NodeId),
+    #[error("node {0:?} is already attached to the tree")]
+    AlreadyAttached(NodeId),
+    #[error("inserting {node:?} into {parent:?} would create a cycle")]
+    Cycle { parent: NodeId, node: NodeId },
+    #[error("node {0:?} is not an element")]
+    NotAnElement(NodeId),
+    #[error("node {0:?} is not a text node")]
+    NotText(NodeId),
+    #[error("the root node cannot be {0}")]
+    Root(&'static str),
+    #[error("unable to materialize element {node:?}")]
+    Materialize {
+        node: NodeId,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("unable to serialize element")]
+    Serialize(#[source] serde_json::Error),
+    #[error("element serialized to an unsupported shape: {0}")]
+    Unrepresentable(Value),
+}
+
+/// A retained element tree, addressed by [`NodeId`] handles.
+#[derive(Clone, Debug)]
+pub struct SceneTree {
+    nodes: HashMap<NodeId, Node>,
+    next_id: u64,
+}
+
+impl Default for SceneTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SceneTree {
+    /// Handle of the root node.
+    pub const ROOT: NodeId = NodeId(0);
+
+    #[must_use]
+    pub fn new() -> Self {
+        let root = Node {
+            parent: None,
+            children: Vec::new(),
+            data: NodeData::Root,
+        };
+        Self {
+            nodes: HashMap::from([(Self::ROOT, root)]),
+            next_id: 1,
+        }
+    }
+
+    /// Build a tree from typed elements.
+    /// ## Errors
+    /// - [`TreeError::Serialize`] or [`TreeError::Unrepresentable`]
+    ///   if an element does not serialize to a known element kind.
+    pub fn from_nodes(nodes: &[ElementNode]) -> Result<Self, TreeError> {
+        let mut tree = Self::new();
+        for node in nodes {
+            tree.create_from(Self::ROOT, &diff::Desc::from_element(node)?);
+        }
+        Ok(tree)
+    }
+
+    /// Create a subtree appended to an existing parent.
+    fn create_from(&mut self, parent: NodeId, desc: &diff::Desc) {
+        let id = self.create(desc.data.clone());
+        if let Some(node) = self.nodes.get_mut(&id) {
+            node.parent = Some(parent);
+        }
+        if let Some(parent) = self.nodes.get_mut(&parent) {
+            parent.children.push(id);
+        }
+        for child in &desc.children {
+            self.create_from(id, child);
+        }
+    }
+
+    fn create(&mut self, data: NodeData) -> NodeId {
+        let id = NodeId(self.next_id);
+        self.next_id += 1;
+        self.nodes.insert(
+            id,
+            Node {
+                parent: None,
+                children: Vec::new(),
+                data,
+            },
+        );
+        id
+    }
+
+    /// Reserve a specific handle for a new node.
+    /// The handle must not have been used before.
+    fn create_with_id(&mut self, id: NodeId, data: NodeData) -> Result<(), TreeError> {
+        if id.0 < self.next_id {
+            return Err(TreeError::DuplicateNode(id));
+        }
+        self.next_id = id.0 + 1;
+        self.nodes.insert(
+            id,
+            Node {
+                parent: None,
+                children: Vec::new(),
+                data,
+            },
+        );
+        Ok(())
+    }
+
+    /// Create a detached element without properties.
+    pub fn create_element(&mut self, kind: ElementKind) -> NodeId {
+        self.create(NodeData::Element {
+            kind,
+            props: Map::new(),
+        })
+    }
+
+    /// Create a detached text node.
+    pub fn create_text(&mut self, text: impl Into<Arc<str>>) -> NodeId {
+        self.create(NodeData::Text(text.into()))
+    }
+
+    fn node(&self, id: NodeId) -> Result<&Node, TreeError> {
+        self.nodes.get(&id).ok_or(TreeError::UnknownNode(id))
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> Result<&mut Node, TreeError> {
+        self.nodes.get_mut(&id).ok_or(TreeError::UnknownNode(id))
+    }
+
+    /// Contents of the node.
+    /// ## Errors
+    /// - [`TreeError::UnknownNode`] if the node does not exist.
+    pub fn data(&self, id: NodeId) -> Result<&NodeData, TreeError> {
+        Ok(&self.node(id)?.data)
+    }
+
+    /// Children of the node, in order.
+    /// ## Errors
+    /// - [`TreeError::UnknownNode`] if the node does not exist.
+    pub fn children(&self, id: NodeId) -> Result<&[NodeId], TreeError> {
+        Ok(&self.node(id)?.children)
+    }
+
+    /// Parent of the node, if it is attached.
+    /// ## Errors
+    /// - [`TreeError::UnknownNode`] if the node does not exist.
+    pub fn parent(&self, id: NodeId) -> Result<Option<NodeId>, TreeError> {
+        Ok(self.node(id)?.parent)
+    }
+
+    /// First child of the node.
+    /// ## Errors
+    /// - [`TreeError::UnknownNode`] if the node does not exist.
+    pub fn first_child(&self, id: NodeId) -> Result<Option<NodeId>, TreeError> {
+        Ok(self.node(id)?.children.first().copied())
+    }
+
+    /// Sibling following the node.
+    /// ## Errors
+    /// - [`TreeError::UnknownNode`] if the node does not exist.
+    pub fn next_sibling(&self, id: NodeId) -> Result<Option<NodeId>, TreeError> {
+        let Some(parent) = self.node(id)?.parent else {
+            return Ok(None);
+        };
+        let siblings = &self.node(parent)?.children;
+        Ok(siblings
+            .iter()
+            .position(|child| *child == id)
+            .and_then(|index| siblings.get(index + 1))
+            .copied())
+    }
+
+    /// Detach the node from its parent, if it has one.
+    /// The node and its descendants are kept, so they can be inserted again,
+    /// until they are dropped by [`Self::remove`] or [`Self::collect_garbage`].
+    /// ## Errors
+    /// - [`TreeError::UnknownNode`] if the node does not exist.
+    pub fn detach(&mut self, id: NodeId) -> Result<(), TreeError> {
+        if id == Self::ROOT {
+            return Err(TreeError::Root("detached"));
+        }
+        if let Some(parent) = self.node_mut(id)?.parent.take() {
+            self.node_mut(parent)?.children.retain(|child| *child != id);
+        }
+        Ok(())
+    }
+
+    /// Insert the node before the anchor, or append it if there is none.
+    /// If the node is already attached, it is moved.
+    /// ## Errors
+    /// - [`TreeError::UnknownNode`] if any of the nodes does not exist.
+    /// - [`TreeError::NotAChild`] if the anchor is not a child of the parent.
+    /// - [`TreeError::Cycle`] if the node is an ancestor of the parent.
+    pub fn insert(
+        &mut self,
+        parent: NodeId,
+        id: NodeId,
+        before: Option<NodeId>,
+    ) -> Result<(), TreeError> {
+        if id == Self::ROOT {
+            return Err(TreeError::Root("inserted"));
+        }
+        self.node(id)?;
+        // Walk up from the parent to make sure the node is not one of its ancestors.
+        let mut ancestor = Some(parent);
+        while let Some(current) = ancestor {
+            if current == id {
+                return Err(TreeError::Cycle { parent, node: id });
+            }
+            ancestor = self.node(current)?.parent;
+        }
+        if let Some(before) = before
+            && (before == id || self.node(before)?.parent != Some(parent))
+        {
+            return Err(TreeError::NotAChild {
+                parent,
+                node: before,
+            });
+        }
+        self.detach(id)?;
+        let children = &mut self.node_mut(parent)?.children;
+        let index = before
+            .and_then(|before| children.iter().position(|child| *child == before))
+            .unwrap_or(children.len());
+        children.insert(index, id);
+        self.node_mut(id)?.parent = Some(parent);
+        Ok(())
+    }
+
+    /// Detach the node and drop it along with all of its descendants.
+    /// ## Errors
+    /// - [`TreeError::UnknownNode`] if the node does not exist.
+    pub fn remove(&mut self, id: NodeId) -> Result<(), TreeError> {
+        if id == Self::ROOT {
+            return Err(TreeError::Root("removed"));
+        }
+        self.detach(id)?;
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            if let Some(node) = self.nodes.remove(&id) {
+                stack.extend(node.children);
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop every node that is not reachable from the root,
+    /// returning how many nodes were dropped.
+    ///
+    /// Handles of dropped nodes become unknown, so this should only run
+    /// once nothing is going to insert the detached nodes again.
+    pub fn collect_garbage(&mut self) -> usize {
+        let mut reachable = HashSet::from([Self::ROOT]);
+        let mut stack = vec![Self::ROOT];
+        while let Some(id) = stack.pop() {
+            if let Some(node) = self.nodes.get(&id) {
+                stack.extend(
+                    node.children
+                        .iter()
+                        .filter(|child| reachable.insert(**child)),
+                );
+            }
+        }
+        let count = self.nodes.len();
+        self.nodes.retain(|id, _| reachable.contains(id));
+        count - self.nodes.len()
+    }
+
+    /// Set a property of an element.
+    /// Setting a property to `null` resets it to its default.
+    /// ## Errors
+    /// - [`TreeError::UnknownNode`] if the node does not exist.
+    /// - [`TreeError::NotAnElement`] if the node is not an element.
+    pub fn set_property(
+        &mut self,
+        id: NodeId,
+        name: impl Into<String>,
+        value: Value,
+    ) -> Result<(), TreeError> {
+        let NodeData::Element { props, .. } = &mut self.node_mut(id)?.data else {
+            return Err(TreeError::NotAnElement(id));
+        };
+        let name = name.into();
+        if value.is_null() {
+            props.remove(&name);
+        } else {
+            props.insert(name, value);
+        }
+        Ok(())
+    }
+
+    /// Replace the contents of a text node.
+    /// ## Errors
+    /// - [`TreeError::UnknownNode`] if the node does not exist.
+    /// - [`TreeError::NotText`] if the node is not a text node.
+    pub fn replace_text(&mut self, id: NodeId, text: impl Into<Arc<str>>) -> Result<(), TreeError> {
+        let NodeData::Text(current) = &mut self.node_mut(id)?.data else {
+            return Err(TreeError::NotText(id));
+        };
+        *current = text.into();
+        Ok(())
+    }
+
+    /// Materialize the typed elements attached to the root.
+    /// ## Errors
+    /// - [`TreeError::Materialize`] if an element has invalid properties or children.
+    pub fn to_nodes(&self) -> Result<Vec<ElementNode>, TreeError> {
+        self.node(Self::ROOT)?
+            .children
+            .iter()
+            .map(|id| {
+                serde_json::from_value(self.to_value(*id)?)
+                    .map_err(|source| TreeError::Materialize { node: *id, source })
+            })
+            .collect()
+    }
+
+    /// Build the serialized form of the node and its descendants.
+    fn to_value(&self, id: NodeId) -> Result<Value, TreeError> {
+        let node = self.node(id)?;
+        match &node.data {
+            NodeData::Root => Err(TreeError::Root("materialized")),
+            NodeData::Text(text) => Ok(Value::String(text.to_string())),
+            NodeData::Element { kind, props } => {
+                let mut object = props.clone();
+                object.insert("type".to_owned(), kind.name().into());
+                let children = node
+                    .children
+                    .iter()
+                    .map(|child| self.to_value(*child))
+                    .collect::<Result<_, _>>()?;
+                object.insert("children".to_owned(), Value::Array(children));
+                Ok(Value::Object(object))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn nodes(value: Value) -> Vec<ElementNode> {
+        serde_json::from_value(value).expect("fixture is a valid element list")
+    }
+
+    fn text(children: &[&str], size: f32) -> Value {
+        json!({
+            "type": "text",
+            "children": children,
+            "font": "Inter",
+            "weight": 400,
+            "size": size,
+            "color": { "type": "rgb", "r": 0, "g": 0, "b": 0 }
+        })
+    }
+
+    fn list(ids: &[&str]) -> Vec<ElementNode> {
+        let items: Vec<_> = ids
+            .iter()
+            .map(|id| json!({ "type": "box", "id": id, "width": 1, "height": "24px" }))
+            .collect();
+        nodes(json!([{ "type": "array", "axis": "y", "children": items }]))
+    }
+
+    /// Apply the diff to the old tree and check it reaches the new elements.
+    fn diff_apply(old: &[ElementNode], new: &[ElementNode]) -> Vec<Patch> {
+        let mut tree = SceneTree::from_nodes(old).expect("tree builds");
+        let patches = tree.diff(new).expect("diff succeeds");
+        tree.apply_all(patches.clone())
+            .expect("diff applies to its own tree");
+        assert_eq!(tree.to_nodes().expect("tree materializes"), new);
+        patches
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let elements = nodes(json!([
+            {
+                "type": "flex",
+                "id": "root",
+                "children": [
+                    { "type": "flex-item", "grow": 2, "children": [text(&["a", "b"], 12.0)] }
+                ]
+            },
+            { "type": "pointer-enter" },
+            {
+                "type": "text",
+                "children": ["C", { "type": "span", "baselineShift": 4, "children": ["#"] }],
+                "font": "Inter",
+                "weight": 400,
+                "size": 12,
+                "color": { "type": "rgb", "r": 0, "g": 0, "b": 0 }
+            }
+        ]));
+        let tree = SceneTree::from_nodes(&elements).expect("tree builds");
+        assert_eq!(tree.to_nodes().expect("tree materializes"), elements);
+    }
+
+    #[test]
+    fn test_diff_property() {
+        let old = nodes(json!([text(&["a"], 12.0)]));
+        let new = nodes(json!([text(&["a"], 14.0)]));
+        let patches = diff_apply(&old, &new);
+        assert_eq!(
+            patches,
+            [Patch::SetProperty {
+                node: NodeId(1),
+                name: "size".to_owned(),
+                value: json!(14.0),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_text() {
+        let old = nodes(json!([text(&["a", "b"], 12.0)]));
+        let new = nodes(json!([text(&["a", "c"], 12.0)]));
+        let patches = diff_apply(&old, &new);
+        assert_eq!(
+            patches,
+            [Patch::ReplaceText {
+                node: NodeId(3),
+                text: Arc::from("c"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_keyed() {
+        // Moving one item to the front only moves that item.
+        let patches = diff_apply(&list(&["a", "b", "c", "d"]), &list(&["d", "a", "b", "c"]));
+        assert_eq!(
+            patches,
+            [Patch::Move {
+                parent: NodeId(1),
+                node: NodeId(5),
+                before: Some(NodeId(2)),
+            }]
+        );
+        let patches = diff_apply(&list(&["a", "b", "c"]), &list(&["c", "x", "a"]));
+        assert!(patches.contains(&Patch::Remove { node: NodeId(3) }));
+        let moves = patches
+            .iter()
+            .filter(|patch| matches!(patch, Patch::Move { .. }))
+            .count();
+        assert_eq!(moves, 1);
+        diff_apply(&list(&["a", "b"]), &[]);
+        diff_apply(&[], &list(&["a", "b"]));
+    }
+
+    #[test]
+    fn test_diff_duplicate_ids() {
+        // Siblings sharing an id are matched in order, and the rest is removed or created.
+        let patches = diff_apply(&list(&["a", "b", "a"]), &list(&["b", "a"]));
+        assert!(patches.contains(&Patch::Remove { node: NodeId(4) }));
+        diff_apply(&list(&["a", "b"]), &list(&["a", "a", "b", "a"]));
+    }
+
+    #[test]
+    fn test_diff_kind_change() {
+        let old = nodes(json!([{ "type": "pointer-enter" }, text(&["a"], 12.0)]));
+        let new = nodes(json!([{ "type": "pointer-leave" }, text(&["a"], 12.0)]));
+        let patches = diff_apply(&old, &new);
+        assert_eq!(patches[0], Patch::Remove { node: NodeId(1) });
+    }
+
+    #[test]
+    fn test_demo_tree() {
+        // The calls the Solid renderer makes for the frontend demo, `<grid arrayLimit={3} />`.
+        let mut tree = SceneTree::new();
+        let grid = tree.create_element(ElementKind::Grid);
+        tree.set_property(grid, "arrayLimit", json!(3))
+            .expect("grid is an element");
+        tree.insert(SceneTree::ROOT, grid, None)
+            .expect("grid attaches");
+        let elements = tree.to_nodes().expect("demo materializes");
+        assert_eq!(
+            elements,
+            nodes(json!([{ "type": "grid", "arrayLimit": 3 }]))
+        );
+        let round_trip = SceneTree::from_nodes(&elements).expect("tree builds");
+        assert_eq!(round_trip.to_nodes().expect("tree materializes"), elements);
+
+        // Snake case names are not the serialized names, so the required limit is missing.
+        tree.set_property(grid, "arrayLimit", Value::Null)
+            .expect("grid is an element");
+        tree.set_property(grid, "array_limit", json!(3))
+            .expect("grid is an element");
+        assert!(matches!(
+            tree.to_nodes(),
+            Err(TreeError::Materialize { .. })
+        ));
+    }
+
+    #[test]
+    fn test_collect_garbage() {
+        let mut tree = SceneTree::new();
+        let outer = tree.create_element(ElementKind::Box);
+        let inner = tree.create_element(ElementKind::Box);
+        tree.insert(SceneTree::ROOT, outer, None)
+            .expect("outer attaches");
+        tree.insert(outer, inner, None).expect("inner attaches");
+        // Detached nodes are kept until collected, so they can move elsewhere.
+        tree.detach(inner).expect("inner detaches");
+        assert_eq!(tree.children(outer).expect("outer exists"), []);
+        tree.insert(SceneTree::ROOT, inner, Some(outer))
+            .expect("inner attaches again");
+        assert_eq!(tree.collect_garbage(), 0);
+        tree.detach(outer).expect("outer detaches");
+        let text = tree.create_text("a");
+        tree.insert(outer, text, None).expect("text attaches");
+        assert_eq!(tree.collect_garbage(), 2);
+        assert!(matches!(tree.data(text), Err(TreeError::UnknownNode(_))));
+        assert_eq!(
+            tree.children(SceneTree::ROOT).expect("root exists"),
+            [inner]
+        );
+    }
+
+    #[test]
+    fn test_tree_errors() {
+        let mut tree = SceneTree::new();
+        let outer = tree.create_element(ElementKind::Box);
+        let inner = tree.create_element(ElementKind::Box);
+        let text = tree.create_text("a");
+        tree.insert(SceneTree::ROOT, outer, None)
+            .expect("outer attaches");
+        tree.insert(outer, inner, None).expect("inner attaches");
+        assert!(matches!(
+            tree.insert(inner, outer, None),
+            Err(TreeError::Cycle { .. })
+        ));
+        assert!(matches!(
+            tree.insert(outer, text, Some(outer)),
+            Err(TreeError::NotAChild { .. })
+        ));
+        assert!(matches!(
+            tree.set_property(text, "id", json!("a")),
+            Err(TreeError::NotAnElement(_))
+        ));
+        assert!(matches!(
+            tree.apply(Patch::Insert {
+                parent: outer,
+                node: inner,
+                before: None,
+            }),
+            Err(TreeError::AlreadyAttached(_))
+        ));
+        tree.remove(outer).expect("outer is removed");
+        assert!(matches!(tree.data(inner), Err(TreeError::UnknownNode(_))));
+        assert_eq!(tree.children(SceneTree::ROOT).expect("root exists"), []);
+    }
+}
@@ -0,0 +1,273 @@
+use std::collections::{HashMap, VecDeque};
+
+use serde_json::{Map, Value};
+
+use super::{NodeData, NodeId, Patch, SceneTree, TreeError};
+use crate::{ElementKind, ElementNode, Scene};
+
+/// Detached description of a node and its descendants.
+#[derive(Clone, Debug)]
+pub struct Desc {
+    pub data: NodeData,
+    pub children: Vec<Self>,
+}
+
+impl Desc {
+    pub fn from_element(node: &ElementNode) -> Result<Self, TreeError> {
+        let value = serde_json::to_value(node).map_err(TreeError::Serialize)?;
+        Self::from_value(value, None)
+    }
+
+    /// Children of some elements are untagged, so their kind is implied by the parent.
+    fn from_value(value: Value, implied: Option<ElementKind>) -> Result<Self, TreeError> {
+        let mut props = match value {
+            Value::Object(props) => props,
+            Value::String(text) => {
+                return Ok(Self {
+                    data: NodeData::Text(text.into()),
+                    children: Vec::new(),
+                });
+            }
+            value => return Err(TreeError::Unrepresentable(value)),
+        };
+        let kind = props
+            .get("type")
+            .and_then(|kind| ElementKind::from_name(kind.as_str()?))
+            .or(implied);
+        let Some(kind) = kind else {
+            return Err(TreeError::Unrepresentable(Value::Object(props)));
+        };
+        props.remove("type");
+        let implied = match kind {
+            ElementKind::Array => Some(ElementKind::Box),
+            ElementKind::Grid => Some(ElementKind::GridItem),
+            ElementKind::Flex => Some(ElementKind::FlexItem),
+            ElementKind::Text | ElementKind::Span => Some(ElementKind::Span),
+            _ => None,
+        };
+        let children = match props.remove("children") {
+            Some(Value::Array(children)) => children
+                .into_iter()
+                .map(|child| Self::from_value(child, implied))
+                .collect::<Result<_, _>>()?,
+            _ => Vec::new(),
+        };
+        Ok(Self {
+            data: NodeData::Element { kind, props },
+            children,
+        })
+    }
+}
+
+/// Identity of a child used to match old and new children.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Key {
+    /// Elements with an explicit id.
+    Id(ElementKind, String),
+    /// The n-th child without an id of the given kind.
+    Kind(ElementKind, usize),
+    /// The n-th text child.
+    Text(usize),
+}
+
+/// Assign keys to a list of children.
+fn keys<'a>(children: impl Iterator<Item = &'a NodeData>) -> Vec<Key> {
+    let mut counts = HashMap::<Option<ElementKind>, usize>::new();
+    children
+        .map(|data| {
+            let kind = match data {
+                NodeData::Element { kind, props } => {
+                    if let Some(id) = props.get("id").and_then(Value::as_str) {
+                        return Key::Id(*kind, id.to_owned());
+                    }
+                    Some(*kind)
+                }
+                NodeData::Text(_) | NodeData::Root => None,
+            };
+            let count = counts.entry(kind).or_default();
+            *count += 1;
+            kind.map_or(Key::Text(*count - 1), |kind| Key::Kind(kind, *count - 1))
+        })
+        .collect()
+}
+
+/// Indices of a longest strictly increasing subsequence.
+fn longest_increasing(values: &[usize]) -> Vec<usize> {
+    // `tails[k]` is the index of the smallest tail of an increasing subsequence of length `k + 1`.
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous = vec![None; values.len()];
+    for (i, value) in values.iter().enumerate() {
+        let k = tails.partition_point(|&j| values[j] < *value);
+        previous[i] = k.checked_sub(1).map(|k| tails[k]);
+        if k == tails.len() {
+            tails.push(i);
+        } else {
+            tails[k] = i;
+        }
+    }
+    let mut result = Vec::with_capacity(tails.len());
+    let mut current = tails.last().copied();
+    while let Some(i) = current {
+        result.push(i);
+        current = previous[i];
+    }
+    result.reverse();
+    result
+}
+
+struct Differ<'a> {
+    tree: &'a SceneTree,
+    next_id: u64,
+    patches: Vec<Patch>,
+}
+
+impl Differ<'_> {
+    fn children(&mut self, parent: NodeId, new: &[Desc]) {
+        let old = self.tree.children(parent).expect("diffed node exists");
+        let old_keys = keys(old.iter().map(|id| &self.tree.nodes[id].data));
+        let new_keys = keys(new.iter().map(|desc| &desc.data));
+        // Siblings may share an id, those are matched in order.
+        let mut unmatched = HashMap::<&Key, VecDeque<usize>>::new();
+        for (i, key) in old_keys.iter().enumerate() {
+            unmatched.entry(key).or_default().push_back(i);
+        }
+        let matches: Vec<Option<usize>> = new_keys
+            .iter()
+            .map(|key| unmatched.get_mut(key)?.pop_front())
+            .collect();
+        // Drop old children that are not part of the new tree.
+        let mut removed: Vec<usize> = unmatched.into_values().flatten().collect();
+        removed.sort_unstable();
+        self.patches
+            .extend(removed.into_iter().map(|i| Patch::Remove { node: old[i] }));
+        // Children in the longest run that keeps its relative order stay in place.
+        let order: Vec<usize> = matches.iter().flatten().copied().collect();
+        let mut stable = vec![false; old.len()];
+        for i in longest_increasing(&order) {
+            stable[order[i]] = true;
+        }
+        // Place children back to front, so the anchor is always in its final position.
+        let mut before = None;
+        for (desc, matched) in new.iter().zip(matches).rev() {
+            let node = if let Some(i) = matched {
+                let node = old[i];
+                if !stable[i] {
+                    self.patches.push(Patch::Move {
+                        parent,
+                        node,
+                        before,
+                    });
+                }
+                self.node(node, desc);
+                node
+            } else {
+                let node = self.create(desc);
+                self.patches.push(Patch::Insert {
+                    parent,
+                    node,
+                    before,
+                });
+                node
+            };
+            before = Some(node);
+        }
+    }
+
+    /// Update a matched node in place.
+    fn node(&mut self, id: NodeId, desc: &Desc) {
+        match (&self.tree.nodes[&id].data, &desc.data) {
+            (NodeData::Text(old), NodeData::Text(new)) => {
+                if old != new {
+                    self.patches.push(Patch::ReplaceText {
+                        node: id,
+                        text: new.clone(),
+                    });
+                }
+            }
+            (NodeData::Element { props: old, .. }, NodeData::Element { props: new, .. }) => {
+                self.props(id, old, new);
+                self.children(id, &desc.children);
+            }
+            _ => unreachable!("matched nodes share their kind"),
+        }
+    }
+
+    fn props(&mut self, id: NodeId, old: &Map<String, Value>, new: &Map<String, Value>) {
+        for (name, value) in new {
+            if old.get(name) != Some(value) {
+                self.patches.push(Patch::SetProperty {
+                    node: id,
+                    name: name.clone(),
+                    value: value.clone(),
+                });
+            }
+        }
+        for name in old.keys().filter(|name| !new.contains_key(*name)) {
+            self.patches.push(Patch::SetProperty {
+                node: id,
+                name: name.clone(),
+                value: Value::Null,
+            });
+        }
+    }
+
+    /// Create a new subtree, returning the handle of its root.
+    fn create(&mut self, desc: &Desc) -> NodeId {
+        let node = NodeId(self.next_id);
+        self.next_id += 1;
+        match &desc.data {
+            NodeData::Text(text) => self.patches.push(Patch::CreateText {
+                node,
+                text: text.clone(),
+            }),
+            NodeData::Element { kind, props } => {
+                self.patches
+                    .push(Patch::CreateElement { node, kind: *kind });
+                self.props(node, &Map::new(), props);
+            }
+            NodeData::Root => unreachable!("descriptions never contain the root"),
+        }
+        for child in &desc.children {
+            let child = self.create(child);
+            self.patches.push(Patch::Insert {
+                parent: node,
+                node: child,
+                before: None,
+            });
+        }
+        node
+    }
+}
+
+impl SceneTree {
+    /// Compute the patches that turn the tree into the given elements.
+    ///
+    /// Children are matched by their id, or by their position among siblings of the same kind,
+    /// and only children that changed their relative order are moved.
+    /// ## Errors
+    /// - [`TreeError::Serialize`] or [`TreeError::Unrepresentable`]
+    ///   if an element does not serialize to a known element kind.
+    pub fn diff(&self, nodes: &[ElementNode]) -> Result<Vec<Patch>, TreeError> {
+        let mut differ = Differ {
+            tree: self,
+            next_id: self.next_id,
+            patches: Vec::new(),
+        };
+        let descs: Vec<_> = nodes
+            .iter()
+            .map(Desc::from_element)
+            .collect::<Result<_, _>>()?;
+        differ.children(Self::ROOT, &descs);
+        Ok(differ.patches)
+    }
+}
+
+/// Compute the patches between the element trees of two scenes.
+///
+/// The patches apply to a tree built with [`SceneTree::from_nodes`] from the old scene.
+/// ## Errors
+/// - [`TreeError::Serialize`] or [`TreeError::Unrepresentable`]
+///   if an element does not serialize to a known element kind.
+pub fn diff(old: &Scene, new: &Scene) -> Result<Vec<Patch>, TreeError> {
+    SceneTree::from_nodes(&old.children)?.diff(&new.children)
+}
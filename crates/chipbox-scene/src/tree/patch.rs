@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use serde_json::{Map, Value};
+
+use super::{NodeData, NodeId, SceneTree, TreeError};
+use crate::ElementKind;
+
+/// A single mutation of a [`SceneTree`].
+///
+/// Mirrors the hooks of the Solid universal renderer,
+/// so the frontend can stream changes instead of whole scenes.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum Patch {
+    /// Create a detached element without properties.
+    CreateElement { node: NodeId, kind: ElementKind },
+    /// Create a detached text node.
+    CreateText { node: NodeId, text: Arc<str> },
+    /// Insert a detached node before the anchor, or append it if there is none.
+    Insert {
+        parent: NodeId,
+        node: NodeId,
+        before: Option<NodeId>,
+    },
+    /// Move an attached node before the anchor, or to the end if there is none.
+    Move {
+        parent: NodeId,
+        node: NodeId,
+        before: Option<NodeId>,
+    },
+    /// Remove a node along with its descendants.
+    Remove { node: NodeId },
+    /// Set a property of an element, `null` resets it to its default.
+    SetProperty {
+        node: NodeId,
+        name: String,
+        value: Value,
+    },
+    /// Replace the contents of a text node.
+    ReplaceText { node: NodeId, text: Arc<str> },
+}
+
+impl SceneTree {
+    /// Apply a single patch.
+    /// ## Errors
+    /// - [`TreeError`] if the patch does not fit the current state of the tree.
+    pub fn apply(&mut self, patch: Patch) -> Result<(), TreeError> {
+        match patch {
+            Patch::CreateElement { node, kind } => self.create_with_id(
+                node,
+                NodeData::Element {
+                    kind,
+                    props: Map::new(),
+                },
+            ),
+            Patch::CreateText { node, text } => self.create_with_id(node, NodeData::Text(text)),
+            Patch::Insert {
+                parent,
+                node,
+                before,
+            } => {
+                if self.parent(node)?.is_some() {
+                    return Err(TreeError::AlreadyAttached(node));
+                }
+                self.insert(parent, node, before)
+            }
+            Patch::Move {
+                parent,
+                node,
+                before,
+            } => {
+                if self.parent(node)?.is_none() {
+                    return Err(TreeError::Detached(node));
+                }
+                self.insert(parent, node, before)
+            }
+            Patch::Remove { node } => self.remove(node),
+            Patch::SetProperty { node, name, value } => self.set_property(node, name, value),
+            Patch::ReplaceText { node, text } => self.replace_text(node, text),
+        }
+    }
+
+    /// Apply patches in order, stopping at the first error.
+    /// ## Errors
+    /// - [`TreeError`] if a patch does not fit the current state of the tree.
+    pub fn apply_all(&mut self, patches: impl IntoIterator<Item = Patch>) -> Result<(), TreeError> {
+        patches.into_iter().try_for_each(|patch| self.apply(patch))
+    }
+}
@@ -0,0 +1,717 @@
+//! Structural validation of a [`Scene`].
+//!
+//! The element types cannot express every rule on their own,
+//! so frontend mistakes are caught here instead of rendering garbage.
+
+use std::collections::{HashMap, hash_map::Entry};
+
+use crate::{
+    BehaviorElement, BoxElement, ContentElement, ElementId, ElementKind, ElementNode, ElementPath,
+    FlexItemElement, GridElement, GridTrack, LayoutElement, LayoutLength, Paint, Scene, TextChild,
+    TextureShader, Timing, ViewBox,
+};
+
+/// Where an element is located in the scene.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    pub path: ElementPath,
+    /// Human readable trail of element types, indices and ids leading to the element.
+    pub trail: String,
+}
+
+impl Location {
+    fn child(&self, index: usize, kind: ElementKind, id: Option<&ElementId>) -> Self {
+        let separator = if self.trail.is_empty() { "" } else { " > " };
+        let id = id.map(|id| format!("#{id}")).unwrap_or_default();
+        Self {
+            path: self.path.child(index),
+            trail: format!("{}{separator}{kind}[{index}]{id}", self.trail),
+        }
+    }
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.trail)
+    }
+}
+
+/// A single violated scene invariant.
+#[derive(Debug, Clone, PartialEq, thiserror::Error, miette::Diagnostic)]
+pub enum ValidationError {
+    #[error("duplicate element id `{id}` at {location}")]
+    #[diagnostic(
+        code(chipbox_scene::duplicate_id),
+        help("the id is already used at {first}")
+    )]
+    DuplicateId {
+        id: ElementId,
+        location: Location,
+        first: Location,
+    },
+    #[error("behavior element at scene root: {location}")]
+    #[diagnostic(
+        code(chipbox_scene::root_behavior),
+        help("behavior elements apply to their parent, wrap it in a layout element")
+    )]
+    RootBehavior { location: Location },
+    #[error("negative {property} ({value}) at {location}")]
+    #[diagnostic(code(chipbox_scene::negative_size))]
+    NegativeSize {
+        property: &'static str,
+        value: f32,
+        location: Location,
+    },
+    #[error("text size must be positive, found {size} at {location}")]
+    #[diagnostic(code(chipbox_scene::text_size))]
+    TextSize { size: f32, location: Location },
+    #[error("gradient without color stops at {location}")]
+    #[diagnostic(code(chipbox_scene::empty_gradient))]
+    EmptyGradient { location: Location },
+    #[error("gradient stop offset must be within 0..=1, found {offset} at {location}")]
+    #[diagnostic(code(chipbox_scene::gradient_stop))]
+    GradientStop { offset: f32, location: Location },
+    #[error("view box size must be positive, found {width}x{height} at {location}")]
+    #[diagnostic(code(chipbox_scene::view_box))]
+    ViewBox {
+        width: f32,
+        height: f32,
+        location: Location,
+    },
+    #[error("miter limit must be at least 1, found {limit} at {location}")]
+    #[diagnostic(
+        code(chipbox_scene::miter_limit),
+        help("the miter length is measured in multiples of the stroke width")
+    )]
+    MiterLimit { limit: f32, location: Location },
+    #[error("waveform range must not be empty, found {start}..{end} at {location}")]
+    #[diagnostic(code(chipbox_scene::waveform_range))]
+    WaveformRange {
+        start: f64,
+        end: f64,
+        location: Location,
+    },
+    #[error("texture opacity must be within 0..=1, found {opacity} at {location}")]
+    #[diagnostic(code(chipbox_scene::texture_opacity))]
+    TextureOpacity { opacity: f32, location: Location },
+    #[error("tint amount must be within 0..=1, found {amount} at {location}")]
+    #[diagnostic(code(chipbox_scene::tint_amount))]
+    TintAmount { amount: f32, location: Location },
+    #[error("transition {property} must be {expected}, found {value} at {location}")]
+    #[diagnostic(code(chipbox_scene::transition_timing))]
+    TransitionTiming {
+        property: &'static str,
+        expected: &'static str,
+        value: f32,
+        location: Location,
+    },
+}
+
+/// All invariants violated by a scene.
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+#[error("invalid scene ({} errors)", .errors.len())]
+pub struct SceneValidationError {
+    #[related]
+    pub errors: Vec<ValidationError>,
+}
+
+/// Check the scene for violated invariants.
+pub fn validate(scene: &Scene) -> Result<(), SceneValidationError> {
+    let mut validator = Validator::default();
+    let root = Location {
+        path: ElementPath::default(),
+        trail: String::new(),
+    };
+    for (index, child) in scene.children.iter().enumerate() {
+        if let ElementNode::Behavior(e) = child {
+            validator.errors.push(ValidationError::RootBehavior {
+                location: root.child(index, e.kind(), e.id().as_ref()),
+            });
+        }
+    }
+    validator.nodes(&scene.children, &root);
+    if validator.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(SceneValidationError {
+            errors: validator.errors,
+        })
+    }
+}
+
+#[derive(Default)]
+struct Validator {
+    errors: Vec<ValidationError>,
+    ids: HashMap<ElementId, Location>,
+}
+
+impl Validator {
+    /// Enter an element, registering its id.
+    fn enter(
+        &mut self,
+        parent: &Location,
+        index: usize,
+        kind: ElementKind,
+        id: Option<ElementId>,
+    ) -> Location {
+        let location = parent.child(index, kind, id.as_ref());
+        if let Some(id) = id {
+            match self.ids.entry(id) {
+                Entry::Occupied(entry) => self.errors.push(ValidationError::DuplicateId {
+                    id: entry.key().clone(),
+                    location: location.clone(),
+                    first: entry.get().clone(),
+                }),
+                Entry::Vacant(entry) => {
+                    entry.insert(location.clone());
+                }
+            }
+        }
+        location
+    }
+
+    fn nodes(&mut self, nodes: &[ElementNode], parent: &Location) {
+        for (index, node) in nodes.iter().enumerate() {
+            let location = self.enter(parent, index, node.kind(), node.id());
+            match node {
+                ElementNode::Layout(e) => self.layout(e, &location),
+                ElementNode::Behavior(e) => self.behavior(e, &location),
+                ElementNode::Content(e) => self.content(e, &location),
+            }
+        }
+    }
+
+    fn length(&mut self, property: &'static str, length: &LayoutLength, location: &Location) {
+        if let Some(value) = length.value().filter(|value| *value < 0.0) {
+            self.errors.push(ValidationError::NegativeSize {
+                property,
+                value,
+                location: location.clone(),
+            });
+        }
+    }
+
+    fn boxes(&mut self, boxes: &[BoxElement], parent: &Location) {
+        for (index, e) in boxes.iter().enumerate() {
+            let location = self.enter(parent, index, ElementKind::Box, e.id());
+            self.r#box(e, &location);
+        }
+    }
+
+    fn r#box(&mut self, element: &BoxElement, location: &Location) {
+        self.length("width", &element.width, location);
+        self.length("height", &element.height, location);
+        self.nodes(&element.children, location);
+    }
+
+    fn flex_items(&mut self, items: &[FlexItemElement], parent: &Location) {
+        for (index, e) in items.iter().enumerate() {
+            let location = self.enter(parent, index, ElementKind::FlexItem, e.id());
+            for (property, value) in [("grow", e.grow), ("shrink", e.shrink)] {
+                if value < 0.0 {
+                    self.errors.push(ValidationError::NegativeSize {
+                        property,
+                        value,
+                        location: location.clone(),
+                    });
+                }
+            }
+            for (property, length) in [
+                ("basis", &e.basis),
+                ("min", &e.min),
+                ("max", &e.max),
+                ("cross", &e.cross),
+            ] {
+                if let Some(length) = length {
+                    self.length(property, length, &location);
+                }
+            }
+            self.nodes(&e.children, &location);
+        }
+    }
+
+    fn track(&mut self, property: &'static str, track: &GridTrack, location: &Location) {
+        match track {
+            GridTrack::Length(length) => self.length(property, length, location),
+            GridTrack::Fraction(value) if *value < 0.0 => {
+                self.errors.push(ValidationError::NegativeSize {
+                    property,
+                    value: *value,
+                    location: location.clone(),
+                });
+            }
+            GridTrack::MinMax { min, max } => {
+                self.track(property, min, location);
+                self.track(property, max, location);
+            }
+            GridTrack::Fraction(_) | GridTrack::Auto => {}
+        }
+    }
+
+    fn grid(&mut self, element: &GridElement, location: &Location) {
+        for (property, template) in [("columns", &element.columns), ("rows", &element.rows)] {
+            for track in &template.0 {
+                self.track(property, track, location);
+            }
+        }
+        for (property, gap) in [
+            ("gap", &element.gap),
+            ("rowGap", &element.row_gap),
+            ("columnGap", &element.column_gap),
+        ] {
+            if let Some(gap) = gap {
+                self.length(property, gap, location);
+            }
+        }
+        for (index, e) in element.children.iter().enumerate() {
+            let location = self.enter(location, index, ElementKind::GridItem, e.id());
+            self.nodes(&e.children, &location);
+        }
+    }
+
+    fn layout(&mut self, element: &LayoutElement, location: &Location) {
+        match element {
+            LayoutElement::Box(e) => self.r#box(e, location),
+            LayoutElement::Margin(e) => self.nodes(&e.children, location),
+            LayoutElement::ContentBox(e) => self.nodes(&e.children, location),
+            LayoutElement::Align(e) => self.nodes(&e.children, location),
+            LayoutElement::Scroll(e) => {
+                if let Some(extent) = &e.item_extent {
+                    self.length("itemExtent", extent, location);
+                }
+                self.nodes(&e.children, location);
+            }
+            LayoutElement::Array(e) => self.boxes(&e.children, location),
+            LayoutElement::Grid(e) => self.grid(e, location),
+            LayoutElement::Flex(e) => {
+                if let Some(gap) = &e.gap {
+                    self.length("gap", gap, location);
+                }
+                self.flex_items(&e.children, location);
+            }
+        }
+    }
+
+    fn behavior(&mut self, element: &BehaviorElement, location: &Location) {
+        let e = match element {
+            BehaviorElement::Texture(e) => e,
+            BehaviorElement::Transition(e) => return self.timing(&e.timing, location),
+            _ => return,
+        };
+        if !(0.0..=1.0).contains(&e.opacity) {
+            self.errors.push(ValidationError::TextureOpacity {
+                opacity: e.opacity,
+                location: location.clone(),
+            });
+        }
+        match &e.shader {
+            Some(TextureShader::Blur { radius }) => self.length("blurRadius", radius, location),
+            Some(TextureShader::Tint { amount, .. }) if !(0.0..=1.0).contains(amount) => {
+                self.errors.push(ValidationError::TintAmount {
+                    amount: *amount,
+                    location: location.clone(),
+                });
+            }
+            Some(TextureShader::Mask { paint }) => self.paint(paint, location),
+            _ => {}
+        }
+    }
+
+    fn timing(&mut self, timing: &Timing, location: &Location) {
+        let mut check = |property, expected, value: f32, valid: bool| {
+            if !valid {
+                self.errors.push(ValidationError::TransitionTiming {
+                    property,
+                    expected,
+                    value,
+                    location: location.clone(),
+                });
+            }
+        };
+        match *timing {
+            Timing::Tween {
+                duration, delay, ..
+            } => {
+                check("duration", "non-negative", duration, duration >= 0.0);
+                check("delay", "non-negative", delay, delay >= 0.0);
+            }
+            Timing::Spring {
+                stiffness,
+                damping,
+                mass,
+            } => {
+                for (property, value) in [
+                    ("stiffness", stiffness),
+                    ("damping", damping),
+                    ("mass", mass),
+                ] {
+                    check(property, "positive", value, value > 0.0);
+                }
+            }
+        }
+    }
+
+    fn content(&mut self, element: &ContentElement, location: &Location) {
+        match element {
+            ContentElement::Text(e) => {
+                if e.size <= 0.0 || e.size.is_nan() {
+                    self.errors.push(ValidationError::TextSize {
+                        size: e.size,
+                        location: location.clone(),
+                    });
+                }
+                self.text_children(&e.children, location);
+            }
+            ContentElement::Rect(e) => {
+                for (corner, radius) in ["topLeft", "topRight", "bottomRight", "bottomLeft"]
+                    .into_iter()
+                    .zip(e.radius.corners())
+                {
+                    self.length(corner, radius, location);
+                }
+                if let Some(fill) = &e.fill {
+                    self.paint(fill, location);
+                }
+                if let Some(border) = &e.border {
+                    self.length("borderWidth", &border.width, location);
+                    for dash in &border.dash {
+                        self.length("dash", dash, location);
+                    }
+                    self.paint(&border.paint, location);
+                }
+                for shadow in &e.shadows {
+                    self.length("blur", &shadow.blur, location);
+                }
+            }
+            ContentElement::Path(e) => {
+                let ViewBox { width, height, .. } = e.view_box;
+                if [width, height]
+                    .iter()
+                    .any(|side| *side <= 0.0 || side.is_nan())
+                {
+                    self.errors.push(ValidationError::ViewBox {
+                        width,
+                        height,
+                        location: location.clone(),
+                    });
+                }
+                if let Some(fill) = &e.fill {
+                    self.paint(fill, location);
+                }
+                if let Some(stroke) = &e.stroke {
+                    self.length("strokeWidth", &stroke.width, location);
+                    for dash in &stroke.dash {
+                        self.length("dash", dash, location);
+                    }
+                    if stroke.miter_limit < 1.0 || stroke.miter_limit.is_nan() {
+                        self.errors.push(ValidationError::MiterLimit {
+                            limit: stroke.miter_limit,
+                            location: location.clone(),
+                        });
+                    }
+                    self.paint(&stroke.paint, location);
+                }
+            }
+            ContentElement::Waveform(e) => {
+                if let Some(end) = e.end
+                    && (end <= e.start || end.is_nan() || e.start.is_nan())
+                {
+                    self.errors.push(ValidationError::WaveformRange {
+                        start: e.start,
+                        end,
+                        location: location.clone(),
+                    });
+                }
+                self.paint(&e.fill, location);
+                if let Some(rms_fill) = &e.rms_fill {
+                    self.paint(rms_fill, location);
+                }
+            }
+            ContentElement::Image(e) => {
+                for (property, length) in [("width", &e.width), ("height", &e.height)] {
+                    if let Some(length) = length {
+                        self.length(property, length, location);
+                    }
+                }
+            }
+        }
+    }
+
+    fn paint(&mut self, paint: &Paint, location: &Location) {
+        if let Paint::Solid { .. } = paint {
+            return;
+        }
+        if paint.stops().is_empty() {
+            self.errors.push(ValidationError::EmptyGradient {
+                location: location.clone(),
+            });
+        }
+        for offset in paint.stops().iter().filter_map(|stop| stop.offset) {
+            if !(0.0..=1.0).contains(&offset) {
+                self.errors.push(ValidationError::GradientStop {
+                    offset,
+                    location: location.clone(),
+                });
+            }
+        }
+        if let Paint::Radial {
+            radius: Some(radius),
+            ..
+        } = paint
+        {
+            self.length("radius", radius, location);
+        }
+    }
+
+    fn text_children(&mut self, children: &[TextChild], parent: &Location) {
+        for (index, child) in children.iter().enumerate() {
+            if let TextChild::Span(span) = child {
+                let location = self.enter(parent, index, ElementKind::Span, span.id());
+                self.text_children(&span.children, &location);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        AnimatedProperty, Border, Color, ColorStop, DisplayMetrics, ImageElement, ImageFit,
+        ImageSource, MarginElement, PathElement, PathStroke, RectElement, TextElement,
+        TextureElement, TransitionElement, WaveformElement,
+    };
+
+    fn id(s: &str) -> ElementId {
+        ElementId(Arc::from(s))
+    }
+
+    fn r#box(id: Option<ElementId>, width: f32, children: Vec<ElementNode>) -> BoxElement {
+        BoxElement {
+            id,
+            children,
+            width: LayoutLength::Pixel(width),
+            height: LayoutLength::ParentHeight(1.0),
+        }
+    }
+
+    fn text(size: f32) -> ElementNode {
+        ElementNode::Content(ContentElement::Text(TextElement {
+            id: None,
+            children: vec![crate::TextChild::from("text")],
+            font: Arc::from("sans"),
+            weight: 400,
+            size,
+            color: Color::BLACK,
+            italic: false,
+            underline: false,
+            overflow: crate::TextOverflow::Wrap,
+        }))
+    }
+
+    fn scene(children: Vec<ElementNode>) -> Scene {
+        Scene {
+            children,
+            px_width: 100,
+            px_height: 100,
+            metrics: DisplayMetrics::default(),
+        }
+    }
+
+    #[test]
+    fn test_valid_scene() {
+        let inner = r#box(Some(id("inner")), 10.0, vec![text(12.0)]);
+        let outer = r#box(
+            Some(id("outer")),
+            20.0,
+            vec![
+                ElementNode::Layout(LayoutElement::Box(inner)),
+                ElementNode::Behavior(BehaviorElement::PointerAction),
+            ],
+        );
+        scene(vec![ElementNode::Layout(LayoutElement::Box(outer))])
+            .validate()
+            .expect("valid scene");
+    }
+
+    #[test]
+    fn test_invalid_scene() {
+        let texture = ElementNode::Behavior(BehaviorElement::Texture(TextureElement {
+            id: None,
+            opacity: 1.5,
+            shader: None,
+        }));
+        let inner = r#box(Some(id("a")), -5.0, vec![text(0.0), texture]);
+        let margin = MarginElement {
+            id: Some(id("a")),
+            children: vec![ElementNode::Layout(LayoutElement::Box(inner))],
+            base: None,
+            horizontal: None,
+            left: None,
+            right: None,
+            vertical: None,
+            top: None,
+            bottom: None,
+        };
+        let err = scene(vec![
+            ElementNode::Behavior(BehaviorElement::PointerEnter),
+            ElementNode::Layout(LayoutElement::Margin(margin)),
+        ])
+        .validate()
+        .expect_err("invalid scene");
+        let messages: Vec<_> = err.errors.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            [
+                "behavior element at scene root: pointer-enter[0]",
+                "duplicate element id `a` at margin[1]#a > box[0]#a",
+                "negative width (-5) at margin[1]#a > box[0]#a",
+                "text size must be positive, found 0 at margin[1]#a > box[0]#a > text[0]",
+                "texture opacity must be within 0..=1, found 1.5 at margin[1]#a > box[0]#a > texture[1]",
+            ]
+        );
+        let ValidationError::DuplicateId {
+            location, first, ..
+        } = &err.errors[1]
+        else {
+            panic!("expected duplicate id");
+        };
+        assert_eq!(location.path, ElementPath(vec![1, 0]));
+        assert_eq!(first.path, ElementPath(vec![1]));
+    }
+
+    /// Messages of the errors of an element placed in a box at the scene root.
+    fn errors(element: ElementNode) -> Vec<String> {
+        let parent = r#box(None, 10.0, vec![element]);
+        scene(vec![ElementNode::Layout(LayoutElement::Box(parent))])
+            .validate()
+            .expect_err("invalid element")
+            .errors
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_invalid_rect() {
+        let rect = ElementNode::Content(ContentElement::Rect(Box::new(RectElement {
+            fill: Some(Paint::Linear {
+                angle: 0.0,
+                stops: vec![ColorStop {
+                    offset: Some(1.5),
+                    color: Color::WHITE,
+                }],
+            }),
+            border: Some(Border {
+                width: LayoutLength::Pixel(-1.0),
+                paint: Paint::from(Color::BLACK),
+                dash: Vec::new(),
+                dash_offset: LayoutLength::Pixel(0.0),
+            }),
+            ..RectElement::default()
+        })));
+        assert_eq!(
+            errors(rect),
+            [
+                "gradient stop offset must be within 0..=1, found 1.5 at box[0] > rect[0]",
+                "negative borderWidth (-1) at box[0] > rect[0]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_path() {
+        let path = ElementNode::Content(ContentElement::Path(Box::new(PathElement {
+            view_box: ViewBox {
+                x: 0.0,
+                y: 0.0,
+                width: 24.0,
+                height: 0.0,
+            },
+            stroke: Some(PathStroke {
+                width: LayoutLength::Pixel(1.0),
+                paint: Paint::from(Color::BLACK),
+                cap: crate::LineCap::Round,
+                join: crate::LineJoin::Miter,
+                miter_limit: 0.5,
+                dash: Vec::new(),
+                dash_offset: LayoutLength::Pixel(0.0),
+            }),
+            ..PathElement::default()
+        })));
+        assert_eq!(
+            errors(path),
+            [
+                "view box size must be positive, found 24x0 at box[0] > path[0]",
+                "miter limit must be at least 1, found 0.5 at box[0] > path[0]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_waveform() {
+        let waveform = ElementNode::Content(ContentElement::Waveform(Box::new(WaveformElement {
+            id: None,
+            buffer: crate::BufferId("clip".into()),
+            channel: None,
+            start: 10.0,
+            end: Some(10.0),
+            gain: 1.0,
+            fill: Paint::from(Color::WHITE),
+            rms_fill: None,
+        })));
+        assert_eq!(
+            errors(waveform),
+            ["waveform range must not be empty, found 10..10 at box[0] > waveform[0]"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_image() {
+        let image = ElementNode::Content(ContentElement::Image(Box::new(ImageElement {
+            id: None,
+            source: ImageSource::Path {
+                path: "knob.png".into(),
+            },
+            fit: ImageFit::Contain,
+            width: Some(LayoutLength::Pixel(-2.0)),
+            height: None,
+        })));
+        assert_eq!(errors(image), ["negative width (-2) at box[0] > image[0]"]);
+    }
+
+    #[test]
+    fn test_invalid_texture_shader() {
+        let texture = ElementNode::Behavior(BehaviorElement::Texture(TextureElement {
+            id: None,
+            opacity: 1.0,
+            shader: Some(TextureShader::Tint {
+                color: Color::BLACK,
+                amount: 2.0,
+            }),
+        }));
+        assert_eq!(
+            errors(texture),
+            ["tint amount must be within 0..=1, found 2 at box[0] > texture[0]"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_transition() {
+        let transition = ElementNode::Behavior(BehaviorElement::Transition(TransitionElement {
+            id: None,
+            property: AnimatedProperty::Width,
+            timing: Timing::Spring {
+                stiffness: Timing::default_stiffness(),
+                damping: 0.0,
+                mass: Timing::default_mass(),
+            },
+        }));
+        assert_eq!(
+            errors(transition),
+            ["transition damping must be positive, found 0 at box[0] > transition[0]"]
+        );
+    }
+}
@@ -0,0 +1,245 @@
+//! Sample buffers referenced by waveform elements, and their peaks in device pixels.
+//!
+//! Peaks are precomputed at several resolutions when a buffer is registered,
+//! so drawing a whole song costs about as much as drawing a few samples.
+
+mod peaks;
+
+use std::{collections::HashMap, sync::Arc};
+
+pub use self::peaks::{Peak, PeakCache};
+use crate::{BufferId, Fill, LengthContext, Rect, WaveformElement};
+
+/// Audio with one or more channels of equal length.
+#[derive(Clone, Debug)]
+pub struct SampleBuffer {
+    channels: Vec<PeakCache>,
+}
+
+impl SampleBuffer {
+    /// A buffer from separate channels.
+    /// ## Panics
+    /// - If the channels differ in length.
+    #[must_use]
+    pub fn new(channels: Vec<Vec<f32>>) -> Self {
+        let frames = channels.first().map_or(0, Vec::len);
+        assert!(
+            channels.iter().all(|channel| channel.len() == frames),
+            "channels must have the same length"
+        );
+        Self {
+            channels: channels.into_iter().map(PeakCache::new).collect(),
+        }
+    }
+
+    /// A buffer from frames of `channel_count` interleaved samples.
+    /// A trailing incomplete frame is dropped.
+    /// ## Panics
+    /// - If `channel_count` is zero.
+    #[must_use]
+    pub fn from_interleaved(samples: &[f32], channel_count: usize) -> Self {
+        assert!(channel_count > 0, "a buffer needs at least one channel");
+        let frames = samples.len() / channel_count;
+        Self::new(
+            (0..channel_count)
+                .map(|channel| {
+                    samples[..frames * channel_count]
+                        .iter()
+                        .skip(channel)
+                        .step_by(channel_count)
+                        .copied()
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    #[must_use]
+    pub fn channels(&self) -> &[PeakCache] {
+        &self.channels
+    }
+
+    /// Number of frames in every channel.
+    #[must_use]
+    pub fn frames(&self) -> usize {
+        self.channels.first().map_or(0, PeakCache::len)
+    }
+}
+
+/// Sample buffers available to waveform elements, by id.
+#[derive(Clone, Debug, Default)]
+pub struct SampleBuffers {
+    buffers: HashMap<BufferId, Arc<SampleBuffer>>,
+}
+
+impl SampleBuffers {
+    /// Register a buffer, replacing any previous buffer with the same id.
+    pub fn insert(&mut self, id: BufferId, buffer: SampleBuffer) {
+        self.buffers.insert(id, Arc::new(buffer));
+    }
+
+    pub fn remove(&mut self, id: &BufferId) -> Option<Arc<SampleBuffer>> {
+        self.buffers.remove(id)
+    }
+
+    #[must_use]
+    pub fn get(&self, id: &BufferId) -> Option<&Arc<SampleBuffer>> {
+        self.buffers.get(id)
+    }
+}
+
+/// A [`WaveformElement`] with its peaks computed for every device pixel column.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaintedWaveform {
+    pub rect: Rect,
+    pub lanes: Vec<WaveformLane>,
+    pub fill: Fill,
+    pub rms_fill: Option<Fill>,
+}
+
+/// The waveform of a single channel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WaveformLane {
+    /// Bounds of the lane, with zero amplitude at its vertical center.
+    pub rect: Rect,
+    /// Peaks scaled by the gain, one per column starting at the left edge.
+    /// Columns outside the buffer are [`None`].
+    pub peaks: Vec<Option<Peak>>,
+}
+
+impl WaveformLane {
+    /// Vertical position of an amplitude, which reaches the lane edges at `-1` and `1`.
+    #[must_use]
+    pub fn y(&self, amplitude: f32) -> f32 {
+        let half = self.rect.height / 2.0;
+        (-amplitude).mul_add(half, self.rect.y + half)
+    }
+}
+
+impl PaintedWaveform {
+    /// Resolve a waveform element laid out at `rect`.
+    /// Returns [`None`] if its buffer or channel does not exist.
+    #[must_use]
+    pub fn of(
+        element: &WaveformElement,
+        rect: Rect,
+        ctx: &LengthContext,
+        buffers: &SampleBuffers,
+    ) -> Option<Self> {
+        let buffer = buffers.get(&element.buffer)?;
+        let channels = match element.channel {
+            Some(channel) => {
+                std::slice::from_ref(buffer.channels.get(usize::try_from(channel).ok()?)?)
+            }
+            None => buffer.channels(),
+        };
+        #[allow(
+            clippy::cast_precision_loss,
+            reason = "frame counts are far below 2^52"
+        )]
+        let end = element.end.unwrap_or_else(|| buffer.frames() as f64);
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            reason = "widths are small and positive"
+        )]
+        let columns = rect.width.ceil().max(0.0) as usize;
+        #[allow(clippy::cast_precision_loss, reason = "channel counts are small")]
+        let height = rect.height / channels.len().max(1) as f32;
+        let ctx = ctx.with_parent(rect.size());
+        Some(Self {
+            rect,
+            lanes: channels
+                .iter()
+                .enumerate()
+                .map(|(index, channel)| {
+                    #[allow(clippy::cast_precision_loss, reason = "channel counts are small")]
+                    let y = height.mul_add(index as f32, rect.y);
+                    WaveformLane {
+                        rect: Rect::new(rect.x, y, rect.width, height),
+                        peaks: channel
+                            .peaks(element.start, end, columns)
+                            .into_iter()
+                            .map(|peak| peak.map(|peak| peak.scaled(element.gain)))
+                            .collect(),
+                    }
+                })
+                .collect(),
+            fill: Fill::of(&element.fill, rect, &ctx),
+            rms_fill: element
+                .rms_fill
+                .as_ref()
+                .map(|paint| Fill::of(paint, rect, &ctx)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, DisplayMetrics, Paint, Size};
+
+    fn ctx() -> LengthContext<'static> {
+        let size = Size::new(200.0, 200.0);
+        LengthContext {
+            scene: size,
+            parent: size,
+            metrics: DisplayMetrics::default(),
+            text: None,
+            images: None,
+        }
+    }
+
+    fn waveform(channel: Option<u32>) -> WaveformElement {
+        WaveformElement {
+            id: None,
+            buffer: BufferId("clip".into()),
+            channel,
+            start: 0.0,
+            end: None,
+            gain: 2.0,
+            fill: Paint::from(Color::WHITE),
+            rms_fill: None,
+        }
+    }
+
+    #[test]
+    fn test_painted_waveform() {
+        let mut buffers = SampleBuffers::default();
+        // The left channel rises and the right channel falls.
+        let samples: Vec<f32> = (0..8)
+            .flat_map(|i| {
+                #[allow(clippy::cast_precision_loss, reason = "test signal")]
+                let value = i as f32 / 16.0;
+                [value, -value]
+            })
+            .collect();
+        buffers.insert(
+            BufferId("clip".into()),
+            SampleBuffer::from_interleaved(&samples, 2),
+        );
+        let rect = Rect::new(10.0, 0.0, 4.0, 100.0);
+
+        let stereo =
+            PaintedWaveform::of(&waveform(None), rect, &ctx(), &buffers).expect("existing buffer");
+        assert_eq!(stereo.lanes.len(), 2);
+        assert_eq!(stereo.lanes[1].rect, Rect::new(10.0, 50.0, 4.0, 50.0));
+        let peaks: Vec<Peak> = stereo.lanes[0].peaks.iter().flatten().copied().collect();
+        assert_eq!(peaks.len(), 4);
+        // Each column covers two frames, amplitudes are doubled by the gain.
+        assert_eq!((peaks[1].min, peaks[1].max), (0.25, 0.375));
+        assert_eq!(stereo.lanes[1].peaks[3].map(|peak| peak.min), Some(-0.875));
+        assert_eq!(
+            (stereo.lanes[0].y(1.0), stereo.lanes[1].y(0.0)),
+            (0.0, 75.0)
+        );
+
+        let right = PaintedWaveform::of(&waveform(Some(1)), rect, &ctx(), &buffers)
+            .expect("existing channel");
+        assert_eq!(right.lanes.len(), 1);
+        assert_eq!(right.lanes[0].rect, rect);
+        assert!(PaintedWaveform::of(&waveform(Some(2)), rect, &ctx(), &buffers).is_none());
+        buffers.remove(&BufferId("clip".into()));
+        assert!(PaintedWaveform::of(&waveform(None), rect, &ctx(), &buffers).is_none());
+    }
+}
@@ -0,0 +1,255 @@
+use std::{ops::Range, sync::Arc};
+
+/// Frames summarized by each block of the finest level.
+const BLOCK_FRAMES: usize = 64;
+
+/// Minimum, maximum and root mean square of a range of samples.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Peak {
+    pub min: f32,
+    pub max: f32,
+    pub rms: f32,
+}
+
+impl Peak {
+    /// The peak with every amplitude multiplied by `gain`.
+    #[must_use]
+    pub fn scaled(self, gain: f32) -> Self {
+        let (min, max) = (self.min * gain, self.max * gain);
+        Self {
+            min: min.min(max),
+            max: max.max(min),
+            rms: self.rms * gain.abs(),
+        }
+    }
+}
+
+/// Mergeable summary of a block of samples.
+#[derive(Clone, Copy, Debug)]
+struct Summary {
+    min: f32,
+    max: f32,
+    squares: f32,
+}
+
+impl Summary {
+    const EMPTY: Self = Self {
+        min: f32::INFINITY,
+        max: f32::NEG_INFINITY,
+        squares: 0.0,
+    };
+
+    const fn sample(sample: f32) -> Self {
+        Self {
+            min: sample,
+            max: sample,
+            squares: sample * sample,
+        }
+    }
+
+    const fn merge(self, other: Self) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+            squares: self.squares + other.squares,
+        }
+    }
+}
+
+/// Samples of a single channel with summaries at halving resolutions.
+///
+/// The finest level summarizes blocks of 64 frames, every further level merges
+/// pairs of blocks of the previous one. A range is summarized from the coarsest blocks
+/// that fit inside it, so the cost does not grow with its length.
+#[derive(Clone, Debug)]
+pub struct PeakCache {
+    samples: Arc<[f32]>,
+    levels: Vec<Vec<Summary>>,
+}
+
+impl PeakCache {
+    #[must_use]
+    pub fn new(samples: impl Into<Arc<[f32]>>) -> Self {
+        let samples = samples.into();
+        let mut levels = vec![
+            samples
+                .chunks(BLOCK_FRAMES)
+                .map(|block| {
+                    block.iter().fold(Summary::EMPTY, |summary, sample| {
+                        summary.merge(Summary::sample(*sample))
+                    })
+                })
+                .collect::<Vec<_>>(),
+        ];
+        while let Some(previous) = levels.last().filter(|level| level.len() > 1) {
+            let next = previous
+                .chunks(2)
+                .map(|pair| pair.iter().fold(Summary::EMPTY, |a, b| a.merge(*b)))
+                .collect();
+            levels.push(next);
+        }
+        Self { samples, levels }
+    }
+
+    #[must_use]
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    /// Number of frames.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// The peak of a range of frames, clamped to the samples.
+    /// Returns [`None`] if no frames remain.
+    #[must_use]
+    pub fn peak(&self, range: Range<usize>) -> Option<Peak> {
+        let end = range.end.min(self.len());
+        let mut frame = range.start;
+        if frame >= end {
+            return None;
+        }
+        let mut summary = Summary::EMPTY;
+        while frame < end {
+            // The coarsest block starting at this frame that ends inside the range.
+            let level = (0..self.levels.len())
+                .take_while(|level| {
+                    let frames = BLOCK_FRAMES << level;
+                    frame.is_multiple_of(frames) && frame + frames <= end
+                })
+                .last();
+            if let Some(level) = level {
+                let frames = BLOCK_FRAMES << level;
+                summary = summary.merge(self.levels[level][frame / frames]);
+                frame += frames;
+            } else {
+                summary = summary.merge(Summary::sample(self.samples[frame]));
+                frame += 1;
+            }
+        }
+        #[allow(clippy::cast_precision_loss, reason = "only used for averaging")]
+        let count = (end - range.start) as f32;
+        Some(Peak {
+            min: summary.min,
+            max: summary.max,
+            rms: (summary.squares / count).sqrt(),
+        })
+    }
+
+    /// Peaks of `columns` equal parts of the frames from `start` to `end`.
+    ///
+    /// Every column covers at least the frame it starts in,
+    /// so zooming in past single samples repeats them across columns.
+    #[must_use]
+    pub fn peaks(&self, start: f64, end: f64, columns: usize) -> Vec<Option<Peak>> {
+        #[allow(clippy::cast_precision_loss, reason = "column counts are small")]
+        let step = (end - start) / columns as f64;
+        let frame = |column: usize| {
+            #[allow(clippy::cast_precision_loss, reason = "column counts are small")]
+            let frame = step.mul_add(column as f64, start);
+            frame.floor()
+        };
+        (0..columns)
+            .map(|column| {
+                let first = frame(column);
+                let last = frame(column + 1).max(first + 1.0);
+                if last <= 0.0 {
+                    return None;
+                }
+                #[allow(
+                    clippy::cast_possible_truncation,
+                    clippy::cast_sign_loss,
+                    reason = "negative frames saturate to the start of the buffer"
+                )]
+                let (first, last) = (first as usize, last as usize);
+                self.peak(first..last)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A deterministic signal with distinct values across the blocks.
+    fn signal(len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| {
+                #[allow(clippy::cast_precision_loss, reason = "test signal")]
+                let t = i as f32;
+                (t * 0.05).sin() * (t * 0.001).cos()
+            })
+            .collect()
+    }
+
+    fn brute_force(samples: &[f32]) -> Peak {
+        #[allow(clippy::cast_precision_loss, reason = "test signal")]
+        let count = samples.len() as f32;
+        Peak {
+            min: samples.iter().copied().fold(f32::INFINITY, f32::min),
+            max: samples.iter().copied().fold(f32::NEG_INFINITY, f32::max),
+            rms: (samples.iter().map(|s| s * s).sum::<f32>() / count).sqrt(),
+        }
+    }
+
+    fn assert_close(actual: Peak, expected: Peak) {
+        for (a, b) in [
+            (actual.min, expected.min),
+            (actual.max, expected.max),
+            (actual.rms, expected.rms),
+        ] {
+            assert!((a - b).abs() < 1e-3, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn test_peak_ranges() {
+        let samples = signal(10_000);
+        let cache = PeakCache::new(samples.clone());
+        assert_eq!(cache.levels.len(), 9);
+        for range in [
+            0..10_000,
+            0..1,
+            63..65,
+            100..9_000,
+            128..4_224,
+            9_990..20_000,
+        ] {
+            let expected = brute_force(&samples[range.start..range.end.min(samples.len())]);
+            assert_close(cache.peak(range).expect("frames in range"), expected);
+        }
+        assert_eq!(cache.peak(10_000..10_100), None);
+        assert_eq!(cache.peak(5..5), None);
+    }
+
+    #[test]
+    fn test_peak_columns() {
+        let samples = signal(1_000);
+        let cache = PeakCache::new(samples.clone());
+        let peaks = cache.peaks(0.0, 1_000.0, 10);
+        assert_eq!(peaks.len(), 10);
+        assert_close(
+            peaks[3].expect("frames in column"),
+            brute_force(&samples[300..400]),
+        );
+
+        // Zoomed in past single samples, each sample spans several columns.
+        let peaks = cache.peaks(10.0, 12.0, 4);
+        let values: Vec<f32> = peaks.iter().flatten().map(|peak| peak.max).collect();
+        assert_eq!(values, [samples[10], samples[10], samples[11], samples[11]]);
+
+        // Columns outside the samples are empty.
+        let peaks = cache.peaks(-20.0, 20.0, 4);
+        assert_eq!(peaks[0], None);
+        assert!(peaks[2].is_some());
+        assert_eq!(cache.peaks(990.0, 1_010.0, 2)[1], None);
+    }
+}
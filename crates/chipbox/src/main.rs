@@ -74,6 +74,8 @@ async fn js_task(#[cfg(debug_assertions)] mut hmr_recv: chipbox_js::HmrRecv) ->
     // Load entry point from Vite dev server
     #[cfg(debug_assertions)]
     load_entry_module(&js_rt).await?;
+    // Drop the scene nodes removed while the scripts settled.
+    chipbox_js::collect_garbage();
 
     // Run HMR event loop
     #[cfg(debug_assertions)]
@@ -100,6 +102,7 @@ async fn js_task(#[cfg(debug_assertions)] mut hmr_recv: chipbox_js::HmrRecv) ->
                     }
                 }
             }
+            chipbox_js::collect_garbage();
         }
     }
 
@@ -1,7 +1,7 @@
-import { render } from "chipbox-solid-render";
+import { render, root } from "chipbox-solid-render";
 
 function App() {
-    return <grid array_limit={3} />;
+    return <grid arrayLimit={3} />;
 }
 
-render(() => <App />, <box width="100su" height="100su" />);
+render(() => <App />, root);
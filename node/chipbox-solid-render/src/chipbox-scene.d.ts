@@ -0,0 +1,31 @@
+// Type declarations for chipbox:scene native module.
+declare module "chipbox:scene" {
+    /** Handle of a node in the retained scene tree. */
+    export type SceneNode = number & { readonly __sceneNode: unique symbol };
+    /** Handle of the root node. Its children are the root elements of the scene. */
+    export const root: SceneNode;
+    /** Create a detached element with the given tag. */
+    export function createElement(tag: string): SceneNode;
+    /** Create a detached text node with the given contents. */
+    export function createTextNode(value: string): SceneNode;
+    /** Replace the contents of the given text node. */
+    export function replaceText(textNode: SceneNode, value: string): void;
+    /** Returns true if the given node is a text node. */
+    export function isTextNode(node: SceneNode): boolean;
+    /** Set a property to a JSON-serializable value. `undefined` resets it. */
+    export function setProperty(node: SceneNode, name: string, value: unknown): void;
+    /** Insert a node before the anchor, moving it if it is already attached. */
+    export function insertNode(parent: SceneNode, node: SceneNode, anchor?: SceneNode): void;
+    /** Detach a node from its parent. Its handle stays valid until the host collects removed nodes. */
+    export function removeNode(parent: SceneNode, node: SceneNode): void;
+    /** Get the parent node of the given node. */
+    export function getParentNode(node: SceneNode): SceneNode | undefined;
+    /** Get the first child of the given node. */
+    export function getFirstChild(node: SceneNode): SceneNode | undefined;
+    /** Get the next sibling of the given node. */
+    export function getNextSibling(node: SceneNode): SceneNode | undefined;
+    /** Bind a global shortcut such as `Ctrl+S` to a name. Returns the name it was previously bound to. */
+    export function registerShortcut(shortcut: string, name: string): string | undefined;
+    /** Remove every shortcut bound to the name. Returns true if any shortcut was removed. */
+    export function unregisterShortcut(name: string): boolean;
+}
@@ -1,9 +1,16 @@
+import type { FlexAlign, LayoutParam } from "../scene/layout";
 import type { JSX } from "src/jsx-runtime";
 
 export type JsxFlexItemProps = {
     id?: string;
     children?: JSX.ElementChildren;
-    proportion?: number; // Defaults to 1.0
+    grow?: number; // Defaults to 1.0
+    shrink?: number; // Defaults to 1.0
+    basis?: LayoutParam; // Defaults to zero
+    min?: LayoutParam;
+    max?: LayoutParam;
+    cross?: LayoutParam; // Defaults to the cross extent of the flex element
+    align?: FlexAlign; // Defaults to the alignment of the flex element
 };
 
 export type JsxFlexItem = JsxFlexItemProps & {
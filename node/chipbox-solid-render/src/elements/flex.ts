@@ -1,4 +1,4 @@
-import type { Axis, LinearDirection } from "../scene/layout";
+import type { Axis, FlexAlign, FlexJustify, LayoutParam, LinearDirection } from "../scene/layout";
 import type { JsxFlexItem } from "./flex-item";
 import type { JSX } from "src/jsx-runtime";
 
@@ -7,6 +7,9 @@ export type JsxFlexProps = {
     children?: JSX.Children<JsxFlexItem>;
     axis?: Axis; // Defaults to "horizontal"
     direction?: LinearDirection; // Defaults to "forward"
+    gap?: LayoutParam; // Defaults to no gap
+    justify?: FlexJustify; // Defaults to "start"
+    align?: FlexAlign; // Defaults to "stretch"
 };
 
 export type JsxFlex = JsxFlexProps & {
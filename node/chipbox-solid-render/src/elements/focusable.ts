@@ -0,0 +1,8 @@
+export type JsxFocusableProps = {
+    id?: string;
+    tabIndex?: number; // Defaults to 0, negative values are skipped by tab traversal
+};
+
+export type JsxFocusable = JsxFocusableProps & {
+    type: "focusable";
+};
@@ -0,0 +1,12 @@
+import type { JSX } from "src/jsx-runtime";
+
+export type JsxGridItemProps = {
+    id?: string;
+    children?: JSX.ElementChildren;
+    columnSpan?: number; // Defaults to 1
+    rowSpan?: number; // Defaults to 1
+};
+
+export type JsxGridItem = JsxGridItemProps & {
+    type: "grid-item";
+};
@@ -1,14 +1,19 @@
-import type { Axis, LinearDirection } from "../scene/layout";
-import type { JsxBox } from "./box";
+import type { Axis, GridTemplate, LayoutParam, LinearDirection } from "../scene/layout";
+import type { JsxGridItem } from "./grid-item";
 import type { JSX } from "src/jsx-runtime";
 
 export type JsxGridProps = {
     id?: string;
-    children?: JSX.Children<JsxBox>;
+    children?: JSX.Children<JsxGridItem>;
     axis?: Axis; // Defaults to "horizontal"
     direction?: LinearDirection; // Defaults to "forward"
-    array_direction?: LinearDirection; // Defaults to "forward"
-    array_limit: number; // NonZeroUsize in Rust, number in TS
+    arrayDirection?: LinearDirection; // Defaults to "forward"
+    arrayLimit: number; // NonZeroUsize in Rust, number in TS
+    columns?: GridTemplate; // Defaults to equally sized columns
+    rows?: GridTemplate; // Defaults to equally sized rows
+    gap?: LayoutParam; // Defaults to no gap
+    rowGap?: LayoutParam; // Defaults to `gap`
+    columnGap?: LayoutParam; // Defaults to `gap`
 };
 
 export type JsxGrid = JsxGridProps & {
@@ -0,0 +1,28 @@
+import type { LayoutParam } from "../scene/layout";
+
+export type ImageSourceParam =
+    | string // Path of a file on disk
+    | { type: "path"; path: string }
+    | { type: "asset"; id: string }; // Asset registered with the image cache
+
+export type ImageFitParam =
+    | "contain"
+    | "cover"
+    | "fill"
+    | { type: "contain" }
+    | { type: "cover" }
+    | { type: "fill" }
+    // Insets in image pixels, corners keep their size
+    | { type: "nineSlice"; top: number; right: number; bottom: number; left: number };
+
+export type JsxImageProps = {
+    id?: string;
+    source: ImageSourceParam; // PNG, JPEG or QOI
+    fit?: ImageFitParam; // Defaults to "contain"
+    width?: LayoutParam; // Follows the aspect ratio if only height is set
+    height?: LayoutParam; // Intrinsic size if neither is set
+};
+
+export type JsxImage = JsxImageProps & {
+    type: "image";
+};
@@ -3,7 +3,18 @@ export type { JsxMargin, JsxMarginProps } from "./margin";
 export type { JsxContentBox, JsxContentBoxProps } from "./content-box";
 export type { JsxArray, JsxArrayProps } from "./array";
 export type { JsxGrid, JsxGridProps } from "./grid";
+export type { JsxGridItem, JsxGridItemProps } from "./grid-item";
 export type { JsxFlex, JsxFlexProps } from "./flex";
 export type { JsxFlexItem, JsxFlexItemProps } from "./flex-item";
+export type { JsxScroll, JsxScrollProps } from "./scroll";
 export type { JsxTexture, JsxTextureProps } from "./texture";
+export type { JsxFocusable, JsxFocusableProps } from "./focusable";
 export type { JsxText, JsxTextProps } from "./text";
+export type { JsxSpan, JsxSpanProps } from "./span";
+export type { JsxRect, JsxRectProps } from "./rect";
+export type { JsxPath, JsxPathProps } from "./path";
+export type { JsxWaveform, JsxWaveformProps } from "./waveform";
+export type { JsxImage, JsxImageProps } from "./image";
+export type { JsxTransform, JsxTransformProps } from "./transform";
+export type { JsxStacking, JsxStackingProps } from "./stacking";
+export type { JsxTransition, JsxTransitionProps } from "./transition";
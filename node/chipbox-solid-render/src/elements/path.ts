@@ -0,0 +1,34 @@
+import type { FillRule, PaintParam, PathStrokeParam } from "../scene/paint";
+
+/** A segment in absolute view box coordinates, as produced by parsing path data. */
+export type PathSegment =
+    | { type: "moveTo"; x: number; y: number }
+    | { type: "lineTo"; x: number; y: number }
+    | { type: "quadTo"; x1: number; y1: number; x: number; y: number }
+    | { type: "cubicTo"; x1: number; y1: number; x2: number; y2: number; x: number; y: number }
+    | { type: "arcTo"; rx: number; ry: number; rotation: number; largeArc: boolean; sweep: boolean; x: number; y: number }
+    | { type: "close" };
+
+/** SVG path data such as `"M0 0 L1 1 Z"`, or already parsed segments. */
+export type PathDataParam = string | PathSegment[];
+
+export type ViewBox = {
+    x?: number;
+    y?: number;
+    width: number;
+    height: number;
+};
+
+export type JsxPathProps = {
+    id?: string;
+    data: PathDataParam;
+    viewBox?: ViewBox; // Defaults to the unit square
+    fit?: "stretch" | "contain"; // Defaults to "stretch"
+    fill?: PaintParam;
+    fillRule?: FillRule; // Defaults to "nonZero"
+    stroke?: PathStrokeParam;
+};
+
+export type JsxPath = JsxPathProps & {
+    type: "path";
+};
@@ -0,0 +1,13 @@
+import type { BorderParam, CornerRadiiParam, PaintParam, ShadowParam } from "../scene/paint";
+
+export type JsxRectProps = {
+    id?: string;
+    fill?: PaintParam;
+    radius?: CornerRadiiParam; // Defaults to square corners
+    border?: BorderParam;
+    shadows?: ShadowParam[];
+};
+
+export type JsxRect = JsxRectProps & {
+    type: "rect";
+};
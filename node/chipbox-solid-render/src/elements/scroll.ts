@@ -0,0 +1,15 @@
+import type { Axis, LayoutParam } from "../scene/layout";
+import type { JSX } from "src/jsx-runtime";
+
+export type JsxScrollProps = {
+    id?: string;
+    children?: JSX.ElementChildren;
+    axis?: Axis; // Defaults to both axes
+    offsetX?: LayoutParam; // Defaults to zero
+    offsetY?: LayoutParam; // Defaults to zero
+    itemExtent?: LayoutParam; // Enables virtualization of uniformly sized rows
+};
+
+export type JsxScroll = JsxScrollProps & {
+    type: "scroll";
+};
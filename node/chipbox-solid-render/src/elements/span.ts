@@ -0,0 +1,17 @@
+import type { ColorParam } from "../scene/color";
+import type { JSX } from "src/jsx-runtime";
+
+export type JsxSpanProps = {
+    id?: string;
+    children?: JSX.ElementChildren;
+    font?: string; // Unset properties are inherited from the enclosing span or text
+    weight?: number;
+    italic?: boolean;
+    color?: ColorParam;
+    underline?: boolean;
+    baselineShift?: number; // Positive values raise the glyphs
+};
+
+export type JsxSpan = JsxSpanProps & {
+    type: "span";
+};
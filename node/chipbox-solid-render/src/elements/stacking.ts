@@ -0,0 +1,8 @@
+export type JsxStackingProps = {
+    id?: string;
+    zIndex?: number; // Defaults to 0, higher indices are painted above their siblings
+};
+
+export type JsxStacking = JsxStackingProps & {
+    type: "stacking";
+};
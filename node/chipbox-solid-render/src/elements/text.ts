@@ -1,13 +1,17 @@
-import type { Color } from "@bindings/scene";
+import type { TextOverflow } from "@bindings/scene";
+import type { ColorParam } from "../scene/color";
 import type { JSX } from "src/jsx-runtime";
 
 export type JsxTextProps = {
     id?: string;
     children?: JSX.ElementChildren;
-    font: string;
+    font: string; // Family, comma-separated stack, or theme token such as "$body"
     weight: number;
     size: number;
-    color?: Color; // Optional since it might not be in the current wrapper
+    color?: ColorParam; // Optional since it might not be in the current wrapper
+    italic?: boolean; // Defaults to false
+    underline?: boolean; // Defaults to false
+    overflow?: TextOverflow; // Defaults to "wrap"
 };
 
 export type JsxText = JsxTextProps & {
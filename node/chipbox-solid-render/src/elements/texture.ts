@@ -1,7 +1,20 @@
+import type { ColorParam } from "../scene/color";
+import type { LayoutParam } from "../scene/layout";
+import type { PaintParam } from "../scene/paint";
+
+export type TextureShaderParam =
+    /** Gaussian blur with the radius as its standard deviation, as in CSS `blur()`. */
+    | { type: "blur"; radius: LayoutParam }
+    /** Row-major 4x5 matrix over straight linear RGBA and a constant one, as in SVG `feColorMatrix`. */
+    | { type: "colorMatrix"; matrix: number[] }
+    | { type: "tint"; color: ColorParam; amount?: number } // Amount defaults to 1
+    /** Multiplies the layer by the alpha of the paint. */
+    | { type: "mask"; paint: PaintParam };
+
 export type JsxTextureProps = {
     id?: string;
-    opacity?: number; // Defaults to 1.0
-    shader: unknown; // () in Rust, unknown in TS
+    opacity?: number; // Defaults to 1.0, applied to the layer as a whole
+    shader?: TextureShaderParam | null;
 };
 
 export type JsxTexture = JsxTextureProps & {
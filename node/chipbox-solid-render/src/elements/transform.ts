@@ -0,0 +1,17 @@
+import type { Alignment, LayoutParam } from "../scene/layout";
+
+export type JsxTransformProps = {
+    id?: string;
+    translateX?: LayoutParam; // Relative lengths refer to the transformed element
+    translateY?: LayoutParam;
+    rotate?: number; // Degrees clockwise
+    scaleX?: number; // Defaults to 1
+    scaleY?: number; // Defaults to 1
+    skewX?: number; // Degrees
+    skewY?: number; // Degrees
+    origin?: Alignment; // Defaults to the center
+};
+
+export type JsxTransform = JsxTransformProps & {
+    type: "transform";
+};
@@ -0,0 +1,33 @@
+export type AnimatedProperty =
+    | "width" // Of a box parent
+    | "height"
+    | "xOffset" // Of an align parent
+    | "yOffset"
+    | "opacity" // Of sibling textures
+    | "transform" // Of sibling transforms
+    | "fill" // Solid fill of sibling rects
+    | "color"; // Of sibling texts
+
+export type Easing =
+    | "linear"
+    | "ease"
+    | "easeIn"
+    | "easeOut"
+    | "easeInOut"
+    | { cubicBezier: { x1: number; y1: number; x2: number; y2: number } };
+
+export type TimingParam =
+    /** Follows the easing curve over a fixed duration, as in CSS transitions. */
+    | { type: "tween"; duration: number; delay?: number; easing?: Easing } // Seconds, easing defaults to "ease"
+    /** A damped spring that keeps its velocity when retargeted. */
+    | { type: "spring"; stiffness?: number; damping?: number; mass?: number }; // Defaults to 170, 26 and 1
+
+export type JsxTransitionProps = {
+    id?: string;
+    property: AnimatedProperty;
+    timing?: TimingParam; // Defaults to a 0.25 s "ease" tween
+};
+
+export type JsxTransition = JsxTransitionProps & {
+    type: "transition";
+};
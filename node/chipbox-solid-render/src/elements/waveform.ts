@@ -0,0 +1,16 @@
+import type { PaintParam } from "../scene/paint";
+
+export type JsxWaveformProps = {
+    id?: string;
+    buffer: string; // Id of a sample buffer registered with the renderer
+    channel?: number; // Every channel in its own lane if omitted
+    start?: number; // First visible frame, defaults to 0
+    end?: number; // Defaults to the end of the buffer
+    gain?: number; // Defaults to 1
+    fill: PaintParam;
+    rmsFill?: PaintParam;
+};
+
+export type JsxWaveform = JsxWaveformProps & {
+    type: "waveform";
+};
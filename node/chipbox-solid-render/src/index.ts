@@ -1,6 +1,7 @@
 import { createRenderer } from 'solid-js/universal';
 import * as impl from './renderer';
 import type { JSX } from './jsx-runtime';
+import type { SceneNode } from './renderer';
 
 // Define SolidJS universal renderer.
 export const {
@@ -14,7 +15,7 @@ export const {
     spread,
     setProp,
     mergeProps,
-} = createRenderer<JSX.Element>({
+} = createRenderer<SceneNode>({
     createElement: impl.createElement,
     createTextNode: impl.createTextNode,
     replaceText: impl.replaceText,
@@ -38,6 +39,10 @@ function createTypedComponent<TProps, TReturn extends JSX.Element>(
     ) as TReturn;
 }
 
+export { root } from './renderer';
+export { registerShortcut, unregisterShortcut } from 'chipbox:scene';
+export type { SceneNode } from './renderer';
+
 // Export control flow components.
 export {
     For,
@@ -5,10 +5,21 @@ import type {
     JsxContentBox, JsxContentBoxProps,
     JsxArray, JsxArrayProps,
     JsxGrid, JsxGridProps,
+    JsxGridItem, JsxGridItemProps,
     JsxFlex, JsxFlexProps,
     JsxFlexItem, JsxFlexItemProps,
+    JsxScroll, JsxScrollProps,
     JsxTexture, JsxTextureProps,
+    JsxFocusable, JsxFocusableProps,
     JsxText, JsxTextProps,
+    JsxSpan, JsxSpanProps,
+    JsxRect, JsxRectProps,
+    JsxPath, JsxPathProps,
+    JsxWaveform, JsxWaveformProps,
+    JsxImage, JsxImageProps,
+    JsxTransform, JsxTransformProps,
+    JsxStacking, JsxStackingProps,
+    JsxTransition, JsxTransitionProps,
 } from "./elements";
 
 export namespace JSX {
@@ -18,14 +29,25 @@ export namespace JSX {
         "content-box": JsxContentBoxProps;
         array: JsxArrayProps;
         grid: JsxGridProps;
+        "grid-item": JsxGridItemProps;
         flex: JsxFlexProps;
         "flex-item": JsxFlexItemProps;
+        scroll: JsxScrollProps;
         texture: JsxTextureProps;
+        focusable: JsxFocusableProps;
         text: JsxTextProps;
+        span: JsxSpanProps;
+        rect: JsxRectProps;
+        path: JsxPathProps;
+        waveform: JsxWaveformProps;
+        image: JsxImageProps;
+        transform: JsxTransformProps;
+        stacking: JsxStackingProps;
+        transition: JsxTransitionProps;
     }
 
     export type Element = JsxBox | JsxMargin | JsxContentBox
-        | JsxArray | JsxGrid | JsxFlex | JsxFlexItem | JsxTexture | JsxText;
+        | JsxArray | JsxGrid | JsxGridItem | JsxFlex | JsxFlexItem | JsxScroll | JsxTexture | JsxFocusable | JsxText | JsxSpan | JsxRect | JsxPath | JsxWaveform | JsxImage | JsxTransform | JsxStacking | JsxTransition;
     interface ChildArray<T> extends Array<T> { }
     export type Children<T> = T | ChildArray<Children<T>> | (() => Children<T>);
     export type ElementChildren = Children<Element>;
@@ -39,10 +61,21 @@ export function jsx(type: "margin", props: JsxMarginProps): JsxMargin;
 export function jsx(type: "content-box", props: JsxContentBoxProps): JsxContentBox;
 export function jsx(type: "array", props: JsxArrayProps): JsxArray;
 export function jsx(type: "grid", props: JsxGridProps): JsxGrid;
+export function jsx(type: "grid-item", props: JsxGridItemProps): JsxGridItem;
 export function jsx(type: "flex", props: JsxFlexProps): JsxFlex;
 export function jsx(type: "flex-item", props: JsxFlexItemProps): JsxFlexItem;
+export function jsx(type: "scroll", props: JsxScrollProps): JsxScroll;
 export function jsx(type: "texture", props: JsxTextureProps): JsxTexture;
+export function jsx(type: "focusable", props: JsxFocusableProps): JsxFocusable;
 export function jsx(type: "text", props: JsxTextProps): JsxText;
+export function jsx(type: "span", props: JsxSpanProps): JsxSpan;
+export function jsx(type: "rect", props: JsxRectProps): JsxRect;
+export function jsx(type: "path", props: JsxPathProps): JsxPath;
+export function jsx(type: "waveform", props: JsxWaveformProps): JsxWaveform;
+export function jsx(type: "image", props: JsxImageProps): JsxImage;
+export function jsx(type: "transform", props: JsxTransformProps): JsxTransform;
+export function jsx(type: "stacking", props: JsxStackingProps): JsxStacking;
+export function jsx(type: "transition", props: JsxTransitionProps): JsxTransition;
 export function jsx(type: any, props: any): JSX.Element {
     const [children, ...rest] = props;
     const childrenArray = Array.isArray(children) ? children : [children];
@@ -1,69 +1,63 @@
-import type { JSX } from './jsx-runtime';
+import * as scene from 'chipbox:scene';
+import type { SceneNode } from 'chipbox:scene';
 
-// TODO: Implement.
+export type { SceneNode };
+
+/** Handle of the root node, to render into. */
+export const root = scene.root;
 
 /** Create an element with the given tag. */
-export function createElement(tag: string): JSX.Element {
-    return {
-        type: "box",
-        id: undefined,
-        children: undefined,
-        width: "0su",
-        height: "0su",
-    };
+export function createElement(tag: string): SceneNode {
+    return scene.createElement(tag);
 }
 
 /** Create a text node with the given contents. */
-export function createTextNode(value: string): JSX.Element {
-    return {
-        type: "text",
-        id: undefined,
-        children: undefined,
-        font: "",
-        weight: 400,
-        size: 16,
-        color: undefined,
-    };
+export function createTextNode(value: string): SceneNode {
+    return scene.createTextNode(value);
 }
 
 /** Replace the contents of the given text node. */
-export function replaceText(textNode: JSX.Element, value: string): void {
-
+export function replaceText(textNode: SceneNode, value: string): void {
+    scene.replaceText(textNode, value);
 }
 
 /** Returns true if the given node is a text node. */
-export function isTextNode(node: JSX.Element): boolean {
-    return false;
+export function isTextNode(node: SceneNode): boolean {
+    return scene.isTextNode(node);
 }
 
 /** Update a property of the given node. */
-export function setProperty<T>(node: JSX.Element, name: string, value: T, prev?: T): void {
-
+export function setProperty<T>(node: SceneNode, name: string, value: T, prev?: T): void {
+    // Children are managed through `insertNode` instead.
+    if (name === "children" || value === prev) {
+        return;
+    }
+    scene.setProperty(node, name, value);
 }
 
 /** Inserts a node before the anchor (another node).
  *  If it is already a child of the parent, it will be moved to the specified location.
  */
-export function insertNode(parent: JSX.Element, node: JSX.Element, anchor?: JSX.Element): void {
-
+export function insertNode(parent: SceneNode, node: SceneNode, anchor?: SceneNode): void {
+    scene.insertNode(parent, node, anchor);
 }
 
 /** Remove a node from the scene. */
-export function removeNode(parent: JSX.Element, node: JSX.Element): void {
-
+export function removeNode(parent: SceneNode, node: SceneNode): void {
+    scene.removeNode(parent, node);
 }
 
 /** Get the parent node of the given node. */
-export function getParentNode(node: JSX.Element): JSX.Element | undefined {
-    return undefined;
+export function getParentNode(node: SceneNode): SceneNode | undefined {
+    return scene.getParentNode(node);
 }
 
 /** Get the first child of the given node. */
-export function getFirstChild(node: JSX.Element): JSX.Element | undefined {
-    return undefined;
+export function getFirstChild(node: SceneNode): SceneNode | undefined {
+    return scene.getFirstChild(node);
 }
 
 /** Get the next sibling of the given node. */
-export function getNextSibling(node: JSX.Element): JSX.Element | undefined {
-    return undefined;
+export function getNextSibling(node: SceneNode): SceneNode | undefined {
+    return scene.getNextSibling(node);
 }
@@ -0,0 +1,14 @@
+import type { Color } from '@bindings/scene';
+
+export type HexColor = `#${string}`;
+export type RgbColor = `rgb(${string})` | `rgba(${string})`;
+export type HslColor = `hsl(${string})` | `hsla(${string})`;
+export type HsvColor = `hsv(${string})` | `hsva(${string})`;
+export type OklchColor = `oklch(${string})`;
+/** A named color of the active theme. */
+export type ColorToken = `$${string}`;
+
+export type ColorLiteral = HexColor | RgbColor | HslColor | HsvColor | OklchColor | ColorToken;
+
+/** A color string such as `"#ff8000"`, `"oklch(0.7 0.15 60 / 50%)"` or `"$accent"`, or a tagged color object. */
+export type ColorParam = ColorLiteral | Color;
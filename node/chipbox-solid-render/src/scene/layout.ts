@@ -1,15 +1,21 @@
 import type { LayoutLength } from '@bindings/scene';
 
 export type SceneUnit = `${number}su`;
+/** Fraction of the scene width, so `"1sw"` is the full width and `"0.5sw"` half of it. */
 export type SceneWidth = `${number}sw`;
+/** Fraction of the scene height, so `"1sh"` is the full height. */
 export type SceneHeight = `${number}sh`;
+/** Fraction of the parent width, so `"1pw"` is the full width, not `"100pw"`. */
 export type ParentWidth = `${number}pw`;
+/** Fraction of the parent height, so `"1ph"` is the full height, not `"100ph"`. */
 export type ParentHeight = `${number}ph`;
 export type Pixel = `${number}px`;
 export type Millimeter = `${number}mm`;
 export type Centimeter = `${number}cm`;
 export type Inch = `${number}in`;
 export type Point = `${number}pt`;
+/** A named length of the active theme. */
+export type LengthToken = `$${string}`;
 
 export type LayoutLiteral =
     | SceneUnit
@@ -21,10 +27,41 @@ export type LayoutLiteral =
     | Millimeter
     | Centimeter
     | Inch
-    | Point;
+    | Point
+    | LengthToken;
 
-export type LayoutParam = LayoutLiteral | LayoutLength | number;
+export type LengthOperator = "+" | "-" | "*" | "/";
+
+/**
+ * An arithmetic expression over lengths and numbers, such as `"1pw - 24px"` or
+ * `"clamp(120px, 0.5pw, 480px)"`, with `+`, `-`, `*`, `/`, parentheses and
+ * the `min`, `max` and `clamp` functions.
+ * Operands are only checked up to the first operator, the rest is parsed by the scene.
+ */
+export type LengthExpression =
+    | `${LayoutLiteral | number} ${LengthOperator} ${string}`
+    | `${LayoutLiteral | number}${LengthOperator}${string}`
+    | `(${string}`
+    | `min(${string})`
+    | `max(${string})`
+    | `clamp(${string})`;
+
+export type LayoutParam = LayoutLiteral | LengthExpression | LayoutLength | number;
 
 export type LinearDirection = "forward" | "backward";
 
 export type Axis = "horizontal" | "vertical" | "x" | "y";
+
+export type FlexJustify = "start" | "end" | "center" | "spaceBetween" | "spaceAround" | "spaceEvenly";
+
+export type FlexAlign = "start" | "center" | "end" | "stretch";
+
+export type GridTrack = LayoutParam | `${number}fr` | "auto" | `minmax(${string})`;
+
+/** Space-separated track sizes, such as `"48px 1fr 1fr"`, or a list of tracks. */
+export type GridTemplate = string | GridTrack[];
+
+export type Alignment = {
+    horizontal: "left" | "center" | "right";
+    vertical: "top" | "middle" | "bottom";
+};
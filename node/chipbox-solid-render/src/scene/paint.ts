@@ -0,0 +1,52 @@
+import type { ColorParam } from './color';
+import type { LayoutParam } from './layout';
+
+export type ColorStop = {
+    offset?: number; // Spread evenly between neighbors if omitted
+    color: ColorParam;
+};
+
+export type SolidPaint = { type: "solid"; color: ColorParam };
+/** `angle` is in degrees clockwise from pointing up. */
+export type LinearPaint = { type: "linear"; angle: number; stops: ColorStop[] };
+/** Centered by default, reaching the farthest corner unless `radius` is set. */
+export type RadialPaint = { type: "radial"; x?: LayoutParam; y?: LayoutParam; radius?: LayoutParam; stops: ColorStop[] };
+export type SweepPaint = { type: "sweep"; x?: LayoutParam; y?: LayoutParam; angle?: number; stops: ColorStop[] };
+
+export type PaintParam = ColorParam | SolidPaint | LinearPaint | RadialPaint | SweepPaint;
+
+export type CornerRadiiParam = LayoutParam | {
+    topLeft?: LayoutParam;
+    topRight?: LayoutParam;
+    bottomRight?: LayoutParam;
+    bottomLeft?: LayoutParam;
+};
+
+export type BorderParam = {
+    width: LayoutParam;
+    paint: PaintParam;
+    dash?: LayoutParam[]; // Alternating dash and gap lengths, solid if omitted
+    dashOffset?: LayoutParam;
+};
+
+export type ShadowParam = {
+    x?: LayoutParam;
+    y?: LayoutParam;
+    blur?: LayoutParam;
+    spread?: LayoutParam;
+    color: ColorParam;
+};
+
+export type LineCap = "butt" | "round" | "square";
+export type LineJoin = "miter" | "round" | "bevel";
+export type FillRule = "nonZero" | "evenOdd";
+
+export type PathStrokeParam = {
+    width: LayoutParam; // Not scaled by the view box
+    paint: PaintParam;
+    cap?: LineCap; // Defaults to "butt"
+    join?: LineJoin; // Defaults to "miter"
+    miterLimit?: number; // Defaults to 4
+    dash?: LayoutParam[];
+    dashOffset?: LayoutParam;
+};